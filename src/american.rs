@@ -0,0 +1,210 @@
+//! American (landline) Morse, as once used on North American telegraph lines.
+//!
+//! Besides dots and dashes it has a long and a longer dash, and several letters are told apart
+//! only by a space inside them, so its codes use the extra symbols described in `data::AMERICAN`
+//! and are keyed with their own timing.
+
+use crate::{
+    data,
+    timing::{Jitter, KeyEvent, Keying, Timing},
+    Code, Error, ErrorPolicy, Options, Result,
+};
+
+/// Encodes a message using the American table. Encoded messages are laid out like canonical
+/// International ones: a space between characters and `" / "` between words.
+pub fn encode_with(message: &str, options: &Options) -> Result<String> {
+    let mut buf = String::with_capacity(message.len() * 4);
+    let mut errors = Vec::new();
+
+    for (idx, c) in message.char_indices() {
+        if c == ' ' {
+            buf.push_str(" /");
+            continue;
+        }
+
+        let code = match encode_char(c) {
+            Ok(code) => Some(code),
+            Err(e) => {
+                let e = e.at(message, &message[idx..idx + c.len_utf8()]);
+                match options.errors {
+                    ErrorPolicy::Strict => return Err(e),
+                    ErrorPolicy::Skip => None,
+                    ErrorPolicy::Collect => {
+                        errors.push(e);
+                        None
+                    }
+                    ErrorPolicy::Replace(replacement) => Some(encode_char(replacement)?),
+                }
+            }
+        };
+
+        if let Some(code) = code {
+            if !buf.is_empty() {
+                buf.push(' ');
+            }
+            buf.push_str(code);
+        }
+    }
+
+    crate::collected(buf, errors)
+}
+
+/// Decodes a message encoded with the American table.
+pub fn decode_with(message: &str, options: &Options) -> Result<String> {
+    decode_counting(message, options).map(|(decoded, _)| decoded)
+}
+
+/// Decodes a message encoded with the American table, also returning the number of sequences
+/// that could not be decoded and were instead handled according to `options.errors`.
+pub fn decode_counting(message: &str, options: &Options) -> Result<(String, usize)> {
+    let mut words = Vec::new();
+    let mut unknown = 0;
+    let mut errors = Vec::new();
+
+    for word in message.split('/').filter(|word| !word.trim().is_empty()) {
+        let mut buf = String::new();
+        for character in word.split_whitespace() {
+            match decode_character(character) {
+                Some(u) => buf.push(u as char),
+                None => {
+                    unknown += 1;
+                    let e = Error::Decode(character.into()).at(message, character);
+                    match options.errors {
+                        ErrorPolicy::Strict => return Err(e),
+                        ErrorPolicy::Skip => (),
+                        ErrorPolicy::Collect => errors.push(e),
+                        ErrorPolicy::Replace(replacement) => buf.push(replacement),
+                    }
+                }
+            }
+        }
+        words.push(buf);
+    }
+
+    let decoded = words.join(&options.word_sep.to_string());
+    crate::collected((decoded, unknown), errors)
+}
+
+/// Returns whether `c` appears in the American table.
+pub fn is_encodable(c: char) -> bool {
+    encode_char(c).is_ok()
+}
+
+/// Expands a message encoded with the American table into key events. Dots are one unit long,
+/// dashes two, the long dash of `L` four and the longer dash of `0` five. Elements are separated
+/// by one unit, or two inside a spaced letter; characters and words by the gaps given by `timing`.
+pub fn to_key_events(encoded: &str, timing: &Timing, jitter: Option<&mut Jitter>) -> Vec<KeyEvent> {
+    let mut events = Keying::new(jitter);
+
+    let dit = timing.dit();
+    for (word_idx, word) in encoded
+        .split('/')
+        .filter(|word| !word.trim().is_empty())
+        .enumerate()
+    {
+        if word_idx > 0 {
+            events.push(KeyEvent::Up(timing.word_gap()));
+        }
+
+        for (character_idx, character) in word.split_whitespace().enumerate() {
+            if character_idx > 0 {
+                events.push(KeyEvent::Up(timing.char_gap()));
+            }
+
+            let mut gap = None;
+            let mut first = true;
+            for element in character.chars() {
+                let length = match element {
+                    '\'' => {
+                        gap = Some(dit * 2);
+                        continue;
+                    }
+                    '.' => 1,
+                    '-' => 2,
+                    '_' => 4,
+                    '=' => 5,
+                    _ => continue,
+                };
+
+                if !first {
+                    events.push(KeyEvent::Up(gap.take().unwrap_or(dit)));
+                }
+                events.push(KeyEvent::Down(dit * length));
+                first = false;
+            }
+        }
+    }
+
+    events.finish()
+}
+
+fn encode_char(c: char) -> Result<Code> {
+    data::AMERICAN
+        .iter()
+        .find(|&&(symbol, _)| c.is_ascii() && symbol == (c as u8).to_ascii_uppercase())
+        .map(|&(_, code)| code)
+        .ok_or(Error::Encode(c))
+}
+
+fn decode_character(character: &str) -> Option<u8> {
+    data::AMERICAN
+        .iter()
+        .find(|&&(_, code)| code == character)
+        .map(|&(symbol, _)| symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        timing::{
+            KeyEvent::{Down, Up},
+            Timing,
+        },
+        Options,
+    };
+    use std::time::Duration;
+
+    fn units(event: crate::timing::KeyEvent, dit: Duration) -> u32 {
+        (event.duration().as_secs_f64() / dit.as_secs_f64()).round() as u32
+    }
+
+    #[test]
+    fn table_round_trips() {
+        let message: String = crate::data::AMERICAN
+            .iter()
+            .map(|&(symbol, _)| symbol as char)
+            .collect();
+        let options = Options::default();
+
+        let encoded = super::encode_with(&message, &options).unwrap();
+        assert_eq!(super::decode_with(&encoded, &options).unwrap(), message);
+    }
+
+    #[test]
+    fn codes_differ_from_international() {
+        let options = Options::default();
+        assert_eq!(
+            super::encode_with("or 0", &options).unwrap(),
+            ".'. .'.. / ="
+        );
+        assert_eq!(super::decode_with("..'. / _", &options).unwrap(), "C L");
+        assert_eq!(super::decode_with("---", &options).unwrap(), "5");
+    }
+
+    #[test]
+    fn spaced_letters_and_long_dashes_are_timed() {
+        let timing = Timing::new(20);
+        let dit = timing.dit();
+
+        let events = super::to_key_events(".'. _", &timing, None);
+        let units: Vec<_> = events.iter().map(|&e| units(e, dit)).collect();
+        assert_eq!(units, [1, 2, 1, 3, 4]);
+        assert!(matches!(
+            events[..],
+            [Down(_), Up(_), Down(_), Up(_), Down(_)]
+        ));
+
+        let events = super::to_key_events("..", &timing, None);
+        assert_eq!(events, [Down(dit), Up(dit), Down(dit)]);
+    }
+}
@@ -0,0 +1,468 @@
+//! Audio synthesis and recognition for keyed messages.
+
+pub mod conditions;
+pub mod detect;
+#[cfg(feature = "soundcard")]
+pub mod device;
+pub mod flac;
+pub mod goertzel;
+pub mod iq;
+pub mod quality;
+pub mod sounder;
+pub mod spectrogram;
+pub mod stream;
+#[cfg(feature = "vorbis")]
+pub mod vorbis;
+pub mod wav;
+
+use std::{f64::consts::PI, path::Path, str::FromStr, time::Duration};
+
+use self::{detect::Detector, quality::Quality};
+use crate::{
+    confidence::{self, Scored},
+    timing::{self, KeyEvent},
+    Error, Options, Result,
+};
+
+/// The sample rate audio is rendered at unless asked otherwise.
+pub const SAMPLE_RATE: u32 = 44_100;
+
+/// Sample rates that audio can be rendered at: telephony, and the common rates of sound cards.
+pub const SAMPLE_RATES: [u32; 4] = [8_000, 22_050, 44_100, 48_000];
+
+/// Peak amplitude of the generated tone by default, leaving some headroom below full scale.
+pub const VOLUME: f64 = 0.8;
+
+/// Rise and fall time of each element by default: short enough to leave fast keying crisp, long
+/// enough to keep the clicks of hard keying off the air.
+pub const RAMP: Duration = Duration::from_millis(5);
+
+/// How key events sound when rendered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Voice {
+    /// Pitch of the tone in Hz.
+    pub tone: f64,
+    /// Peak amplitude, from 0 to 1 for full scale.
+    pub volume: f64,
+    pub sample_rate: u32,
+    /// Rise and fall time of each element, shaped as a raised cosine. Zero keys the tone hard on
+    /// and off, which clicks.
+    pub ramp: Duration,
+    /// Key by shifting the tone up this many Hz, over the same ramp, rather than by turning it on
+    /// and off (FSK-CW, as QRSS beacons often send). The tone then never stops.
+    pub shift: Option<f64>,
+}
+
+impl Default for Voice {
+    fn default() -> Self {
+        Voice {
+            tone: 600.0,
+            volume: VOLUME,
+            sample_rate: SAMPLE_RATE,
+            ramp: RAMP,
+            shift: None,
+        }
+    }
+}
+
+impl Voice {
+    /// Renders key events as a sine tone, keyed on for each key-down period and silent otherwise.
+    pub fn render(&self, events: &[KeyEvent]) -> Vec<f32> {
+        self.samples(events).collect()
+    }
+
+    /// Renders key events a sample at a time, for audio too long to hold all at once.
+    ///
+    /// Each edge is centred on the moment the key moves, so elements keep their length measured
+    /// halfway up the ramp. Edges are made steeper where an element or gap is too short for the
+    /// whole ramp.
+    pub fn samples(&self, events: &[KeyEvent]) -> Samples {
+        let rate = f64::from(self.sample_rate);
+        let mut marks = Vec::new();
+        let mut len = 0;
+        for &event in events {
+            let n = (event.duration().as_secs_f64() * rate).round() as usize;
+            if let KeyEvent::Down(_) = event {
+                marks.push((len, len + n));
+            }
+            len += n;
+        }
+
+        let half = (self.ramp.as_secs_f64() * rate / 2.0) as usize;
+        let edges = marks
+            .iter()
+            .enumerate()
+            .map(|(idx, &(start, end))| {
+                let inside = half.min((end - start) / 2);
+                // The silence before the first mark and after the last is not shared with
+                // another edge.
+                let before = match idx.checked_sub(1) {
+                    Some(prev) => (start - marks[prev].1) / 2,
+                    None => start,
+                };
+                let after = match marks.get(idx + 1) {
+                    Some(next) => (next.0 - end) / 2,
+                    None => len - end,
+                };
+                Edges {
+                    rise: (start - half.min(before), start + inside),
+                    fall: (end - inside, end + half.min(after)),
+                }
+            })
+            .collect();
+
+        Samples {
+            edges,
+            mark: 0,
+            n: 0,
+            len,
+            step: 2.0 * PI * self.tone / rate,
+            shift: self.shift.map(|shift| 2.0 * PI * shift / rate),
+            phase: 0.0,
+            volume: self.volume,
+        }
+    }
+}
+
+/// Where an element's envelope rises and falls, in samples from the start.
+#[derive(Clone, Copy, Debug)]
+struct Edges {
+    rise: (usize, usize),
+    fall: (usize, usize),
+}
+
+impl Edges {
+    fn gain(&self, n: usize) -> f64 {
+        let (rise, fall) = (self.rise, self.fall);
+        if n < rise.0 || n >= fall.1 {
+            0.0
+        } else if n < rise.1 {
+            envelope(n - rise.0, rise.1 - rise.0)
+        } else if n >= fall.0 {
+            envelope(fall.1 - n, fall.1 - fall.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// The samples of rendered key events; see [`Voice::samples`].
+pub struct Samples {
+    edges: Vec<Edges>,
+    /// The first element not yet finished.
+    mark: usize,
+    n: usize,
+    len: usize,
+    step: f64,
+    /// The extra phase step while the key is down, when keying by frequency.
+    shift: Option<f64>,
+    phase: f64,
+    volume: f64,
+}
+
+impl Iterator for Samples {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.n == self.len {
+            return None;
+        }
+        while self
+            .edges
+            .get(self.mark)
+            .is_some_and(|edges| edges.fall.1 <= self.n)
+        {
+            self.mark += 1;
+        }
+        let gain = self
+            .edges
+            .get(self.mark)
+            .map_or(0.0, |edges| edges.gain(self.n));
+
+        let (gain, step) = match self.shift {
+            Some(shift) => (1.0, self.step + shift * gain),
+            None => (gain, self.step),
+        };
+        let sample = self.phase.sin() * self.volume * gain;
+        self.phase = (self.phase + step) % (2.0 * PI);
+        self.n += 1;
+        Some(sample as f32)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.len - self.n;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for Samples {}
+
+/// The gain `n` samples into an edge `width` samples long, rising as a raised cosine.
+fn envelope(n: usize, width: usize) -> f64 {
+    0.5 - 0.5 * (PI * n as f64 / width as f64).cos()
+}
+
+/// How rendered audio is written out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Wav,
+    Flac,
+    Ogg,
+    /// Raw samples with no header, to pipe into another program.
+    Pcm,
+    /// Not audio at all but a MIDI file of notes following the keying; see `crate::midi`.
+    Midi,
+}
+
+impl Encoding {
+    /// The encoding a file's extension calls for, if it is one of these.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "wav" => Some(Encoding::Wav),
+            "flac" => Some(Encoding::Flac),
+            "ogg" => Some(Encoding::Ogg),
+            "pcm" | "raw" => Some(Encoding::Pcm),
+            "mid" | "midi" => Some(Encoding::Midi),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wav" => Ok(Encoding::Wav),
+            "flac" => Ok(Encoding::Flac),
+            "ogg" => Ok(Encoding::Ogg),
+            "pcm" => Ok(Encoding::Pcm),
+            "midi" => Ok(Encoding::Midi),
+            _ => Err(format!(
+                "unknown audio format {:?} (expected wav, flac, ogg, pcm or midi)",
+                s
+            )),
+        }
+    }
+}
+
+/// Renders key events as a sine tone at `tone` Hz with the default ramp.
+pub fn render(events: &[KeyEvent], tone: f64) -> Vec<f32> {
+    Voice {
+        tone,
+        ..Voice::default()
+    }
+    .render(events)
+}
+
+/// Decodes a recording of a keyed tone, whatever its speed, as `detector` hears it.
+pub fn decode(samples: &[f32], sample_rate: u32, detector: &Detector) -> Result<String> {
+    let (_, events) = listen(samples, sample_rate, detector)?;
+    crate::decode(&timing::from_key_events(&events))
+}
+
+/// Decodes a recording like `decode` while following changes in the sender's speed, word by word,
+/// with the speed each word was sent at in WPM.
+pub fn decode_tracking(
+    samples: &[f32],
+    sample_rate: u32,
+    detector: &Detector,
+) -> Result<Vec<(String, f64)>> {
+    let (_, events) = listen(samples, sample_rate, detector)?;
+    timing::from_key_events_tracking(&events)
+        .into_iter()
+        .map(|(code, wpm)| crate::decode(&code).map(|word| (word, wpm)))
+        .collect()
+}
+
+/// Decodes a recording like `decode`, scoring each character by how clearly it was keyed.
+pub fn decode_scored(
+    samples: &[f32],
+    sample_rate: u32,
+    detector: &Detector,
+    options: &Options,
+) -> Result<Vec<Vec<Scored>>> {
+    let (_, events) = listen(samples, sample_rate, detector)?;
+    Ok(confidence::decode_key_events(&events, options))
+}
+
+/// Measures the tone, signal-to-noise ratio and keying of a recording.
+pub fn quality(samples: &[f32], sample_rate: u32, detector: &Detector) -> Result<Quality> {
+    let (tone, events) = listen(samples, sample_rate, detector)?;
+    Ok(Quality::measure(
+        samples,
+        sample_rate,
+        tone,
+        &events,
+        detector,
+    ))
+}
+
+/// Recovers the key events of a recording, for timing analysis.
+pub fn key_events(samples: &[f32], sample_rate: u32) -> Result<Vec<KeyEvent>> {
+    listen(samples, sample_rate, &Detector::default()).map(|(_, events)| events)
+}
+
+/// A signal picked out of a recording of several.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signal {
+    /// Its frequency in Hz.
+    pub tone: f64,
+    pub words: Vec<Vec<Scored>>,
+}
+
+/// Decodes every keyed signal in a recording separately, lowest tone first, as a skimmer does.
+/// Signals closer together than [`detect::MIN_SPACING`] are heard as one. The detector's center
+/// frequency is not used.
+pub fn decode_signals(
+    samples: &[f32],
+    sample_rate: u32,
+    detector: &Detector,
+    options: &Options,
+) -> Vec<Signal> {
+    detect::find_tones(samples, sample_rate)
+        .into_iter()
+        .filter_map(|tone| {
+            let events = detector.key_events(samples, sample_rate, tone);
+            let words = confidence::decode_key_events(&events, options);
+            (!words.is_empty()).then_some(Signal { tone, words })
+        })
+        .collect()
+}
+
+/// Finds the keyed tone, unless `detector` was given one, and recovers its key events, for
+/// callers that measure and decode a recording from the one pass.
+pub fn listen(
+    samples: &[f32],
+    sample_rate: u32,
+    detector: &Detector,
+) -> Result<(f64, Vec<KeyEvent>)> {
+    let tone = detector
+        .tone(samples, sample_rate)
+        .ok_or_else(|| Error::Audio("no tone found".into()))?;
+
+    let events = detector.key_events(samples, sample_rate, tone);
+    if events.is_empty() {
+        return Err(Error::Audio(format!("no keying detected at {} Hz", tone)));
+    }
+    Ok((tone, events))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Voice;
+    use crate::timing::{self, KeyEvent, Timing};
+
+    #[test]
+    fn render_length_matches_timing() {
+        // PARIS is exactly 50 units long including the trailing word gap, which is not rendered.
+        let timing = Timing::new(20);
+        let encoded = crate::encode("PARIS").unwrap();
+        let events = timing::to_key_events(&encoded, &timing, None);
+        let samples = super::render(&events, 600.0);
+
+        let expected = super::SAMPLE_RATE as usize * 43 * 60 / 1000;
+        assert_eq!(samples.len(), expected);
+        assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+    }
+
+    #[test]
+    fn renders_at_other_rates_and_volumes() {
+        let encoded = crate::encode("PARIS").unwrap();
+        let events = timing::to_key_events(&encoded, &Timing::new(20), None);
+        let voice = Voice {
+            volume: 0.5,
+            sample_rate: 8_000,
+            ..Voice::default()
+        };
+        let samples = voice.render(&events);
+
+        assert_eq!(samples.len(), 8_000 * 43 * 60 / 1000);
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!((peak - 0.5).abs() < 0.01, "{}", peak);
+        assert_eq!(
+            super::decode(&samples, 8_000, &Default::default()).unwrap(),
+            "PARIS"
+        );
+    }
+
+    #[test]
+    fn samples_know_their_length() {
+        let encoded = crate::encode("CQ").unwrap();
+        let events = timing::to_key_events(&encoded, &Timing::new(25), None);
+        let mut samples = Voice::default().samples(&events);
+        let len = samples.len();
+        samples.next();
+        assert_eq!(samples.len(), len - 1);
+        assert_eq!(samples.count(), len - 1);
+    }
+
+    #[test]
+    fn shifts_tone_for_fsk() {
+        let second = Duration::from_secs(1);
+        let events = [KeyEvent::Up(second), KeyEvent::Down(second)];
+        let voice = Voice {
+            shift: Some(100.0),
+            ..Voice::default()
+        };
+        let samples = voice.render(&events);
+        let rate = super::SAMPLE_RATE;
+        let (up, down) = samples.split_at(rate as usize);
+
+        assert!(up.iter().any(|&sample| sample != 0.0));
+        let tone = |samples: &[f32]| super::detect::find_tone(samples, rate).unwrap();
+        assert!((tone(up) - 600.0).abs() <= 10.0);
+        assert!((tone(down) - 700.0).abs() <= 10.0);
+    }
+
+    #[test]
+    fn ramps_element_edges() {
+        let (gap, mark) = (Duration::from_millis(20), Duration::from_millis(60));
+        let events = [KeyEvent::Up(gap), KeyEvent::Down(mark), KeyEvent::Up(gap)];
+        let rate = super::SAMPLE_RATE as usize;
+        let (edge, half) = (rate * 20 / 1000, rate * 5 / 2000);
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+        let shaped = super::render(&events, 600.0);
+        assert_eq!(shaped.len(), rate * 100 / 1000);
+        assert_eq!(peak(&shaped[..edge - half]), 0.0);
+        assert!(peak(&shaped[edge - half..edge - half / 2]) < 0.15);
+        assert!(peak(&shaped[edge + half..edge + 3 * half]) > 0.75);
+        assert_eq!(peak(&shaped[edge * 4 + half..]), 0.0);
+
+        let hard = Voice {
+            ramp: Duration::from_millis(0),
+            ..Voice::default()
+        }
+        .render(&events);
+        assert_eq!(peak(&hard[..edge]), 0.0);
+        assert!(peak(&hard[edge..edge + half]) > 0.75);
+    }
+
+    #[test]
+    fn decodes_each_signal() {
+        let options = crate::Options::default();
+        let render = |message: &str, wpm: u32, tone: f64| {
+            let encoded = crate::encode(message).unwrap();
+            super::render(
+                &timing::to_key_events(&encoded, &Timing::new(wpm), None),
+                tone,
+            )
+        };
+        let (a, b) = (render("CQ DE W1AW", 18, 650.0), render("5NN TU", 30, 950.0));
+        let samples: Vec<f32> = (0..a.len().max(b.len()))
+            .map(|n| (a.get(n).unwrap_or(&0.0) + b.get(n).unwrap_or(&0.0)) / 2.0)
+            .collect();
+
+        let signals =
+            super::decode_signals(&samples, super::SAMPLE_RATE, &Default::default(), &options);
+        let texts: Vec<String> = signals
+            .iter()
+            .map(|signal| crate::confidence::text(&signal.words, '?', &options))
+            .collect();
+        assert_eq!(texts, ["CQ DE W1AW", "5NN TU"]);
+    }
+}
@@ -0,0 +1,171 @@
+//! Band conditions for practice audio: noise, fading (QSB) and other stations (QRM), so that
+//! copying practice is more like copying off the air.
+
+use std::f64::consts::PI;
+
+use super::Voice;
+use crate::{rng::Rng, timing, KeyEvent, Timing};
+
+/// The periods of the two swells that make up fading, in seconds. They are not multiples of
+/// each other, so the fading does not repeat exactly.
+const QSB_PERIODS: [f64; 2] = [9.0, 5.7];
+
+/// Interfering stations are this far from the wanted tone at least, and at most
+/// `QRM_SPREAD.1`, in Hz: close enough to be a nuisance, far enough to be told apart.
+const QRM_SPREAD: (f64, f64) = (150.0, 600.0);
+
+/// Interfering stations send at a random speed in this range, in WPM.
+const QRM_SPEEDS: (u32, u32) = (14, 32);
+
+/// What to mix into a rendered signal.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Conditions {
+    /// Signal-to-noise ratio in dB of white noise to add, measured against the signal while keyed
+    /// over the whole audio bandwidth. An operator's narrow filter hears a much better ratio.
+    pub snr: Option<f64>,
+    /// How deep the signal fades, from 0 for not at all to 1 for fading out entirely.
+    pub qsb: f64,
+    /// How many other stations to mix in.
+    pub qrm: usize,
+}
+
+impl Conditions {
+    /// Applies the conditions to `samples`, a signal rendered in `voice`, keeping the result
+    /// within full scale. Other stations sound like `voice` but for their pitch.
+    pub fn apply(&self, samples: &mut [f32], voice: &Voice, rng: &mut Rng) {
+        let keyed: Vec<f64> = samples
+            .iter()
+            .filter(|&&sample| sample != 0.0)
+            .map(|&sample| f64::from(sample).powi(2))
+            .collect();
+        let power = keyed.iter().sum::<f64>() / keyed.len().max(1) as f64;
+
+        if self.qsb > 0.0 {
+            let phases = [rng.next_f64() * 2.0 * PI, rng.next_f64() * 2.0 * PI];
+            for (n, sample) in samples.iter_mut().enumerate() {
+                *sample *= fade(n, voice.sample_rate, self.qsb.min(1.0), phases) as f32;
+            }
+        }
+
+        for _ in 0..self.qrm {
+            let station = interference(samples.len(), voice, rng);
+            for (sample, interference) in samples.iter_mut().zip(station) {
+                *sample += interference;
+            }
+        }
+
+        if let Some(snr) = self.snr {
+            let deviation = (power / 10f64.powf(snr / 10.0)).sqrt();
+            for sample in samples.iter_mut() {
+                *sample += (rng.next_gaussian() * deviation) as f32;
+            }
+        }
+
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        if peak > 1.0 {
+            for sample in samples.iter_mut() {
+                *sample /= peak;
+            }
+        }
+    }
+}
+
+/// The gain of the fading signal at sample `n`.
+fn fade(n: usize, sample_rate: u32, depth: f64, phases: [f64; 2]) -> f64 {
+    let t = n as f64 / f64::from(sample_rate);
+    let swell: f64 = QSB_PERIODS
+        .iter()
+        .zip(phases)
+        .map(|(period, phase)| (2.0 * PI * t / period + phase).sin())
+        .sum::<f64>()
+        / QSB_PERIODS.len() as f64;
+    1.0 - depth * (0.5 + 0.5 * swell)
+}
+
+/// Another station calling CQ over and over, near the pitch of `voice`, for `len` samples.
+fn interference(len: usize, voice: &Voice, rng: &mut Rng) -> Vec<f32> {
+    let spread = QRM_SPREAD.0 + rng.next_f64() * (QRM_SPREAD.1 - QRM_SPREAD.0);
+    let tone = if rng.next_f64() < 0.5 && voice.tone - spread > QRM_SPREAD.0 {
+        voice.tone - spread
+    } else {
+        voice.tone + spread
+    };
+    let wpm = QRM_SPEEDS.0 + (rng.next_f64() * f64::from(QRM_SPEEDS.1 - QRM_SPEEDS.0)) as u32;
+    let level = 0.3 + rng.next_f64() * 0.5;
+
+    let timing = Timing::new(wpm);
+    let call = crate::practice::callsigns(1, rng);
+    let encoded =
+        crate::encode(&format!("CQ CQ DE {} {} K", call, call)).expect("callsigns are encodable");
+    let mut events = vec![KeyEvent::Up(
+        timing.word_gap().mul_f64(rng.next_f64() * 4.0),
+    )];
+    events.extend(timing::to_key_events(&encoded, &timing, None));
+    events.push(KeyEvent::Up(timing.word_gap() * 2));
+
+    let once = Voice { tone, ..*voice }.render(&events);
+    once.iter()
+        .cycle()
+        .take(len)
+        .map(|sample| sample * level as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Conditions;
+    use crate::{audio, rng::Rng, timing, Timing};
+
+    fn render(message: &str) -> Vec<f32> {
+        let encoded = crate::encode(message).unwrap();
+        audio::render(
+            &timing::to_key_events(&encoded, &Timing::new(20), None),
+            600.0,
+        )
+    }
+
+    #[test]
+    fn noise_meets_target_snr() {
+        let clean = render("PARIS PARIS");
+        let mut noisy = clean.clone();
+        // Quiet enough that the result is not scaled back to full scale.
+        let conditions = Conditions {
+            snr: Some(30.0),
+            ..Conditions::default()
+        };
+        conditions.apply(&mut noisy, &Default::default(), &mut Rng::new(1));
+
+        let keyed: Vec<(f32, f32)> = clean
+            .iter()
+            .zip(&noisy)
+            .map(|(&clean, &noisy)| (clean, noisy - clean))
+            .filter(|&(clean, _)| clean != 0.0)
+            .collect();
+        let power = |values: &mut dyn Iterator<Item = f32>| {
+            values.map(|v| f64::from(v).powi(2)).sum::<f64>() / keyed.len() as f64
+        };
+        let snr = 10.0
+            * (power(&mut keyed.iter().map(|k| k.0)) / power(&mut keyed.iter().map(|k| k.1)))
+                .log10();
+        assert!((snr - 30.0).abs() < 0.5, "{}", snr);
+    }
+
+    #[test]
+    fn still_decodes_through_fading_and_qrm() {
+        let mut samples = render("CQ DE W1AW");
+        let conditions = Conditions {
+            snr: Some(15.0),
+            qsb: 0.5,
+            qrm: 1,
+        };
+        conditions.apply(&mut samples, &Default::default(), &mut Rng::new(4));
+
+        assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+        let detector = audio::detect::Detector {
+            center: Some(600.0),
+            ..Default::default()
+        };
+        let decoded = audio::decode(&samples, audio::SAMPLE_RATE, &detector).unwrap();
+        assert_eq!(decoded, "CQ DE W1AW");
+    }
+}
@@ -0,0 +1,318 @@
+//! Recovering key events from recorded audio.
+
+use std::time::Duration;
+
+use super::goertzel;
+use crate::timing::KeyEvent;
+
+/// Length of each analysis block. Short enough to resolve dits at 40 WPM, long enough to give
+/// the Goertzel filter a usefully narrow bandwidth.
+const BLOCK: Duration = Duration::from_millis(5);
+
+/// The bandwidth of a Goertzel filter over one `BLOCK`, in Hz.
+const BANDWIDTH: f64 = 200.0;
+
+/// How a recording is listened to: at which pitch, through how wide a filter, and in steps of
+/// what length.
+///
+/// The filter's bandwidth is set by how much audio it looks at, so a narrow filter is slow to
+/// respond: narrower than 1 / `block` Hz, each step looks back over the last 1 / `bandwidth`
+/// seconds, and keying much faster than that smears together.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Detector {
+    /// The tone to listen for in Hz, or `None` to find the loudest.
+    pub center: Option<f64>,
+    /// Width of the filter around the tone, in Hz.
+    pub bandwidth: f64,
+    /// How often the tone is measured; the resolution of the key events recovered.
+    pub block: Duration,
+}
+
+impl Default for Detector {
+    fn default() -> Self {
+        Detector {
+            center: None,
+            bandwidth: BANDWIDTH,
+            block: BLOCK,
+        }
+    }
+}
+
+impl Detector {
+    /// The tone to listen for: the center frequency if one was given, or the one found.
+    pub fn tone(&self, samples: &[f32], sample_rate: u32) -> Option<f64> {
+        self.center.or_else(|| find_tone(samples, sample_rate))
+    }
+
+    /// Converts the presence or absence of `tone` into key events, trimming leading and trailing
+    /// silence. Returns nothing if the tone is never clearly keyed.
+    pub fn key_events(&self, samples: &[f32], sample_rate: u32, tone: f64) -> Vec<KeyEvent> {
+        let keyed = self.keying(samples, sample_rate, tone);
+        let mut events = Vec::new();
+        let mut blocks = keyed.iter().skip_while(|&&down| !down).peekable();
+        while let Some(&down) = blocks.next() {
+            let mut len = 1;
+            while blocks.peek() == Some(&&down) {
+                blocks.next();
+                len += 1;
+            }
+
+            let duration = self.block * len;
+            events.push(if down {
+                KeyEvent::Down(duration)
+            } else {
+                KeyEvent::Up(duration)
+            });
+        }
+
+        if let Some(KeyEvent::Up(_)) = events.last() {
+            events.pop();
+        }
+        events
+    }
+
+    /// Whether the key is down in each block, from start to end of the recording. Empty if the
+    /// tone is never clearly keyed.
+    pub fn keying(&self, samples: &[f32], sample_rate: u32, tone: f64) -> Vec<bool> {
+        let magnitudes = self.magnitudes(samples, sample_rate, tone);
+        let threshold = match threshold(&magnitudes) {
+            Some(threshold) => threshold,
+            None => return Vec::new(),
+        };
+
+        let mut keyed: Vec<bool> = magnitudes.iter().map(|&m| m > threshold).collect();
+
+        // A single block that disagrees with both neighbors is noise, not keying.
+        for idx in 1..keyed.len().saturating_sub(1) {
+            if keyed[idx - 1] == keyed[idx + 1] && keyed[idx] != keyed[idx - 1] {
+                keyed[idx] = keyed[idx - 1];
+            }
+        }
+        keyed
+    }
+
+    /// The strength of `tone` in each block, each measured over the block or the filter's
+    /// response time, whichever is longer.
+    pub fn magnitudes(&self, samples: &[f32], sample_rate: u32, tone: f64) -> Vec<f64> {
+        let block = self.block_len(sample_rate);
+        let window = ((f64::from(sample_rate) / self.bandwidth.max(1.0)) as usize).max(block);
+        (0..samples.len())
+            .step_by(block)
+            .map(|start| {
+                let end = (start + block).min(samples.len());
+                let from = end.saturating_sub(window);
+                goertzel::magnitude(&samples[from..end], tone, sample_rate)
+            })
+            .collect()
+    }
+
+    pub(crate) fn block_len(&self, sample_rate: u32) -> usize {
+        ((f64::from(sample_rate) * self.block.as_secs_f64()) as usize).max(1)
+    }
+}
+
+/// The range and resolution of the search for the keyed tone, in Hz.
+const TONE_SEARCH: (u32, u32, u32) = (200, 2000, 10);
+
+/// Finds the frequency of the keyed tone by sweeping the loudest second of the recording.
+pub fn find_tone(samples: &[f32], sample_rate: u32) -> Option<f64> {
+    let block = block_len(sample_rate);
+    let energies: Vec<f32> = samples
+        .chunks(block)
+        .map(|chunk| chunk.iter().map(|s| s * s).sum())
+        .collect();
+
+    let window = (sample_rate as usize / block)
+        .max(1)
+        .min(energies.len().max(1));
+    let start = (0..=energies.len().saturating_sub(window))
+        .max_by(|&a, &b| {
+            let a: f32 = energies[a..a + window].iter().sum();
+            let b: f32 = energies[b..b + window].iter().sum();
+            a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap_or(0);
+    let from = (start * block).min(samples.len());
+    let to = ((start + window) * block).min(samples.len());
+    let excerpt = &samples[from..to];
+
+    let (low, high, step) = TONE_SEARCH;
+    (low..=high)
+        .step_by(step as usize)
+        .map(|frequency| {
+            let frequency = f64::from(frequency);
+            (
+                frequency,
+                goertzel::magnitude(excerpt, frequency, sample_rate),
+            )
+        })
+        .filter(|&(_, magnitude)| magnitude > 0.0)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(frequency, _)| frequency)
+}
+
+/// The closest two signals can be and still be told apart by `key_events`, whose short blocks
+/// hear a couple of hundred Hz either side of the tone.
+pub const MIN_SPACING: f64 = 250.0;
+
+/// Length of the blocks used to measure each candidate tone when looking for several. Longer than
+/// `BLOCK`, for finer frequency resolution; keying still shows up as the tone coming and going.
+const SWEEP_BLOCK: Duration = Duration::from_millis(50);
+
+/// How far above the typical level of the band a tone must peak to count as a signal.
+const MIN_PROMINENCE: f64 = 4.0;
+
+/// Finds every keyed tone in the recording, lowest first, for decoding several signals at once.
+///
+/// Each candidate is scored by how loud it gets at its loudest, and the strongest are picked in
+/// turn, skipping any within `MIN_SPACING` of one already picked. Steady tones, which never go
+/// quiet, are not keyed signals and are left out.
+pub fn find_tones(samples: &[f32], sample_rate: u32) -> Vec<f64> {
+    let block = ((f64::from(sample_rate) * SWEEP_BLOCK.as_secs_f64()) as usize).max(1);
+    let (low, high, step) = TONE_SEARCH;
+    let mut candidates: Vec<(f64, f64)> = (low..=high)
+        .step_by(step as usize)
+        .filter_map(|frequency| {
+            let frequency = f64::from(frequency);
+            let mut magnitudes: Vec<f64> = samples
+                .chunks(block)
+                .map(|chunk| goertzel::magnitude(chunk, frequency, sample_rate))
+                .collect();
+            magnitudes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let percentile = |p: usize| magnitudes.get(magnitudes.len() * p / 100).copied();
+            let (floor, peak) = (percentile(10)?, percentile(95)?);
+            (peak > floor * 2.0).then_some((frequency, peak))
+        })
+        .collect();
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+
+    let mut levels: Vec<f64> = candidates.iter().map(|&(_, peak)| peak).collect();
+    levels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let typical = levels[levels.len() / 2];
+
+    candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    let mut tones: Vec<f64> = Vec::new();
+    for (frequency, peak) in candidates {
+        if peak > typical * MIN_PROMINENCE
+            && tones
+                .iter()
+                .all(|&tone| (tone - frequency).abs() >= MIN_SPACING)
+        {
+            tones.push(frequency);
+        }
+    }
+    tones.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    tones
+}
+
+/// Converts the presence or absence of `tone` into key events with the default [`Detector`].
+pub fn key_events(samples: &[f32], sample_rate: u32, tone: f64) -> Vec<KeyEvent> {
+    Detector::default().key_events(samples, sample_rate, tone)
+}
+
+/// The magnitude above which a block counts as key-down, halfway between the noise floor and the
+/// peak. `None` if the two are too close to tell apart.
+pub(crate) fn threshold(magnitudes: &[f64]) -> Option<f64> {
+    let mut sorted = magnitudes.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let percentile = |p: usize| sorted.get(sorted.len() * p / 100).copied().unwrap_or(0.0);
+    let (floor, peak) = (percentile(10), percentile(99));
+    if peak <= floor * 2.0 || peak <= f64::EPSILON {
+        return None;
+    }
+    Some((floor + peak) / 2.0)
+}
+
+pub(crate) fn block_len(sample_rate: u32) -> usize {
+    Detector::default().block_len(sample_rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        audio,
+        rng::Rng,
+        timing::{self, Timing},
+    };
+
+    #[test]
+    fn decodes_rendered_audio() {
+        let encoded = crate::encode("CQ TEST").unwrap();
+        let events = timing::to_key_events(&encoded, &Timing::new(25), None);
+        let samples = audio::render(&events, 700.0);
+
+        let tone = super::find_tone(&samples, audio::SAMPLE_RATE).unwrap();
+        assert!((tone - 700.0).abs() <= 10.0, "{}", tone);
+
+        let detected = super::key_events(&samples, audio::SAMPLE_RATE, tone);
+        assert_eq!(timing::from_key_events(&detected), encoded);
+    }
+
+    #[test]
+    fn decodes_noisy_audio() {
+        let encoded = crate::encode("PARIS").unwrap();
+        let events = timing::to_key_events(&encoded, &Timing::new(20), None);
+        let mut rng = Rng::new(3);
+        let samples: Vec<f32> = audio::render(&events, 550.0)
+            .into_iter()
+            .map(|sample| sample + (rng.next_f64() as f32 - 0.5) * 0.6)
+            .collect();
+
+        let tone = super::find_tone(&samples, audio::SAMPLE_RATE).unwrap();
+        let detected = super::key_events(&samples, audio::SAMPLE_RATE, tone);
+        assert_eq!(timing::from_key_events(&detected), encoded);
+    }
+
+    #[test]
+    fn finds_several_tones() {
+        let render = |message: &str, wpm: u32, tone: f64| {
+            let encoded = crate::encode(message).unwrap();
+            audio::render(
+                &timing::to_key_events(&encoded, &Timing::new(wpm), None),
+                tone,
+            )
+        };
+        let (a, b) = (render("CQ CQ", 20, 500.0), render("TEST", 28, 1100.0));
+        let samples: Vec<f32> = (0..a.len().max(b.len()))
+            .map(|n| (a.get(n).unwrap_or(&0.0) + b.get(n).unwrap_or(&0.0)) / 2.0)
+            .collect();
+
+        let tones = super::find_tones(&samples, audio::SAMPLE_RATE);
+        assert_eq!(tones.len(), 2, "{:?}", tones);
+        assert!((tones[0] - 500.0).abs() <= 10.0, "{:?}", tones);
+        assert!((tones[1] - 1100.0).abs() <= 10.0, "{:?}", tones);
+    }
+
+    #[test]
+    fn narrow_filter_rejects_neighbor() {
+        let encoded = crate::encode("TEST").unwrap();
+        let events = timing::to_key_events(&encoded, &Timing::new(15), None);
+        let wanted = audio::render(&events, 600.0);
+        // A steady carrier 150 Hz away, louder than the signal.
+        let samples: Vec<f32> = wanted
+            .iter()
+            .enumerate()
+            .map(|(n, &sample)| {
+                let t = n as f64 / f64::from(audio::SAMPLE_RATE);
+                sample + (2.0 * std::f64::consts::PI * 750.0 * t).sin() as f32
+            })
+            .collect();
+
+        let narrow = super::Detector {
+            center: Some(600.0),
+            bandwidth: 50.0,
+            ..super::Detector::default()
+        };
+        let tone = narrow.tone(&samples, audio::SAMPLE_RATE).unwrap();
+        let detected = narrow.key_events(&samples, audio::SAMPLE_RATE, tone);
+        assert_eq!(timing::from_key_events(&detected), encoded);
+    }
+
+    #[test]
+    fn silence_has_no_key_events() {
+        let samples = vec![0.0; audio::SAMPLE_RATE as usize];
+        assert!(super::key_events(&samples, audio::SAMPLE_RATE, 600.0).is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+//! Playback and capture through the system sound card.
+
+use std::sync::mpsc::{self, Receiver};
+
+use rodio::{
+    buffer::SamplesBuffer,
+    cpal::{
+        self,
+        traits::{DeviceTrait, HostTrait, StreamTrait},
+    },
+    OutputStream, Sink,
+};
+
+use crate::{Error, Result};
+
+/// Plays mono samples on the default output device, blocking until they have finished.
+pub fn play(samples: Vec<f32>, sample_rate: u32) -> Result<()> {
+    let (_stream, handle) = OutputStream::try_default().map_err(|e| Error::Audio(e.to_string()))?;
+    let sink = Sink::try_new(&handle).map_err(|e| Error::Audio(e.to_string()))?;
+
+    sink.append(SamplesBuffer::new(1, sample_rate, samples));
+    sink.sleep_until_end();
+    Ok(())
+}
+
+/// A running recording from the default input device. Iterating yields mono samples in the
+/// order they were captured, blocking until more are available.
+pub struct Capture {
+    _stream: cpal::Stream,
+    receiver: Receiver<Vec<f32>>,
+    pub sample_rate: u32,
+}
+
+impl Iterator for Capture {
+    type Item = Vec<f32>;
+
+    fn next(&mut self) -> Option<Vec<f32>> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// Starts recording from the default input device.
+pub fn capture() -> Result<Capture> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or_else(|| Error::Audio("no input device available".into()))?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| Error::Audio(e.to_string()))?;
+
+    let format = supported.sample_format();
+    let config: cpal::StreamConfig = supported.into();
+    let (sender, receiver) = mpsc::channel();
+
+    let stream = match format {
+        cpal::SampleFormat::F32 => build::<f32>(&device, &config, sender),
+        cpal::SampleFormat::I16 => build::<i16>(&device, &config, sender),
+        cpal::SampleFormat::U16 => build::<u16>(&device, &config, sender),
+    }?;
+    stream.play().map_err(|e| Error::Audio(e.to_string()))?;
+
+    Ok(Capture {
+        _stream: stream,
+        receiver,
+        sample_rate: config.sample_rate.0,
+    })
+}
+
+fn build<T: cpal::Sample>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    sender: mpsc::Sender<Vec<f32>>,
+) -> Result<cpal::Stream> {
+    let channels = usize::from(config.channels.max(1));
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let mono = data
+                    .chunks(channels)
+                    .map(|frame| frame.iter().map(|s| s.to_f32()).sum::<f32>() / frame.len() as f32)
+                    .collect();
+                let _ = sender.send(mono);
+            },
+            // Dropped buffers only cost a few milliseconds of audio, which the decoder rides out.
+            |_| (),
+        )
+        .map_err(|e| Error::Audio(e.to_string()))
+}
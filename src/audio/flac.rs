@@ -0,0 +1,274 @@
+//! A FLAC encoder, for audio that is lossless like WAV but a fraction of the size: the silences
+//! between elements cost a few bytes a block, and a steady tone is all but predictable.
+//!
+//! Each block of samples is stored as a constant, as the residual of whichever fixed predictor
+//! suits it best, Rice coded, or verbatim if neither helps.
+
+use std::io::{self, Write};
+
+use super::wav::SampleFormat;
+
+/// Samples per frame, the reference encoder's default.
+const BLOCK_SIZE: usize = 4096;
+
+/// The highest order of fixed predictor there is.
+const MAX_ORDER: usize = 4;
+
+/// The largest Rice parameter with 4-bit parameters; 15 escapes to unencoded residuals.
+const MAX_RICE: u32 = 14;
+
+/// Writes mono samples as a FLAC file with the bit depth of `format`, which must be an integer
+/// one.
+pub fn write<W: Write>(
+    mut writer: W,
+    samples: &[f32],
+    sample_rate: u32,
+    format: SampleFormat,
+) -> io::Result<()> {
+    let bits = match format {
+        SampleFormat::U8 => 8,
+        SampleFormat::S16 => 16,
+        SampleFormat::S24 => 24,
+        SampleFormat::F32 => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "FLAC stores integer samples only (u8, s16 or s24)",
+            ))
+        }
+    };
+    let scale = ((1i64 << (bits - 1)) - 1) as f64;
+    let samples: Vec<i64> = samples
+        .iter()
+        .map(|&sample| (f64::from(sample.clamp(-1.0, 1.0)) * scale).round() as i64)
+        .collect();
+
+    let mut info = Bits::default();
+    info.push(BLOCK_SIZE as u64, 16);
+    info.push(BLOCK_SIZE as u64, 16);
+    // The smallest and largest frame sizes, left unknown.
+    info.push(0, 24);
+    info.push(0, 24);
+    info.push(u64::from(sample_rate), 20);
+    info.push(0, 3); // one channel
+    info.push(u64::from(bits) - 1, 5);
+    info.push(samples.len() as u64, 36);
+    // The MD5 signature of the samples, left unset.
+    info.push(0, 64);
+    info.push(0, 64);
+
+    writer.write_all(b"fLaC")?;
+    // The last (and only) metadata block, STREAMINFO, and its length.
+    writer.write_all(&[0x80, 0, 0, info.bytes.len() as u8])?;
+    writer.write_all(&info.bytes)?;
+    for (number, block) in samples.chunks(BLOCK_SIZE).enumerate() {
+        writer.write_all(&frame(number as u64, block, bits))?;
+    }
+    writer.flush()
+}
+
+fn frame(number: u64, block: &[i64], bits: u32) -> Vec<u8> {
+    let mut out = Bits::default();
+    out.push(0xfff8, 16); // sync code, and a fixed block size
+    out.push(0b0111, 4); // block size in 16 bits at the end of the header
+    out.push(0, 4); // sample rate as in STREAMINFO
+    out.push(0, 4); // one channel
+    out.push(
+        match bits {
+            8 => 0b001,
+            16 => 0b100,
+            _ => 0b110,
+        },
+        3,
+    );
+    out.push(0, 1);
+    out.push_utf8(number);
+    out.push(block.len() as u64 - 1, 16);
+    let crc = crc8(&out.bytes);
+    out.push(u64::from(crc), 8);
+
+    subframe(&mut out, block, bits);
+    out.align();
+    let crc = crc16(&out.bytes);
+    out.push(u64::from(crc), 16);
+    out.bytes
+}
+
+fn subframe(out: &mut Bits, block: &[i64], bits: u32) {
+    if block.iter().all(|&sample| sample == block[0]) {
+        out.push(0b0000_0000, 8);
+        out.push_signed(block[0], bits);
+        return;
+    }
+
+    // The fixed predictor leaving the smallest residual, then the Rice parameter that codes it
+    // in the fewest bits.
+    let (order, residuals) = (0..=MAX_ORDER.min(block.len() - 1))
+        .map(|order| (order, residuals(block, order)))
+        .min_by_key(|(_, residuals)| residuals.iter().map(|r| r.abs()).sum::<i64>())
+        .expect("there is always order 0");
+    let folded: Vec<u64> = residuals.iter().map(|&r| fold(r)).collect();
+    let (rice, cost) = (0..=MAX_RICE)
+        .map(|k| {
+            let cost: u64 = folded.iter().map(|&u| (u >> k) + 1 + u64::from(k)).sum();
+            (k, cost)
+        })
+        .min_by_key(|&(_, cost)| cost)
+        .expect("there is always parameter 0");
+
+    if cost >= u64::from(bits) * residuals.len() as u64 {
+        out.push(0b0000_0010, 8);
+        for &sample in block {
+            out.push_signed(sample, bits);
+        }
+        return;
+    }
+
+    out.push((0b00_1000 | order as u64) << 1, 8);
+    for &sample in &block[..order] {
+        out.push_signed(sample, bits);
+    }
+    out.push(0, 2); // 4-bit Rice parameters
+    out.push(0, 4); // in a single partition
+    out.push(u64::from(rice), 4);
+    for u in folded {
+        out.push_unary(u >> rice);
+        out.push(u & ((1 << rice) - 1), rice);
+    }
+}
+
+/// What is left of each sample after the fixed predictor of `order`, from sample `order` on.
+fn residuals(block: &[i64], order: usize) -> Vec<i64> {
+    let mut residuals = block.to_vec();
+    for _ in 0..order {
+        residuals = residuals.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    }
+    residuals
+}
+
+/// Maps signed residuals onto unsigned ones: 0, -1, 1, -2 and so on.
+fn fold(residual: i64) -> u64 {
+    ((residual << 1) ^ (residual >> 63)) as u64
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 == 0 {
+                crc << 1
+            } else {
+                crc << 1 ^ 0x07
+            }
+        })
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ u16::from(byte) << 8, |crc, _| {
+            if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                crc << 1 ^ 0x8005
+            }
+        })
+    })
+}
+
+/// Bits written most significant first.
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    partial: u8,
+    len: u32,
+}
+
+impl Bits {
+    fn push(&mut self, value: u64, bits: u32) {
+        for bit in (0..bits).rev() {
+            self.push_bit(value >> bit & 1 == 1);
+        }
+    }
+
+    fn push_signed(&mut self, value: i64, bits: u32) {
+        self.push(value as u64 & ((1 << bits) - 1), bits);
+    }
+
+    fn push_unary(&mut self, zeros: u64) {
+        for _ in 0..zeros {
+            self.push_bit(false);
+        }
+        self.push_bit(true);
+    }
+
+    /// Pushes a number coded the way UTF-8 codes characters, extended to 36 bits.
+    fn push_utf8(&mut self, n: u64) {
+        if n < 0x80 {
+            return self.push(n, 8);
+        }
+        let len = (2..7)
+            .find(|&len| n < 1 << (7 - len + 6 * (len - 1)))
+            .unwrap_or(7);
+        self.push((0xff00 >> len) & 0xff | n >> (6 * (len - 1)), 8);
+        for idx in (0..len - 1).rev() {
+            self.push(0x80 | (n >> (6 * idx)) & 0x3f, 8);
+        }
+    }
+
+    fn push_bit(&mut self, bit: bool) {
+        self.partial = self.partial << 1 | u8::from(bit);
+        self.len += 1;
+        if self.len == 8 {
+            self.bytes.push(self.partial);
+            self.partial = 0;
+            self.len = 0;
+        }
+    }
+
+    /// Pads with zeros to a whole byte.
+    fn align(&mut self) {
+        while self.len != 0 {
+            self.push_bit(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        audio::{self, wav::SampleFormat},
+        timing::{self, Timing},
+    };
+
+    #[test]
+    fn keyed_tone_shrinks() {
+        let encoded = crate::encode("PARIS PARIS").unwrap();
+        let events = timing::to_key_events(&encoded, &Timing::new(20), None);
+        let samples = audio::render(&events, 600.0);
+
+        let mut flac = Vec::new();
+        super::write(&mut flac, &samples, audio::SAMPLE_RATE, SampleFormat::S16).unwrap();
+        assert_eq!(&flac[..4], b"fLaC");
+        // The sample count fills the low 36 bits of bytes 21 to 25.
+        let count = flac[21..26]
+            .iter()
+            .fold(0u64, |count, &byte| count << 8 | u64::from(byte))
+            & 0xf_ffff_ffff;
+        assert_eq!(count, samples.len() as u64);
+        assert!(flac.len() < samples.len() * 2 / 3, "{}", flac.len());
+    }
+
+    #[test]
+    fn codes_frame_numbers_like_utf8() {
+        let mut bits = super::Bits::default();
+        bits.push_utf8(0x7f);
+        bits.push_utf8(0x80);
+        bits.push_utf8(0x1_0000);
+        assert_eq!(bits.bytes, [0x7f, 0xc2, 0x80, 0xf0, 0x90, 0x80, 0x80]);
+    }
+
+    #[test]
+    fn rejects_float_samples() {
+        let result = super::write(Vec::new(), &[0.0], 8000, SampleFormat::F32);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,34 @@
+use std::f64::consts::PI;
+
+/// Measures the strength of a single frequency in a block of samples using the Goertzel
+/// algorithm, which is much cheaper than a full FFT when only one bin is needed.
+pub fn magnitude(samples: &[f32], frequency: f64, sample_rate: u32) -> f64 {
+    let coefficient = 2.0 * (2.0 * PI * frequency / f64::from(sample_rate)).cos();
+    let (mut s1, mut s2) = (0.0, 0.0);
+
+    for &sample in samples {
+        let s0 = f64::from(sample) + coefficient * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+
+    let power = s1 * s1 + s2 * s2 - coefficient * s1 * s2;
+    power.max(0.0).sqrt() / samples.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    #[test]
+    fn magnitude_peaks_at_tone() {
+        let samples: Vec<f32> = (0..800)
+            .map(|n| (2.0 * PI * 600.0 * f64::from(n) / 8000.0).sin() as f32)
+            .collect();
+
+        let on = super::magnitude(&samples, 600.0, 8000);
+        let off = super::magnitude(&samples, 1000.0, 8000);
+        assert!(on > 0.4, "{}", on);
+        assert!(off < 0.05, "{}", off);
+    }
+}
@@ -0,0 +1,223 @@
+//! Raw IQ samples from a software-defined radio, turned into audio for the tone decoder.
+//!
+//! The signal `offset` Hz from the centre frequency is mixed down to a beat note of [`PITCH`] Hz,
+//! as a direct-conversion receiver would, then filtered and decimated to about [`AUDIO_RATE`].
+
+use std::{
+    f64::consts::PI,
+    io::{self, Read},
+    str::FromStr,
+};
+
+/// The rate audio is decimated to, give or take: IQ rates are divided by a whole number.
+pub const AUDIO_RATE: u32 = 8_000;
+
+/// The pitch of the beat note the signal is heard at.
+pub const PITCH: f64 = 600.0;
+
+/// Cutoff of the audio low-pass filter, in Hz: wide enough for the beat note and the sidebands of
+/// fast keying, narrow enough to leave out most of the band.
+const CUTOFF: f64 = 1_200.0;
+
+/// How many IQ pairs to read at a time.
+const CHUNK: usize = 1 << 16;
+
+/// How IQ samples are stored: interleaved I and Q, in one of these sample types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Unsigned bytes centred on 127.5, as written by `rtl_sdr`.
+    U8,
+    /// Signed 16-bit little-endian integers.
+    S16,
+    /// 32-bit little-endian floats.
+    F32,
+}
+
+impl Format {
+    fn sample_len(self) -> usize {
+        match self {
+            Format::U8 => 1,
+            Format::S16 => 2,
+            Format::F32 => 4,
+        }
+    }
+
+    fn sample(self, bytes: &[u8]) -> f32 {
+        match self {
+            Format::U8 => (f32::from(bytes[0]) - 127.5) / 127.5,
+            Format::S16 => f32::from(i16::from_le_bytes([bytes[0], bytes[1]])) / 32768.0,
+            Format::F32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "u8" => Ok(Format::U8),
+            "s16" => Ok(Format::S16),
+            "f32" => Ok(Format::F32),
+            _ => Err(format!(
+                "unknown IQ format {:?} (expected u8, s16 or f32)",
+                s
+            )),
+        }
+    }
+}
+
+/// Mixes, filters and decimates IQ samples as they arrive.
+pub struct Demodulator {
+    /// Phase step of the local oscillator per IQ sample.
+    step: f64,
+    phase: f64,
+    decimation: usize,
+    audio_rate: u32,
+    /// The running sum and count of mixed samples toward the next audio sample.
+    sum: (f64, f64),
+    count: usize,
+    /// Two one-pole low-pass stages at the audio rate, and their coefficient.
+    stages: [(f64, f64); 2],
+    alpha: f64,
+}
+
+impl Demodulator {
+    /// Creates a demodulator for IQ at `sample_rate`, listening `offset` Hz from the centre.
+    pub fn new(sample_rate: u32, offset: f64) -> Self {
+        let decimation = (sample_rate / AUDIO_RATE).max(1);
+        let audio_rate = sample_rate / decimation;
+        Demodulator {
+            step: -2.0 * PI * (offset - PITCH) / f64::from(sample_rate),
+            phase: 0.0,
+            decimation: decimation as usize,
+            audio_rate,
+            sum: (0.0, 0.0),
+            count: 0,
+            stages: [(0.0, 0.0); 2],
+            alpha: 1.0 - (-2.0 * PI * CUTOFF / f64::from(audio_rate)).exp(),
+        }
+    }
+
+    /// The rate of the audio produced.
+    pub fn audio_rate(&self) -> u32 {
+        self.audio_rate
+    }
+
+    /// Demodulates IQ pairs, appending any audio completed to `audio`.
+    pub fn push(&mut self, iq: &[(f32, f32)], audio: &mut Vec<f32>) {
+        for &(i, q) in iq {
+            let (sin, cos) = self.phase.sin_cos();
+            let (i, q) = (f64::from(i), f64::from(q));
+            self.sum.0 += i * cos - q * sin;
+            self.sum.1 += i * sin + q * cos;
+            self.phase = (self.phase + self.step) % (2.0 * PI);
+
+            self.count += 1;
+            if self.count == self.decimation {
+                let mut sample = (
+                    self.sum.0 / self.count as f64,
+                    self.sum.1 / self.count as f64,
+                );
+                for stage in &mut self.stages {
+                    stage.0 += self.alpha * (sample.0 - stage.0);
+                    stage.1 += self.alpha * (sample.1 - stage.1);
+                    sample = *stage;
+                }
+                audio.push(sample.0 as f32);
+                self.sum = (0.0, 0.0);
+                self.count = 0;
+            }
+        }
+    }
+}
+
+/// Reads IQ samples at `sample_rate` until the end of `reader`, returning audio of the signal
+/// `offset` Hz from the centre and its sample rate.
+pub fn read(
+    mut reader: impl Read,
+    format: Format,
+    sample_rate: u32,
+    offset: f64,
+) -> io::Result<(Vec<f32>, u32)> {
+    let mut demodulator = Demodulator::new(sample_rate, offset);
+    let pair_len = 2 * format.sample_len();
+    let mut buf = vec![0; CHUNK * pair_len];
+    let mut filled = 0;
+    let mut iq = Vec::with_capacity(CHUNK);
+    let mut audio = Vec::new();
+
+    loop {
+        let n = match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        filled += n;
+
+        let whole = filled - filled % pair_len;
+        iq.clear();
+        iq.extend(buf[..whole].chunks(pair_len).map(|pair| {
+            let (i, q) = pair.split_at(format.sample_len());
+            (format.sample(i), format.sample(q))
+        }));
+        demodulator.push(&iq, &mut audio);
+
+        buf.copy_within(whole..filled, 0);
+        filled -= whole;
+    }
+
+    Ok((audio, demodulator.audio_rate()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::Format;
+    use crate::{audio, timing, KeyEvent, Timing};
+
+    #[test]
+    fn decodes_offset_signal() {
+        let rate = 96_000;
+        let offset = 5_000.0;
+        let events = timing::to_key_events(&crate::encode("CQ").unwrap(), &Timing::new(20), None);
+
+        // The keyed carrier, plus a steady one elsewhere in the band that should be filtered out,
+        // as `rtl_sdr` would record them.
+        let mut bytes = Vec::new();
+        let mut n = 0;
+        for event in events {
+            let len = (event.duration().as_secs_f64() * f64::from(rate)) as usize;
+            let carrier = match event {
+                KeyEvent::Down(_) => 0.4,
+                KeyEvent::Up(_) => 0.0,
+            };
+            for _ in 0..len {
+                let t = n as f64 / f64::from(rate);
+                let (signal, interference) = (2.0 * PI * offset * t, -2.0 * PI * 20_000.0 * t);
+                let i = carrier * signal.cos() + 0.3 * interference.cos();
+                let q = carrier * signal.sin() + 0.3 * interference.sin();
+                for value in [i, q] {
+                    bytes.push(((value + 1.0) * 127.5).round() as u8);
+                }
+                n += 1;
+            }
+        }
+
+        let (samples, sample_rate) = super::read(&bytes[..], Format::U8, rate, offset).unwrap();
+        assert_eq!(sample_rate, rate / 12);
+        assert_eq!(
+            audio::decode(&samples, sample_rate, &Default::default()).unwrap(),
+            "CQ"
+        );
+    }
+
+    #[test]
+    fn parses_formats() {
+        assert_eq!("u8".parse(), Ok(Format::U8));
+        assert_eq!("S16".parse(), Ok(Format::S16));
+        assert!("cu8".parse::<Format>().is_err());
+    }
+}
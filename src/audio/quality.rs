@@ -0,0 +1,106 @@
+//! How good a recording is: what the decoder had to work with, for judging how far to trust it.
+
+use std::fmt;
+
+use super::detect::{self, Detector};
+use crate::timing::{self, KeyEvent};
+
+/// The highest signal-to-noise ratio reported, in dB. Synthesized audio has no noise at all.
+const MAX_SNR: f64 = 60.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quality {
+    /// Frequency of the keyed tone, in Hz.
+    pub tone: f64,
+    /// Power of the tone while keyed against what is left while it is not, in dB.
+    pub snr: f64,
+    pub wpm: f64,
+    /// From 0 to 1, as in [`timing::Rhythm`].
+    pub consistency: f64,
+}
+
+impl Quality {
+    /// Measures a recording whose `tone` has already been found and decoded into `events` by
+    /// `detector`.
+    pub fn measure(
+        samples: &[f32],
+        sample_rate: u32,
+        tone: f64,
+        events: &[KeyEvent],
+        detector: &Detector,
+    ) -> Self {
+        let rhythm = timing::rhythm(events);
+        Quality {
+            tone,
+            snr: snr(&detector.magnitudes(samples, sample_rate, tone)),
+            wpm: rhythm.map_or(0.0, |rhythm| rhythm.wpm),
+            consistency: rhythm.map_or(0.0, |rhythm| rhythm.consistency),
+        }
+    }
+}
+
+impl fmt::Display for Quality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tone {:.0} Hz, SNR {:.1} dB, {:.1} WPM, timing {:.0}% consistent",
+            self.tone,
+            self.snr,
+            self.wpm,
+            self.consistency * 100.0
+        )
+    }
+}
+
+fn snr(magnitudes: &[f64]) -> f64 {
+    let threshold = match detect::threshold(magnitudes) {
+        Some(threshold) => threshold,
+        None => return 0.0,
+    };
+
+    let power = |keyed: bool| {
+        let blocks: Vec<f64> = magnitudes
+            .iter()
+            .filter(|&&m| (m > threshold) == keyed)
+            .map(|m| m * m)
+            .collect();
+        blocks.iter().sum::<f64>() / blocks.len().max(1) as f64
+    };
+    let (signal, noise) = (power(true), power(false));
+
+    if noise <= signal * 10f64.powf(-MAX_SNR / 10.0) {
+        MAX_SNR
+    } else {
+        10.0 * (signal / noise).log10()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        audio,
+        rng::Rng,
+        timing::{self, Timing},
+    };
+
+    #[test]
+    fn noise_lowers_the_measured_quality() {
+        let encoded = crate::encode("CQ DX").unwrap();
+        let events = timing::to_key_events(&encoded, &Timing::new(20), None);
+        let clean = audio::render(&events, 600.0);
+        let mut rng = Rng::new(5);
+        let noisy: Vec<f32> = clean
+            .iter()
+            .map(|sample| sample + (rng.next_f64() as f32 - 0.5) * 0.6)
+            .collect();
+
+        let clean = audio::quality(&clean, audio::SAMPLE_RATE, &Default::default()).unwrap();
+        assert!((clean.tone - 600.0).abs() <= 10.0, "{}", clean);
+        assert!((clean.wpm - 20.0).abs() < 1.0, "{}", clean);
+        assert!(clean.consistency > 0.9, "{}", clean);
+
+        let noisy = audio::quality(&noisy, audio::SAMPLE_RATE, &Default::default()).unwrap();
+        assert!(noisy.snr < clean.snr, "{} vs {}", noisy, clean);
+        assert!(noisy.snr > 3.0, "{}", noisy);
+    }
+}
@@ -0,0 +1,109 @@
+//! A telegraph sounder, as landline Morse was copied by ear: there is no tone, only the click of
+//! the armature pulled down onto its anvil as the key closes and the lighter clack of it springing
+//! back up as the key opens. Dots and dashes are told apart by the time between the two.
+
+use std::f64::consts::PI;
+
+use super::Voice;
+use crate::{rng::Rng, KeyEvent};
+
+/// A ringing part of a strike: frequency in Hz, relative amplitude and decay time in seconds.
+type Mode = (f64, f64, f64);
+
+/// The armature striking down: heavy, with the frame of the sounder ringing under it.
+const DOWN: [Mode; 3] = [
+    (420.0, 0.5, 0.030),
+    (1_150.0, 1.0, 0.012),
+    (2_900.0, 0.6, 0.006),
+];
+
+/// The armature springing back against the upper stop: lighter and brighter.
+const UP: [Mode; 3] = [
+    (600.0, 0.2, 0.015),
+    (1_700.0, 1.0, 0.008),
+    (3_600.0, 0.5, 0.004),
+];
+
+/// How loud the clack of the armature going up is next to the click of it going down.
+const UP_LEVEL: f64 = 0.6;
+
+/// Renders key events as a sounder would sound them, at the sample rate and volume of `voice`.
+/// The recording runs on past the last event for as long as its clack takes to die away.
+pub fn render(events: &[KeyEvent], voice: &Voice) -> Vec<f32> {
+    let rate = f64::from(voice.sample_rate);
+    let mut rng = Rng::new(1);
+    let down = strike(&DOWN, 1.0, rate, &mut rng);
+    let up = strike(&UP, UP_LEVEL, rate, &mut rng);
+
+    let mut onsets = Vec::new();
+    let mut len = 0;
+    for &event in events {
+        if let KeyEvent::Down(duration) = event {
+            let n = (duration.as_secs_f64() * rate).round() as usize;
+            onsets.push((len, &down));
+            onsets.push((len + n, &up));
+        }
+        len += (event.duration().as_secs_f64() * rate).round() as usize;
+    }
+
+    let mut samples = vec![0.0f32; len + up.len()];
+    for (start, sound) in onsets {
+        for (sample, &value) in samples[start..].iter_mut().zip(sound.iter()) {
+            *sample += value * voice.volume as f32;
+        }
+    }
+    // Strikes closer together than they take to ring out can add up past full scale.
+    for sample in &mut samples {
+        *sample = sample.clamp(-1.0, 1.0);
+    }
+    samples
+}
+
+/// One strike of the armature: a scrape of noise as it lands, then its modes ringing down, scaled
+/// so that its peak is `level`.
+fn strike(modes: &[Mode], level: f64, rate: f64, rng: &mut Rng) -> Vec<f32> {
+    let longest = modes.iter().map(|&(_, _, decay)| decay).fold(0.0, f64::max);
+    let len = (longest * 5.0 * rate) as usize;
+    let sound: Vec<f64> = (0..len)
+        .map(|n| {
+            let t = n as f64 / rate;
+            let ring: f64 = modes
+                .iter()
+                .map(|&(frequency, amplitude, decay)| {
+                    amplitude * (-t / decay).exp() * (2.0 * PI * frequency * t).sin()
+                })
+                .sum();
+            ring + 0.4 * (-t / 0.002).exp() * (rng.next_f64() * 2.0 - 1.0)
+        })
+        .collect();
+
+    let peak = sound.iter().fold(0.0, |peak: f64, s| peak.max(s.abs()));
+    sound.iter().map(|s| (s / peak * level) as f32).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{american, audio::Voice, Timing};
+
+    #[test]
+    fn clicks_as_the_key_closes_and_opens() {
+        let voice = Voice::default();
+        let encoded = american::encode_with("L", &Default::default()).unwrap();
+        let events = american::to_key_events(&encoded, &Timing::new(10), None);
+        let samples = super::render(&events, &voice);
+
+        let ms = |ms: usize| ms * voice.sample_rate as usize / 1000;
+        let peak = |range: std::ops::Range<usize>| {
+            samples[range]
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+        };
+        // L is one long dash, 480ms at 10 WPM: a click, silence, and a quieter clack.
+        let click = peak(0..ms(20));
+        let clack = peak(ms(480)..ms(500));
+        assert!((click - voice.volume as f32).abs() < 0.01, "{}", click);
+        assert!(clack > 0.3 && clack < click, "{}", clack);
+        assert!(peak(ms(200)..ms(400)) < 0.001);
+        assert!(samples.len() > ms(480));
+    }
+}
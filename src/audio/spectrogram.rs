@@ -0,0 +1,172 @@
+//! Spectrograms of recordings as a detector hears them, with the keying it detected drawn in a
+//! strip underneath, to show why a stretch of a recording did or did not decode.
+
+use super::detect::Detector;
+use crate::image::Image;
+
+/// The band shown, from low to high, and the spacing of its rows, in Hz.
+const BAND: (u32, u32, u32) = (200, 2000, 20);
+
+/// Height in pixels of each row of the band, and of the keying strip.
+const ROW_HEIGHT: u32 = 2;
+const STRIP_HEIGHT: u32 = 8;
+
+/// Width in pixels of the mark at either end of the row of the keyed tone.
+const TONE_MARK: u32 = 6;
+
+/// The widest a spectrogram gets; longer recordings put several blocks in each column.
+const MAX_WIDTH: usize = 4000;
+
+/// How far below the loudest point the scale reaches, in dB. Anything quieter is black.
+const RANGE: f64 = 60.0;
+
+/// Palette entries given to the heat scale, and those after it.
+const SHADES: u8 = 252;
+const KEYED: u8 = SHADES;
+const UNKEYED: u8 = SHADES + 1;
+const TONE: u8 = SHADES + 2;
+
+/// Colors along the heat scale, from silence to the loudest.
+const HEAT: [[u8; 3]; 5] = [
+    [0, 0, 0],
+    [30, 0, 140],
+    [200, 0, 110],
+    [255, 190, 0],
+    [255, 255, 255],
+];
+
+/// Draws the spectrum of a recording over time, one column per block of `detector`, highest
+/// frequencies at the top. Underneath, the keying detected at the tone `detector` listens for is
+/// drawn green where the key was down, and that tone's row is marked red at either end.
+pub fn render(samples: &[f32], sample_rate: u32, detector: &Detector) -> Image {
+    let tone = detector.tone(samples, sample_rate);
+    let keyed = tone
+        .map(|tone| detector.keying(samples, sample_rate, tone))
+        .unwrap_or_default();
+
+    let (low, high, step) = BAND;
+    let frequencies: Vec<u32> = (low..=high).rev().step_by(step as usize).collect();
+    let blocks = samples.len().div_ceil(detector.block_len(sample_rate));
+    let per_column = blocks.div_ceil(MAX_WIDTH).max(1);
+    let width = blocks.div_ceil(per_column).max(1);
+
+    // Each row as the loudest of the blocks in each column.
+    let rows: Vec<Vec<f64>> = frequencies
+        .iter()
+        .map(|&frequency| {
+            detector
+                .magnitudes(samples, sample_rate, f64::from(frequency))
+                .chunks(per_column)
+                .map(|blocks| blocks.iter().copied().fold(0.0, f64::max))
+                .collect()
+        })
+        .collect();
+    let peak = rows.iter().flatten().copied().fold(0.0, f64::max);
+    let tone_row = tone.and_then(|tone| {
+        frequencies
+            .iter()
+            .position(|&frequency| (f64::from(frequency) - tone).abs() <= f64::from(step) / 2.0)
+    });
+
+    let mut pixels = Vec::new();
+    for (idx, row) in rows.iter().enumerate() {
+        let line: Vec<u8> = (0..width)
+            .map(|column| {
+                let at_edge = column < TONE_MARK as usize || column + TONE_MARK as usize >= width;
+                if tone_row == Some(idx) && at_edge {
+                    return TONE;
+                }
+                let magnitude = row.get(column).copied().unwrap_or(0.0);
+                shade(magnitude, peak)
+            })
+            .collect();
+        for _ in 0..ROW_HEIGHT {
+            pixels.extend_from_slice(&line);
+        }
+    }
+    let strip: Vec<u8> = (0..width)
+        .map(|column| {
+            let start = (column * per_column).min(keyed.len());
+            let end = (start + per_column).min(keyed.len());
+            if keyed[start..end].iter().any(|&down| down) {
+                KEYED
+            } else {
+                UNKEYED
+            }
+        })
+        .collect();
+    for _ in 0..STRIP_HEIGHT {
+        pixels.extend_from_slice(&strip);
+    }
+
+    Image {
+        width: width as u32,
+        height: frequencies.len() as u32 * ROW_HEIGHT + STRIP_HEIGHT,
+        palette: palette(),
+        pixels,
+    }
+}
+
+/// The heat scale entry for a magnitude, on a logarithmic scale below the loudest.
+fn shade(magnitude: f64, peak: f64) -> u8 {
+    if magnitude <= 0.0 || peak <= 0.0 {
+        return 0;
+    }
+    let db = 20.0 * (magnitude / peak).log10();
+    let level = ((db + RANGE) / RANGE).clamp(0.0, 1.0);
+    (level * f64::from(SHADES - 1)).round() as u8
+}
+
+fn palette() -> Vec<[u8; 3]> {
+    let mut palette: Vec<[u8; 3]> = (0..SHADES)
+        .map(|shade| {
+            let position = f64::from(shade) / f64::from(SHADES - 1) * (HEAT.len() - 1) as f64;
+            let idx = (position as usize).min(HEAT.len() - 2);
+            let mix = position - idx as f64;
+            let mut color = [0; 3];
+            for (channel, color) in color.iter_mut().enumerate() {
+                let (from, to) = (
+                    f64::from(HEAT[idx][channel]),
+                    f64::from(HEAT[idx + 1][channel]),
+                );
+                *color = (from + (to - from) * mix).round() as u8;
+            }
+            color
+        })
+        .collect();
+    palette.push([0, 200, 60]);
+    palette.push([60, 60, 60]);
+    palette.push([230, 30, 30]);
+    palette
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        audio,
+        timing::{self, Timing},
+    };
+
+    #[test]
+    fn shows_the_tone_and_its_keying() {
+        let encoded = crate::encode("T T").unwrap();
+        let events = timing::to_key_events(&encoded, &Timing::new(20), None);
+        let samples = audio::render(&events, 600.0);
+        let image = super::render(&samples, audio::SAMPLE_RATE, &Default::default());
+
+        // 91 rows of the band, two pixels each, and the strip.
+        assert_eq!(image.height, 91 * 2 + 8);
+        let width = image.width as usize;
+        let pixel = |x: usize, y: usize| image.pixels[y * width + x];
+        let strip = (image.height - 1) as usize;
+        // The first dah takes 180 ms, 36 blocks of 5 ms, and the word gap 420 ms after it.
+        assert_eq!(pixel(10, strip), super::KEYED);
+        assert_eq!(pixel(36 + 40, strip), super::UNKEYED);
+
+        // 600 Hz is 70 rows down from 2000 Hz, and loudest in the middle of the dah.
+        let row = 70 * 2;
+        assert_eq!(pixel(0, row), super::TONE);
+        assert!(pixel(20, row) >= super::SHADES - 4);
+        assert!(pixel(36 + 40, row) < super::SHADES / 2);
+    }
+}
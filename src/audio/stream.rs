@@ -0,0 +1,217 @@
+//! Incremental decoding of live audio.
+
+use super::{detect, goertzel};
+
+/// How much audio to collect before guessing the tone, when it was not given.
+const CALIBRATION_SECS: u32 = 2;
+
+/// Per-block decay of the tracked signal peak, so that the detector follows fading signals.
+const PEAK_DECAY: f64 = 0.999;
+
+/// Per-block rate at which the tracked noise floor rises toward louder input.
+const FLOOR_RISE: f64 = 0.001;
+
+/// Decodes a keyed tone from audio arriving in arbitrary chunks, emitting each character as soon
+/// as the gap after it is long enough to be sure it has ended.
+///
+/// Unlike `audio::decode`, nothing is known about the whole recording in advance, so the signal
+/// level and keying speed are tracked as they go.
+pub struct StreamDecoder {
+    sample_rate: u32,
+    tone: Option<f64>,
+    buffered: Vec<f32>,
+    floor: Option<f64>,
+    peak: f64,
+    down: bool,
+    run: u32,
+    flips: u32,
+    unit: Option<f64>,
+    elements: Vec<f64>,
+    word_pending: bool,
+}
+
+impl StreamDecoder {
+    /// Creates a decoder listening for `tone` Hz, or for whichever tone is loudest in the first
+    /// couple of seconds of audio if `tone` is `None`.
+    pub fn new(sample_rate: u32, tone: Option<f64>) -> Self {
+        StreamDecoder {
+            sample_rate,
+            tone,
+            buffered: Vec::new(),
+            floor: None,
+            peak: 0.0,
+            down: false,
+            run: 0,
+            flips: 0,
+            unit: None,
+            elements: Vec::new(),
+            word_pending: false,
+        }
+    }
+
+    /// The tone being decoded, once known.
+    pub fn tone(&self) -> Option<f64> {
+        self.tone
+    }
+
+    /// The sender's speed in WPM, as tracked so far.
+    pub fn wpm(&self) -> Option<f64> {
+        let block = detect::block_len(self.sample_rate) as f64 / f64::from(self.sample_rate);
+        self.unit.map(|unit| 1.2 / (unit * block))
+    }
+
+    /// Feeds more audio to the decoder, returning any text decoded as a result.
+    pub fn push(&mut self, samples: &[f32]) -> String {
+        self.buffered.extend_from_slice(samples);
+
+        let tone = match self.tone {
+            Some(tone) => tone,
+            None if self.buffered.len() < (self.sample_rate * CALIBRATION_SECS) as usize => {
+                return String::new();
+            }
+            None => match detect::find_tone(&self.buffered, self.sample_rate) {
+                Some(tone) => *self.tone.get_or_insert(tone),
+                None => {
+                    self.buffered.clear();
+                    return String::new();
+                }
+            },
+        };
+
+        let block = detect::block_len(self.sample_rate);
+        let mut output = String::new();
+        let mut consumed = 0;
+
+        while self.buffered.len() - consumed >= block {
+            let chunk = &self.buffered[consumed..consumed + block];
+            let magnitude = goertzel::magnitude(chunk, tone, self.sample_rate);
+            self.step(magnitude, &mut output);
+            consumed += block;
+        }
+
+        self.buffered.drain(..consumed);
+        output
+    }
+
+    /// Decodes whatever character is still pending at the end of the stream.
+    pub fn finish(&mut self) -> String {
+        let mut output = String::new();
+        if self.down {
+            self.end_element(self.run);
+        }
+        self.emit_symbol(&mut output);
+        output
+    }
+
+    fn step(&mut self, magnitude: f64, output: &mut String) {
+        let floor = match self.floor {
+            Some(floor) if magnitude < floor => magnitude,
+            Some(floor) => floor + (magnitude - floor) * FLOOR_RISE,
+            None => magnitude,
+        };
+        self.floor = Some(floor);
+        self.peak = magnitude.max(self.peak * PEAK_DECAY);
+
+        let keyed = self.peak > floor * 3.0 && magnitude > (floor + self.peak) / 2.0;
+
+        // A state change only counts once it has lasted two blocks, to ride out noise.
+        if keyed == self.down {
+            self.run += self.flips + 1;
+            self.flips = 0;
+        } else {
+            self.flips += 1;
+            if self.flips >= 2 {
+                if self.down {
+                    self.end_element(self.run);
+                }
+                self.down = keyed;
+                self.run = self.flips;
+                self.flips = 0;
+            }
+        }
+
+        if !self.down {
+            self.check_gap(output);
+        }
+    }
+
+    fn end_element(&mut self, len: u32) {
+        let len = f64::from(len);
+        let unit = match self.unit {
+            Some(unit) if len >= unit * 0.5 => unit,
+            _ => len,
+        };
+
+        let units = if len < unit * 2.0 { 1.0 } else { 3.0 };
+        self.unit = Some(unit * 0.8 + len / units * 0.2);
+        self.elements.push(len);
+    }
+
+    fn check_gap(&mut self, output: &mut String) {
+        let unit = match self.unit {
+            Some(unit) => unit,
+            None => return,
+        };
+
+        let gap = f64::from(self.run);
+        if gap >= unit * 2.0 && !self.elements.is_empty() {
+            self.emit_symbol(output);
+            self.word_pending = true;
+        }
+
+        if gap >= unit * 5.0 && self.word_pending {
+            output.push(' ');
+            self.word_pending = false;
+        }
+    }
+
+    /// Elements are only classified once the character is complete, so that a character which
+    /// began before the speed was known (e.g. with a dah) is read with the corrected estimate.
+    fn emit_symbol(&mut self, output: &mut String) {
+        let unit = match self.unit {
+            Some(unit) if !self.elements.is_empty() => unit,
+            _ => return,
+        };
+
+        let symbol: String = self
+            .elements
+            .drain(..)
+            .map(|len| if len < unit * 2.0 { '.' } else { '-' })
+            .collect();
+
+        match crate::decode(&symbol) {
+            Ok(text) => output.push_str(&text),
+            Err(_) => output.push('#'),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        audio,
+        timing::{self, Timing},
+    };
+
+    #[test]
+    fn decodes_audio_in_chunks() {
+        let encoded = crate::encode("CQ DE TEST").unwrap();
+        let events = timing::to_key_events(&encoded, &Timing::new(22), None);
+
+        let mut samples = vec![0.0; audio::SAMPLE_RATE as usize / 2];
+        samples.extend(audio::render(&events, 650.0));
+        samples.extend(vec![0.0; audio::SAMPLE_RATE as usize / 2]);
+
+        let mut decoder = super::StreamDecoder::new(audio::SAMPLE_RATE, None);
+        let mut text = String::new();
+        for chunk in samples.chunks(1024) {
+            text.push_str(&decoder.push(chunk));
+        }
+        text.push_str(&decoder.finish());
+
+        assert_eq!(text.trim_end(), "CQ DE TEST");
+        assert!((decoder.tone().unwrap() - 650.0).abs() <= 10.0);
+        let wpm = decoder.wpm().unwrap();
+        assert!((wpm - 22.0).abs() <= 2.0, "{}", wpm);
+    }
+}
@@ -0,0 +1,30 @@
+//! Ogg Vorbis output, through libvorbis: lossy, and smaller again than FLAC for long sessions.
+
+use std::{
+    io::{self, Write},
+    num::{NonZeroU32, NonZeroU8},
+};
+
+use vorbis_rs::{VorbisEncoderBuilder, VorbisError};
+
+/// Samples handed to the encoder at a time.
+const CHUNK: usize = 4096;
+
+/// Writes mono samples as an Ogg Vorbis file at the encoder's default quality.
+pub fn write<W: Write>(writer: W, samples: &[f32], sample_rate: u32) -> io::Result<()> {
+    let sample_rate = NonZeroU32::new(sample_rate)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "sample rate of 0"))?;
+    let channels = NonZeroU8::new(1).expect("one is not zero");
+
+    let mut encoder = VorbisEncoderBuilder::new(sample_rate, channels, writer)
+        .and_then(|mut builder| builder.build())
+        .map_err(vorbis_error)?;
+    for chunk in samples.chunks(CHUNK) {
+        encoder.encode_audio_block([chunk]).map_err(vorbis_error)?;
+    }
+    encoder.finish().map_err(vorbis_error)?.flush()
+}
+
+fn vorbis_error(e: VorbisError) -> io::Error {
+    io::Error::other(format!("vorbis: {}", e))
+}
@@ -0,0 +1,334 @@
+use std::{
+    convert::TryFrom,
+    io::{self, Read, Write},
+    str::FromStr,
+};
+
+/// How samples are stored in a WAV file written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleFormat {
+    /// Unsigned bytes, as telephony systems often want.
+    U8,
+    /// Signed 16-bit integers, which nearly everything reads.
+    S16,
+    /// Signed 24-bit integers.
+    S24,
+    /// 32-bit floats.
+    F32,
+}
+
+impl SampleFormat {
+    fn bits(self) -> u16 {
+        match self {
+            SampleFormat::U8 => 8,
+            SampleFormat::S16 => 16,
+            SampleFormat::S24 => 24,
+            SampleFormat::F32 => 32,
+        }
+    }
+
+    /// Encodes `sample` into the start of `buf`, returning its length.
+    fn encode(self, sample: f32, buf: &mut [u8; 4]) -> usize {
+        let sample = sample.clamp(-1.0, 1.0);
+        match self {
+            SampleFormat::U8 => buf[0] = (sample * 127.0 + 128.0).round() as u8,
+            SampleFormat::S16 => {
+                buf[..2].copy_from_slice(&((sample * f32::from(i16::MAX)) as i16).to_le_bytes())
+            }
+            SampleFormat::S24 => buf[..3]
+                .copy_from_slice(&((f64::from(sample) * 8_388_607.0) as i32).to_le_bytes()[..3]),
+            SampleFormat::F32 => *buf = sample.to_le_bytes(),
+        }
+        usize::from(self.bits() / 8)
+    }
+}
+
+impl FromStr for SampleFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "u8" => Ok(SampleFormat::U8),
+            "s16" => Ok(SampleFormat::S16),
+            "s24" => Ok(SampleFormat::S24),
+            "f32" => Ok(SampleFormat::F32),
+            _ => Err(format!(
+                "unknown sample format {:?} (expected u8, s16, s24 or f32)",
+                s
+            )),
+        }
+    }
+}
+
+/// Writes mono samples as a 16-bit PCM WAV file.
+pub fn write<W: Write>(writer: W, samples: &[f32], sample_rate: u32) -> io::Result<()> {
+    write_as(writer, samples, sample_rate, SampleFormat::S16)
+}
+
+/// Writes mono samples as a WAV file in `format`.
+pub fn write_as<W: Write>(
+    writer: W,
+    samples: &[f32],
+    sample_rate: u32,
+    format: SampleFormat,
+) -> io::Result<()> {
+    write_stream(writer, samples.iter().copied(), sample_rate, format)
+}
+
+/// Writes mono samples as a WAV file in `format` as they come, without holding them all. Fails
+/// without writing anything if there are too many for the 4 GiB a WAV file can hold.
+pub fn write_stream<W: Write>(
+    mut writer: W,
+    samples: impl ExactSizeIterator<Item = f32>,
+    sample_rate: u32,
+    format: SampleFormat,
+) -> io::Result<()> {
+    const CHANNELS: u16 = 1;
+
+    let block_align = CHANNELS * format.bits() / 8;
+    let byte_rate = sample_rate * u32::from(block_align);
+    let too_long = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "too many samples for a WAV file",
+        )
+    };
+    let count = u32::try_from(samples.len()).map_err(|_| too_long())?;
+    let data_len = count
+        .checked_mul(u32::from(block_align))
+        .ok_or_else(too_long)?;
+    // Formats other than integer PCM take a size for the (empty) format extension, and a fact
+    // chunk giving the number of samples.
+    let float = format == SampleFormat::F32;
+    let (fmt_len, fact_len) = if float { (18, 12) } else { (16, 0) };
+    let riff_len = data_len
+        .checked_add(4 + (8 + fmt_len) + fact_len + 8 + data_len % 2)
+        .ok_or_else(too_long)?;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&riff_len.to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&fmt_len.to_le_bytes())?;
+    writer.write_all(&(if float { 3u16 } else { 1 }).to_le_bytes())?;
+    writer.write_all(&CHANNELS.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&byte_rate.to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&format.bits().to_le_bytes())?;
+    if float {
+        writer.write_all(&0u16.to_le_bytes())?;
+        writer.write_all(b"fact")?;
+        writer.write_all(&4u32.to_le_bytes())?;
+        writer.write_all(&count.to_le_bytes())?;
+    }
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data_len.to_le_bytes())?;
+    write_raw(&mut writer, samples, format)?;
+    // Chunks are padded to an even length.
+    if data_len % 2 == 1 {
+        writer.write_all(&[0])?;
+    }
+
+    writer.flush()
+}
+
+/// Writes samples as they come with no header at all: little-endian PCM in `format`, as `aplay`,
+/// `sox` and the like take on standard input.
+pub fn write_raw<W: Write>(
+    mut writer: W,
+    samples: impl IntoIterator<Item = f32>,
+    format: SampleFormat,
+) -> io::Result<()> {
+    let mut buf = [0; 4];
+    for sample in samples {
+        let len = format.encode(sample, &mut buf);
+        writer.write_all(&buf[..len])?;
+    }
+    writer.flush()
+}
+
+/// Reads a PCM (8, 16, 24 or 32-bit integer, or 32-bit float) WAV file, mixing all channels down
+/// to mono. Returns the samples and the sample rate.
+pub fn read<R: Read>(mut reader: R) -> io::Result<(Vec<f32>, u32)> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+
+    if buf.len() < 12 || &buf[..4] != b"RIFF" || &buf[8..12] != b"WAVE" {
+        return Err(invalid("not a WAV file"));
+    }
+
+    let mut format = None;
+    let mut rest = &buf[12..];
+    while rest.len() >= 8 {
+        let id = &rest[..4];
+        let len = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let body = rest.get(8..8 + len).unwrap_or(&rest[8..]);
+
+        match id {
+            b"fmt " => format = Some(Format::parse(body)?),
+            b"data" => {
+                let format = format.ok_or_else(|| invalid("data chunk before fmt chunk"))?;
+                return Ok((format.decode(body), format.sample_rate));
+            }
+            _ => (),
+        }
+
+        // Chunks are padded to an even length.
+        let next = (8 + len + len % 2).min(rest.len());
+        rest = &rest[next..];
+    }
+
+    Err(invalid("missing data chunk"))
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Format {
+    float: bool,
+    channels: usize,
+    sample_rate: u32,
+    bits_per_sample: u16,
+}
+
+impl Format {
+    fn parse(chunk: &[u8]) -> io::Result<Format> {
+        if chunk.len() < 16 {
+            return Err(invalid("truncated fmt chunk"));
+        }
+
+        let field = |idx: usize| u16::from_le_bytes([chunk[idx], chunk[idx + 1]]);
+        let mut tag = field(0);
+        if tag == 0xfffe && chunk.len() >= 26 {
+            // WAVE_FORMAT_EXTENSIBLE keeps the real format at the start of the subformat GUID.
+            tag = field(24);
+        }
+
+        let format = Format {
+            float: tag == 3,
+            channels: usize::from(field(2).max(1)),
+            sample_rate: u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]),
+            bits_per_sample: field(14),
+        };
+
+        match (tag, format.bits_per_sample) {
+            (1, 8) | (1, 16) | (1, 24) | (1, 32) | (3, 32) => Ok(format),
+            _ => Err(invalid("unsupported sample format")),
+        }
+    }
+
+    fn decode(&self, data: &[u8]) -> Vec<f32> {
+        let width = usize::from(self.bits_per_sample / 8);
+        data.chunks_exact(width * self.channels)
+            .map(|frame| {
+                let sum: f32 = frame.chunks_exact(width).map(|s| self.sample(s)).sum();
+                sum / self.channels as f32
+            })
+            .collect()
+    }
+
+    fn sample(&self, bytes: &[u8]) -> f32 {
+        match (self.float, bytes.len()) {
+            (true, _) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            (false, 1) => (f32::from(bytes[0]) - 128.0) / 128.0,
+            (false, 2) => f32::from(i16::from_le_bytes([bytes[0], bytes[1]])) / 32_768.0,
+            (false, 3) => {
+                let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+                value as f32 / 8_388_608.0
+            }
+            (false, _) => {
+                let value = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                value as f32 / 2_147_483_648.0
+            }
+        }
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SampleFormat;
+
+    #[test]
+    fn header_describes_samples() {
+        let mut buf = Vec::new();
+        super::write(&mut buf, &[0.0, 1.0, -1.0], 8000).unwrap();
+
+        assert_eq!(buf.len(), 44 + 6);
+        assert_eq!(&buf[..4], b"RIFF");
+        assert_eq!(&buf[8..12], b"WAVE");
+        assert_eq!(
+            u32::from_le_bytes([buf[24], buf[25], buf[26], buf[27]]),
+            8000
+        );
+        assert_eq!(&buf[36..40], b"data");
+        assert_eq!(u32::from_le_bytes([buf[40], buf[41], buf[42], buf[43]]), 6);
+        assert_eq!(i16::from_le_bytes([buf[46], buf[47]]), i16::MAX);
+    }
+
+    #[test]
+    fn read_returns_written_samples() {
+        let samples = [0.0, 0.5, -0.5, 0.25];
+        let mut buf = Vec::new();
+        super::write(&mut buf, &samples, 8000).unwrap();
+
+        let (read, sample_rate) = super::read(&buf[..]).unwrap();
+        assert_eq!(sample_rate, 8000);
+        assert_eq!(read.len(), samples.len());
+        for (a, b) in read.iter().zip(&samples) {
+            assert!((a - b).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn reads_back_each_sample_format() {
+        let samples = [0.0, 0.5, -0.5, 0.25, -1.0];
+        for format in ["u8", "s16", "s24", "f32"] {
+            let format: SampleFormat = format.parse().unwrap();
+            let mut buf = Vec::new();
+            super::write_as(&mut buf, &samples, 8000, format).unwrap();
+            assert_eq!(buf.len() % 2, 0);
+            assert_eq!(
+                u32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]) as usize,
+                buf.len() - 8
+            );
+
+            let (read, sample_rate) = super::read(&buf[..]).unwrap();
+            assert_eq!(sample_rate, 8000);
+            assert_eq!(read.len(), samples.len());
+            let tolerance = if format == SampleFormat::U8 {
+                0.01
+            } else {
+                0.001
+            };
+            for (a, b) in read.iter().zip(&samples) {
+                assert!((a - b).abs() < tolerance, "{:?}: {} vs {}", format, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn raw_samples_have_no_header() {
+        let mut buf = Vec::new();
+        super::write_raw(&mut buf, vec![0.0, 1.0, -1.0], SampleFormat::S16).unwrap();
+        assert_eq!(buf, [0, 0, 0xff, 0x7f, 0x01, 0x80]);
+    }
+
+    #[test]
+    fn write_refuses_more_than_a_wav_file_holds() {
+        let mut buf = Vec::new();
+        let samples = (0..1usize << 31).map(|_| 0.0);
+        let e = super::write_stream(&mut buf, samples, 8000, SampleFormat::S16).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn read_rejects_other_files() {
+        assert!(super::read(&b"not a wav file at all"[..]).is_err());
+    }
+}
@@ -0,0 +1,54 @@
+//! Base64, the standard alphabet, as used by XML-RPC and the WebSocket handshake.
+
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `bytes` with padding.
+pub fn encode(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (idx, &u)| n | u32::from(u) << (16 - 8 * idx));
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                out.push(char::from(ALPHABET[(n >> (18 - 6 * idx) & 0x3f) as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes `data`, ignoring whitespace and padding, or returns `None` if it is not base64.
+pub fn decode(data: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut n = 0u32;
+    let mut bits = 0;
+    for u in data
+        .bytes()
+        .filter(|u| !u.is_ascii_whitespace() && *u != b'=')
+    {
+        let digit = ALPHABET.iter().position(|&d| d == u)? as u32;
+        n = n << 6 | digit;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((n >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn round_trips() {
+        for bytes in [&b""[..], b"C", b"CQ", b"CQ ", b"CQ DE"] {
+            let encoded = super::encode(bytes);
+            assert_eq!(super::decode(&encoded).unwrap(), bytes, "{}", encoded);
+        }
+        assert_eq!(super::encode(b"CQ DE"), "Q1EgREU=");
+    }
+}
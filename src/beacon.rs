@@ -0,0 +1,119 @@
+//! Scheduling a beacon: one message sent over and over, starting at a fixed interval, with an
+//! optional limit on the share of the time spent transmitting.
+
+use std::{str::FromStr, time::Duration};
+
+/// Where a beacon is sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    /// A Raspberry Pi GPIO pin.
+    Gpio,
+    /// The sound card.
+    Audio,
+    /// A serial port's DTR or RTS line.
+    Serial,
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "gpio" => Ok(Output::Gpio),
+            "audio" => Ok(Output::Audio),
+            "serial" => Ok(Output::Serial),
+            _ => Err(format!(
+                "unknown output {:?} (expected gpio, audio or serial)",
+                s
+            )),
+        }
+    }
+}
+
+/// A length of time written with a unit, as in `500ms`, `90s`, `5m` or `1h`. A bare number is
+/// taken as seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interval(pub Duration);
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let bad = || format!("bad interval {:?} (expected e.g. 90s or 5m)", s);
+        let number: u64 = number.parse().map_err(|_| bad())?;
+        let duration = match unit {
+            "ms" => Some(Duration::from_millis(number)),
+            "" | "s" => Some(Duration::from_secs(number)),
+            "m" => number.checked_mul(60).map(Duration::from_secs),
+            "h" => number.checked_mul(3600).map(Duration::from_secs),
+            _ => {
+                return Err(format!(
+                    "unknown unit {:?} in interval (expected ms, s, m or h)",
+                    unit
+                ))
+            }
+        };
+        duration.map(Interval).ok_or_else(bad)
+    }
+}
+
+/// When each transmission of a beacon starts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Schedule {
+    /// Time from the start of one transmission to the start of the next.
+    pub every: Duration,
+    /// The largest share of the time, from 0 to 1, that may be spent transmitting.
+    pub duty_cycle: Option<f64>,
+}
+
+impl Schedule {
+    /// How long to wait after the start of a transmission lasting `length` before starting the
+    /// next: the interval, stretched if need be to keep within the duty cycle. A message longer
+    /// than the interval is followed straight away by the next.
+    pub fn period(&self, length: Duration) -> Duration {
+        let period = match self.duty_cycle {
+            Some(duty) if duty > 0.0 => self.every.max(length.div_f64(duty.min(1.0))),
+            _ => self.every,
+        };
+        period.max(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Interval, Schedule};
+
+    #[test]
+    fn parses_intervals() {
+        let secs = |s: &str| s.parse::<Interval>().map(|i| i.0);
+        assert_eq!(secs("60s"), Ok(Duration::from_secs(60)));
+        assert_eq!(secs("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(secs("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(secs("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(secs("250ms"), Ok(Duration::from_millis(250)));
+        assert!(secs("s").is_err());
+        assert!(secs("5d").is_err());
+        assert!(secs(&format!("{}h", u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn limits_duty_cycle() {
+        let schedule = |duty_cycle| Schedule {
+            every: Duration::from_secs(60),
+            duty_cycle,
+        };
+        let length = Duration::from_secs(20);
+        assert_eq!(schedule(None).period(length), Duration::from_secs(60));
+        assert_eq!(schedule(Some(0.5)).period(length), Duration::from_secs(60));
+        assert_eq!(schedule(Some(0.25)).period(length), Duration::from_secs(80));
+        assert_eq!(
+            schedule(None).period(Duration::from_secs(70)),
+            Duration::from_secs(70)
+        );
+    }
+}
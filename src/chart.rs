@@ -0,0 +1,258 @@
+//! The code chart: every character that can be sent, read straight from the code tables.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{data, Alphabet, Options, Standard};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    Letters,
+    Digits,
+    Punctuation,
+    Prosigns,
+}
+
+impl FromStr for Category {
+    type Err = UnknownName;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "letters" => Ok(Category::Letters),
+            "digits" => Ok(Category::Digits),
+            "punctuation" => Ok(Category::Punctuation),
+            "prosigns" => Ok(Category::Prosigns),
+            _ => Err(UnknownName(
+                s.into(),
+                "letters, digits, punctuation or prosigns",
+            )),
+        }
+    }
+}
+
+/// How the chart is laid out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned columns, for reading at a terminal.
+    Text,
+    Markdown,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = UnknownName;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "markdown" | "md" => Ok(Format::Markdown),
+            "csv" => Ok(Format::Csv),
+            _ => Err(UnknownName(s.into(), "text, markdown or csv")),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownName(String, &'static str);
+
+impl Display for UnknownName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown name {:?} (expected {})", self.0, self.1)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub category: Category,
+    /// The character, a digraph such as `CH`, or a prosign such as `<SK>`.
+    pub text: String,
+    pub code: String,
+}
+
+/// Lists every character that can be sent with `standard` and `options`, in table order.
+pub fn entries(standard: Standard, options: &Options) -> Vec<Entry> {
+    if standard == Standard::American {
+        return data::AMERICAN
+            .iter()
+            .map(|&(u, code)| entry(category_of(u as char), (u as char).to_string(), code))
+            .collect();
+    }
+
+    let mut entries = Vec::new();
+    match options.alphabet {
+        Alphabet::Wabun => entries.extend(
+            data::WABUN
+                .iter()
+                .map(|&(c, code)| entry(Category::Letters, c.to_string(), code)),
+        ),
+        Alphabet::Korean => {
+            let jamo = data::SKATS_INITIALS
+                .iter()
+                .chain(data::SKATS_VOWELS)
+                .filter_map(|&(c, _)| Some((c, crate::encode_with(&c.to_string(), options).ok()?)));
+            entries.extend(jamo.map(|(c, code)| entry(Category::Letters, c.to_string(), &code)));
+        }
+        _ => (),
+    }
+
+    match options.alphabet.letters() {
+        Some(letters) => entries.extend(
+            letters
+                .iter()
+                .map(|&(c, code)| entry(Category::Letters, c.to_string(), code)),
+        ),
+        None => entries.extend(
+            (b'A'..=b'Z')
+                .zip(data::ENCODED_SEQUENCES)
+                .map(|(u, &code)| entry(Category::Letters, (u as char).to_string(), code)),
+        ),
+    }
+
+    if options.extended {
+        entries.extend(
+            data::EXTENDED_LETTERS
+                .iter()
+                .map(|&(text, code)| entry(Category::Letters, text.into(), code)),
+        );
+    }
+
+    entries.extend(
+        (b'0'..=b'9')
+            .zip(&data::ENCODED_SEQUENCES[26..])
+            .map(|(u, &code)| entry(Category::Digits, (u as char).to_string(), code)),
+    );
+
+    let symbols = if options.extended {
+        data::EXTENDED_SYMBOLS
+    } else {
+        &[]
+    };
+    entries.extend(
+        data::PUNCTUATION
+            .iter()
+            .chain(symbols)
+            .map(|&(u, code)| entry(Category::Punctuation, (u as char).to_string(), code)),
+    );
+
+    entries.extend(
+        data::PROSIGNS
+            .iter()
+            .map(|&(name, code)| entry(Category::Prosigns, format!("<{}>", name), code)),
+    );
+
+    entries
+}
+
+/// Lays the entries out as a chart.
+pub fn render(entries: &[Entry], format: Format) -> String {
+    let mut lines = Vec::with_capacity(entries.len() + 2);
+
+    match format {
+        Format::Text => {
+            let width = entries
+                .iter()
+                .map(|entry| entry.text.chars().count())
+                .max()
+                .unwrap_or(0);
+            for entry in entries {
+                let padding = width - entry.text.chars().count();
+                lines.push(format!(
+                    "{}{}  {}",
+                    entry.text,
+                    " ".repeat(padding),
+                    entry.code
+                ));
+            }
+        }
+
+        Format::Markdown => {
+            lines.push("| Character | Code |".into());
+            lines.push("|---|---|".into());
+            for entry in entries {
+                lines.push(format!(
+                    "| `{}` | `{}` |",
+                    entry.text.replace('|', "\\|"),
+                    entry.code
+                ));
+            }
+        }
+
+        Format::Csv => {
+            lines.push("character,code".into());
+            for entry in entries {
+                lines.push(format!(
+                    "{},{}",
+                    csv_field(&entry.text),
+                    csv_field(&entry.code)
+                ));
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn entry(category: Category, text: String, code: &str) -> Entry {
+    Entry {
+        category,
+        text,
+        code: code.into(),
+    }
+}
+
+fn category_of(c: char) -> Category {
+    if c.is_ascii_digit() {
+        Category::Digits
+    } else if c.is_alphabetic() {
+        Category::Letters
+    } else {
+        Category::Punctuation
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Category, Format};
+    use crate::{Options, Standard};
+
+    #[test]
+    fn every_entry_round_trips() {
+        let options = Options {
+            extended: true,
+            ..Options::default()
+        };
+
+        for entry in super::entries(Standard::International, &options) {
+            if entry.category == Category::Prosigns {
+                continue;
+            }
+            let encoded = crate::encode_with(&entry.text, &options).unwrap();
+            assert_eq!(encoded, entry.code, "{}", entry.text);
+        }
+    }
+
+    #[test]
+    fn charts_are_laid_out() {
+        let entries: Vec<_> = super::entries(Standard::International, &Options::default())
+            .into_iter()
+            .filter(|entry| entry.text == "A" || entry.text == "," || entry.text == "<SOS>")
+            .collect();
+
+        assert_eq!(
+            super::render(&entries, Format::Text),
+            "A      .-\n,      --..--\n<SOS>  ...---..."
+        );
+        assert_eq!(
+            super::render(&entries, Format::Csv),
+            "character,code\nA,.-\n\",\",--..--\n<SOS>,...---..."
+        );
+        assert!(super::render(&entries, Format::Markdown).contains("| `A` | `.-` |"));
+    }
+}
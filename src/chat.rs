@@ -0,0 +1,117 @@
+//! Two-way text chat in CW over the sound card: lines typed are played as tones, and tones heard
+//! by the microphone are decoded and printed.
+//!
+//! The link is full duplex: the microphone is captured and decoded on its own thread, so the
+//! other side is heard while a line is being played. That only works if the two sides key
+//! different tones, so anything within [`OWN_TONE`] Hz of the tone played is taken for our own
+//! signal coming back and is not decoded.
+
+use std::{
+    io::{self, BufRead, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+use morse::{audio, timing, Error, Options, Result, Timing};
+
+/// How close to the tone played a heard tone may be before it is taken for our own, in Hz.
+const OWN_TONE: f64 = 50.0;
+
+/// Chats until standard input ends: typed lines are sent at `timing` in `voice`, and the other
+/// side is decoded on `listen` Hz, or whichever tone other than ours is loudest if `listen` is
+/// `None`.
+pub fn run(timing: Timing, voice: audio::Voice, listen: Option<f64>) -> Result<()> {
+    if listen.is_some_and(|listen| (listen - voice.tone).abs() < OWN_TONE) {
+        return Err(Error::Audio(format!(
+            "--listen must be at least {} Hz away from --tone, or our own sending is heard",
+            OWN_TONE
+        )));
+    }
+
+    let done = Arc::new(AtomicBool::new(false));
+    let (started, ready) = mpsc::channel();
+    let listener = {
+        let done = Arc::clone(&done);
+        let own = voice.tone;
+        thread::spawn(move || listen_lines(own, listen, &done, started))
+    };
+    // Wait for the microphone to open, so that a missing device is reported before typing.
+    match ready.recv() {
+        Ok(Ok(())) => (),
+        Ok(Err(e)) => return Err(e),
+        Err(_) => return Err(Error::Audio("the listening thread stopped".into())),
+    }
+
+    eprintln!("type a line to send it; end input (Ctrl-D) to quit");
+    let sent = send_lines(&timing, &voice);
+    done.store(true, Ordering::SeqCst);
+    let heard = listener
+        .join()
+        .map_err(|_| Error::Audio("the listening thread panicked".into()))?;
+    sent.and(heard)
+}
+
+/// Decodes the microphone until `done`, printing text as it is heard. Reports on `started`
+/// whether the microphone could be opened.
+fn listen_lines(
+    own: f64,
+    listen: Option<f64>,
+    done: &AtomicBool,
+    started: mpsc::Sender<Result<()>>,
+) -> Result<()> {
+    let capture = match audio::device::capture() {
+        Ok(capture) => {
+            let _ = started.send(Ok(()));
+            capture
+        }
+        Err(e) => {
+            let _ = started.send(Err(e));
+            return Ok(());
+        }
+    };
+    let sample_rate = capture.sample_rate;
+    let mut decoder = audio::stream::StreamDecoder::new(sample_rate, listen);
+
+    let mut stdout = io::stdout();
+    for samples in capture {
+        if done.load(Ordering::SeqCst) {
+            break;
+        }
+        let text = decoder.push(&samples);
+        // Picked our own signal out of the air; listen for the other side afresh.
+        if decoder
+            .tone()
+            .is_some_and(|tone| (tone - own).abs() < OWN_TONE)
+        {
+            decoder = audio::stream::StreamDecoder::new(sample_rate, listen);
+            continue;
+        }
+
+        if !text.is_empty() {
+            write!(stdout, "{}", text)
+                .and_then(|_| stdout.flush())
+                .map_err(Error::Io)?;
+        }
+    }
+    println!("{}", decoder.finish());
+    Ok(())
+}
+
+/// Plays each line of standard input as it is typed.
+fn send_lines(timing: &Timing, voice: &audio::Voice) -> Result<()> {
+    let options = Options::default();
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(Error::Io)?;
+        let encoded = morse::encode(&morse::sanitize(&line, &options))?;
+        if encoded.trim().is_empty() {
+            continue;
+        }
+
+        let events = timing::to_key_events(&encoded, timing, None);
+        audio::device::play(voice.render(&events), voice.sample_rate)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! Source code that keys a message: an Arduino sketch blinking an LED, or a C array of the times
+//! to key it for, for firmware to play however it likes. Times come from `haptic::durations`, in
+//! milliseconds, alternately on and off and starting on.
+
+use std::{fmt::Write, str::FromStr, time::Duration};
+
+use crate::{haptic, timing::KeyEvent};
+
+/// Values to a line of an array, to keep lines readable.
+const PER_LINE: usize = 12;
+
+/// What to generate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Arduino,
+    C,
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "arduino" | "ino" => Ok(Target::Arduino),
+            "c" => Ok(Target::C),
+            _ => Err(format!("unknown target {:?} (expected arduino or c)", s)),
+        }
+    }
+}
+
+/// An Arduino sketch that blinks the LED on `pin` with key events over and over, pausing for
+/// `pause` between repeats. The times are kept in flash, so long messages fit small boards.
+pub fn arduino(events: &[KeyEvent], message: &str, pin: &str, pause: Duration) -> String {
+    let mut buf = String::new();
+    let _ = writeln!(buf, "// {} in Morse, blinked on an LED.", comment(message));
+    let _ = writeln!(
+        buf,
+        "// Times are in milliseconds, alternately on and off, starting on."
+    );
+    let _ = writeln!(buf);
+    let _ = writeln!(buf, "#include <avr/pgmspace.h>");
+    let _ = writeln!(buf);
+    let _ = writeln!(buf, "const int LED_PIN = {};", pin);
+    let _ = writeln!(buf, "const unsigned long PAUSE = {};", pause.as_millis());
+    let _ = writeln!(buf);
+    let _ = write!(buf, "const unsigned long TIMES[] PROGMEM = ");
+    array(&mut buf, events);
+    let _ = writeln!(buf, ";");
+    let _ = writeln!(
+        buf,
+        "const unsigned int COUNT = sizeof(TIMES) / sizeof(TIMES[0]);"
+    );
+    buf.push_str(
+        "
+void setup() {
+  pinMode(LED_PIN, OUTPUT);
+}
+
+void loop() {
+  for (unsigned int i = 0; i < COUNT; i++) {
+    digitalWrite(LED_PIN, i % 2 == 0 ? HIGH : LOW);
+    delay(pgm_read_dword(&TIMES[i]));
+  }
+  digitalWrite(LED_PIN, LOW);
+  delay(PAUSE);
+}
+",
+    );
+    buf
+}
+
+/// A C array of the times to key key events for, with its length, to include in other code.
+pub fn c(events: &[KeyEvent], message: &str) -> String {
+    let mut buf = String::new();
+    let _ = writeln!(buf, "/* {} in Morse.", comment(message));
+    let _ = writeln!(
+        buf,
+        " * Times are in milliseconds, alternately on and off, starting on. */"
+    );
+    let _ = writeln!(buf);
+    let _ = writeln!(buf, "#include <stddef.h>");
+    let _ = writeln!(buf);
+    let _ = write!(buf, "static const unsigned long MORSE_TIMES[] = ");
+    array(&mut buf, events);
+    let _ = writeln!(buf, ";");
+    let _ = writeln!(
+        buf,
+        "static const size_t MORSE_TIMES_LEN = sizeof(MORSE_TIMES) / sizeof(MORSE_TIMES[0]);"
+    );
+    buf
+}
+
+/// Writes the times for key events as a C array initializer.
+fn array(buf: &mut String, events: &[KeyEvent]) {
+    let durations = haptic::durations(events);
+    buf.push_str("{\n");
+    for line in durations.chunks(PER_LINE) {
+        let values: Vec<String> = line.iter().map(u64::to_string).collect();
+        let _ = writeln!(buf, "  {},", values.join(", "));
+    }
+    buf.push('}');
+}
+
+/// A message quoted for a comment, on one line and unable to end the comment early.
+fn comment(message: &str) -> String {
+    let message: Vec<&str> = message.split_whitespace().collect();
+    format!("{:?}", message.join(" ").replace("*/", "* /"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::timing::{self, Timing};
+
+    #[test]
+    fn sketch_blinks_the_message() {
+        let timing = Timing::new(20);
+        let events = timing::to_key_events(".- / -", &timing, None);
+        let sketch = super::arduino(&events, "A\nT", "LED_BUILTIN", Duration::from_millis(420));
+        assert!(sketch.starts_with("// \"A T\" in Morse"), "{}", sketch);
+        assert!(sketch.contains("const int LED_PIN = LED_BUILTIN;\n"));
+        assert!(sketch.contains("const unsigned long PAUSE = 420;\n"));
+        assert!(sketch.contains("PROGMEM = {\n  60, 60, 180, 420, 180,\n};\n"));
+        assert!(sketch.contains("void loop() {"));
+    }
+
+    #[test]
+    fn arrays_wrap_and_comments_stay_closed() {
+        let events = timing::to_key_events(&"..".repeat(4), &Timing::new(20), None);
+        let code = super::c(&events, "*/ E");
+        assert!(code.starts_with("/* \"* / E\" in Morse."), "{}", code);
+        let line = ["60"; 12].join(", ");
+        assert!(code.contains(&format!("= {{\n  {},\n  60, 60, 60,\n}};", line)));
+    }
+}
@@ -0,0 +1,135 @@
+//! Decoding that says how sure it is of each character, so that uncertain copy from noisy
+//! sources can be flagged.
+//!
+//! Text input is either right or wrong, so its characters score 1 when they decode and 0 when they
+//! do not. Characters recovered from key events score as low as the least certain dit, dah or gap
+//! that went into them, including the gaps on either side.
+
+use crate::{
+    timing::{self, Mark},
+    ErrorPolicy, KeyEvent, Options,
+};
+
+/// A decoded character.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scored {
+    pub code: String,
+    /// `None` when the code does not decode.
+    pub text: Option<String>,
+    /// From 0 to 1.
+    pub confidence: f64,
+}
+
+/// Decodes a message in canonical notation, word by word.
+pub fn decode(message: &str, options: &Options) -> Vec<Vec<Scored>> {
+    message
+        .split('/')
+        .filter(|word| !word.trim().is_empty())
+        .map(|word| {
+            word.split_whitespace()
+                .map(|code| score(code, 1.0, options))
+                .collect()
+        })
+        .collect()
+}
+
+/// Decodes key events of unknown speed, word by word.
+pub fn decode_key_events(events: &[KeyEvent], options: &Options) -> Vec<Vec<Scored>> {
+    let mut words = Vec::new();
+    let mut word = Vec::new();
+    let mut code = String::new();
+    let mut confidence = 1.0f64;
+
+    for classified in timing::classify(events) {
+        confidence = confidence.min(classified.confidence);
+        match classified.mark {
+            Mark::Dit => code.push('.'),
+            Mark::Dah => code.push('-'),
+            Mark::ElementGap => (),
+            Mark::CharGap | Mark::WordGap => {
+                word.push(score(&code, confidence, options));
+                code.clear();
+                // The gap is as much a part of the next character as of this one.
+                confidence = classified.confidence;
+                if classified.mark == Mark::WordGap {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+        }
+    }
+
+    if !code.is_empty() {
+        word.push(score(&code, confidence, options));
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Joins decoded words into text, writing `replacement` for codes that do not decode.
+pub fn text(words: &[Vec<Scored>], replacement: char, options: &Options) -> String {
+    let words: Vec<String> = words
+        .iter()
+        .map(|word| {
+            word.iter()
+                .map(|scored| match &scored.text {
+                    Some(text) => text.clone(),
+                    None => replacement.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    words.join(&options.word_sep.to_string())
+}
+
+fn score(code: &str, confidence: f64, options: &Options) -> Scored {
+    let options = Options {
+        errors: ErrorPolicy::Strict,
+        ..*options
+    };
+    let text = crate::decode_with(code, &options).ok();
+
+    Scored {
+        confidence: if text.is_some() { confidence } else { 0.0 },
+        code: code.into(),
+        text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{timing, KeyEvent, Options, Timing};
+    use std::time::Duration;
+
+    #[test]
+    fn text_scores_are_certain_or_zero() {
+        let words = super::decode("... --- / ..--..-", &Options::default());
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0][1].text.as_deref(), Some("O"));
+        assert_eq!(words[0][1].confidence, 1.0);
+        assert_eq!(words[1][0].text, None);
+        assert_eq!(words[1][0].confidence, 0.0);
+        assert_eq!(super::text(&words, '#', &Options::default()), "SO #");
+    }
+
+    #[test]
+    fn doubtful_elements_lower_their_character() {
+        let options = Options::default();
+        let mut events = timing::to_key_events("-.-. / --.-", &Timing::new(20), None);
+        let words = super::decode_key_events(&events, &options);
+        assert_eq!(super::text(&words, '#', &options), "C Q");
+        assert!(words
+            .iter()
+            .flatten()
+            .all(|scored| scored.confidence == 1.0));
+
+        // Stretch the first gap inside Q until it is nearly a character gap.
+        let gap = events.len() - 6;
+        events[gap] = KeyEvent::Up(Duration::from_millis(60 * 19 / 10));
+        let words = super::decode_key_events(&events, &options);
+        assert_eq!(super::text(&words, '#', &options), "C Q");
+        assert_eq!(words[0][0].confidence, 1.0);
+        assert!(words[1][0].confidence < 0.5);
+    }
+}
@@ -0,0 +1,244 @@
+//! A server speaking the UDP protocol of `cwdaemon`, so that loggers and contest programs that
+//! can drive `cwdaemon` can key messages through this crate instead.
+//!
+//! Each datagram is either text to send or, if it starts with an escape character, a command.
+//! Text is queued behind whatever is already being sent; an abort empties the queue.
+
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+use crate::{
+    output::{self, Key},
+    timing, Options, Timing,
+};
+
+/// The port `cwdaemon` listens on.
+pub const PORT: u16 = 6789;
+
+/// The speeds `cwdaemon` accepts.
+const SPEEDS: (u32, u32) = (4, 60);
+/// The tones `cwdaemon` accepts; 0 turns the sidetone off.
+const TONES: (u32, u32) = (0, 4000);
+
+const ESCAPE: u8 = 0x1b;
+/// Marks the end of text whose sending is to be acknowledged.
+const CARET: char = '^';
+
+/// One datagram's worth of instructions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Request {
+    /// Text to send. With `echo`, the text is sent back once it has been keyed.
+    Text {
+        text: String,
+        echo: bool,
+    },
+    /// Go back to the speed and tone the server started with.
+    Reset,
+    Speed(u32),
+    Tone(u32),
+    /// Stop sending and forget anything queued.
+    Abort,
+    /// Stop sending and shut the server down.
+    Exit,
+    /// Reply with this text once the next text has been keyed.
+    Reply(String),
+    /// A command this server has no use for, such as PTT or sound system control, with its
+    /// argument.
+    Unsupported(char, String),
+}
+
+impl Request {
+    /// Parses a datagram, or returns `None` for a command with a bad argument.
+    pub fn parse(datagram: &[u8]) -> Option<Request> {
+        let message = String::from_utf8_lossy(datagram);
+        let message = message.trim_end_matches(&['\r', '\n', '\0'][..]);
+
+        let command = match message.as_bytes().first() {
+            Some(&ESCAPE) => &message[1..],
+            _ => {
+                return Some(match message.strip_suffix(CARET) {
+                    Some(text) => Request::Text {
+                        text: text.into(),
+                        echo: true,
+                    },
+                    None => Request::Text {
+                        text: message.into(),
+                        echo: false,
+                    },
+                })
+            }
+        };
+
+        let mut chars = command.chars();
+        let code = chars.next()?;
+        let argument = chars.as_str();
+        let number = |(min, max): (u32, u32)| {
+            argument
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|n| (min..=max).contains(n))
+        };
+
+        Some(match code {
+            '0' => Request::Reset,
+            '2' => Request::Speed(number(SPEEDS)?),
+            '3' => Request::Tone(number(TONES)?),
+            '4' => Request::Abort,
+            '5' => Request::Exit,
+            'h' => Request::Reply(argument.into()),
+            code => Request::Unsupported(code, argument.into()),
+        })
+    }
+}
+
+/// Serves requests arriving on `socket`, keying text on `key` at `timing` and, for keys that
+/// sound one, at `tone`. Returns when asked to exit.
+pub fn serve(socket: UdpSocket, key: &mut impl Key, timing: Timing, tone: u32) -> io::Result<()> {
+    // Requests are handled in order by this thread while another receives them, so that an
+    // abort can cut short the text being sent. Each abort starts a new generation, and anything
+    // queued in an earlier one is dropped.
+    let generation = Arc::new(AtomicUsize::new(0));
+    let (requests, queue) = mpsc::channel();
+
+    let receiver = socket.try_clone()?;
+    let current = Arc::clone(&generation);
+    let receiving = thread::spawn(move || -> io::Result<()> {
+        let mut buf = [0; 1024];
+        loop {
+            let (len, peer) = receiver.recv_from(&mut buf)?;
+            let request = match Request::parse(&buf[..len]) {
+                Some(request) => request,
+                None => continue,
+            };
+            if let Request::Abort | Request::Exit = request {
+                current.fetch_add(1, Ordering::SeqCst);
+            }
+            let exit = request == Request::Exit;
+            if requests
+                .send((current.load(Ordering::SeqCst), peer, request))
+                .is_err()
+                || exit
+            {
+                return Ok(());
+            }
+        }
+    });
+
+    let mut state = State::new(timing, tone);
+    key.set_tone(tone);
+    let mut reply: Option<(SocketAddr, String)> = None;
+
+    for (queued, peer, request) in queue {
+        match request {
+            Request::Exit => break,
+            Request::Text { text, echo } => {
+                if queued != generation.load(Ordering::SeqCst) {
+                    continue;
+                }
+
+                let encoded =
+                    crate::encode(&crate::sanitize(&text, &Options::default())).unwrap_or_default();
+                let events = timing::to_key_events(&encoded, &state.timing, None);
+                output::send_while(key, &events, || generation.load(Ordering::SeqCst) == queued)?;
+
+                if echo {
+                    socket.send_to(format!("{}\r\n", text).as_bytes(), peer)?;
+                }
+                if let Some((peer, reply)) = reply.take() {
+                    socket.send_to(format!("h{}\r\n", reply).as_bytes(), peer)?;
+                }
+            }
+            Request::Reply(text) => reply = Some((peer, text)),
+            request => {
+                state.apply(&request);
+                key.set_tone(state.tone);
+            }
+        }
+    }
+
+    key.up()?;
+    receiving
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("cwdaemon receiver panicked")))
+}
+
+/// The settings that commands change between texts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct State {
+    timing: Timing,
+    tone: u32,
+    initial: (Timing, u32),
+}
+
+impl State {
+    fn new(timing: Timing, tone: u32) -> Self {
+        State {
+            timing,
+            tone,
+            initial: (timing, tone),
+        }
+    }
+
+    fn apply(&mut self, request: &Request) {
+        match *request {
+            Request::Reset => {
+                let (timing, tone) = self.initial;
+                self.timing = timing;
+                self.tone = tone;
+            }
+            Request::Speed(wpm) => self.timing = Timing::new(wpm).weighted(self.timing.weight),
+            Request::Tone(tone) => self.tone = tone,
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Request, State};
+    use crate::Timing;
+
+    #[test]
+    fn parses_requests() {
+        let text = |text: &str, echo| {
+            Some(Request::Text {
+                text: text.into(),
+                echo,
+            })
+        };
+        assert_eq!(Request::parse(b"cq test\r\n"), text("cq test", false));
+        assert_eq!(Request::parse(b"tu 5nn^"), text("tu 5nn", true));
+        assert_eq!(Request::parse(b"\x1b228"), Some(Request::Speed(28)));
+        assert_eq!(Request::parse(b"\x1b3800"), Some(Request::Tone(800)));
+        assert_eq!(Request::parse(b"\x1b2999"), None);
+        assert_eq!(Request::parse(b"\x1b4"), Some(Request::Abort));
+        assert_eq!(
+            Request::parse(b"\x1bhdone"),
+            Some(Request::Reply("done".into()))
+        );
+        assert_eq!(
+            Request::parse(b"\x1ba1"),
+            Some(Request::Unsupported('a', "1".into()))
+        );
+    }
+
+    #[test]
+    fn resets_to_initial_settings() {
+        let mut state = State::new(Timing::farnsworth(20, 15), 700);
+        state.apply(&Request::Speed(30));
+        state.apply(&Request::Tone(500));
+        assert_eq!(state.timing, Timing::new(30));
+        assert_eq!(state.tone, 500);
+
+        state.apply(&Request::Reset);
+        assert_eq!(state, State::new(Timing::farnsworth(20, 15), 700));
+    }
+}
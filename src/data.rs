@@ -0,0 +1,706 @@
+/// Codes for `A` through `Z` followed by `0` through `9`.
+pub static ENCODED_SEQUENCES: &[&str] = &[
+    ".-", "-...", "-.-.", "-..", ".", "..-.", "--.", "....", "..", ".---", "-.-", ".-..", "--",
+    "-.", "---", ".--.", "--.-", ".-.", "...", "-", "..-", "...-", ".--", "-..-", "-.--", "--..",
+    "-----", ".----", "..---", "...--", "....-", ".....", "-....", "--...", "---..", "----.",
+];
+
+/// Characters indexed by the position of their code in a binary tree laid out as an array:
+/// starting from zero, a dot moves to `idx * 2 + 1` and a dash to `idx * 2 + 2`.
+pub static DECODING_ARRAY: &[Option<u8>] = &[
+    None,
+    Some(b'E'),
+    Some(b'T'),
+    Some(b'I'),
+    Some(b'A'),
+    Some(b'N'),
+    Some(b'M'),
+    Some(b'S'),
+    Some(b'U'),
+    Some(b'R'),
+    Some(b'W'),
+    Some(b'D'),
+    Some(b'K'),
+    Some(b'G'),
+    Some(b'O'),
+    Some(b'H'),
+    Some(b'V'),
+    Some(b'F'),
+    None,
+    Some(b'L'),
+    None,
+    Some(b'P'),
+    Some(b'J'),
+    Some(b'B'),
+    Some(b'X'),
+    Some(b'C'),
+    Some(b'Y'),
+    Some(b'Z'),
+    Some(b'Q'),
+    None,
+    None,
+    Some(b'5'),
+    Some(b'4'),
+    None,
+    Some(b'3'),
+    None,
+    None,
+    None,
+    Some(b'2'),
+    Some(b'&'),
+    None,
+    Some(b'+'),
+    None,
+    None,
+    None,
+    None,
+    Some(b'1'),
+    Some(b'6'),
+    Some(b'='),
+    Some(b'/'),
+    None,
+    None,
+    None,
+    Some(b'('),
+    None,
+    Some(b'7'),
+    None,
+    None,
+    None,
+    Some(b'8'),
+    None,
+    Some(b'9'),
+    Some(b'0'),
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    Some(b'?'),
+    Some(b'_'),
+    None,
+    None,
+    None,
+    None,
+    Some(b'"'),
+    None,
+    None,
+    Some(b'.'),
+    None,
+    None,
+    None,
+    None,
+    Some(b'@'),
+    None,
+    None,
+    None,
+    Some(b'\''),
+    None,
+    None,
+    Some(b'-'),
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    Some(b';'),
+    Some(b'!'),
+    None,
+    Some(b')'),
+    None,
+    None,
+    None,
+    None,
+    None,
+    Some(b','),
+    None,
+    None,
+    None,
+    None,
+    Some(b':'),
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    Some(b'$'),
+];
+
+/// ITU punctuation, in no particular order.
+pub static PUNCTUATION: &[(u8, &str)] = &[
+    (b'.', ".-.-.-"),
+    (b',', "--..--"),
+    (b'?', "..--.."),
+    (b'\'', ".----."),
+    (b'!', "-.-.--"),
+    (b'/', "-..-."),
+    (b'(', "-.--."),
+    (b')', "-.--.-"),
+    (b'&', ".-..."),
+    (b':', "---..."),
+    (b';', "-.-.-."),
+    (b'=', "-...-"),
+    (b'+', ".-.-."),
+    (b'-', "-....-"),
+    (b'_', "..--.-"),
+    (b'"', ".-..-."),
+    (b'$', "...-..-"),
+    (b'@', ".--.-."),
+];
+
+/// Prosigns, written as letters sent without the usual gap between them. Several share their code
+/// with a punctuation mark (e.g. `<AR>` and `+`).
+pub static PROSIGNS: &[(&str, &str)] = &[
+    ("AR", ".-.-."),
+    ("AS", ".-..."),
+    ("BK", "-...-.-"),
+    ("BT", "-...-"),
+    ("CL", "-.-..-.."),
+    ("CT", "-.-.-"),
+    ("HH", "........"),
+    ("KN", "-.--."),
+    ("SK", "...-.-"),
+    ("SN", "...-."),
+    ("SOS", "...---..."),
+];
+
+/// Symbols outside the ITU standard, enabled with `Options::extended`. These codes are not
+/// recognized by other operators and are chosen only to avoid colliding with standard ones.
+pub static EXTENDED_SYMBOLS: &[(u8, &str)] = &[
+    (b'*', "-..-.-"),
+    (b'%', "--.--."),
+    (b'^', "..-.-."),
+    (b'~', ".-.--."),
+    (b'|', "-.--.."),
+];
+
+/// The American (landline) Morse table. Besides `.` and `-`, codes use `_` for the long dash of
+/// `L`, `=` for the longer dash of `0`, and `'` for the extra space inside spaced letters such as
+/// `O` (`.'.`).
+pub static AMERICAN: &[(u8, &str)] = &[
+    (b'A', ".-"),
+    (b'B', "-..."),
+    (b'C', "..'."),
+    (b'D', "-.."),
+    (b'E', "."),
+    (b'F', ".-."),
+    (b'G', "--."),
+    (b'H', "...."),
+    (b'I', ".."),
+    (b'J', "-.-."),
+    (b'K', "-.-"),
+    (b'L', "_"),
+    (b'M', "--"),
+    (b'N', "-."),
+    (b'O', ".'."),
+    (b'P', "....."),
+    (b'Q', "..-."),
+    (b'R', ".'.."),
+    (b'S', "..."),
+    (b'T', "-"),
+    (b'U', "..-"),
+    (b'V', "...-"),
+    (b'W', ".--"),
+    (b'X', ".-.."),
+    (b'Y', "..'.."),
+    (b'Z', "...'."),
+    (b'1', ".--."),
+    (b'2', "..-.."),
+    (b'3', "...-."),
+    (b'4', "....-"),
+    (b'5', "---"),
+    (b'6', "......"),
+    (b'7', "--.."),
+    (b'8', "-...."),
+    (b'9', "-..-"),
+    (b'0', "="),
+    (b'&', ".'..."),
+    (b'.', "..--.."),
+    (b',', ".-.-"),
+    (b'?', "-..-."),
+    (b'!', "---."),
+];
+
+/// The Russian letters. `Ё` shares its code with `Е` and decodes as `Е`.
+pub static CYRILLIC: &[(char, &str)] = &[
+    ('А', ".-"),
+    ('Б', "-..."),
+    ('В', ".--"),
+    ('Г', "--."),
+    ('Д', "-.."),
+    ('Е', "."),
+    ('Ё', "."),
+    ('Ж', "...-"),
+    ('З', "--.."),
+    ('И', ".."),
+    ('Й', ".---"),
+    ('К', "-.-"),
+    ('Л', ".-.."),
+    ('М', "--"),
+    ('Н', "-."),
+    ('О', "---"),
+    ('П', ".--."),
+    ('Р', ".-."),
+    ('С', "..."),
+    ('Т', "-"),
+    ('У', "..-"),
+    ('Ф', "..-."),
+    ('Х', "...."),
+    ('Ц', "-.-."),
+    ('Ч', "---."),
+    ('Ш', "----"),
+    ('Щ', "--.-"),
+    ('Ъ', "--.--"),
+    ('Ы', "-.--"),
+    ('Ь', "-..-"),
+    ('Э', "..-.."),
+    ('Ю', "..--"),
+    ('Я', ".-.-"),
+];
+
+/// The Greek letters. Accented capitals share the code of their base letter and decode as it;
+/// final sigma is covered by uppercasing to `Σ`.
+pub static GREEK: &[(char, &str)] = &[
+    ('Α', ".-"),
+    ('Β', "-..."),
+    ('Γ', "--."),
+    ('Δ', "-.."),
+    ('Ε', "."),
+    ('Ζ', "--.."),
+    ('Η', "...."),
+    ('Θ', "-.-."),
+    ('Ι', ".."),
+    ('Κ', "-.-"),
+    ('Λ', ".-.."),
+    ('Μ', "--"),
+    ('Ν', "-."),
+    ('Ξ', "-..-"),
+    ('Ο', "---"),
+    ('Π', ".--."),
+    ('Ρ', ".-."),
+    ('Σ', "..."),
+    ('Τ', "-"),
+    ('Υ', "-.--"),
+    ('Φ', "..-."),
+    ('Χ', "----"),
+    ('Ψ', "--.-"),
+    ('Ω', ".--"),
+    ('Ά', ".-"),
+    ('Έ', "."),
+    ('Ή', "...."),
+    ('Ί', ".."),
+    ('Ϊ', ".."),
+    ('Ό', "---"),
+    ('Ύ', "-.--"),
+    ('Ϋ', "-.--"),
+    ('Ώ', ".--"),
+];
+
+/// The Hebrew letters. Final forms share the code of their base letter.
+pub static HEBREW: &[(char, &str)] = &[
+    ('א', ".-"),
+    ('ב', "-..."),
+    ('ג', "--."),
+    ('ד', "-.."),
+    ('ה', "---"),
+    ('ו', "."),
+    ('ז', "--.."),
+    ('ח', "...."),
+    ('ט', "..-"),
+    ('י', ".."),
+    ('כ', "-.-"),
+    ('ל', ".-.."),
+    ('מ', "--"),
+    ('נ', "-."),
+    ('ס', "-.-."),
+    ('ע', ".---"),
+    ('פ', ".--."),
+    ('צ', ".--"),
+    ('ק', "--.-"),
+    ('ר', ".-."),
+    ('ש', "..."),
+    ('ת', "-"),
+    ('ך', "-.-"),
+    ('ם', "--"),
+    ('ן', "-."),
+    ('ף', ".--."),
+    ('ץ', ".--"),
+];
+
+/// The Arabic letters, with the Arabic comma and question mark. Hamza-carrying and madda forms of
+/// alif share its code, as does alif maqsura with ya.
+pub static ARABIC: &[(char, &str)] = &[
+    ('ا', ".-"),
+    ('ب', "-..."),
+    ('ت', "-"),
+    ('ث', "-.-."),
+    ('ج', ".---"),
+    ('ح', "...."),
+    ('خ', "---"),
+    ('د', "-.."),
+    ('ذ', "--.."),
+    ('ر', ".-."),
+    ('ز', "---."),
+    ('س', "..."),
+    ('ش', "----"),
+    ('ص', "-..-"),
+    ('ض', "...-"),
+    ('ط', "..-"),
+    ('ظ', "-.--"),
+    ('ع', ".-.-"),
+    ('غ', "--."),
+    ('ف', "..-."),
+    ('ق', "--.-"),
+    ('ك', "-.-"),
+    ('ل', ".-.."),
+    ('م', "--"),
+    ('ن', "-."),
+    ('ه', "..-.."),
+    ('و', ".--"),
+    ('ي', ".."),
+    ('ء', "."),
+    ('،', "--..--"),
+    ('؟', "..--.."),
+    ('أ', ".-"),
+    ('إ', ".-"),
+    ('آ', ".-"),
+    ('ى', ".."),
+];
+
+/// The Wabun code for Japanese kana, written in katakana. Voiced and semi-voiced kana are sent as
+/// their base kana followed by `゛` or `゜`.
+pub static WABUN: &[(char, &str)] = &[
+    ('イ', ".-"),
+    ('ロ', ".-.-"),
+    ('ハ', "-..."),
+    ('ニ', "-.-."),
+    ('ホ', "-.."),
+    ('ヘ', "."),
+    ('ト', "..-.."),
+    ('チ', "..-."),
+    ('リ', "--."),
+    ('ヌ', "...."),
+    ('ル', "-.--."),
+    ('ヲ', ".---"),
+    ('ワ', "-.-"),
+    ('カ', ".-.."),
+    ('ヨ', "--"),
+    ('タ', "-."),
+    ('レ', "---"),
+    ('ソ', "---."),
+    ('ツ', ".--."),
+    ('ネ', "--.-"),
+    ('ナ', ".-."),
+    ('ラ', "..."),
+    ('ム', "-"),
+    ('ウ', "..-"),
+    ('ヰ', ".-..-"),
+    ('ノ', "..--"),
+    ('オ', ".-..."),
+    ('ク', "...-"),
+    ('ヤ', ".--"),
+    ('マ', "-..-"),
+    ('ケ', "-.--"),
+    ('フ', "--.."),
+    ('コ', "----"),
+    ('エ', "-.---"),
+    ('テ', ".-.--"),
+    ('ア', "--.--"),
+    ('サ', "-.-.-"),
+    ('キ', "-.-.."),
+    ('ユ', "-..--"),
+    ('メ', "-...-"),
+    ('ミ', "..-.-"),
+    ('シ', "--.-."),
+    ('ヱ', ".--.."),
+    ('ヒ', "--..-"),
+    ('モ', "-..-."),
+    ('セ', ".---."),
+    ('ス', "---.-"),
+    ('ン', ".-.-."),
+    ('゛', ".."),
+    ('゜', "..--."),
+    ('ー', ".--.-"),
+    ('、', ".-.-.-"),
+];
+
+/// SKATS letters for the Hangul initial consonants, in Unicode syllable order.
+pub static SKATS_INITIALS: &[(char, &str)] = &[
+    ('ㄱ', "K"),
+    ('ㄲ', "KK"),
+    ('ㄴ', "N"),
+    ('ㄷ', "T"),
+    ('ㄸ', "TT"),
+    ('ㄹ', "L"),
+    ('ㅁ', "M"),
+    ('ㅂ', "P"),
+    ('ㅃ', "PP"),
+    ('ㅅ', "S"),
+    ('ㅆ', "SS"),
+    ('ㅇ', "G"),
+    ('ㅈ', "J"),
+    ('ㅉ', "JJ"),
+    ('ㅊ', "C"),
+    ('ㅋ', "Z"),
+    ('ㅌ', "D"),
+    ('ㅍ', "B"),
+    ('ㅎ', "H"),
+];
+
+/// SKATS letters for the Hangul vowels, in Unicode syllable order.
+pub static SKATS_VOWELS: &[(char, &str)] = &[
+    ('ㅏ', "F"),
+    ('ㅐ', "FI"),
+    ('ㅑ', "E"),
+    ('ㅒ', "EI"),
+    ('ㅓ', "V"),
+    ('ㅔ', "VI"),
+    ('ㅕ', "R"),
+    ('ㅖ', "RI"),
+    ('ㅗ', "W"),
+    ('ㅘ', "WF"),
+    ('ㅙ', "WFI"),
+    ('ㅚ', "WI"),
+    ('ㅛ', "Q"),
+    ('ㅜ', "U"),
+    ('ㅝ', "UV"),
+    ('ㅞ', "UVI"),
+    ('ㅟ', "UI"),
+    ('ㅠ', "Y"),
+    ('ㅡ', "O"),
+    ('ㅢ', "OI"),
+    ('ㅣ', "I"),
+];
+
+/// SKATS letters for the Hangul final consonants, in Unicode syllable order (after "none").
+pub static SKATS_FINALS: &[(char, &str)] = &[
+    ('ㄱ', "K"),
+    ('ㄲ', "KK"),
+    ('ㄳ', "KS"),
+    ('ㄴ', "N"),
+    ('ㄵ', "NJ"),
+    ('ㄶ', "NH"),
+    ('ㄷ', "T"),
+    ('ㄹ', "L"),
+    ('ㄺ', "LK"),
+    ('ㄻ', "LM"),
+    ('ㄼ', "LP"),
+    ('ㄽ', "LS"),
+    ('ㄾ', "LD"),
+    ('ㄿ', "LB"),
+    ('ㅀ', "LH"),
+    ('ㅁ', "M"),
+    ('ㅂ', "P"),
+    ('ㅄ', "PS"),
+    ('ㅅ', "S"),
+    ('ㅆ', "SS"),
+    ('ㅇ', "G"),
+    ('ㅈ', "J"),
+    ('ㅊ', "C"),
+    ('ㅋ', "Z"),
+    ('ㅌ', "D"),
+    ('ㅍ', "B"),
+    ('ㅎ', "H"),
+];
+
+/// Accented letters and the `CH` digraph, enabled with `Options::extended` alongside
+/// `EXTENDED_SYMBOLS`. Where several letters share a code, the first one listed is decoded.
+pub static EXTENDED_LETTERS: &[(&str, &str)] = &[
+    ("À", ".--.-"),
+    ("Å", ".--.-"),
+    ("Ä", ".-.-"),
+    ("Æ", ".-.-"),
+    ("CH", "----"),
+    ("Ç", "-.-.."),
+    ("É", "..-.."),
+    ("È", ".-..-"),
+    ("Ð", "..--."),
+    ("Ĝ", "--.-."),
+    ("Ĵ", ".---."),
+    ("Ñ", "--.--"),
+    ("Ö", "---."),
+    ("Ø", "---."),
+    ("Ś", "...-..."),
+    ("Þ", ".--.."),
+    ("Ü", "..--"),
+    ("Ź", "--..-."),
+    ("Ż", "--..-"),
+];
+
+/// Common English words and amateur radio abbreviations, used where a word list is needed and
+/// none is given.
+pub static WORDS: &[&str] = &[
+    "A", "ABOUT", "AFTER", "AGAIN", "AGE", "AIR", "ALL", "ALSO", "AM", "AN", "AND", "ANT", "ANY",
+    "ARE", "AREA", "ARM", "ART", "AS", "ASK", "AT", "AWAY", "BACK", "BAD", "BAND", "BE", "BED",
+    "BEEN", "BEST", "BIG", "BIRD", "BOAT", "BODY", "BOOK", "BOTH", "BOX", "BOY", "BUT", "BY",
+    "CALL", "CAME", "CAN", "CAR", "CAT", "CITY", "COLD", "COME", "COPY", "CQ", "CUL", "DAY", "DE",
+    "DID", "DO", "DOES", "DOG", "DONE", "DOOR", "DOWN", "DX", "EACH", "EAR", "EARTH", "EAST",
+    "EASY", "EAT", "END", "ES", "EVEN", "EVER", "EYE", "FACE", "FAR", "FAST", "FB", "FEW", "FIND",
+    "FIRE", "FISH", "FIVE", "FOR", "FORM", "FOUR", "FREE", "FROM", "FULL", "GAME", "GAVE", "GE",
+    "GET", "GIVE", "GM", "GN", "GO", "GOOD", "GOT", "GREAT", "HAD", "HAND", "HARD", "HAS", "HAVE",
+    "HE", "HEAR", "HELP", "HER", "HERE", "HIGH", "HIM", "HIS", "HOME", "HOT", "HOW", "HR", "HW",
+    "I", "IDEA", "IF", "IN", "INTO", "IS", "IT", "JUST", "KEEP", "KEY", "KIND", "KNOW", "LAND",
+    "LAST", "LATE", "LEFT", "LESS", "LET", "LIFE", "LIGHT", "LIKE", "LINE", "LIST", "LITTLE",
+    "LIVE", "LONG", "LOOK", "LOW", "MADE", "MAKE", "MAN", "MANY", "MAP", "MAY", "ME", "MEN",
+    "MIND", "MORE", "MOST", "MOVE", "MUCH", "MUST", "MY", "NAME", "NEAR", "NEED", "NEW", "NEXT",
+    "NIGHT", "NO", "NOT", "NOW", "NR", "NUMBER", "OF", "OFF", "OLD", "OM", "ON", "ONE", "ONLY",
+    "OPEN", "OR", "OTHER", "OUR", "OUT", "OVER", "OWN", "PAGE", "PART", "PEOPLE", "PLACE", "PLAY",
+    "POWER", "PSE", "PUT", "QRM", "QRN", "QRS", "QRZ", "QSL", "QSO", "QTH", "RADIO", "RAIN",
+    "READ", "RED", "RIG", "RIGHT", "RIVER", "ROAD", "ROOM", "RST", "RUN", "SAID", "SAME", "SAW",
+    "SAY", "SEA", "SEE", "SEND", "SET", "SHE", "SHIP", "SHORT", "SHOW", "SIDE", "SIGNAL", "SIX",
+    "SMALL", "SO", "SOME", "SONG", "SOON", "SOS", "SOUND", "SPELL", "STAR", "STILL", "STOP", "SUN",
+    "TAKE", "TEN", "TEST", "THAN", "THAT", "THE", "THEM", "THEN", "THERE", "THESE", "THEY",
+    "THING", "THINK", "THIS", "TIME", "TNX", "TO", "TOLD", "TOO", "TOOK", "TOWN", "TREE", "TRY",
+    "TU", "TURN", "TWO", "UP", "UR", "US", "USE", "VERY", "WANT", "WAS", "WATER", "WAY", "WE",
+    "WELL", "WENT", "WERE", "WEST", "WHAT", "WHEN", "WHERE", "WHICH", "WHILE", "WHO", "WHY",
+    "WILL", "WIND", "WITH", "WORD", "WORK", "WORLD", "WOULD", "WX", "YEAR", "YES", "YOU", "YOUR",
+];
+
+/// How often each letter appears in English text, in percent.
+pub static LETTER_FREQUENCIES: &[(u8, f64)] = &[
+    (b'A', 8.2),
+    (b'B', 1.5),
+    (b'C', 2.8),
+    (b'D', 4.3),
+    (b'E', 12.7),
+    (b'F', 2.2),
+    (b'G', 2.0),
+    (b'H', 6.1),
+    (b'I', 7.0),
+    (b'J', 0.15),
+    (b'K', 0.77),
+    (b'L', 4.0),
+    (b'M', 2.4),
+    (b'N', 6.7),
+    (b'O', 7.5),
+    (b'P', 1.9),
+    (b'Q', 0.095),
+    (b'R', 6.0),
+    (b'S', 6.3),
+    (b'T', 9.1),
+    (b'U', 2.8),
+    (b'V', 0.98),
+    (b'W', 2.4),
+    (b'X', 0.15),
+    (b'Y', 2.0),
+    (b'Z', 0.074),
+];
+
+/// Amateur-radio callsign prefixes with a rough weight for how often each is heard on the air, so
+/// that generated callsigns favor the busiest regions: North America, then Europe, then Asia and
+/// the rest of the world.
+pub static CALLSIGN_PREFIXES: &[(&str, u32)] = &[
+    // North America
+    ("K", 60),
+    ("W", 60),
+    ("N", 30),
+    ("KA", 8),
+    ("KB", 8),
+    ("KC", 8),
+    ("KD", 8),
+    ("AA", 6),
+    ("AB", 4),
+    ("VE", 10),
+    ("VA", 4),
+    ("XE", 3),
+    // Europe
+    ("G", 12),
+    ("M", 10),
+    ("F", 10),
+    ("DL", 20),
+    ("DK", 8),
+    ("I", 10),
+    ("EA", 10),
+    ("ON", 5),
+    ("PA", 6),
+    ("OH", 5),
+    ("SM", 5),
+    ("LA", 3),
+    ("OK", 6),
+    ("SP", 8),
+    ("HA", 4),
+    ("YU", 3),
+    ("9A", 3),
+    ("S5", 3),
+    ("UA", 12),
+    ("UR", 5),
+    // Asia
+    ("JA", 15),
+    ("JH", 5),
+    ("JR", 4),
+    ("BY", 3),
+    ("HL", 3),
+    ("VU", 2),
+    // Oceania
+    ("VK", 5),
+    ("ZL", 3),
+    // South America
+    ("PY", 5),
+    ("LU", 4),
+    ("CE", 2),
+    ("CX", 1),
+    // Africa
+    ("ZS", 2),
+    ("5N", 1),
+    ("CN", 1),
+];
+
+/// Operator names as sent on the air: short, and easy to copy.
+pub static OPERATOR_NAMES: &[&str] = &[
+    "BOB", "JIM", "TOM", "BILL", "JOHN", "MIKE", "DAVE", "STEVE", "ED", "AL", "ANN", "SUE", "MARY",
+    "JO", "KEN", "RON", "HANS", "PETER", "KLAUS", "JEAN", "LUC", "PAOLO", "MARCO", "JOSE", "KARL",
+    "OLE", "JAN", "TARO", "IVAN", "SERGEI", "RAY", "PAT", "LES", "DON", "GUS",
+];
+
+/// Places an operator might give as their QTH.
+pub static QTHS: &[&str] = &[
+    "BOSTON",
+    "DENVER",
+    "DALLAS",
+    "SEATTLE",
+    "OHIO",
+    "MAINE",
+    "TEXAS",
+    "TORONTO",
+    "LONDON",
+    "PARIS",
+    "BERLIN",
+    "MUNICH",
+    "ROME",
+    "MADRID",
+    "OSLO",
+    "PRAGUE",
+    "WARSAW",
+    "TOKYO",
+    "OSAKA",
+    "SYDNEY",
+    "AUCKLAND",
+    "LIMA",
+    "RIO",
+    "CAPE TOWN",
+    "HELSINKI",
+    "VIENNA",
+    "ZAGREB",
+    "KYIV",
+];
@@ -0,0 +1,208 @@
+//! The allocation-free core of the crate: character codes and key timing, with no dependency on
+//! `std`, so that messages can be keyed from firmware. Everything else builds on these.
+
+use core::{str::Chars, time::Duration};
+
+use crate::{data, Code};
+
+/// A single stretch of key-down or key-up time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyEvent {
+    Down(Duration),
+    Up(Duration),
+}
+
+impl KeyEvent {
+    pub fn duration(self) -> Duration {
+        match self {
+            KeyEvent::Down(duration) | KeyEvent::Up(duration) => duration,
+        }
+    }
+}
+
+/// Keying speed, measured in words per minute of the standard word "PARIS".
+///
+/// Characters are always sent at `char_wpm`. When `effective_wpm` is slower, the gaps between
+/// characters and words are stretched so that the overall rate matches it (Farnsworth timing).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timing {
+    pub char_wpm: u32,
+    pub effective_wpm: u32,
+    /// How many dits long a dah is. Some operators like a heavier or lighter fist than the
+    /// standard [`WEIGHT`].
+    pub weight: f64,
+}
+
+/// The standard length of a dah, in dits.
+pub const WEIGHT: f64 = 3.0;
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing::new(20)
+    }
+}
+
+impl Timing {
+    pub fn new(wpm: u32) -> Self {
+        Timing {
+            char_wpm: wpm,
+            effective_wpm: wpm,
+            weight: WEIGHT,
+        }
+    }
+
+    pub fn farnsworth(char_wpm: u32, effective_wpm: u32) -> Self {
+        Timing {
+            char_wpm,
+            effective_wpm,
+            weight: WEIGHT,
+        }
+    }
+
+    /// The same speed with dahs `weight` dits long.
+    pub fn weighted(self, weight: f64) -> Self {
+        Timing { weight, ..self }
+    }
+
+    /// The length of a dit, which is also the gap between elements of a character.
+    pub fn dit(&self) -> Duration {
+        Duration::from_micros(1_200_000 / u64::from(self.char_wpm.max(1)))
+    }
+
+    pub fn dah(&self) -> Duration {
+        // Rounded by hand, as `f64::round` needs std.
+        let dit = self.dit().as_nanos() as f64;
+        Duration::from_nanos((dit * self.weight.max(0.0) + 0.5) as u64)
+    }
+
+    pub fn char_gap(&self) -> Duration {
+        self.farnsworth_gap(3).unwrap_or_else(|| self.dit() * 3)
+    }
+
+    pub fn word_gap(&self) -> Duration {
+        self.farnsworth_gap(7).unwrap_or_else(|| self.dit() * 7)
+    }
+
+    /// PARIS contains 19 units of inter-character and inter-word spacing; when sending
+    /// Farnsworth, that spacing is spread over whatever time remains in the slower minute.
+    fn farnsworth_gap(&self, units: u32) -> Option<Duration> {
+        if self.effective_wpm == 0 || self.effective_wpm >= self.char_wpm {
+            return None;
+        }
+
+        let c = f64::from(self.char_wpm);
+        let s = f64::from(self.effective_wpm);
+        let spacing = (60.0 * c - 37.2 * s) / (s * c);
+        Some(Duration::from_secs_f64(spacing * f64::from(units) / 19.0))
+    }
+}
+
+/// The code for a letter, digit or punctuation mark, ignoring case. Only the basic ITU set is
+/// covered; see `encode_with` for prosigns and the other alphabets.
+pub fn code(c: char) -> Option<Code> {
+    match c {
+        'A'..='Z' => Some(data::ENCODED_SEQUENCES[(c as u8 - b'A') as usize]),
+        'a'..='z' => Some(data::ENCODED_SEQUENCES[(c as u8 - b'a') as usize]),
+        '0'..='9' => Some(data::ENCODED_SEQUENCES[(c as u8 - b'0' + 26) as usize]),
+        c if c.is_ascii() => data::PUNCTUATION
+            .iter()
+            .find(|&&(symbol, _)| symbol == c as u8)
+            .map(|&(_, code)| code),
+        _ => None,
+    }
+}
+
+/// Keys `message` directly, without encoding it first. Characters without a code are skipped,
+/// and any run of spaces becomes a single word gap.
+pub fn key_events(message: &str, timing: Timing) -> KeyEvents<'_> {
+    KeyEvents {
+        message: message.chars(),
+        timing,
+        elements: &[],
+        gap: None,
+        space: false,
+    }
+}
+
+/// The key events for a message, produced one at a time; see `key_events`.
+#[derive(Clone, Debug)]
+pub struct KeyEvents<'a> {
+    message: Chars<'a>,
+    timing: Timing,
+    elements: &'static [u8],
+    /// The gap owed before the next element; `None` until the first has been sent.
+    gap: Option<Duration>,
+    space: bool,
+}
+
+impl Iterator for KeyEvents<'_> {
+    type Item = KeyEvent;
+
+    fn next(&mut self) -> Option<KeyEvent> {
+        loop {
+            if let Some((&element, rest)) = self.elements.split_first() {
+                if let Some(gap) = self.gap.take() {
+                    return Some(KeyEvent::Up(gap));
+                }
+
+                self.elements = rest;
+                self.gap = Some(self.timing.dit());
+                return Some(match element {
+                    b'-' => KeyEvent::Down(self.timing.dah()),
+                    _ => KeyEvent::Down(self.timing.dit()),
+                });
+            }
+
+            let c = self.message.next()?;
+            if c == ' ' {
+                self.space = true;
+                continue;
+            }
+
+            if let Some(code) = code(c) {
+                if self.gap.is_some() {
+                    self.gap = Some(if self.space {
+                        self.timing.word_gap()
+                    } else {
+                        self.timing.char_gap()
+                    });
+                }
+                self.space = false;
+                self.elements = code.as_bytes();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::{KeyEvent, Timing};
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn matches_encoded_key_events() {
+        let timing = Timing::farnsworth(20, 12);
+        for message in &["sos", "  cq de w1aw k ", "73, om?", "e  e"] {
+            let encoded = crate::encode(message).unwrap();
+            let expected = crate::timing::to_key_events(&encoded, &timing, None);
+            let actual: Vec<KeyEvent> = super::key_events(message, timing).collect();
+            assert_eq!(actual, expected, "{:?}", message);
+        }
+    }
+
+    #[test]
+    fn skips_characters_without_codes() {
+        let timing = Timing::new(20);
+        let events: Vec<KeyEvent> = super::key_events("e\u{2603}e", timing).collect();
+        assert_eq!(
+            events,
+            [
+                KeyEvent::Down(timing.dit()),
+                KeyEvent::Up(timing.char_gap()),
+                KeyEvent::Down(timing.dit()),
+            ]
+        );
+    }
+}
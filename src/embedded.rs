@@ -0,0 +1,95 @@
+//! Keying an `embedded-hal` output pin, for blinking status messages from firmware. This module
+//! needs neither `std` nor an allocator.
+
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::elements::{self, KeyEvent, Timing};
+
+/// Sends `message` on `pin`, driving it high while the key is down and waiting out each element
+/// and gap on `delay`. Characters without a code are skipped, and the pin is left low.
+pub fn blink<P: OutputPin, D: DelayNs>(
+    pin: &mut P,
+    delay: &mut D,
+    message: &str,
+    timing: Timing,
+) -> Result<(), P::Error> {
+    for event in elements::key_events(message, timing) {
+        match event {
+            KeyEvent::Down(_) => pin.set_high()?,
+            KeyEvent::Up(_) => pin.set_low()?,
+        }
+
+        let micros = event.duration().as_micros();
+        delay.delay_us(micros.min(u128::from(u32::MAX)) as u32);
+    }
+
+    pin.set_low()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, convert::Infallible, vec::Vec};
+
+    use embedded_hal::{
+        delay::DelayNs,
+        digital::{ErrorType, OutputPin},
+    };
+
+    use crate::elements::Timing;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Step {
+        High,
+        Low,
+        Wait(u32),
+    }
+
+    struct Pin<'a>(&'a RefCell<Vec<Step>>);
+
+    impl ErrorType for Pin<'_> {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for Pin<'_> {
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.0.borrow_mut().push(Step::Low);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            self.0.borrow_mut().push(Step::High);
+            Ok(())
+        }
+    }
+
+    struct Delay<'a>(&'a RefCell<Vec<Step>>);
+
+    impl DelayNs for Delay<'_> {
+        fn delay_ns(&mut self, ns: u32) {
+            self.delay_us(ns / 1000);
+        }
+
+        fn delay_us(&mut self, us: u32) {
+            self.0.borrow_mut().push(Step::Wait(us));
+        }
+    }
+
+    #[test]
+    fn blinks_message() {
+        let steps = RefCell::new(Vec::new());
+        super::blink(&mut Pin(&steps), &mut Delay(&steps), "e t", Timing::new(20)).unwrap();
+
+        assert_eq!(
+            steps.into_inner(),
+            [
+                Step::High,
+                Step::Wait(60_000),
+                Step::Low,
+                Step::Wait(420_000),
+                Step::High,
+                Step::Wait(180_000),
+                Step::Low,
+            ]
+        );
+    }
+}
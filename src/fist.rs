@@ -0,0 +1,119 @@
+//! Sending-accuracy analysis: how closely a fist keeps to the ideal 1:3 element ratio and to even
+//! spacing.
+
+use crate::timing::{self, KeyEvent, Mark};
+
+/// Measurements of hand-sent code. Ratios are relative to the average dit; spreads are
+/// coefficients of variation (standard deviation over mean), 0 for perfectly even timing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fist {
+    pub wpm: f64,
+    /// Average dah to average dit, ideally 3.
+    pub dah_ratio: f64,
+    /// Average gap inside characters to average dit, ideally 1.
+    pub gap_ratio: f64,
+    /// Average gap between characters to average dit: ideally 3, more with Farnsworth spacing.
+    pub char_gap_ratio: Option<f64>,
+    /// Average gap between words to average dit: ideally 7, more with Farnsworth spacing.
+    pub word_gap_ratio: Option<f64>,
+    pub dit_spread: f64,
+    pub dah_spread: f64,
+    pub gap_spread: f64,
+    /// From 0 to 100. Every error in the element ratios and every bit of spread costs points;
+    /// character and word spacing do not count, since slowing them down is a choice.
+    pub score: f64,
+}
+
+/// Analyzes key events, or returns `None` if they hold no dits or no dahs to compare.
+pub fn analyze(events: &[KeyEvent]) -> Option<Fist> {
+    let (mut dits, mut dahs, mut gaps, mut char_gaps, mut word_gaps) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+
+    // Classifying skips leading gaps, so skip them here too to keep the two in step.
+    let start = events
+        .iter()
+        .position(|event| matches!(event, KeyEvent::Down(_)))
+        .unwrap_or(events.len());
+    let events = &events[start..];
+    for (event, classified) in events.iter().zip(timing::classify(events)) {
+        let ms = event.duration().as_secs_f64() * 1000.0;
+        match classified.mark {
+            Mark::Dit => dits.push(ms),
+            Mark::Dah => dahs.push(ms),
+            Mark::ElementGap => gaps.push(ms),
+            Mark::CharGap => char_gaps.push(ms),
+            Mark::WordGap => word_gaps.push(ms),
+        }
+    }
+
+    let dit = mean(&dits)?;
+    let dah = mean(&dahs)?;
+    let ratio = |values: &[f64]| mean(values).map(|mean| mean / dit);
+    let gap_ratio = ratio(&gaps).unwrap_or(1.0);
+    let (dit_spread, dah_spread, gap_spread) = (spread(&dits), spread(&dahs), spread(&gaps));
+
+    let penalty =
+        (dah / dit / 3.0).ln().abs() + gap_ratio.ln().abs() + dit_spread + dah_spread + gap_spread;
+
+    Some(Fist {
+        wpm: 1200.0 / dit,
+        dah_ratio: dah / dit,
+        gap_ratio,
+        char_gap_ratio: ratio(&char_gaps),
+        word_gap_ratio: ratio(&word_gaps),
+        dit_spread,
+        dah_spread,
+        gap_spread,
+        score: 100.0 * (-penalty).exp(),
+    })
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+fn spread(values: &[f64]) -> f64 {
+    let mean = match mean(values) {
+        Some(mean) if mean > 0.0 => mean,
+        _ => return 0.0,
+    };
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    variance.sqrt() / mean
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rng::Rng,
+        timing::{self, Jitter, Timing},
+    };
+
+    #[test]
+    fn perfect_timing_scores_full_marks() {
+        let events = timing::to_key_events("-.-. --.- / -.. -..-", &Timing::new(25), None);
+        let fist = super::analyze(&events).unwrap();
+        assert!((fist.wpm - 25.0).abs() < 1e-6, "{:?}", fist);
+        assert!((fist.dah_ratio - 3.0).abs() < 1e-9);
+        assert!((fist.char_gap_ratio.unwrap() - 3.0).abs() < 1e-9);
+        assert!((fist.word_gap_ratio.unwrap() - 7.0).abs() < 1e-9);
+        assert!((fist.score - 100.0).abs() < 1e-6, "{:?}", fist);
+    }
+
+    #[test]
+    fn uneven_timing_costs_points() {
+        let mut jitter = Jitter::new(15.0, Rng::new(2));
+        let events =
+            timing::to_key_events("-.-. --.- / -.. -..-", &Timing::new(25), Some(&mut jitter));
+        let fist = super::analyze(&events).unwrap();
+        assert!(fist.dit_spread > 0.0 && fist.gap_spread > 0.0, "{:?}", fist);
+        assert!(fist.score < 95.0 && fist.score > 30.0, "{:?}", fist);
+
+        assert!(
+            super::analyze(&timing::to_key_events("... . ..", &Timing::new(20), None)).is_none()
+        );
+    }
+}
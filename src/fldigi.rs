@@ -0,0 +1,253 @@
+//! Using a running fldigi for CW through its XML-RPC interface: text is queued on its transmit
+//! pane, and whatever it decodes is read back from its receive pane.
+//!
+//! Only as much of XML-RPC as fldigi's CW calls need is spoken: strings, integers and base64.
+
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+};
+
+use crate::{Options, Result};
+
+/// The port fldigi serves XML-RPC on.
+pub const PORT: u16 = 7362;
+
+/// An XML-RPC value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Int(i32),
+    String(String),
+    Bytes(Vec<u8>),
+    Nil,
+}
+
+/// A running fldigi.
+pub struct Fldigi {
+    address: String,
+    /// How much of the receive pane has been read so far.
+    received: usize,
+}
+
+impl Fldigi {
+    /// Checks that fldigi is answering at `address` (on its usual port, if none is given), and
+    /// puts it in CW mode. Text already in its receive pane is skipped.
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let address = if address.contains(':') {
+            address.to_string()
+        } else {
+            format!("{}:{}", address, PORT)
+        };
+        let mut fldigi = Fldigi {
+            address,
+            received: 0,
+        };
+        fldigi.call("modem.set_by_name", &[Value::String("CW".into())])?;
+        fldigi.received = fldigi.rx_length()?;
+        Ok(fldigi)
+    }
+
+    pub fn set_wpm(&mut self, wpm: u32) -> io::Result<()> {
+        self.call("cw.set_wpm", &[Value::Int(wpm as i32)])?;
+        Ok(())
+    }
+
+    /// Queues `message` and starts transmitting, leaving out anything that cannot be encoded.
+    /// fldigi goes back to receiving once the message has been sent.
+    pub fn send(&mut self, message: &str) -> Result<()> {
+        let words: Vec<&str> = message.split_whitespace().collect();
+        let message = crate::sanitize(&words.join(" "), &Options::default());
+        self.call("text.add_tx", &[Value::String(format!("{}^r", message))])
+            .and_then(|_| self.call("main.tx", &[]))
+            .map(|_| ())
+            .map_err(crate::Error::Io)
+    }
+
+    /// Stops transmitting at once, abandoning anything not yet sent.
+    pub fn abort(&mut self) -> io::Result<()> {
+        self.call("main.abort", &[])?;
+        self.call("text.clear_tx", &[]).map(|_| ())
+    }
+
+    /// Text decoded since the last call (or since connecting).
+    pub fn receive(&mut self) -> io::Result<String> {
+        let length = self.rx_length()?;
+        if length < self.received {
+            // The receive pane was cleared.
+            self.received = 0;
+        }
+        if length == self.received {
+            return Ok(String::new());
+        }
+
+        let text = match self.call(
+            "text.get_rx",
+            &[
+                Value::Int(self.received as i32),
+                Value::Int((length - self.received) as i32),
+            ],
+        )? {
+            Value::Bytes(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Value::String(text) => text,
+            value => return Err(unexpected(&value)),
+        };
+        self.received = length;
+        Ok(text)
+    }
+
+    fn rx_length(&mut self) -> io::Result<usize> {
+        match self.call("text.get_rx_length", &[])? {
+            Value::Int(length) => Ok(length.max(0) as usize),
+            value => Err(unexpected(&value)),
+        }
+    }
+
+    /// Makes one call, over a connection of its own.
+    pub fn call(&mut self, method: &str, params: &[Value]) -> io::Result<Value> {
+        let body = request(method, params);
+        let mut stream = TcpStream::connect(&self.address)?;
+        write!(
+            stream,
+            "POST /RPC2 HTTP/1.0\r\nHost: {}\r\nContent-Type: text/xml\r\nContent-Length: {}\r\n\r\n{}",
+            self.address,
+            body.len(),
+            body
+        )?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let body = response
+            .split_once("\r\n\r\n")
+            .map(|(_, body)| body)
+            .ok_or_else(|| invalid("response without a body"))?;
+        response_value(body)
+    }
+}
+
+/// The XML for a call.
+pub fn request(method: &str, params: &[Value]) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\"?><methodCall><methodName>{}</methodName><params>",
+        method
+    );
+    for param in params {
+        xml.push_str("<param><value>");
+        match param {
+            Value::Int(n) => xml.push_str(&format!("<i4>{}</i4>", n)),
+            Value::String(s) => xml.push_str(&format!("<string>{}</string>", escape(s))),
+            Value::Bytes(bytes) => xml.push_str(&format!(
+                "<base64>{}</base64>",
+                crate::base64::encode(bytes)
+            )),
+            Value::Nil => xml.push_str("<nil/>"),
+        }
+        xml.push_str("</value></param>");
+    }
+    xml.push_str("</params></methodCall>");
+    xml
+}
+
+/// The value returned in a response, or the fault it reports as an error.
+pub fn response_value(xml: &str) -> io::Result<Value> {
+    if xml.contains("<fault>") {
+        let message = between(xml, "<name>faultString</name>", "</member>")
+            .and_then(|member| between(member, "<value>", "</value>"))
+            .map(|value| text(value).unwrap_or_else(|| unescape(value)))
+            .unwrap_or_else(|| "unknown fault".into());
+        return Err(io::Error::other(format!("fldigi: {}", message)));
+    }
+
+    let value = between(xml, "<value>", "</value>").ok_or_else(|| invalid("no value"))?;
+    let value = value.trim();
+    if value.is_empty() || value == "<nil/>" {
+        return Ok(Value::Nil);
+    }
+    if let Some(n) = between(value, "<i4>", "</i4>").or_else(|| between(value, "<int>", "</int>")) {
+        return n
+            .trim()
+            .parse()
+            .map(Value::Int)
+            .map_err(|_| invalid("bad integer"));
+    }
+    if let Some(data) = between(value, "<base64>", "</base64>") {
+        return crate::base64::decode(data)
+            .map(Value::Bytes)
+            .ok_or_else(|| invalid("bad base64"));
+    }
+    Ok(Value::String(
+        text(value).unwrap_or_else(|| unescape(value)),
+    ))
+}
+
+/// The contents of a `<string>` element.
+fn text(value: &str) -> Option<String> {
+    if value.contains("<string/>") {
+        return Some(String::new());
+    }
+    between(value, "<string>", "</string>").map(unescape)
+}
+
+fn between<'a>(xml: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let (_, rest) = xml.split_once(start)?;
+    rest.split_once(end).map(|(inner, _)| inner)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn unexpected(value: &Value) -> io::Error {
+    invalid(&format!("unexpected value {:?}", value))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("fldigi: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+
+    #[test]
+    fn writes_calls() {
+        let xml = super::request("text.add_tx", &[Value::String("<SK> & 73".into())]);
+        assert!(xml.contains("<methodName>text.add_tx</methodName>"));
+        assert!(xml.contains("<value><string>&lt;SK&gt; &amp; 73</string></value>"));
+    }
+
+    #[test]
+    fn reads_responses() {
+        let response = |value: &str| {
+            super::response_value(&format!(
+                "<?xml version=\"1.0\"?><methodResponse><params><param><value>{}</value></param></params></methodResponse>",
+                value
+            ))
+            .unwrap()
+        };
+        assert_eq!(response("<i4>42</i4>"), Value::Int(42));
+        assert_eq!(response("CW"), Value::String("CW".into()));
+        assert_eq!(
+            response("<string>a &amp; b</string>"),
+            Value::String("a & b".into())
+        );
+        assert_eq!(
+            response("<base64>Q1EgREU=</base64>"),
+            Value::Bytes(b"CQ DE".to_vec())
+        );
+        assert_eq!(response(""), Value::Nil);
+
+        let fault = "<methodResponse><fault><value><struct><member><name>faultCode</name><value><i4>1</i4></value></member><member><name>faultString</name><value>No such method</value></member></struct></value></fault></methodResponse>";
+        let e = super::response_value(fault).unwrap_err();
+        assert_eq!(e.to_string(), "fldigi: No such method");
+    }
+}
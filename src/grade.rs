@@ -0,0 +1,181 @@
+//! Grading copy against what was sent, by aligning the two with the fewest edits.
+
+use std::collections::BTreeMap;
+
+/// One step of an alignment between sent text and its copy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edit {
+    Match(char),
+    /// `sent` was copied as `copied`.
+    Substitute {
+        sent: char,
+        copied: char,
+    },
+    /// Sent but not copied.
+    Delete(char),
+    /// Copied but never sent.
+    Insert(char),
+}
+
+/// A graded copy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grade {
+    pub edits: Vec<Edit>,
+    /// One less the number of edits relative to the length of the sent text, down to 0.
+    pub accuracy: f64,
+}
+
+impl Grade {
+    /// Aligns `copied` with `sent`. Case and runs of whitespace do not count.
+    pub fn of(sent: &str, copied: &str) -> Self {
+        let sent = normalize(sent);
+        let copied = normalize(copied);
+        let edits = align(&sent, &copied);
+
+        let errors = edits
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Match(_)))
+            .count();
+        let accuracy = if sent.is_empty() {
+            if copied.is_empty() {
+                1.0
+            } else {
+                0.0
+            }
+        } else {
+            (1.0 - errors as f64 / sent.len() as f64).max(0.0)
+        };
+
+        Grade { edits, accuracy }
+    }
+
+    /// How often each sent character was copied as each other one. Deletions are counted against
+    /// `None` in place of the copied character, and insertions against `None` in place of the sent
+    /// one.
+    pub fn confusions(&self) -> BTreeMap<(Option<char>, Option<char>), u32> {
+        let mut confusions = BTreeMap::new();
+        for edit in &self.edits {
+            let key = match *edit {
+                Edit::Match(_) => continue,
+                Edit::Substitute { sent, copied } => (Some(sent), Some(copied)),
+                Edit::Delete(sent) => (Some(sent), None),
+                Edit::Insert(copied) => (None, Some(copied)),
+            };
+            *confusions.entry(key).or_insert(0) += 1;
+        }
+        confusions
+    }
+
+    /// The copy with its mistakes marked in wdiff style: `[-sent-]` for what was missed or
+    /// miscopied and `{+copied+}` for what was written instead.
+    pub fn marked(&self) -> String {
+        let mut buf = String::new();
+        let mut missed = String::new();
+        let mut added = String::new();
+
+        let flush = |buf: &mut String, missed: &mut String, added: &mut String| {
+            if !missed.is_empty() {
+                buf.push_str(&format!("[-{}-]", missed));
+                missed.clear();
+            }
+            if !added.is_empty() {
+                buf.push_str(&format!("{{+{}+}}", added));
+                added.clear();
+            }
+        };
+
+        for edit in &self.edits {
+            match *edit {
+                Edit::Match(c) => {
+                    flush(&mut buf, &mut missed, &mut added);
+                    buf.push(c);
+                }
+                Edit::Substitute { sent, copied } => {
+                    missed.push(sent);
+                    added.push(copied);
+                }
+                Edit::Delete(sent) => missed.push(sent),
+                Edit::Insert(copied) => added.push(copied),
+            }
+        }
+        flush(&mut buf, &mut missed, &mut added);
+        buf
+    }
+}
+
+fn normalize(text: &str) -> Vec<char> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words.join(" ").to_uppercase().chars().collect()
+}
+
+/// A least-cost alignment by Levenshtein distance, preferring matches and substitutions over
+/// deletions and insertions when they tie.
+fn align(sent: &[char], copied: &[char]) -> Vec<Edit> {
+    let width = copied.len() + 1;
+    let mut costs: Vec<usize> = (0..width).collect();
+    costs.resize((sent.len() + 1) * width, 0);
+    for i in 1..=sent.len() {
+        costs[i * width] = i;
+        for j in 1..width {
+            let substitute =
+                costs[(i - 1) * width + j - 1] + usize::from(sent[i - 1] != copied[j - 1]);
+            let delete = costs[(i - 1) * width + j] + 1;
+            let insert = costs[i * width + j - 1] + 1;
+            costs[i * width + j] = substitute.min(delete).min(insert);
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (sent.len(), copied.len());
+    while i > 0 || j > 0 {
+        let cost = costs[i * width + j];
+        if i > 0 && j > 0 {
+            let same = sent[i - 1] == copied[j - 1];
+            if cost == costs[(i - 1) * width + j - 1] + usize::from(!same) {
+                edits.push(if same {
+                    Edit::Match(sent[i - 1])
+                } else {
+                    Edit::Substitute {
+                        sent: sent[i - 1],
+                        copied: copied[j - 1],
+                    }
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && cost == costs[(i - 1) * width + j] + 1 {
+            edits.push(Edit::Delete(sent[i - 1]));
+            i -= 1;
+        } else {
+            edits.push(Edit::Insert(copied[j - 1]));
+            j -= 1;
+        }
+    }
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Edit, Grade};
+
+    #[test]
+    fn aligns_copy_with_the_source() {
+        let grade = Grade::of("the quick fox", "THE QUIK FOKS");
+        assert_eq!(grade.marked(), "THE QUI[-C-]K FO[-X-]{+KS+}");
+        assert!((grade.accuracy - (1.0 - 3.0 / 13.0)).abs() < 1e-9);
+        assert_eq!(grade.confusions()[&(Some('C'), None)], 1);
+        assert_eq!(grade.confusions().values().sum::<u32>(), 3);
+        assert_eq!(Grade::of("5", "H").confusions()[&(Some('5'), Some('H'))], 1);
+
+        assert_eq!(Grade::of("SOS", " s o s ").edits.len(), 5);
+        assert_eq!(Grade::of("SOS", "sos").accuracy, 1.0);
+        assert_eq!(
+            Grade::of("SOS", "").edits,
+            vec![Edit::Delete('S'), Edit::Delete('O'), Edit::Delete('S')]
+        );
+        assert_eq!(Grade::of("", "").accuracy, 1.0);
+    }
+}
@@ -0,0 +1,110 @@
+//! Vibration patterns: alternating vibrate and pause times in milliseconds, starting with a
+//! vibration, as taken by the Web Vibration API's `navigator.vibrate`. Android's
+//! `VibrationEffect.createWaveform` takes the same times after a leading pause of 0.
+
+use std::str::FromStr;
+
+use crate::timing::KeyEvent;
+
+/// How a pattern is written out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    /// A JSON array, ready to pass to `navigator.vibrate`.
+    Json,
+    /// Comma-separated values on one line.
+    Csv,
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "haptic" | "haptic-json" => Ok(Pattern::Json),
+            "haptic-csv" => Ok(Pattern::Csv),
+            _ => Err(format!(
+                "unknown haptic format {:?} (expected haptic, haptic-json or haptic-csv)",
+                s
+            )),
+        }
+    }
+}
+
+/// Alternating vibrate and pause times in milliseconds for key events, dropping any pause before
+/// the first vibration or after the last. Times are rounded counting from the start, so that
+/// rounding does not build up over a long message.
+pub fn durations(events: &[KeyEvent]) -> Vec<u64> {
+    let start = events
+        .iter()
+        .position(|event| matches!(event, KeyEvent::Down(_)))
+        .unwrap_or(events.len());
+    let end = events
+        .iter()
+        .rposition(|event| matches!(event, KeyEvent::Down(_)))
+        .map_or(start, |idx| idx + 1);
+
+    let mut durations: Vec<u64> = Vec::new();
+    let (mut elapsed, mut written, mut down) = (0.0f64, 0, false);
+    for event in &events[start..end] {
+        elapsed += event.duration().as_secs_f64();
+        let end = (elapsed * 1000.0).round() as u64;
+        let is_down = matches!(event, KeyEvent::Down(_));
+        match durations.last_mut() {
+            // Two periods the same way round, as from a zero-length gap, make one.
+            Some(last) if is_down == down => *last += end - written,
+            _ => durations.push(end - written),
+        }
+        down = is_down;
+        written = end;
+    }
+    durations
+}
+
+/// Writes the vibration pattern for key events.
+pub fn write(pattern: Pattern, events: &[KeyEvent]) -> String {
+    let durations: Vec<String> = durations(events).iter().map(u64::to_string).collect();
+    match pattern {
+        Pattern::Json => format!("[{}]", durations.join(",")),
+        Pattern::Csv => durations.join(","),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Pattern;
+    use crate::timing::{
+        self,
+        KeyEvent::{Down, Up},
+        Timing,
+    };
+
+    #[test]
+    fn alternates_vibrations_and_pauses() {
+        let timing = Timing::new(20);
+        let events = timing::to_key_events(".- / -", &timing, None);
+        assert_eq!(super::write(Pattern::Json, &events), "[60,60,180,420,180]");
+        assert_eq!(super::write(Pattern::Csv, &events), "60,60,180,420,180");
+    }
+
+    #[test]
+    fn trims_pauses_and_rounds_from_the_start() {
+        let us = Duration::from_micros;
+        let events = [
+            Up(us(500_000)),
+            Down(us(60_400)),
+            Up(us(60_400)),
+            Down(us(60_400)),
+            Up(us(420_000)),
+        ];
+        assert_eq!(super::durations(&events), [60, 61, 60]);
+    }
+
+    #[test]
+    fn parses_patterns() {
+        assert_eq!("haptic".parse(), Ok(Pattern::Json));
+        assert_eq!("Haptic-CSV".parse(), Ok(Pattern::Csv));
+        assert!("csv".parse::<Pattern>().is_err());
+    }
+}
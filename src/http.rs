@@ -0,0 +1,596 @@
+//! The HTTP API served by `morse serve --http`, for web apps and other services.
+//!
+//! `POST /encode` and `POST /decode` take a JSON object with the message under `text` and answer
+//! with the same report as `--format json`. `/wav` renders `text` as audio, taking `wpm`,
+//! `farnsworth`, `tone` and the sample `rate` as well; it also answers `GET`, with the
+//! parameters in the query string, so that a page can point an `<audio>` element at it.
+//!
+//! `/stream` is a WebSocket that answers text a word at a time; see [`crate::websocket`].
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use morse::{audio, timing, Options, Standard, Timing};
+
+use crate::{
+    report::{self, Report},
+    websocket,
+};
+
+/// The largest request body accepted.
+const MAX_BODY: usize = 1 << 20;
+
+/// The longest request line or header line accepted, in bytes.
+const MAX_LINE: u64 = 8 << 10;
+
+/// The most header lines accepted in a request.
+const MAX_HEADERS: usize = 100;
+
+/// The longest recording `/wav` renders, or `/stream` for a single message.
+pub const MAX_AUDIO: Duration = Duration::from_secs(10 * 60);
+
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Connections handled at once; any more are turned away until one finishes.
+const MAX_CONNECTIONS: usize = 64;
+
+/// Request parameters, from the query string or a JSON body.
+pub type Params = BTreeMap<String, String>;
+
+#[derive(Debug, Default)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub params: Params,
+    /// Header values, by lower-cased name.
+    pub headers: BTreeMap<String, String>,
+}
+
+pub enum Body {
+    Json(String),
+    /// Samples and their rate.
+    Wav(Vec<f32>, u32),
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: Body,
+}
+
+impl Response {
+    fn json(status: u16, json: String) -> Self {
+        Response {
+            status,
+            body: Body::Json(json),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        let mut json = String::from("{\"error\":");
+        report::push_string(&mut json, message);
+        json.push('}');
+        Response::json(status, json)
+    }
+}
+
+/// Serves the API on `address` until an I/O error stops it, one thread per connection, up to
+/// `MAX_CONNECTIONS` at a time.
+pub fn serve(address: SocketAddr) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    eprintln!("serving HTTP on {}", listener.local_addr()?);
+    let open = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let stream = stream?;
+        if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            open.fetch_sub(1, Ordering::SeqCst);
+            let busy = Response::error(503, "too many connections");
+            if let Err(e) = write_response(BufWriter::new(stream), &busy) {
+                eprintln!("http: {}", e);
+            }
+            continue;
+        }
+        let open = Arc::clone(&open);
+        thread::spawn(move || {
+            if let Err(e) = handle(stream) {
+                eprintln!("http: {}", e);
+            }
+            open.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+    Ok(())
+}
+
+fn handle(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader) {
+        Ok(request) if websocket::is_upgrade(&request) => {
+            // A socket may sit idle between messages for as long as the client likes.
+            stream.set_read_timeout(None)?;
+            return websocket::run(stream, &request);
+        }
+        Ok(request) => respond(&request),
+        Err(e) if e.get_ref().is_some_and(|e| e.is::<HeadersTooLarge>()) => {
+            Response::error(431, &e.to_string())
+        }
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Response::error(400, &e.to_string()),
+        Err(e) => return Err(e),
+    };
+    write_response(BufWriter::new(stream), &response)
+}
+
+/// Reads a request line, headers and body, merging query and body parameters.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Request> {
+    let mut line = String::new();
+    read_line(reader, &mut line, || invalid("request line too long"))?;
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Err(invalid("malformed request line")),
+    };
+
+    let mut length = 0;
+    let mut headers = BTreeMap::new();
+    let too_large = || io::Error::new(io::ErrorKind::InvalidData, HeadersTooLarge);
+    for count in 0.. {
+        if !read_line(reader, &mut line, too_large)? || line.trim().is_empty() {
+            break;
+        }
+        if count == MAX_HEADERS {
+            return Err(too_large());
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let name = name.trim().to_ascii_lowercase();
+            if name == "content-length" {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid("bad Content-Length"))?;
+            }
+            headers.insert(name, value.trim().to_string());
+        }
+    }
+    if length > MAX_BODY {
+        return Err(invalid("request body too large"));
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let mut params = parse_query(query);
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).map_err(|_| invalid("request body is not UTF-8"))?;
+    if !body.trim().is_empty() {
+        params.extend(parse_json(&body).map_err(|e| invalid(&e))?);
+    }
+
+    Ok(Request {
+        method,
+        path: path.into(),
+        params,
+        headers,
+    })
+}
+
+/// Reads a line of at most `MAX_LINE` bytes into `line`, returning false at the end of input,
+/// or the error `too_long` gives if the line goes on past that.
+fn read_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    too_long: impl FnOnce() -> io::Error,
+) -> io::Result<bool> {
+    line.clear();
+    let read = reader.by_ref().take(MAX_LINE).read_line(line)?;
+    if read as u64 == MAX_LINE && !line.ends_with('\n') {
+        return Err(too_long());
+    }
+    Ok(read > 0)
+}
+
+/// Headers too long or too many to read, answered with 431.
+#[derive(Debug)]
+struct HeadersTooLarge;
+
+impl fmt::Display for HeadersTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("request headers too large")
+    }
+}
+
+impl std::error::Error for HeadersTooLarge {}
+
+/// Works out the response to a request.
+pub fn respond(request: &Request) -> Response {
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/encode") => encode(&request.params),
+        ("POST", "/decode") => decode(&request.params),
+        ("GET", "/wav") | ("POST", "/wav") => wav(&request.params),
+        (_, "/encode") | (_, "/decode") | (_, "/wav") => {
+            return Response::error(405, "method not allowed")
+        }
+        _ => return Response::error(404, "not found"),
+    };
+    result.unwrap_or_else(|message| Response::error(400, &message))
+}
+
+fn encode(params: &Params) -> Result<Response, String> {
+    let text = param(params, "text")?;
+    let options = Options::default();
+    let message = morse::sanitize(text, &options);
+    let encoded = morse::encode(&message).map_err(|e| e.to_string())?;
+
+    let warnings = text
+        .chars()
+        .filter(|&c| !c.is_whitespace() && !morse::is_encodable(c) && c != '<' && c != '>')
+        .map(|c| format!("dropped unencodable character {:?}", c))
+        .fold(Vec::new(), |mut warnings, warning| {
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+            warnings
+        });
+
+    let report = Report {
+        input: text.into(),
+        output: encoded.clone(),
+        characters: crate::map_characters(&encoded, |code| {
+            crate::decode_code(code, None, Standard::International, &options)
+        }),
+        warnings,
+    };
+    Ok(Response::json(200, report.to_json()))
+}
+
+fn decode(params: &Params) -> Result<Response, String> {
+    let code = param(params, "text")?;
+    let options = Options::default();
+    let output = morse::decode(code).map_err(|e| e.to_string())?;
+
+    let report = Report {
+        input: code.into(),
+        output,
+        characters: crate::map_characters(code, |code| {
+            crate::decode_code(code, None, Standard::International, &options)
+        }),
+        warnings: Vec::new(),
+    };
+    Ok(Response::json(200, report.to_json()))
+}
+
+fn wav(params: &Params) -> Result<Response, String> {
+    let text = param(params, "text")?;
+    let wpm = number(params, "wpm", 20)?;
+    let timing = Timing::farnsworth(wpm, number(params, "farnsworth", wpm)?);
+    let tone = number(params, "tone", 600)?;
+    let rate = number(params, "rate", audio::SAMPLE_RATE)?;
+    if !audio::SAMPLE_RATES.contains(&rate) {
+        return Err(format!("unsupported sample rate {}", rate));
+    }
+    let voice = audio::Voice {
+        tone: f64::from(tone),
+        sample_rate: rate,
+        ..audio::Voice::default()
+    };
+
+    let encoded =
+        morse::encode(&morse::sanitize(text, &Options::default())).map_err(|e| e.to_string())?;
+    let events = timing::to_key_events(&encoded, &timing, None);
+    let length: Duration = events.iter().map(|event| event.duration()).sum();
+    if length > MAX_AUDIO {
+        let message = format!(
+            "the recording would be {} s long, more than the {} s allowed",
+            length.as_secs(),
+            MAX_AUDIO.as_secs()
+        );
+        return Ok(Response::error(413, &message));
+    }
+    Ok(Response {
+        status: 200,
+        body: Body::Wav(voice.render(&events), rate),
+    })
+}
+
+fn param<'a>(params: &'a Params, name: &str) -> Result<&'a str, String> {
+    params
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| format!("missing parameter {:?}", name))
+}
+
+pub fn number(params: &Params, name: &str, default: u32) -> Result<u32, String> {
+    match params.get(name) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("bad value for {:?}: {:?}", name, value)),
+        None => Ok(default),
+    }
+}
+
+fn write_response(mut out: impl Write, response: &Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "",
+    };
+    let wav;
+    let (content_type, body) = match &response.body {
+        Body::Json(json) => ("application/json", json.as_bytes()),
+        // Rendered up front, so that the length sent is that of the file written.
+        Body::Wav(samples, rate) => {
+            let mut buf = Vec::new();
+            audio::wav::write(&mut buf, samples, *rate)?;
+            wav = buf;
+            ("audio/wav", &wav[..])
+        }
+    };
+    write!(
+        out,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason,
+        content_type,
+        body.len()
+    )?;
+    out.write_all(body)?;
+    out.flush()
+}
+
+/// Decodes `name=value` pairs, with `+` for spaces and `%XX` escapes.
+fn parse_query(query: &str) -> Params {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = s.as_bytes();
+    while let Some((&u, tail)) = rest.split_first() {
+        rest = tail;
+        match u {
+            b'+' => bytes.push(b' '),
+            b'%' => match rest
+                .get(..2)
+                .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+            {
+                Some(u) => {
+                    bytes.push(u);
+                    rest = &rest[2..];
+                }
+                None => bytes.push(b'%'),
+            },
+            u => bytes.push(u),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Parses a flat JSON object. Strings, numbers and booleans are kept as text; nested values
+/// are not accepted.
+fn parse_json(json: &str) -> Result<Params, String> {
+    let mut chars = json.trim().chars().peekable();
+    let mut params = Params::new();
+    let expected = |what: &str| format!("malformed JSON: expected {}", what);
+
+    if chars.next() != Some('{') {
+        return Err(expected("an object"));
+    }
+    loop {
+        skip_whitespace(&mut chars);
+        match chars.peek() {
+            Some('}') if params.is_empty() => {
+                chars.next();
+                break;
+            }
+            Some('"') => (),
+            _ => return Err(expected("a name")),
+        }
+        let name = json_string(&mut chars).ok_or_else(|| expected("a name"))?;
+        skip_whitespace(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(expected("':'"));
+        }
+        skip_whitespace(&mut chars);
+        let value = match chars.peek() {
+            Some('"') => json_string(&mut chars).ok_or_else(|| expected("a string"))?,
+            Some(_) => {
+                let mut value = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == ',' || c == '}' || c.is_whitespace() {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                if value.is_empty() || value.starts_with(&['{', '['][..]) {
+                    return Err(expected("a string, number or boolean"));
+                }
+                value
+            }
+            None => return Err(expected("a value")),
+        };
+        params.insert(name, value);
+
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some(',') => (),
+            Some('}') => break,
+            _ => return Err(expected("',' or '}'")),
+        }
+    }
+
+    skip_whitespace(&mut chars);
+    match chars.next() {
+        None => Ok(params),
+        Some(_) => Err(expected("the end of the object")),
+    }
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// Reads a quoted string, undoing escapes. Surrogate pairs are not put back together.
+fn json_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+    if chars.next() != Some('"') {
+        return None;
+    }
+    let mut s = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(s),
+            '\\' => s.push(match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    char::from_u32(u32::from_str_radix(&hex, 16).ok()?)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER)
+                }
+                c => c,
+            }),
+            c => s.push(c),
+        }
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{Body, Request};
+
+    fn request(method: &str, path: &str, params: &[(&str, &str)]) -> Request {
+        Request {
+            method: method.into(),
+            path: path.into(),
+            params: params
+                .iter()
+                .map(|&(name, value)| (name.into(), value.into()))
+                .collect(),
+            ..Request::default()
+        }
+    }
+
+    fn json(request: &Request) -> (u16, String) {
+        let response = super::respond(request);
+        match response.body {
+            Body::Json(json) => (response.status, json),
+            Body::Wav(..) => panic!("expected JSON"),
+        }
+    }
+
+    #[test]
+    fn parses_requests() {
+        let body = "{\"tone\": 700, \"text\": \"s\\\"os\"}";
+        let raw = format!(
+            "POST /wav?wpm=25&text=cq+de%20me HTTP/1.1\r\nHost: x\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let request = super::read_request(&mut Cursor::new(raw)).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/wav");
+        assert_eq!(request.params["wpm"], "25");
+        assert_eq!(request.params["tone"], "700");
+        assert_eq!(request.params["text"], "s\"os");
+        assert_eq!(request.headers["host"], "x");
+
+        assert!(super::parse_json("{\"text\": {}}").is_err());
+        assert!(super::parse_json("[1]").is_err());
+        assert!(super::parse_json("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn limits_request_headers() {
+        let status = |raw: String| match super::read_request(&mut Cursor::new(raw)) {
+            Ok(_) => 200,
+            Err(e)
+                if e.get_ref()
+                    .is_some_and(|e| e.is::<super::HeadersTooLarge>()) =>
+            {
+                431
+            }
+            Err(_) => 400,
+        };
+
+        let long = "x".repeat(super::MAX_LINE as usize);
+        assert_eq!(status(format!("GET /{} HTTP/1.1\r\n\r\n", long)), 400);
+        assert_eq!(
+            status(format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", long)),
+            431
+        );
+
+        let many = "X: y\r\n".repeat(super::MAX_HEADERS);
+        assert_eq!(status(format!("GET / HTTP/1.1\r\n{}\r\n", many)), 200);
+        let many = "X: y\r\n".repeat(super::MAX_HEADERS + 1);
+        assert_eq!(status(format!("GET / HTTP/1.1\r\n{}\r\n", many)), 431);
+    }
+
+    #[test]
+    fn responds_with_reports() {
+        let (status, body) = json(&request("POST", "/encode", &[("text", "sos")]));
+        assert_eq!(status, 200);
+        assert!(body.contains("\"output\":\"... --- ...\""), "{}", body);
+
+        let (status, body) = json(&request("POST", "/decode", &[("text", "... --- ...")]));
+        assert_eq!(status, 200);
+        assert!(body.contains("\"output\":\"SOS\""), "{}", body);
+
+        let (status, body) = json(&request("POST", "/decode", &[("text", "........-")]));
+        assert_eq!(status, 400);
+        assert!(body.starts_with("{\"error\":"), "{}", body);
+
+        assert_eq!(json(&request("POST", "/encode", &[])).0, 400);
+        assert_eq!(json(&request("GET", "/encode", &[("text", "e")])).0, 405);
+        assert_eq!(json(&request("GET", "/", &[])).0, 404);
+    }
+
+    #[test]
+    fn renders_wav() {
+        let response = super::respond(&request("GET", "/wav", &[("text", "e"), ("wpm", "30")]));
+        let mut out = Vec::new();
+        super::write_response(&mut out, &response).unwrap();
+
+        let header_end = out.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        let headers = String::from_utf8_lossy(&out[..header_end]);
+        assert!(headers.contains("Content-Type: audio/wav"));
+        assert!(headers.contains(&format!("Content-Length: {}", out.len() - header_end)));
+        assert_eq!(&out[header_end..header_end + 4], b"RIFF");
+
+        // Sixteen minutes of dits and word gaps at 5 wpm.
+        let text = "e ".repeat(500);
+        let (status, body) = json(&request("GET", "/wav", &[("text", &text), ("wpm", "5")]));
+        assert_eq!(status, 413, "{}", body);
+    }
+}
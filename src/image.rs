@@ -0,0 +1,126 @@
+//! Pictures of Morse: an animated lamp blinking out a message, written as a GIF or an animated
+//! PNG by the encoders in the submodules, which need nothing beyond the standard library.
+
+pub mod gif;
+pub mod png;
+
+use std::{path::Path, time::Duration};
+
+use crate::timing::KeyEvent;
+
+/// An image whose pixels are indexes into a palette of at most 256 RGB colors, row by row from the
+/// top left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub palette: Vec<[u8; 3]>,
+    pub pixels: Vec<u8>,
+}
+
+/// One frame of an animation and how long it shows.
+pub type Frame = (Image, Duration);
+
+/// How an animation is written out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Animation {
+    Gif,
+    /// Animated PNG, which shows as a still of its first frame where animation is not supported.
+    Apng,
+}
+
+impl Animation {
+    /// The format a file's extension calls for, if it is one of these.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gif" => Some(Animation::Gif),
+            "png" | "apng" => Some(Animation::Apng),
+            _ => None,
+        }
+    }
+}
+
+const BACKGROUND: [u8; 3] = [0x20, 0x20, 0x20];
+const LIT: [u8; 3] = [0xff, 0xd0, 0x40];
+const UNLIT: [u8; 3] = [0x48, 0x40, 0x28];
+
+/// Shades between the background and the lamp, for smooth edges.
+const SHADES: u8 = 8;
+
+/// Frames of a lamp `size` pixels square, lit while the key is down and dark while it is up. The
+/// animation ends with whatever gap ends `events`, so a gap there keeps the loop from running the
+/// end of the message into its start.
+pub fn blink(events: &[KeyEvent], size: u32) -> Vec<Frame> {
+    let lit = lamp(size, true);
+    let unlit = lamp(size, false);
+    events
+        .iter()
+        .map(|&event| match event {
+            KeyEvent::Down(duration) => (lit.clone(), duration),
+            KeyEvent::Up(duration) => (unlit.clone(), duration),
+        })
+        .collect()
+}
+
+/// A round lamp on a dark background, filling most of a `size` pixel square. Lit and unlit lamps
+/// share a palette, shading from the background to each color in turn.
+fn lamp(size: u32, on: bool) -> Image {
+    let palette = [LIT, UNLIT]
+        .iter()
+        .flat_map(|color| {
+            (0..SHADES).map(move |shade| {
+                let mix = f64::from(shade) / f64::from(SHADES - 1);
+                let mut blended = [0; 3];
+                for (channel, blended) in blended.iter_mut().enumerate() {
+                    let (from, to) = (f64::from(BACKGROUND[channel]), f64::from(color[channel]));
+                    *blended = (from + (to - from) * mix).round() as u8;
+                }
+                blended
+            })
+        })
+        .collect();
+    let offset = if on { 0 } else { SHADES };
+
+    let center = f64::from(size) / 2.0;
+    let radius = f64::from(size) * 0.4;
+    let mut pixels = Vec::with_capacity((size * size) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let (dx, dy) = (f64::from(x) + 0.5 - center, f64::from(y) + 0.5 - center);
+            // How much of the pixel the lamp covers, roughly.
+            let coverage = (radius + 0.5 - (dx * dx + dy * dy).sqrt()).clamp(0.0, 1.0);
+            pixels.push(offset + (coverage * f64::from(SHADES - 1)).round() as u8);
+        }
+    }
+    Image {
+        width: size,
+        height: size,
+        palette,
+        pixels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::timing::KeyEvent::{Down, Up};
+
+    #[test]
+    fn lamp_lights_while_key_is_down() {
+        let ms = Duration::from_millis;
+        let frames = super::blink(&[Down(ms(60)), Up(ms(180))], 32);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].1, ms(180));
+
+        let (lit, unlit) = (&frames[0].0, &frames[1].0);
+        let color = |image: &super::Image, idx: usize| image.palette[image.pixels[idx] as usize];
+        let center = 16 * 32 + 16;
+        assert_eq!(color(lit, center), super::LIT);
+        assert_eq!(color(unlit, center), super::UNLIT);
+        assert_eq!(lit.palette, unlit.palette);
+        assert_eq!(color(lit, 0), super::BACKGROUND);
+        assert_eq!(color(unlit, 0), super::BACKGROUND);
+    }
+}
@@ -0,0 +1,239 @@
+//! A GIF encoder for animations, with its own LZW compression. Each frame carries its own
+//! palette, and frames are expected to share the size of the first.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use super::Frame;
+
+/// The largest code LZW may use, 12 bits.
+const MAX_CODE_BITS: u32 = 12;
+
+/// Writes frames as an animation that loops forever. Frame times are kept to the hundredth of a
+/// second, counted from the start so that rounding does not build up.
+pub fn write<W: Write>(mut writer: W, frames: &[Frame]) -> io::Result<()> {
+    let first = match frames.first() {
+        Some((image, _)) => image,
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "an animation needs at least one frame",
+            ))
+        }
+    };
+
+    writer.write_all(b"GIF89a")?;
+    writer.write_all(&(first.width as u16).to_le_bytes())?;
+    writer.write_all(&(first.height as u16).to_le_bytes())?;
+    writer.write_all(&[0, 0, 0])?; // no global palette, background 0, square pixels
+
+    // Loop forever.
+    writer.write_all(&[0x21, 0xff, 0x0b])?;
+    writer.write_all(b"NETSCAPE2.0")?;
+    writer.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+
+    let (mut elapsed, mut written) = (0.0f64, 0u64);
+    for (image, duration) in frames {
+        elapsed += duration.as_secs_f64();
+        let end = (elapsed * 100.0).round() as u64;
+        // Most viewers slow anything shorter than two hundredths to a tenth, so don't go under.
+        let delay = end.saturating_sub(written).clamp(2, u64::from(u16::MAX));
+        written += delay;
+
+        writer.write_all(&[0x21, 0xf9, 0x04, 0x00])?;
+        writer.write_all(&(delay as u16).to_le_bytes())?;
+        writer.write_all(&[0x00, 0x00])?;
+
+        let bits = palette_bits(image.palette.len());
+        writer.write_all(&[0x2c, 0, 0, 0, 0])?;
+        writer.write_all(&(image.width as u16).to_le_bytes())?;
+        writer.write_all(&(image.height as u16).to_le_bytes())?;
+        writer.write_all(&[0x80 | (bits - 1) as u8])?; // a local palette of 2^bits colors
+        for idx in 0..1 << bits {
+            writer.write_all(image.palette.get(idx).unwrap_or(&[0; 3]))?;
+        }
+
+        let min_code_bits = bits.max(2);
+        writer.write_all(&[min_code_bits as u8])?;
+        for block in lzw(&image.pixels, min_code_bits).chunks(255) {
+            writer.write_all(&[block.len() as u8])?;
+            writer.write_all(block)?;
+        }
+        writer.write_all(&[0])?;
+    }
+    writer.write_all(&[0x3b])?;
+    writer.flush()
+}
+
+/// Bits needed to index a palette of `len` colors, from 1 to 8.
+fn palette_bits(len: usize) -> u32 {
+    let mut bits = 1;
+    while 1 << bits < len && bits < 8 {
+        bits += 1;
+    }
+    bits
+}
+
+/// Compresses palette indexes as GIF's variable-width LZW codes, starting at `min_code_bits + 1`
+/// bits and clearing the table when codes run out.
+fn lzw(pixels: &[u8], min_code_bits: u32) -> Vec<u8> {
+    let clear = 1u16 << min_code_bits;
+    let end = clear + 1;
+
+    let mut out = Bits::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut code_bits = min_code_bits + 1;
+    let mut next_code = end + 1;
+    out.push(clear, code_bits);
+
+    let mut pixels = pixels.iter();
+    let mut prefix = match pixels.next() {
+        Some(&pixel) => u16::from(pixel),
+        None => {
+            out.push(end, code_bits);
+            return out.finish();
+        }
+    };
+    for &pixel in pixels {
+        if let Some(&code) = table.get(&(prefix, pixel)) {
+            prefix = code;
+            continue;
+        }
+        out.push(prefix, code_bits);
+        if next_code == 1 << MAX_CODE_BITS {
+            out.push(clear, code_bits);
+            table.clear();
+            code_bits = min_code_bits + 1;
+            next_code = end + 1;
+        } else {
+            // The decoder widens its codes as soon as the next one would not fit.
+            if next_code == 1 << code_bits && code_bits < MAX_CODE_BITS {
+                code_bits += 1;
+            }
+            table.insert((prefix, pixel), next_code);
+            next_code += 1;
+        }
+        prefix = u16::from(pixel);
+    }
+    out.push(prefix, code_bits);
+    out.push(end, code_bits);
+    out.finish()
+}
+
+/// Codes packed least significant bit first.
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    partial: u32,
+    len: u32,
+}
+
+impl Bits {
+    fn push(&mut self, code: u16, bits: u32) {
+        self.partial |= u32::from(code) << self.len;
+        self.len += bits;
+        while self.len >= 8 {
+            self.bytes.push(self.partial as u8);
+            self.partial >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.partial as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use crate::image::Image;
+
+    /// Undoes `lzw`, as a GIF decoder would.
+    fn unlzw(bytes: &[u8], min_code_bits: u32) -> Vec<u8> {
+        let clear = 1usize << min_code_bits;
+        let mut table: HashMap<usize, Vec<u8>> = HashMap::new();
+        let mut code_bits = min_code_bits + 1;
+        let (mut at, mut out, mut last): (u32, Vec<u8>, Option<Vec<u8>>) = (0, Vec::new(), None);
+        loop {
+            let mut code = 0;
+            for bit in 0..code_bits {
+                let (byte, shift) = ((at + bit) / 8, (at + bit) % 8);
+                code |= ((bytes[byte as usize] >> shift) as usize & 1) << bit;
+            }
+            at += code_bits;
+            if code == clear {
+                table = (0..clear).map(|n| (n, vec![n as u8])).collect();
+                code_bits = min_code_bits + 1;
+                last = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return out;
+            }
+            let entry = match (table.get(&code), &last) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(last)) => [&last[..], &last[..1]].concat(),
+                (None, None) => panic!("unknown code {}", code),
+            };
+            if let Some(last) = last {
+                let next = table.len() + 2;
+                table.insert(next, [&last[..], &entry[..1]].concat());
+                if next + 1 == 1 << code_bits && code_bits < 12 {
+                    code_bits += 1;
+                }
+            }
+            out.extend_from_slice(&entry);
+            last = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trips_through_table_resets() {
+        // Enough noise to fill the table more than once.
+        let mut state = 1u32;
+        let pixels: Vec<u8> = (0..40_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8 & 0x0f
+            })
+            .collect();
+        assert_eq!(unlzw(&super::lzw(&pixels, 4), 4), pixels);
+
+        let flat = vec![1; 10_000];
+        let compressed = super::lzw(&flat, 2);
+        assert!(compressed.len() < 200, "{}", compressed.len());
+        assert_eq!(unlzw(&compressed, 2), flat);
+    }
+
+    #[test]
+    fn writes_delays_in_hundredths() {
+        let image = Image {
+            width: 2,
+            height: 2,
+            palette: vec![[0, 0, 0], [255, 255, 255], [255, 0, 0]],
+            pixels: vec![0, 1, 2, 1],
+        };
+        let ms = Duration::from_millis;
+        let frames = [(image.clone(), ms(60)), (image, ms(60))];
+        let mut gif = Vec::new();
+        super::write(&mut gif, &frames).unwrap();
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!(gif.last(), Some(&0x3b));
+
+        let delays: Vec<u16> = gif
+            .windows(4)
+            .enumerate()
+            .filter(|(_, window)| window == &[0x21, 0xf9, 0x04, 0x00])
+            .map(|(at, _)| u16::from_le_bytes([gif[at + 4], gif[at + 5]]))
+            .collect();
+        // 6 and 12 hundredths from the start.
+        assert_eq!(delays, [6, 6]);
+    }
+}
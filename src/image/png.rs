@@ -0,0 +1,340 @@
+//! A PNG encoder for palette images, still or animated (APNG), with its own deflate: fixed
+//! Huffman codes over LZ77 matches, which does well enough on flat drawn pictures.
+
+use std::io::{self, Write};
+
+use super::{Frame, Image};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Color type 3: each pixel a palette index.
+const INDEXED: u8 = 3;
+
+/// The deflate window, and the longest and shortest matches deflate can code.
+const WINDOW: usize = 32 * 1024;
+const MAX_MATCH: usize = 258;
+const MIN_MATCH: usize = 3;
+
+/// Candidate matches tried at each position, trading speed for size.
+const CHAIN: usize = 16;
+
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Writes a still image.
+pub fn write<W: Write>(mut writer: W, image: &Image) -> io::Result<()> {
+    writer.write_all(&SIGNATURE)?;
+    header(&mut writer, image)?;
+    chunk(&mut writer, b"IDAT", &zlib(&scanlines(image)))?;
+    chunk(&mut writer, b"IEND", &[])?;
+    writer.flush()
+}
+
+/// Writes an animation that loops forever. Every frame must have the size and palette of the
+/// first. Frame times are kept to the millisecond.
+pub fn write_animated<W: Write>(mut writer: W, frames: &[Frame]) -> io::Result<()> {
+    let first = match frames.first() {
+        Some((image, _)) => image,
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "an animation needs at least one frame",
+            ))
+        }
+    };
+    if frames.iter().any(|(image, _)| {
+        (image.width, image.height) != (first.width, first.height) || image.palette != first.palette
+    }) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "every frame of an APNG must share the size and palette of the first",
+        ));
+    }
+
+    writer.write_all(&SIGNATURE)?;
+    header(&mut writer, first)?;
+    let mut control = Vec::new();
+    control.extend_from_slice(&(frames.len() as u32).to_be_bytes());
+    control.extend_from_slice(&0u32.to_be_bytes()); // play forever
+    chunk(&mut writer, b"acTL", &control)?;
+
+    // Frame control and frame data chunks share one sequence of numbers.
+    let mut sequence = 0u32;
+    for (idx, (image, delay)) in frames.iter().enumerate() {
+        let mut control = Vec::new();
+        control.extend_from_slice(&sequence.to_be_bytes());
+        control.extend_from_slice(&image.width.to_be_bytes());
+        control.extend_from_slice(&image.height.to_be_bytes());
+        control.extend_from_slice(&[0; 8]); // no offset
+        let ms = delay.as_millis().min(u128::from(u16::MAX)) as u16;
+        control.extend_from_slice(&ms.to_be_bytes());
+        control.extend_from_slice(&1000u16.to_be_bytes());
+        control.extend_from_slice(&[0, 0]); // no disposal, no blending: each frame replaces the last
+        chunk(&mut writer, b"fcTL", &control)?;
+        sequence += 1;
+
+        let data = zlib(&scanlines(image));
+        if idx == 0 {
+            // The first frame doubles as the still image.
+            chunk(&mut writer, b"IDAT", &data)?;
+        } else {
+            let mut frame = sequence.to_be_bytes().to_vec();
+            frame.extend_from_slice(&data);
+            chunk(&mut writer, b"fdAT", &frame)?;
+            sequence += 1;
+        }
+    }
+    chunk(&mut writer, b"IEND", &[])?;
+    writer.flush()
+}
+
+/// The image header and palette.
+fn header<W: Write>(writer: &mut W, image: &Image) -> io::Result<()> {
+    let mut header = Vec::new();
+    header.extend_from_slice(&image.width.to_be_bytes());
+    header.extend_from_slice(&image.height.to_be_bytes());
+    // 8-bit indexes, then deflate, adaptive filtering and no interlacing, the only options.
+    header.extend_from_slice(&[8, INDEXED, 0, 0, 0]);
+    chunk(writer, b"IHDR", &header)?;
+    chunk(writer, b"PLTE", &image.palette.concat())
+}
+
+fn chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(&[&kind[..], data].concat());
+    writer.write_all(&crc.to_be_bytes())
+}
+
+/// Rows of pixels, each led by filter type 0: none. Drawn pictures repeat enough as they are.
+fn scanlines(image: &Image) -> Vec<u8> {
+    image
+        .pixels
+        .chunks(image.width.max(1) as usize)
+        .flat_map(|row| std::iter::once(0).chain(row.iter().copied()))
+        .collect()
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                crc >> 1 ^ 0xedb8_8320
+            }
+        })
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % 65_521;
+        (a, (b + a) % 65_521)
+    });
+    b << 16 | a
+}
+
+/// Compresses `bytes` as a zlib stream of one fixed-Huffman deflate block.
+fn zlib(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Bits::default();
+    out.push(1, 1); // the last block
+    out.push(1, 2); // fixed Huffman codes
+
+    // The last position seen for each three-byte prefix, and before that for each position.
+    let mut heads = vec![usize::MAX; 1 << 15];
+    let mut previous = vec![usize::MAX; bytes.len()];
+    let hash = |at: usize| {
+        let prefix = u32::from(bytes[at]) << 16 | u32::from(bytes[at + 1]) << 8;
+        ((prefix | u32::from(bytes[at + 2])).wrapping_mul(2_654_435_761) >> 17) as usize
+    };
+    let insert = |at: usize, heads: &mut [usize], previous: &mut [usize]| {
+        if at + MIN_MATCH <= bytes.len() {
+            let h = hash(at);
+            previous[at] = heads[h];
+            heads[h] = at;
+        }
+    };
+
+    let mut at = 0;
+    while at < bytes.len() {
+        let (mut best_len, mut best_distance) = (0, 0);
+        if at + MIN_MATCH <= bytes.len() {
+            let mut candidate = heads[hash(at)];
+            let mut tries = 0;
+            while candidate != usize::MAX && at - candidate <= WINDOW && tries < CHAIN {
+                let len = bytes[candidate..]
+                    .iter()
+                    .zip(&bytes[at..])
+                    .take(MAX_MATCH)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    best_len = len;
+                    best_distance = at - candidate;
+                }
+                candidate = previous[candidate];
+                tries += 1;
+            }
+        }
+
+        if best_len >= MIN_MATCH {
+            out.push_length(best_len);
+            out.push_distance(best_distance);
+            for skipped in at..at + best_len {
+                insert(skipped, &mut heads, &mut previous);
+            }
+            at += best_len;
+        } else {
+            out.push_literal(u16::from(bytes[at]));
+            insert(at, &mut heads, &mut previous);
+            at += 1;
+        }
+    }
+    out.push_literal(256); // end of block
+    out.align();
+
+    let mut zlib = vec![0x78, 0x9c];
+    zlib.extend_from_slice(&out.bytes);
+    zlib.extend_from_slice(&adler32(bytes).to_be_bytes());
+    zlib
+}
+
+/// Bits written least significant first, as deflate packs them.
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    partial: u32,
+    len: u32,
+}
+
+impl Bits {
+    fn push(&mut self, value: u32, bits: u32) {
+        self.partial |= value << self.len;
+        self.len += bits;
+        while self.len >= 8 {
+            self.bytes.push(self.partial as u8);
+            self.partial >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    /// Pushes a Huffman code, which deflate packs most significant bit first.
+    fn push_code(&mut self, code: u32, bits: u32) {
+        self.push(code.reverse_bits() >> (32 - bits), bits);
+    }
+
+    /// Pushes a literal byte, the end of block, or a length symbol, in the fixed code.
+    fn push_literal(&mut self, symbol: u16) {
+        let symbol = u32::from(symbol);
+        match symbol {
+            0..=143 => self.push_code(0x30 + symbol, 8),
+            144..=255 => self.push_code(0x190 + symbol - 144, 9),
+            256..=279 => self.push_code(symbol - 256, 7),
+            _ => self.push_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn push_length(&mut self, len: usize) {
+        let idx = LENGTH_BASES
+            .iter()
+            .rposition(|&base| usize::from(base) <= len)
+            .expect("matches are at least 3 long");
+        self.push_literal(257 + idx as u16);
+        let extra = len - usize::from(LENGTH_BASES[idx]);
+        self.push(extra as u32, u32::from(LENGTH_EXTRA[idx]));
+    }
+
+    fn push_distance(&mut self, distance: usize) {
+        let idx = DISTANCE_BASES
+            .iter()
+            .rposition(|&base| usize::from(base) <= distance)
+            .expect("distances are at least 1");
+        self.push_code(idx as u32, 5);
+        let extra = distance - usize::from(DISTANCE_BASES[idx]);
+        self.push(extra as u32, u32::from(DISTANCE_EXTRA[idx]));
+    }
+
+    /// Pads with zeros to a whole byte.
+    fn align(&mut self) {
+        if self.len > 0 {
+            self.push(0, 8 - self.len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::image::{self, Image};
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(super::crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(super::adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn flat_pictures_compress() {
+        let image = Image {
+            width: 64,
+            height: 64,
+            palette: vec![[0, 0, 0], [255, 255, 255]],
+            pixels: (0..64 * 64).map(|n| (n / 64 % 8 == 0) as u8).collect(),
+        };
+        let mut png = Vec::new();
+        super::write(&mut png, &image).unwrap();
+        assert_eq!(&png[..8], super::SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert!(png.len() < 300, "{}", png.len());
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+    }
+
+    #[test]
+    fn numbers_animation_chunks_in_sequence() {
+        let ms = Duration::from_millis;
+        let frames = image::blink(
+            &crate::timing::to_key_events(".-", &Default::default(), None),
+            16,
+        );
+        let mut png = Vec::new();
+        super::write_animated(&mut png, &frames).unwrap();
+
+        let mut chunks = Vec::new();
+        let mut at = 8;
+        while at < png.len() {
+            let len = u32::from_be_bytes([png[at], png[at + 1], png[at + 2], png[at + 3]]);
+            let kind = String::from_utf8_lossy(&png[at + 4..at + 8]).into_owned();
+            let sequence =
+                u32::from_be_bytes([png[at + 8], png[at + 9], png[at + 10], png[at + 11]]);
+            chunks.push((kind, sequence));
+            at += 12 + len as usize;
+        }
+        let sequenced: Vec<String> = chunks
+            .iter()
+            .filter(|(kind, _)| kind == "fcTL" || kind == "fdAT")
+            .enumerate()
+            .map(|(idx, (kind, sequence))| {
+                assert_eq!(*sequence, idx as u32);
+                kind.clone()
+            })
+            .collect();
+        assert_eq!(sequenced, ["fcTL", "fcTL", "fdAT", "fcTL", "fdAT"]);
+        assert_eq!(frames[2].1, ms(180));
+    }
+}
@@ -0,0 +1,174 @@
+//! Driving a K3NG Arduino keyer through its serial command line. Text written to it is queued
+//! and sent by the keyer itself, and commands start with a backslash.
+//!
+//! The command line has no flow control, so text is fed to the keyer only a little ahead of
+//! where it is estimated to be sending, to keep its buffer from overflowing.
+
+use std::{
+    io::{self, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{timing, Options, Result, Timing};
+
+/// The keyer's default serial speed.
+pub const BAUD: u32 = 115_200;
+
+/// Opening the port resets most Arduinos, and the keyer ignores anything sent while it starts up.
+pub const STARTUP: Duration = Duration::from_secs(2);
+
+/// How far ahead of the keyer to stay. The keyer's buffer holds 150 characters; a few seconds of
+/// text is well short of that at any speed and still never leaves it waiting.
+const LEAD: Duration = Duration::from_secs(3);
+
+/// A K3NG keyer on the other end of `port`.
+pub struct K3ng<P: Write> {
+    port: P,
+    timing: Timing,
+    /// When the keyer should be done with everything written to it so far.
+    drained: Instant,
+}
+
+impl<P: Write> K3ng<P> {
+    /// Takes over a keyer that has finished starting up, setting it to `timing`.
+    pub fn new(port: P, timing: Timing) -> io::Result<Self> {
+        let mut keyer = K3ng {
+            port,
+            timing,
+            drained: Instant::now(),
+        };
+        keyer.set_timing(timing)?;
+        Ok(keyer)
+    }
+
+    /// Changes speed, keeping Farnsworth timing if `timing` has it: the keyer's own speed is the
+    /// overall one, and its Farnsworth speed the one characters are sent at. The weight is kept
+    /// too, in hundredths of a dit. Applies to text still in the keyer's buffer too.
+    pub fn set_timing(&mut self, timing: Timing) -> io::Result<()> {
+        self.timing = timing;
+        self.command(&format!("W{}", timing.effective_wpm))?;
+        if timing.effective_wpm < timing.char_wpm {
+            self.command(&format!("M{}", timing.char_wpm))?;
+        }
+        if timing.weight != timing::WEIGHT {
+            self.command(&format!("Q{}", (timing.weight * 100.0).round()))?;
+        }
+        Ok(())
+    }
+
+    /// Sets the sidetone frequency in Hz.
+    pub fn set_tone(&mut self, tone: u32) -> io::Result<()> {
+        self.command(&format!("F{}", tone))
+    }
+
+    /// Queues `message`, word by word, waiting whenever the keyer is far enough ahead. Anything
+    /// that cannot be encoded is left out.
+    pub fn send(&mut self, message: &str) -> Result<()> {
+        let options = Options::default();
+        for word in message.split_whitespace() {
+            let word = crate::sanitize(word, &options);
+            if word.is_empty() {
+                continue;
+            }
+
+            let now = Instant::now();
+            thread::sleep(self.drained.saturating_duration_since(now + LEAD));
+            self.port
+                .write_all(&text(&word))
+                .and_then(|_| self.port.flush())
+                .map_err(crate::Error::Io)?;
+
+            let encoded = crate::encode(&word)?;
+            let length = timing::to_key_events(&encoded, &self.timing, None)
+                .iter()
+                .map(|event| event.duration())
+                .sum::<Duration>()
+                + self.timing.word_gap();
+            self.drained = self.drained.max(Instant::now()) + length;
+        }
+        Ok(())
+    }
+
+    /// Waits until the keyer should have finished sending.
+    pub fn wait(&self) {
+        thread::sleep(self.drained.saturating_duration_since(Instant::now()));
+    }
+
+    /// Stops sending and empties the keyer's buffer.
+    pub fn abort(&mut self) -> io::Result<()> {
+        self.drained = Instant::now();
+        self.port.write_all(b"\\\\")?;
+        self.port.flush()
+    }
+
+    fn command(&mut self, command: &str) -> io::Result<()> {
+        write!(self.port, "\\{}\r", command)?;
+        self.port.flush()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+impl K3ng<std::fs::File> {
+    /// Opens a keyer on the serial port at `path`, waiting for it to start up.
+    pub fn open_serial(path: &std::path::Path, timing: Timing) -> io::Result<Self> {
+        let port = crate::output::open_raw(path, BAUD, &[])?;
+        thread::sleep(STARTUP);
+        K3ng::new(port, timing)
+    }
+}
+
+/// The bytes that send a word: upper case, with the prosign command, which runs a character into
+/// the next, before every letter of a prosign but the last.
+fn text(word: &str) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let mut rest = word;
+
+    while !rest.is_empty() {
+        match rest.strip_prefix('<').and_then(|tail| tail.split_once('>')) {
+            Some((letters, tail)) => {
+                let mut letters = letters.bytes().map(|u| u.to_ascii_uppercase()).peekable();
+                while let Some(letter) = letters.next() {
+                    if letters.peek().is_some() {
+                        buf.extend_from_slice(b"\\+");
+                    }
+                    buf.push(letter);
+                }
+                rest = tail;
+            }
+            None => {
+                let c = rest.chars().next().unwrap_or_default();
+                if c.is_ascii() {
+                    buf.push(c.to_ascii_uppercase() as u8);
+                }
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    buf.push(b' ');
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::K3ng;
+    use crate::Timing;
+
+    #[test]
+    fn maps_commands() {
+        let mut keyer = K3ng::new(Vec::new(), Timing::farnsworth(30, 18).weighted(3.2)).unwrap();
+        keyer.set_tone(700).unwrap();
+        keyer.send("cq test").unwrap();
+        keyer.abort().unwrap();
+
+        let written = String::from_utf8(keyer.port).unwrap();
+        assert_eq!(written, "\\W18\r\\M30\r\\Q320\r\\F700\rCQ TEST \\\\");
+    }
+
+    #[test]
+    fn joins_prosigns() {
+        assert_eq!(super::text("tu<sk>"), b"TU\\+SK ");
+        assert_eq!(super::text("<sos>"), b"\\+S\\+OS ");
+    }
+}
@@ -0,0 +1,249 @@
+//! The keyboard as a straight key or a pair of paddles.
+//!
+//! Terminals report characters, not key presses and releases, so a held key shows up as one
+//! character, a pause of the keyboard's repeat delay, then a steady stream of repeats. A key is
+//! taken to be down from its first character until the repeats stop. A tap too short to repeat
+//! cannot be timed at all and counts as one dit long. Terminals also repeat only the most recently
+//! pressed key, so a paddle squeeze is seen as the first paddle tapped and the second held.
+
+use std::{
+    io::{self, Read, Write},
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use morse::{
+    keyer::{Edge, Iambic, IambicMode, Paddle, StraightKey},
+    Error, KeyEvent, Options, Result, Timing,
+};
+
+/// How long without a repeat before a held key counts as released.
+const REPEAT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How often silences are checked for the end of a character or word.
+const POLL: Duration = Duration::from_millis(5);
+
+/// Ctrl-C, Ctrl-D and Escape end the session.
+const QUIT: &[u8] = &[0x03, 0x04, 0x1b];
+
+/// Puts the terminal into unbuffered, unechoed mode until dropped.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "-isig", "min", "1"])?;
+        Ok(RawMode {
+            saved: saved.trim().into(),
+        })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[self.saved.as_str()]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Every byte typed on the terminal, with when it arrived.
+fn keystrokes() -> Receiver<(u8, Instant)> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for byte in io::stdin().lock().bytes() {
+            match byte {
+                Ok(byte) if sender.send((byte, Instant::now())).is_ok() => (),
+                _ => break,
+            }
+        }
+    });
+    receiver
+}
+
+/// A key being held down, as far as its repeats tell.
+struct Held {
+    key: u8,
+    pressed: Instant,
+    last: Instant,
+    repeating: bool,
+}
+
+impl Held {
+    fn new(key: u8, at: Instant) -> Self {
+        Held {
+            key,
+            pressed: at,
+            last: at,
+            repeating: false,
+        }
+    }
+
+    /// When the key came up, if it has stopped repeating by `now`. A tap is taken to have lasted
+    /// `tap`.
+    fn released(&self, now: Instant, repeat_delay: Duration, tap: Duration) -> Option<Instant> {
+        match self.repeating {
+            true if now - self.last >= REPEAT_TIMEOUT => Some(self.last),
+            false if now - self.last >= repeat_delay => Some(self.pressed + tap),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes what is keyed on the terminal with any key as a straight key, writing it to `out` as
+/// it comes, until a quit key or the end of input. `repeat_delay` is how long the keyboard waits
+/// before repeating a held key. Returns the key events as they were timed.
+pub fn straight(
+    timing: Timing,
+    options: Options,
+    repeat_delay: Duration,
+    mut out: impl Write,
+) -> Result<Vec<KeyEvent>> {
+    let _raw = RawMode::enable().map_err(Error::Io)?;
+    let receiver = keystrokes();
+    let start = Instant::now();
+    let mut key = StraightKey::new(timing, options);
+    let mut held: Option<Held> = None;
+    let mut events = Vec::new();
+    let mut released_at: Option<Instant> = None;
+
+    loop {
+        let text = match receiver.recv_timeout(POLL) {
+            Ok((byte, _)) if QUIT.contains(&byte) => break,
+            Ok((byte, at)) => match &mut held {
+                Some(held) => {
+                    held.last = at;
+                    held.repeating = true;
+                    continue;
+                }
+                None => {
+                    held = Some(Held::new(byte, at));
+                    if let Some(released) = released_at {
+                        events.push(KeyEvent::Up(at.saturating_duration_since(released)));
+                    }
+                    key.press(at - start)?
+                }
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                let now = Instant::now();
+                if let Some((pressed, released)) = held.as_ref().and_then(|held| {
+                    let released = held.released(now, repeat_delay, timing.dit())?;
+                    Some((held.pressed, released))
+                }) {
+                    events.push(KeyEvent::Down(released - pressed));
+                    released_at = Some(released);
+                    key.release(released - start);
+                    held = None;
+                }
+                key.idle(now - start)?
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        write!(out, "{}", text)
+            .and_then(|_| out.flush())
+            .map_err(Error::Io)?;
+    }
+
+    if let Some(held) = held {
+        events.push(KeyEvent::Down(held.last - held.pressed));
+        key.release(held.last - start);
+    }
+    writeln!(out, "{}", key.finish()?).map_err(Error::Io)?;
+    Ok(events)
+}
+
+/// Like `straight`, but with `dit_key` and `dah_key` as the paddles of an iambic keyer. Only ASCII
+/// keys can be told apart. Each element the keyer sends is also passed to `sidetone`, with its
+/// length.
+pub fn paddles(
+    timing: Timing,
+    options: Options,
+    mode: IambicMode,
+    (dit_key, dah_key): (char, char),
+    repeat_delay: Duration,
+    mut sidetone: impl FnMut(Duration),
+    mut out: impl Write,
+) -> Result<()> {
+    let _raw = RawMode::enable().map_err(Error::Io)?;
+    let receiver = keystrokes();
+    let start = Instant::now();
+    let mut keyer = Iambic::new(timing, mode);
+    let mut key = StraightKey::new(timing, options);
+    let mut held: Vec<Held> = Vec::new();
+    let paddle = |byte: u8| match char::from(byte) {
+        key if key == dit_key => Some(Paddle::Dit),
+        key if key == dah_key => Some(Paddle::Dah),
+        _ => None,
+    };
+
+    loop {
+        let mut edges = Vec::new();
+        let now = match receiver.recv_timeout(POLL) {
+            Ok((byte, _)) if QUIT.contains(&byte) => break,
+            Ok((byte, at)) => {
+                if let Some(paddle) = paddle(byte) {
+                    match held.iter_mut().find(|held| held.key == byte) {
+                        Some(held) => {
+                            held.last = at;
+                            held.repeating = true;
+                        }
+                        None => {
+                            held.push(Held::new(byte, at));
+                            edges.extend(keyer.set(paddle, true, at - start));
+                        }
+                    }
+                }
+                at
+            }
+            Err(RecvTimeoutError::Timeout) => Instant::now(),
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        let mut released = Vec::new();
+        held.retain(
+            |held| match held.released(now, repeat_delay, timing.dit()) {
+                Some(at) => {
+                    released.push((held.key, at));
+                    false
+                }
+                None => true,
+            },
+        );
+        for (byte, at) in released {
+            if let Some(paddle) = paddle(byte) {
+                edges.extend(keyer.set(paddle, false, at.max(start) - start));
+            }
+        }
+        edges.extend(keyer.poll(now - start));
+
+        let mut text = String::new();
+        for pair in edges.chunks(2) {
+            if let [Edge { at: down, .. }, Edge { at: up, .. }] = *pair {
+                sidetone(up - down);
+                text.push_str(&key.press(down)?);
+                key.release(up);
+            }
+        }
+        text.push_str(&key.idle(now - start)?);
+        write!(out, "{}", text)
+            .and_then(|_| out.flush())
+            .map_err(Error::Io)?;
+    }
+
+    writeln!(out, "{}", key.finish()?).map_err(Error::Io)
+}
@@ -0,0 +1,319 @@
+//! Keys operated by hand: live decoding of a straight key at a known speed, and an emulated
+//! iambic keyer that turns paddle presses into properly timed elements.
+//!
+//! Presses and releases are fed in as they happen, timed from any fixed starting point. Key-down
+//! periods shorter than two dits are dits and longer ones dahs; a silence ends the character once
+//! it lasts halfway from a dit to a character gap, and the word once it lasts halfway from a
+//! character gap to a word gap.
+
+use std::{str::FromStr, time::Duration};
+
+use crate::{timing::Timing, Options, Result};
+
+pub struct StraightKey {
+    timing: Timing,
+    options: Options,
+    code: String,
+    down_since: Option<Duration>,
+    up_since: Option<Duration>,
+    /// Whether anything has been decoded since the last word gap.
+    in_word: bool,
+}
+
+impl StraightKey {
+    pub fn new(timing: Timing, options: Options) -> Self {
+        StraightKey {
+            timing,
+            options,
+            code: String::new(),
+            down_since: None,
+            up_since: None,
+            in_word: false,
+        }
+    }
+
+    /// The key went down. Returns whatever the silence before it completed.
+    pub fn press(&mut self, at: Duration) -> Result<String> {
+        if self.down_since.is_some() {
+            return Ok(String::new());
+        }
+        let text = self.idle(at)?;
+        self.down_since = Some(at);
+        self.up_since = None;
+        Ok(text)
+    }
+
+    /// The key came up.
+    pub fn release(&mut self, at: Duration) {
+        if let Some(since) = self.down_since.take() {
+            let held = at.saturating_sub(since);
+            self.code.push(if held < self.timing.dit() * 2 {
+                '.'
+            } else {
+                '-'
+            });
+            self.up_since = Some(at);
+        }
+    }
+
+    /// Nothing has happened up to `now`. Returns the character, and then the word separator, once
+    /// the key has been up long enough for either.
+    pub fn idle(&mut self, now: Duration) -> Result<String> {
+        let silence = match self.up_since {
+            Some(since) => now.saturating_sub(since),
+            None => return Ok(String::new()),
+        };
+
+        let mut text = String::new();
+        if !self.code.is_empty() && silence >= (self.timing.dit() + self.timing.char_gap()) / 2 {
+            text.push_str(&self.flush()?);
+        }
+        if self.in_word && silence >= (self.timing.char_gap() + self.timing.word_gap()) / 2 {
+            text.push(self.options.word_sep);
+            self.in_word = false;
+        }
+        Ok(text)
+    }
+
+    /// Decodes whatever character is still being keyed.
+    pub fn finish(&mut self) -> Result<String> {
+        if let Some(since) = self.down_since {
+            self.release(since + self.timing.dit());
+        }
+        self.flush()
+    }
+
+    fn flush(&mut self) -> Result<String> {
+        if self.code.is_empty() {
+            return Ok(String::new());
+        }
+        let code = std::mem::take(&mut self.code);
+        self.in_word = true;
+        crate::decode_with(&code, &self.options)
+    }
+}
+
+/// The key going down or up at a point in time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub at: Duration,
+    pub down: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Paddle {
+    Dit,
+    Dah,
+}
+
+impl Paddle {
+    fn opposite(self) -> Self {
+        match self {
+            Paddle::Dit => Paddle::Dah,
+            Paddle::Dah => Paddle::Dit,
+        }
+    }
+}
+
+/// What an iambic keyer does when a squeeze is let go. In mode A it stops after the element it is
+/// sending; in mode B it also sends the opposite element if that paddle was down at any point
+/// during the current one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IambicMode {
+    A,
+    B,
+}
+
+impl FromStr for IambicMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "a" => Ok(IambicMode::A),
+            "b" => Ok(IambicMode::B),
+            _ => Err(format!("unknown iambic mode {:?} (expected a or b)", s)),
+        }
+    }
+}
+
+/// An iambic keyer. Holding one paddle repeats its element; squeezing both alternates them,
+/// starting with whichever went down first.
+pub struct Iambic {
+    timing: Timing,
+    mode: IambicMode,
+    dit: bool,
+    dah: bool,
+    /// The element being sent, and when the gap after it ends.
+    sending: Option<(Paddle, Duration)>,
+    /// The opposite paddle has been down at some point during the current element.
+    memory: bool,
+}
+
+impl Iambic {
+    pub fn new(timing: Timing, mode: IambicMode) -> Self {
+        Iambic {
+            timing,
+            mode,
+            dit: false,
+            dah: false,
+            sending: None,
+            memory: false,
+        }
+    }
+
+    /// A paddle went down or up at `at`. Returns the key edges produced up to then, including the
+    /// end of any element just started.
+    pub fn set(&mut self, paddle: Paddle, pressed: bool, at: Duration) -> Vec<Edge> {
+        let mut edges = self.poll(at);
+        match paddle {
+            Paddle::Dit => self.dit = pressed,
+            Paddle::Dah => self.dah = pressed,
+        }
+
+        match self.sending {
+            Some((current, _)) if pressed && current != paddle => self.memory = true,
+            None if pressed => edges.extend(self.start(paddle, at)),
+            _ => (),
+        }
+        edges
+    }
+
+    /// Returns the key edges of the elements started up to `now`, given the paddles as they stand.
+    pub fn poll(&mut self, now: Duration) -> Vec<Edge> {
+        let mut edges = Vec::new();
+        while let Some((current, free)) = self.sending {
+            if free > now {
+                break;
+            }
+            self.sending = None;
+
+            let memory = std::mem::take(&mut self.memory);
+            let next = match (self.dit, self.dah) {
+                (true, true) => Some(current.opposite()),
+                (true, false) => Some(Paddle::Dit),
+                (false, true) => Some(Paddle::Dah),
+                (false, false) if memory && self.mode == IambicMode::B => Some(current.opposite()),
+                (false, false) => None,
+            };
+            if let Some(next) = next {
+                edges.extend(self.start(next, free));
+            }
+        }
+        edges
+    }
+
+    fn start(&mut self, paddle: Paddle, at: Duration) -> [Edge; 2] {
+        let len = match paddle {
+            Paddle::Dit => self.timing.dit(),
+            Paddle::Dah => self.timing.dah(),
+        };
+        self.sending = Some((paddle, at + len + self.timing.dit()));
+        self.memory = match paddle {
+            Paddle::Dit => self.dah,
+            Paddle::Dah => self.dit,
+        };
+        [
+            Edge { at, down: true },
+            Edge {
+                at: at + len,
+                down: false,
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Edge, Iambic, IambicMode, Paddle, StraightKey};
+    use crate::{timing::Timing, ErrorPolicy, Options};
+    use std::time::Duration;
+
+    /// Keys `pattern` at 20 WPM (60 ms dits), with each character of it held or released for
+    /// that many units: `=` is a unit of key-down and `_` a unit of key-up.
+    fn key(pattern: &str, errors: ErrorPolicy) -> String {
+        let options = Options {
+            errors,
+            ..Options::default()
+        };
+        let mut key = StraightKey::new(Timing::new(20), options);
+        let unit = Duration::from_millis(60);
+        let mut text = String::new();
+        let mut down = false;
+
+        for (idx, c) in pattern.chars().enumerate() {
+            let at = unit * idx as u32;
+            text.push_str(&key.idle(at).unwrap());
+            match (c, down) {
+                ('=', false) => text.push_str(&key.press(at).unwrap()),
+                ('_', true) => key.release(at),
+                _ => (),
+            }
+            down = c == '=';
+        }
+        let end = unit * pattern.len() as u32;
+        if down {
+            key.release(end);
+        }
+        text + &key.finish().unwrap()
+    }
+
+    #[test]
+    fn keyed_elements_decode_at_the_set_speed() {
+        // S, then O after a character gap, then E after a word gap.
+        assert_eq!(
+            key("=_=_=___===_===_===_______=", ErrorPolicy::Strict),
+            "SO E"
+        );
+        assert_eq!(key("=_=_=_=_=_=_=_=_=", ErrorPolicy::Replace('#')), "#");
+    }
+
+    #[test]
+    fn sloppy_elements_still_decode() {
+        // A 1.5-unit dit and a 2.5-unit dah, a little over a unit apart.
+        let ms = Duration::from_millis;
+        let mut key = StraightKey::new(Timing::new(20), Options::default());
+        key.press(ms(0)).unwrap();
+        key.release(ms(90));
+        assert_eq!(key.idle(ms(150)).unwrap(), "");
+        key.press(ms(160)).unwrap();
+        key.release(ms(310));
+        assert_eq!(key.idle(ms(500)).unwrap(), "A");
+        assert_eq!(key.idle(ms(700)).unwrap(), " ");
+        assert_eq!(key.finish().unwrap(), "");
+    }
+
+    /// Renders keyer edges as `=` for each unit down and `_` for each unit up.
+    fn pattern(edges: &[Edge]) -> String {
+        let unit = Duration::from_millis(60);
+        let mut buf = String::new();
+        for pair in edges.windows(2) {
+            let units = ((pair[1].at - pair[0].at).as_millis() / unit.as_millis()) as usize;
+            buf.push_str(&(if pair[0].down { "=" } else { "_" }).repeat(units));
+        }
+        buf
+    }
+
+    #[test]
+    fn iambic_squeeze_alternates_elements() {
+        let ms = Duration::from_millis;
+
+        // Hold dah, squeeze dit 100 ms in, let both go at 250 ms: dah, dit, and in mode B one more
+        // dah because dah was still pressed during the dit.
+        for &(mode, expected) in &[(IambicMode::A, "===_="), (IambicMode::B, "===_=_===")] {
+            let mut keyer = Iambic::new(Timing::new(20), mode);
+            let mut edges = keyer.set(Paddle::Dah, true, ms(0));
+            edges.extend(keyer.set(Paddle::Dit, true, ms(100)));
+            edges.extend(keyer.set(Paddle::Dah, false, ms(250)));
+            edges.extend(keyer.set(Paddle::Dit, false, ms(250)));
+            edges.extend(keyer.poll(ms(2000)));
+            assert_eq!(pattern(&edges), expected, "{:?}", mode);
+        }
+
+        // Holding one paddle repeats its element.
+        let mut keyer = Iambic::new(Timing::new(20), IambicMode::A);
+        let mut edges = keyer.set(Paddle::Dit, true, ms(0));
+        edges.extend(keyer.set(Paddle::Dit, false, ms(290)));
+        edges.extend(keyer.poll(ms(2000)));
+        assert_eq!(pattern(&edges), "=_=_=");
+    }
+}
@@ -0,0 +1,74 @@
+//! Practice material for the Koch method: characters are learned at full speed from the start,
+//! two at first and one more each time copy reaches 90% accuracy.
+
+use crate::{grade::Grade, practice, rng::Rng};
+
+/// The order in which characters are introduced, as used by LCWO.
+pub const ORDER: &str = "KMURESNAPTLWI.JZ=FOY,VG5/Q92H38B?47C1D60X";
+
+/// The last lesson, which covers every character in `ORDER`.
+pub const LESSONS: usize = ORDER.len() - 1;
+
+/// The accuracy needed to move on to the next lesson.
+pub const PASS: f64 = 0.9;
+
+/// The characters covered by `lesson`, counting from 1. Lessons past the last cover everything.
+pub fn characters(lesson: usize) -> &'static str {
+    &ORDER[..lesson.clamp(1, LESSONS) + 1]
+}
+
+/// Random five-character groups drawn from the characters of `lesson`.
+pub fn practice(lesson: usize, groups: usize, rng: &mut Rng) -> String {
+    practice::groups(characters(lesson), groups, rng)
+}
+
+/// The share of `sent` that `copied` got right, as graded by [`Grade`].
+pub fn accuracy(sent: &str, copied: &str) -> f64 {
+    Grade::of(sent, copied).accuracy
+}
+
+/// The lesson to practice after scoring `accuracy` on `lesson`.
+pub fn next_lesson(lesson: usize, accuracy: f64) -> usize {
+    if accuracy >= PASS {
+        (lesson + 1).min(LESSONS)
+    } else {
+        lesson
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::Rng;
+
+    #[test]
+    fn practice_uses_only_the_lesson_characters() {
+        assert_eq!(super::characters(1), "KM");
+        assert_eq!(super::characters(5), "KMURES");
+        assert_eq!(super::characters(0), "KM");
+        assert_eq!(super::characters(1000), super::ORDER);
+
+        let text = super::practice(5, 10, &mut Rng::new(1));
+        assert_eq!(text.split(' ').count(), 10);
+        assert!(text.split(' ').all(|group| group.len() == 5));
+        assert!(
+            text.chars().all(|c| c == ' ' || "KMURES".contains(c)),
+            "{}",
+            text
+        );
+        assert!(crate::encode(super::ORDER).is_ok());
+    }
+
+    #[test]
+    fn lessons_advance_at_ninety_percent() {
+        assert_eq!(super::accuracy("KMKMU RESSE", "kmkmu  resse"), 1.0);
+        assert_eq!(
+            super::accuracy("KMKMU RESSE", "KMKMU RESS"),
+            1.0 - 1.0 / 11.0
+        );
+        assert_eq!(super::accuracy("KMKMU", ""), 0.0);
+
+        assert_eq!(super::next_lesson(5, 0.91), 6);
+        assert_eq!(super::next_lesson(5, 0.89), 5);
+        assert_eq!(super::next_lesson(super::LESSONS, 1.0), super::LESSONS);
+    }
+}
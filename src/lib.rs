@@ -0,0 +1,1149 @@
+//! Encoding and decoding of International Morse code, covering letters, digits and ITU
+//! punctuation.
+//!
+//! Encoded messages use `.` for dots and `-` for dashes, with a space between characters and
+//! `" / "` between words:
+//!
+//! ```
+//! assert_eq!(morse::encode("sos").unwrap(), "... --- ...");
+//! assert_eq!(morse::decode(".... .. / - .... . .-. .").unwrap(), "HI THERE");
+//! ```
+//!
+//! Prosigns are written in angle brackets and sent as a single run-together character:
+//!
+//! ```
+//! assert_eq!(morse::encode("<SOS>").unwrap(), "...---...");
+//! assert_eq!(morse::decode("--... ...-- / ...-.-").unwrap(), "73 <SK>");
+//! ```
+//!
+//! Without the default `std` feature only [`elements`], [`data`] and, with the `embedded`
+//! feature, [`embedded`] are built, for firmware with no operating system underneath.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+#[cfg(feature = "std")]
+pub mod american;
+#[cfg(feature = "std")]
+pub mod audio;
+#[cfg(feature = "std")]
+pub mod base64;
+#[cfg(feature = "std")]
+pub mod beacon;
+#[cfg(feature = "std")]
+pub mod chart;
+#[cfg(feature = "std")]
+pub mod codegen;
+#[cfg(feature = "std")]
+pub mod confidence;
+#[cfg(feature = "std")]
+pub mod cwdaemon;
+pub mod data;
+pub mod elements;
+#[cfg(feature = "embedded")]
+pub mod embedded;
+#[cfg(feature = "std")]
+pub mod fist;
+#[cfg(feature = "std")]
+pub mod fldigi;
+#[cfg(feature = "std")]
+pub mod grade;
+#[cfg(feature = "std")]
+pub mod haptic;
+#[cfg(feature = "std")]
+pub mod image;
+#[cfg(feature = "std")]
+pub mod k3ng;
+#[cfg(feature = "std")]
+pub mod keyer;
+#[cfg(feature = "std")]
+pub mod koch;
+#[cfg(feature = "std")]
+pub mod midi;
+#[cfg(feature = "std")]
+pub mod mqtt;
+#[cfg(feature = "std")]
+pub mod notation;
+#[cfg(feature = "std")]
+pub mod numbers;
+#[cfg(feature = "std")]
+pub mod output;
+#[cfg(feature = "std")]
+pub mod packed;
+#[cfg(feature = "std")]
+pub mod pipe;
+#[cfg(feature = "std")]
+pub mod practice;
+#[cfg(feature = "std")]
+pub mod qrss;
+#[cfg(feature = "std")]
+pub mod quiz;
+#[cfg(feature = "rig")]
+pub mod rig;
+#[cfg(feature = "std")]
+pub mod rng;
+#[cfg(feature = "std")]
+pub mod search;
+#[cfg(feature = "std")]
+pub mod segment;
+#[cfg(feature = "std")]
+pub mod sheet;
+#[cfg(feature = "std")]
+pub mod skats;
+#[cfg(feature = "std")]
+pub mod srt;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod suggest;
+#[cfg(feature = "std")]
+pub mod table;
+#[cfg(feature = "std")]
+pub mod timing;
+#[cfg(feature = "std")]
+pub mod transliterate;
+#[cfg(feature = "std")]
+pub mod tree;
+#[cfg(feature = "std")]
+pub mod verify;
+#[cfg(feature = "std")]
+pub mod wabun;
+#[cfg(feature = "std")]
+pub mod waveform;
+#[cfg(feature = "std")]
+pub mod winkeyer;
+
+pub use elements::{KeyEvent, Timing};
+
+#[cfg(feature = "std")]
+use std::{fmt::Display, io, str::FromStr};
+
+pub type Code = &'static str;
+#[cfg(feature = "std")]
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum Error {
+    Encode(char),
+    Decode(String),
+    Io(io::Error),
+    Audio(String),
+    Table(String),
+    /// An encoding or decoding error, along with where in the message it occurred.
+    At(Position, Box<Error>),
+    /// Every error found in a message, when using `ErrorPolicy::Collect`.
+    Many(Vec<Error>),
+    /// An error along with a hint for fixing it; see the `suggest` module.
+    Hint(Box<Error>, String),
+}
+
+#[cfg(feature = "std")]
+impl Error {
+    /// Attaches the position of `part`, which must be a slice of `text`, replacing any position
+    /// the error already had.
+    pub(crate) fn at(self, text: &str, part: &str) -> Error {
+        if let Error::Many(errors) = self {
+            return Error::Many(errors.into_iter().map(|e| e.at(text, part)).collect());
+        }
+
+        Error::At(Position::of(text, part), Box::new(self.without_position()))
+    }
+
+    pub fn without_position(self) -> Error {
+        match self {
+            Error::At(_, error) => *error,
+            error => error,
+        }
+    }
+
+    /// The individual errors making up this one.
+    pub fn into_errors(self) -> Vec<Error> {
+        match self {
+            Error::Many(errors) => errors,
+            error => vec![error],
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// Fails with the errors gathered under `ErrorPolicy::Collect`, if there were any.
+pub(crate) fn collected<T>(value: T, errors: Vec<Error>) -> Result<T> {
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(Error::Many(errors))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Encode(u) => write!(f, "unable to encode value: {:?}", u),
+            Error::Decode(code) => write!(f, "unable to decode sequence: {:?}", code),
+            Error::Io(e) => e.fmt(f),
+            Error::Audio(e) => write!(f, "audio error: {}", e),
+            Error::Table(e) => write!(f, "invalid code table: {}", e),
+            Error::At(position, e) => write!(f, "{}: {}", position, e),
+            Error::Hint(e, hint) => write!(f, "{}; {}", e, hint),
+            Error::Many(errors) => {
+                for (idx, e) in errors.iter().enumerate() {
+                    if idx > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "std")]
+/// A location in a message. Lines and columns count from one, and columns count characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    /// Byte offset from the start of the message.
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[cfg(feature = "std")]
+impl Position {
+    pub fn locate(text: &str, offset: usize) -> Self {
+        let before = &text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        Position {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// The position of `part`, which must be a slice of `text`.
+    pub(crate) fn of(text: &str, part: &str) -> Self {
+        Position::locate(text, part.as_ptr() as usize - text.as_ptr() as usize)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[cfg(feature = "std")]
+/// Settings shared by encoding and decoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Options {
+    /// Enables the non-standard symbols in `data::EXTENDED_SYMBOLS` and the accented letters in
+    /// `data::EXTENDED_LETTERS`.
+    pub extended: bool,
+    /// Decodes sequences shared by a punctuation mark and a prosign (e.g. `.-.-.`, which is both
+    /// `+` and `<AR>`) as the prosign.
+    pub prosigns: bool,
+    /// Placed between decoded words.
+    pub word_sep: char,
+    /// What to do with characters that cannot be encoded and sequences that cannot be decoded.
+    pub errors: ErrorPolicy,
+    /// Which letters to encode, and to decode letter codes as.
+    pub alphabet: Alphabet,
+}
+
+#[cfg(feature = "std")]
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            extended: false,
+            prosigns: false,
+            word_sep: ' ',
+            errors: ErrorPolicy::Strict,
+            alphabet: Alphabet::Latin,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// How encoding and decoding deal with input they do not understand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Fail on the first bad character or sequence.
+    Strict,
+    /// Leave bad characters or sequences out of the output.
+    Skip,
+    /// Leave them out like `Skip`, then fail with every one of them at the end.
+    Collect,
+    /// Put the given character in their place. When encoding, it must itself be encodable.
+    Replace(char),
+}
+
+#[cfg(feature = "std")]
+/// A set of letters sharing the standard digits and punctuation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alphabet {
+    Latin,
+    Cyrillic,
+    Greek,
+    /// Decoded text is in logical order, like any other, with final letter forms restored at the
+    /// ends of words; laying it out right to left is left to whatever displays it.
+    Hebrew,
+    /// Short-vowel marks and tatweel are not sent, and Arabic-Indic digits are sent as digits.
+    Arabic,
+    /// Japanese kana, shifted in and out of with prosigns; see the `wabun` module.
+    Wabun,
+    /// Hangul, transliterated into Latin letters; see the `skats` module.
+    Korean,
+}
+
+#[cfg(feature = "std")]
+impl Alphabet {
+    /// Letters replacing the Latin ones, if any.
+    fn letters(self) -> Option<&'static [(char, Code)]> {
+        match self {
+            Alphabet::Latin => None,
+            Alphabet::Cyrillic => Some(data::CYRILLIC),
+            Alphabet::Greek => Some(data::GREEK),
+            Alphabet::Hebrew => Some(data::HEBREW),
+            Alphabet::Arabic => Some(data::ARABIC),
+            Alphabet::Wabun | Alphabet::Korean => None,
+        }
+    }
+
+    /// Maps a character onto the one to encode in its place. `None` means it is only part of the
+    /// way the text is written and is left out entirely.
+    fn fold(self, c: char) -> Option<char> {
+        match (self, c) {
+            // Harakat (fathatan through sukun) and tatweel.
+            (Alphabet::Arabic, '\u{64b}'..='\u{652}') | (Alphabet::Arabic, '\u{640}') => None,
+            (Alphabet::Arabic, '\u{660}'..='\u{669}') => {
+                std::char::from_u32(c as u32 - 0x660 + u32::from(b'0'))
+            }
+            _ => Some(c),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for Alphabet {
+    type Err = UnknownAlphabet;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "latin" => Ok(Alphabet::Latin),
+            "cyrillic" | "russian" => Ok(Alphabet::Cyrillic),
+            "greek" => Ok(Alphabet::Greek),
+            "hebrew" => Ok(Alphabet::Hebrew),
+            "arabic" => Ok(Alphabet::Arabic),
+            "wabun" | "japanese" => Ok(Alphabet::Wabun),
+            "korean" | "skats" => Ok(Alphabet::Korean),
+            _ => Err(UnknownAlphabet(s.into())),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct UnknownAlphabet(String);
+
+#[cfg(feature = "std")]
+impl Display for UnknownAlphabet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown alphabet {:?} (expected latin, cyrillic, greek, hebrew, arabic, wabun or korean)",
+            self.0
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+/// A code table, with its own sequences and timing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Standard {
+    International,
+    American,
+}
+
+#[cfg(feature = "std")]
+impl FromStr for Standard {
+    type Err = UnknownStandard;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "international" | "itu" => Ok(Standard::International),
+            "american" | "landline" => Ok(Standard::American),
+            _ => Err(UnknownStandard(s.into())),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct UnknownStandard(String);
+
+#[cfg(feature = "std")]
+impl Display for UnknownStandard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown code {:?} (expected international or american)",
+            self.0
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+/// Encodes a message using the standard table. Fails on the first character that cannot be
+/// encoded; see `sanitize` to drop those beforehand.
+pub fn encode(message: &str) -> Result<String> {
+    encode_with(message, &Options::default())
+}
+
+#[cfg(feature = "std")]
+/// Decodes a message using the standard table. Fails on the first unknown sequence.
+pub fn decode(message: &str) -> Result<String> {
+    decode_with(message, &Options::default())
+}
+
+#[cfg(feature = "std")]
+/// Drops anything that cannot be encoded from a message.
+pub fn sanitize(message: &str, options: &Options) -> String {
+    let mut buf = String::with_capacity(message.len());
+    let mut rest = message.trim();
+
+    while let Some(c) = rest.chars().next() {
+        if let Some((letters, tail)) = split_prosign(rest) {
+            buf.push('<');
+            buf.push_str(letters);
+            buf.push('>');
+            rest = tail;
+            continue;
+        }
+
+        if c == ' ' || is_encodable_with(c, options) {
+            buf.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+
+    buf
+}
+
+#[cfg(feature = "std")]
+/// Encodes a message. Any letters and digits between angle brackets are sent as a single prosign,
+/// whether or not it appears in `data::PROSIGNS`.
+pub fn encode_with(message: &str, options: &Options) -> Result<String> {
+    match options.alphabet {
+        Alphabet::Wabun => return wabun::encode_with(message, options),
+        Alphabet::Korean => return skats::encode_with(message, options),
+        _ => (),
+    }
+
+    let mut buf = String::with_capacity(message.len() * 4);
+    let mut errors = Vec::new();
+    let mut rest = message;
+
+    while let Some(c) = rest.chars().next() {
+        if c == ' ' {
+            buf.push_str(" /");
+            rest = &rest[1..];
+            continue;
+        }
+
+        if let Some((letters, tail)) = split_prosign(rest) {
+            if !buf.is_empty() {
+                buf.push(' ');
+            }
+            for (idx, u) in letters.bytes().enumerate() {
+                let code =
+                    encode_byte(u, options).map_err(|e| e.at(message, &letters[idx..idx + 1]))?;
+                buf.push_str(code);
+            }
+            rest = tail;
+            continue;
+        }
+
+        if let Some((code, tail)) = split_digraph(rest, options) {
+            if !buf.is_empty() {
+                buf.push(' ');
+            }
+            buf.push_str(code);
+            rest = tail;
+            continue;
+        }
+
+        let (current, tail) = rest.split_at(c.len_utf8());
+        rest = tail;
+        let c = match options.alphabet.fold(c) {
+            Some(c) => c,
+            None => continue,
+        };
+
+        let code = match encode_char(c, options) {
+            Ok(code) => Some(code),
+            Err(e) => match options.errors {
+                ErrorPolicy::Strict => return Err(e.at(message, current)),
+                ErrorPolicy::Skip => None,
+                ErrorPolicy::Collect => {
+                    errors.push(e.at(message, current));
+                    None
+                }
+                ErrorPolicy::Replace(replacement) => Some(encode_char(replacement, options)?),
+            },
+        };
+
+        if let Some(code) = code {
+            if !buf.is_empty() {
+                buf.push(' ');
+            }
+            buf.push_str(code);
+        }
+    }
+
+    collected(buf, errors)
+}
+
+#[cfg(feature = "std")]
+/// Splits the first character, or a whole prosign or digraph, from the rest of a message, as
+/// `encode_with` does.
+pub(crate) fn split_character<'a>(
+    message: &'a str,
+    options: &Options,
+) -> Option<(&'a str, &'a str)> {
+    let c = message.chars().next()?;
+    let tail = split_prosign(message)
+        .map(|(_, tail)| tail)
+        .or_else(|| split_digraph(message, options).map(|(_, tail)| tail));
+    match tail {
+        Some(tail) => Some(message.split_at(message.len() - tail.len())),
+        None => Some(message.split_at(c.len_utf8())),
+    }
+}
+
+#[cfg(feature = "std")]
+/// Splits a leading prosign, such as `<SK>`, from the rest of a message.
+fn split_prosign(message: &str) -> Option<(&str, &str)> {
+    let body = message.strip_prefix('<')?;
+    let end = body.find('>')?;
+    let letters = &body[..end];
+
+    if letters.is_empty() || !letters.bytes().all(|u| u.is_ascii_alphanumeric()) {
+        return None;
+    }
+
+    Some((letters, &body[end + 1..]))
+}
+
+#[cfg(feature = "std")]
+/// Splits a leading digraph with a code of its own, such as `CH`, from the rest of a message.
+fn split_digraph<'a>(message: &'a str, options: &Options) -> Option<(Code, &'a str)> {
+    if !options.extended {
+        return None;
+    }
+
+    data::EXTENDED_LETTERS
+        .iter()
+        .filter(|&&(text, _)| text.len() > 1)
+        .find(|&&(text, _)| {
+            message
+                .get(..text.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(text))
+        })
+        .map(|&(text, code)| (code, &message[text.len()..]))
+}
+
+#[cfg(feature = "std")]
+/// Removes `#` comments, which run to the end of the line. `#` is not used by any code table, so
+/// this cannot swallow Morse.
+pub fn strip_comments(message: &str) -> String {
+    message
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(feature = "std")]
+pub fn decode_with(message: &str, options: &Options) -> Result<String> {
+    decode_counting(message, options).map(|(decoded, _)| decoded)
+}
+
+#[cfg(feature = "std")]
+/// Decodes a message, also returning the number of sequences that could not be decoded and were
+/// instead handled according to `options.errors`.
+pub fn decode_counting(message: &str, options: &Options) -> Result<(String, usize)> {
+    match options.alphabet {
+        Alphabet::Wabun => return wabun::decode_counting(message, options),
+        Alphabet::Korean => return skats::decode_counting(message, options),
+        _ => (),
+    }
+
+    let mut unknown = 0;
+    let mut errors = Vec::new();
+    let decoded = decode_words(message, options, &mut |character, buf| {
+        unknown += 1;
+        let e = Error::Decode(character.into()).at(message, character);
+        match options.errors {
+            ErrorPolicy::Strict => return Err(e),
+            ErrorPolicy::Skip => (),
+            ErrorPolicy::Collect => errors.push(e),
+            ErrorPolicy::Replace(replacement) => buf.push(replacement),
+        }
+        Ok(())
+    })?;
+
+    collected((decoded, unknown), errors)
+}
+
+#[cfg(feature = "std")]
+/// Decodes a message, passing each unknown sequence to `unknown` along with the output so far.
+fn decode_words(message: &str, options: &Options, unknown: &mut UnknownHandler) -> Result<String> {
+    let mut buf = String::new();
+    let mut words = message.split('/').filter(|word| !word.trim().is_empty());
+
+    if let Some(word) = words.next() {
+        decode_word_into(word, &mut buf, options, unknown)?;
+    }
+
+    for word in words {
+        buf.push(options.word_sep);
+        decode_word_into(word, &mut buf, options, unknown)?;
+    }
+
+    Ok(buf)
+}
+
+#[cfg(feature = "std")]
+type UnknownHandler<'a> = dyn FnMut(&str, &mut String) -> Result<()> + 'a;
+
+#[cfg(feature = "std")]
+/// Returns whether `c` can be encoded using the standard table.
+pub fn is_encodable(c: char) -> bool {
+    is_encodable_with(c, &Options::default())
+}
+
+#[cfg(feature = "std")]
+/// Returns whether `c` can be encoded with the given options.
+pub fn is_encodable_with(c: char, options: &Options) -> bool {
+    match options.alphabet {
+        Alphabet::Wabun if wabun::is_encodable(c) => return true,
+        Alphabet::Korean if skats::is_encodable(c) => return true,
+        _ => (),
+    }
+
+    match options.alphabet.fold(c) {
+        Some(c) => encode_char(c, options).is_ok(),
+        None => false,
+    }
+}
+
+#[cfg(feature = "std")]
+/// Letters of the selected alphabet come first; digits and punctuation are shared by all of them.
+fn encode_char(c: char, options: &Options) -> Result<Code> {
+    if let Some(code) = options
+        .alphabet
+        .letters()
+        .and_then(|letters| lookup_letter(letters, c))
+    {
+        return Ok(code);
+    }
+
+    if c.is_ascii() {
+        return encode_byte(c as u8, options);
+    }
+
+    let mut upper = c.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(upper), None) if options.extended => data::EXTENDED_LETTERS
+            .iter()
+            .find(|&&(text, _)| text.chars().eq(std::iter::once(upper)))
+            .map(|&(_, code)| code)
+            .ok_or(Error::Encode(c)),
+        _ => Err(Error::Encode(c)),
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn encode_byte(u: u8, options: &Options) -> Result<Code> {
+    elements::code(u as char)
+        .or_else(|| {
+            if options.extended {
+                lookup_code(data::EXTENDED_SYMBOLS, u)
+            } else {
+                None
+            }
+        })
+        .ok_or(Error::Encode(u as char))
+}
+
+#[cfg(feature = "std")]
+fn lookup_letter(letters: &[(char, Code)], c: char) -> Option<Code> {
+    let mut upper = c.to_uppercase();
+    let c = match (upper.next(), upper.next()) {
+        (Some(upper), None) => upper,
+        _ => c,
+    };
+
+    letters
+        .iter()
+        .find(|&&(letter, _)| letter == c)
+        .map(|&(_, code)| code)
+}
+
+#[cfg(feature = "std")]
+fn lookup_code(table: &[(u8, Code)], u: u8) -> Option<Code> {
+    table
+        .iter()
+        .find(|&&(symbol, _)| symbol == u)
+        .map(|&(_, code)| code)
+}
+
+#[cfg(feature = "std")]
+fn decode_word_into(
+    word: &str,
+    buf: &mut String,
+    options: &Options,
+    unknown: &mut UnknownHandler,
+) -> Result<()> {
+    for character in word.split_whitespace() {
+        decode_character_into(character, buf, options, unknown)?;
+    }
+
+    if options.alphabet == Alphabet::Hebrew {
+        if let Some(last) = buf.pop() {
+            buf.push(hebrew_final_form(last));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+/// Five Hebrew letters take a different form at the end of a word.
+fn hebrew_final_form(c: char) -> char {
+    match c {
+        'כ' => 'ך',
+        'מ' => 'ם',
+        'נ' => 'ן',
+        'פ' => 'ף',
+        'צ' => 'ץ',
+        c => c,
+    }
+}
+
+#[cfg(feature = "std")]
+/// Prosigns that share a code with another character are only used when `options.prosigns` is
+/// set; the rest are always recognized.
+fn decode_character_into(
+    character: &str,
+    buf: &mut String,
+    options: &Options,
+    unknown: &mut UnknownHandler,
+) -> Result<()> {
+    let prosign = data::PROSIGNS
+        .iter()
+        .find(|&&(_, code)| code == character)
+        .map(|&(name, _)| name);
+
+    let letter = options.alphabet.letters().and_then(|letters| {
+        letters
+            .iter()
+            .find(|&&(_, code)| code == character)
+            .map(|&(letter, _)| letter)
+    });
+
+    match (prosign, decode_character(character, options)) {
+        (Some(name), _) if options.prosigns => push_prosign(name, buf),
+        _ if letter.is_some() => buf.extend(letter),
+        (_, Ok(u)) => buf.push(u as char),
+        (Some(name), Err(_)) => push_prosign(name, buf),
+        (None, Err(_)) => match extended_letter(character, options) {
+            Some(text) => buf.push_str(text),
+            None => return unknown(character, buf),
+        },
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+fn extended_letter(character: &str, options: &Options) -> Option<&'static str> {
+    if !options.extended {
+        return None;
+    }
+
+    data::EXTENDED_LETTERS
+        .iter()
+        .find(|&&(_, code)| code == character)
+        .map(|&(text, _)| text)
+}
+
+#[cfg(feature = "std")]
+fn push_prosign(name: &str, buf: &mut String) {
+    buf.push('<');
+    buf.push_str(name);
+    buf.push('>');
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn decode_character(character: &str, options: &Options) -> Result<u8> {
+    let idx = character_index(character);
+    data::DECODING_ARRAY
+        .get(idx as usize)
+        .copied()
+        .and_then(|x| x)
+        .or_else(|| {
+            if options.extended {
+                lookup_symbol(data::EXTENDED_SYMBOLS, character)
+            } else {
+                None
+            }
+        })
+        .ok_or_else(|| Error::Decode(character.into()))
+}
+
+#[cfg(feature = "std")]
+fn lookup_symbol(table: &[(u8, Code)], character: &str) -> Option<u8> {
+    table
+        .iter()
+        .find(|&&(_, code)| code == character)
+        .map(|&(symbol, _)| symbol)
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn character_index(character: &str) -> i32 {
+    character.bytes().fold(0, |idx, u| match u {
+        b'.' => idx * 2 + 1,
+        b'-' => idx * 2 + 2,
+        _ => idx,
+    })
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{Alphabet, ErrorPolicy, Options};
+
+    const EXTENDED: Options = Options {
+        extended: true,
+        prosigns: false,
+        word_sep: ' ',
+        errors: super::ErrorPolicy::Strict,
+        alphabet: super::Alphabet::Latin,
+    };
+
+    #[test]
+    fn char_to_code_works() {
+        let sequence = "abcdefghijklmnopqrstuvwxyz0123456789";
+        let pairs = sequence.bytes().zip(super::data::ENCODED_SEQUENCES);
+
+        for (u, &code) in pairs {
+            assert_eq!(super::encode_byte(u, &Options::default()).unwrap(), code);
+        }
+    }
+
+    #[test]
+    fn decode_one_word_per_line() {
+        let options = Options {
+            word_sep: '\n',
+            ..Options::default()
+        };
+
+        let message = "- .... .-. . . / .-- --- .-. -.. ... / .... . .-. . /";
+        let decoded = super::decode_with(message, &options).unwrap();
+        assert_eq!(decoded, "THREE\nWORDS\nHERE");
+    }
+
+    #[test]
+    fn extended_symbols_round_trip() {
+        for &(symbol, code) in super::data::EXTENDED_SYMBOLS {
+            let message = format!("2{}3", symbol as char);
+            let encoded = super::encode_with(&message, &EXTENDED).unwrap();
+            assert_eq!(encoded, format!("..--- {} ...--", code));
+            assert_eq!(super::decode_with(&encoded, &EXTENDED).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn extended_symbols_require_flag() {
+        assert!(super::encode("2*3").is_err());
+        assert!(super::decode("-..-.-").is_err());
+    }
+
+    #[test]
+    fn is_encodable_reflects_table() {
+        assert!(super::is_encodable('a'));
+        assert!(super::is_encodable('5'));
+        assert!(!super::is_encodable('#'));
+        assert!(!super::is_encodable('*'));
+        assert!(super::is_encodable_with('*', &EXTENDED));
+    }
+
+    #[test]
+    fn comments_are_ignored() {
+        let file = "# this is a note\n.... . .-.. .-.. --- # greeting\n# another note\n";
+        let message = super::strip_comments(file);
+        assert_eq!(super::decode(message.trim()).unwrap(), "HELLO");
+    }
+
+    #[test]
+    fn spelled_numbers_encode_as_words() {
+        let message = super::numbers::spell("2");
+        assert_eq!(
+            super::encode(&message).unwrap(),
+            super::encode("TWO").unwrap()
+        );
+    }
+
+    #[test]
+    fn punctuation_round_trips() {
+        let message = "HELLO, WORLD. (1+1=2?) \"A/B\" & A-B_C: $5 @ 'X'; OK!";
+        let encoded = super::encode(message).unwrap();
+        assert!(encoded.starts_with(".... . .-.. .-.. --- --..-- /"));
+        assert_eq!(super::decode(&encoded).unwrap(), message);
+    }
+
+    #[test]
+    fn every_punctuation_mark_decodes() {
+        for &(symbol, code) in super::data::PUNCTUATION {
+            assert_eq!(super::decode(code).unwrap(), (symbol as char).to_string());
+        }
+    }
+
+    #[test]
+    fn prosigns_encode_run_together() {
+        assert_eq!(super::encode("<SOS>").unwrap(), "...---...");
+        assert_eq!(super::encode("CQ <KN>").unwrap(), "-.-. --.- / -.--.");
+        assert_eq!(super::encode("<sk>").unwrap(), "...-.-");
+        assert!(super::encode("<S K>").is_err());
+    }
+
+    #[test]
+    fn prosigns_decode_in_brackets() {
+        for &(name, code) in super::data::PROSIGNS {
+            let options = Options {
+                prosigns: true,
+                ..Options::default()
+            };
+            let decoded = super::decode_with(code, &options).unwrap();
+            assert_eq!(decoded, format!("<{}>", name));
+        }
+    }
+
+    #[test]
+    fn errors_report_their_position() {
+        let e = super::encode("SOS HELP ~").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 10: unable to encode value: '~'"
+        );
+
+        let e = super::decode("... --- ...\n.... / ..--..-").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 8: unable to decode sequence: \"..--..-\""
+        );
+
+        let position = super::Position::locate("é\nüx", 5);
+        assert_eq!((position.line, position.column), (2, 2));
+    }
+
+    #[test]
+    fn collect_policy_reports_every_error() {
+        let options = Options {
+            errors: ErrorPolicy::Collect,
+            ..Options::default()
+        };
+
+        let e = super::encode_with("a~b^", &options).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 2: unable to encode value: '~'\n\
+             line 1, column 4: unable to encode value: '^'"
+        );
+
+        let e = super::decode_with(".- ........-\n-... / ..--..-", &options).unwrap_err();
+        let columns: Vec<_> = e
+            .into_errors()
+            .into_iter()
+            .map(|e| match e {
+                super::Error::At(position, _) => (position.line, position.column),
+                e => panic!("unexpected error: {}", e),
+            })
+            .collect();
+        assert_eq!(columns, [(1, 4), (2, 8)]);
+
+        assert_eq!(super::decode_with(".- -...", &options).unwrap(), "AB");
+    }
+
+    #[test]
+    fn ambiguous_prosigns_prefer_punctuation() {
+        assert_eq!(super::decode(".-.-.").unwrap(), "+");
+        assert_eq!(super::decode("...-.-").unwrap(), "<SK>");
+        assert_eq!(super::decode("...---...").unwrap(), "<SOS>");
+    }
+
+    #[test]
+    fn sanitize_keeps_prosigns() {
+        let options = Options::default();
+        assert_eq!(super::sanitize("73 <SK> #", &options), "73 <SK> ");
+        assert_eq!(super::sanitize("<3 é", &options), "3 ");
+    }
+
+    #[test]
+    fn error_policy_applies_to_decoding() {
+        let message = ".... .. / .-.-.-.- / - .-.-.-.-";
+        assert!(super::decode(message).is_err());
+
+        let options = Options {
+            errors: ErrorPolicy::Replace('#'),
+            ..Options::default()
+        };
+        let (decoded, unknown) = super::decode_counting(message, &options).unwrap();
+        assert_eq!(decoded, "HI # T#");
+        assert_eq!(unknown, 2);
+
+        let options = Options {
+            errors: ErrorPolicy::Skip,
+            ..Options::default()
+        };
+        assert_eq!(super::decode_with(message, &options).unwrap(), "HI  T");
+    }
+
+    #[test]
+    fn error_policy_applies_to_encoding() {
+        assert!(super::encode("a~b").is_err());
+
+        let options = Options {
+            errors: ErrorPolicy::Skip,
+            ..Options::default()
+        };
+        assert_eq!(super::encode_with("a~é b", &options).unwrap(), ".- / -...");
+
+        let options = Options {
+            errors: ErrorPolicy::Replace('?'),
+            ..Options::default()
+        };
+        assert_eq!(
+            super::encode_with("a~b", &options).unwrap(),
+            ".- ..--.. -..."
+        );
+
+        let options = Options {
+            errors: ErrorPolicy::Replace('~'),
+            ..Options::default()
+        };
+        assert!(super::encode_with("a~b", &options).is_err());
+    }
+
+    #[test]
+    fn cyrillic_round_trip() {
+        let options = Options {
+            alphabet: Alphabet::Cyrillic,
+            ..Options::default()
+        };
+
+        let encoded = super::encode_with("Привет мир 73", &options).unwrap();
+        assert_eq!(encoded, ".--. .-. .. .-- . - / -- .. .-. / --... ...--");
+        assert_eq!(
+            super::decode_with(&encoded, &options).unwrap(),
+            "ПРИВЕТ МИР 73"
+        );
+
+        assert_eq!(super::encode_with("ёж", &options).unwrap(), ". ...-");
+        assert!(super::encode("мир").is_err());
+        assert_eq!(super::sanitize("мир!", &options), "мир!");
+    }
+
+    #[test]
+    fn greek_round_trip() {
+        let options = Options {
+            alphabet: Alphabet::Greek,
+            ..Options::default()
+        };
+
+        let encoded = super::encode_with("Καλημέρα κόσμε", &options).unwrap();
+        assert_eq!(
+            super::decode_with(&encoded, &options).unwrap(),
+            "ΚΑΛΗΜΕΡΑ ΚΟΣΜΕ"
+        );
+        assert_eq!(
+            super::encode_with("ς", &options).unwrap(),
+            super::encode_with("Σ", &options).unwrap()
+        );
+
+        for &(letter, _) in super::data::GREEK {
+            let encoded = super::encode_with(&letter.to_string(), &options).unwrap();
+            let decoded = super::decode_with(&encoded, &options).unwrap();
+            assert_eq!(super::encode_with(&decoded, &options).unwrap(), encoded);
+        }
+    }
+
+    #[test]
+    fn hebrew_round_trip() {
+        let options = Options {
+            alphabet: Alphabet::Hebrew,
+            ..Options::default()
+        };
+
+        let encoded = super::encode_with("שלום עולם", &options).unwrap();
+        assert_eq!(encoded, "... .-.. . -- / .--- . .-.. --");
+
+        // Final forms are restored at the end of each word, and the text stays in logical order.
+        let decoded = super::decode_with(&encoded, &options).unwrap();
+        assert_eq!(decoded, "שלום עולם");
+        assert_eq!(decoded.chars().next(), Some('ש'));
+    }
+
+    #[test]
+    fn arabic_round_trip() {
+        let options = Options {
+            alphabet: Alphabet::Arabic,
+            ..Options::default()
+        };
+
+        let encoded = super::encode_with("سلام", &options).unwrap();
+        assert_eq!(encoded, "... .-.. .- --");
+        assert_eq!(super::decode_with(&encoded, &options).unwrap(), "سلام");
+
+        // Vowel marks are dropped and Arabic-Indic digits are sent as digits.
+        assert_eq!(
+            super::encode_with("سَلَام ٧٣", &options).unwrap(),
+            "... .-.. .- -- / --... ...--"
+        );
+        assert_eq!(super::decode_with("..--..", &options).unwrap(), "؟");
+    }
+
+    #[test]
+    fn extended_letters_round_trip() {
+        let encoded = super::encode_with("Müller Façade Mañana", &EXTENDED).unwrap();
+        assert_eq!(
+            super::decode_with(&encoded, &EXTENDED).unwrap(),
+            "MÜLLER FAÇADE MAÑANA"
+        );
+
+        assert_eq!(
+            super::encode_with("chéz", &EXTENDED).unwrap(),
+            "---- ..-.. --.."
+        );
+        assert_eq!(
+            super::decode_with("---- ..-.. --..", &EXTENDED).unwrap(),
+            "CHÉZ"
+        );
+        assert_eq!(super::decode_with("...-...", &EXTENDED).unwrap(), "Ś");
+
+        assert!(super::encode("ü").is_err());
+        assert!(super::decode("..--").is_err());
+        assert_eq!(super::encode("ch").unwrap(), "-.-. ....");
+    }
+}
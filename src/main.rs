@@ -1,240 +1,3462 @@
 use std::{
-    fmt::Display,
-    io::{self, Read},
-    ops::RangeInclusive,
+    cmp::Reverse,
+    env,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, IsTerminal, Read, Write},
+    net::{IpAddr, SocketAddr, UdpSocket},
+    path::{Path, PathBuf},
     process,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
 };
 
+#[cfg(feature = "soundcard")]
+mod chat;
+mod http;
+mod keyboard;
+mod repl;
+mod report;
+#[cfg(feature = "tui")]
+mod tui;
+mod websocket;
+
 use clap::Clap;
+#[cfg(any(target_os = "linux", windows))]
+use morse::output::Speaker;
+use morse::{
+    american,
+    audio::{self, wav::SampleFormat, Encoding},
+    beacon::{self, Interval, Schedule},
+    chart, codegen,
+    confidence::{self, Scored},
+    cwdaemon, data, fist,
+    fldigi::Fldigi,
+    grade::Grade,
+    haptic, image,
+    keyer::IambicMode,
+    koch, midi, mqtt,
+    notation::Notation,
+    numbers,
+    output::{self, Bell, Flash, FlashStyle, KeyLine},
+    packed, pipe, practice, qrss, quiz,
+    rng::Rng,
+    search, segment, sheet, srt,
+    stats::Stats,
+    suggest,
+    table::Table,
+    timing::{self, Jitter, KeyEvent, Timing, Weight},
+    transliterate, tree, verify, waveform, Alphabet, Error, ErrorPolicy, Options, Position, Result,
+    Standard,
+};
+use report::{EncodeFormat, Mapping, OutputFormat, Report};
+
+/// Scored characters below this confidence are called out in JSON warnings.
+const UNCERTAIN: f64 = 0.5;
+
+/// The speeds spanned by a Winkeyer's speed pot with `send --winkeyer --pot`.
+const POT_RANGE: (u32, u32) = (10, 40);
+
+/// How far, in WPM, a tracked speed must move before the change is reported.
+const SPEED_CHANGE: f64 = 2.0;
+
+#[derive(Clap, Clone)]
+enum Opts {
+    Encode(EncodeOpts),
+    Decode(DecodeOpts),
+    /// Decode the input if it looks like Morse, and encode it otherwise
+    Auto(AutoOpts),
+    /// Check that a message survives being encoded and decoded again
+    Verify(VerifyOpts),
+    /// Count characters and elements, and estimate how long a message takes to send
+    Stats(StatsOpts),
+    /// Print the code chart
+    Table(ChartOpts),
+    /// Draw the tree that decoding walks, one branch per dot or dash
+    Tree(TreeOpts),
+    /// List words whose code matches a pattern of dots, dashes and wildcards (? and *)
+    Search(SearchOpts),
+    /// Convert between any two formats
+    Pipe(PipeOpts),
+    /// Render a message as CW audio: WAV, FLAC, Ogg Vorbis or raw PCM
+    Wav(WavOpts),
+    /// Decode CW from a WAV recording
+    DecodeAudio(DecodeAudioOpts),
+    /// Practice copying code by ear
+    Train(TrainOpts),
+    /// Generate practice material
+    Practice(PracticeOpts),
+    /// Play characters or words and score the answers typed back
+    Quiz(QuizOpts),
+    /// Grade copy against the text that was sent
+    Grade(GradeOpts),
+    /// Hold any key down as a straight key and see what you send decoded live
+    Key(KeyOpts),
+    /// Use two keys as the paddles of an iambic keyer and see what you send decoded live
+    Paddles(PaddlesOpts),
+    /// Measure the element ratios and spacing of hand-sent code
+    Fist(FistOpts),
+    /// Flash a message on the terminal in real time
+    Flash(FlashOpts),
+    /// Generate an Arduino sketch that blinks a message on an LED, or a C array of its timing
+    Codegen(CodegenOpts),
+    /// Beep a message on the terminal bell or the PC speaker in real time
+    Beep(BeepOpts),
+    /// Key a transmitter through a serial port line or a GPIO pin in real time
+    Send(SendOpts),
+    /// Repeat a message at a fixed interval until interrupted
+    Beacon(BeaconOpts),
+    /// Key messages sent over the network by loggers and contest programs
+    Serve(ServeOpts),
+    /// Send and receive CW through a running fldigi
+    Fldigi(FldigiOpts),
+    /// Encode or decode every message published on an MQTT topic, republishing the results
+    Mqtt(MqttOpts),
+    /// Key a message through the sound card
+    #[cfg(feature = "soundcard")]
+    Play(PlayOpts),
+    /// Decode CW from the microphone as it arrives
+    #[cfg(feature = "soundcard")]
+    Listen(ListenOpts),
+    /// Chat in CW over the sound card: typed lines are played, and tones heard are decoded
+    #[cfg(feature = "soundcard")]
+    Chat(ChatOpts),
+    /// Full-screen receiving and sending practice
+    #[cfg(feature = "tui")]
+    Tui(TuiOpts),
+}
+
+#[derive(Clap, Clone)]
+struct EncodeOpts {
+    /// Also render the transmission as an SVG timing diagram, labelled with its characters and
+    /// the length of every element and gap
+    #[clap(long, parse(from_os_str))]
+    svg: Option<PathBuf>,
+
+    /// Also render the transmission as an animated image of a blinking lamp, a GIF or an APNG
+    /// depending on the extension
+    #[clap(long, parse(from_os_str))]
+    animation: Option<PathBuf>,
+
+    /// Size in pixels of the animated lamp
+    #[clap(long, default_value = "64")]
+    lamp_size: u32,
+
+    /// Print an ASCII-art waveform instead of the encoded message
+    #[clap(long)]
+    waveform: bool,
+
+    /// Width of the SVG timing diagram
+    #[clap(long, default_value = "800")]
+    width: u32,
+
+    /// Height of the SVG timing diagram
+    #[clap(long, default_value = "120")]
+    height: u32,
+
+    /// Enable non-standard symbols (* % ^ ~ |) and accented letters (É Ü Ä Ñ CH ...)
+    #[clap(long)]
+    extended: bool,
+
+    /// Code table to use (international, american)
+    #[clap(long, default_value = "international")]
+    code: Standard,
+
+    /// Letters to use with the international table (latin, cyrillic, greek, hebrew, arabic,
+    /// wabun, korean)
+    #[clap(long, default_value = "latin")]
+    alphabet: Alphabet,
+
+    /// Load the code table from a TOML file mapping characters to codes, instead of --code
+    #[clap(long, parse(from_os_str))]
+    table: Option<PathBuf>,
+
+    /// Output format (text, json), the rhythm as sheet music (abc, lilypond), vibrate and pause
+    /// times in milliseconds for the Web Vibration API or Android (haptic, haptic-csv), key down
+    /// and up times in milliseconds, positive and negative, for keyers and hardware (timing), or
+    /// the whole input packed two bits an element (bin), which decode reads back as it is
+    #[clap(long, default_value = "text")]
+    format: EncodeFormat,
+
+    /// Speed at which individual characters are sent, in words per minute
+    #[clap(long, visible_alias = "char-wpm", default_value = "20")]
+    wpm: u32,
+
+    /// Overall speed, reached by stretching the gaps between characters and words while keeping
+    /// the characters themselves at --wpm
+    #[clap(long, visible_alias = "effective-wpm")]
+    farnsworth: Option<u32>,
+
+    /// Length of a dah in dits, for a heavier or lighter fist than the standard 3
+    #[clap(long, default_value = "3")]
+    weight: Weight,
+
+    /// Randomly vary element and gap lengths by up to this percentage, like a human fist
+    #[clap(long, default_value = "0")]
+    jitter: f64,
+
+    /// Seed for --jitter, for reproducible output
+    #[clap(long)]
+    seed: Option<u64>,
+
+    /// Spell out numbers up to 9999 in words before encoding
+    #[clap(long)]
+    spell_numbers: bool,
+
+    /// Send accented letters as plain ones (é as E, ß as SS) instead of leaving them out
+    #[clap(long)]
+    transliterate: bool,
+
+    /// Fail on characters that cannot be encoded, instead of leaving them out
+    #[clap(long)]
+    strict: bool,
+
+    /// Keep going past characters that cannot be encoded, then report every one of them
+    #[clap(long)]
+    all_errors: bool,
+
+    /// Encode this character in place of any that cannot be encoded
+    #[clap(long)]
+    replace: Option<char>,
+
+    #[clap(flatten)]
+    notation: NotationOpts,
+
+    #[clap(flatten)]
+    files: FileOpts,
+}
+
+impl EncodeOpts {
+    fn error_policy(&self) -> ErrorPolicy {
+        match self.replace {
+            Some(replacement) => ErrorPolicy::Replace(replacement),
+            None if self.all_errors => ErrorPolicy::Collect,
+            None if self.strict => ErrorPolicy::Strict,
+            None => ErrorPolicy::Skip,
+        }
+    }
+
+    /// The first option given that the interactive prompt would ignore. The speed is passed on
+    /// to the prompt for its timing estimates.
+    fn prompt_conflict(&self) -> Option<&'static str> {
+        [
+            (self.table.is_some(), "--table"),
+            (self.code != Standard::International, "--code"),
+            (
+                self.error_policy() != ErrorPolicy::Skip,
+                "--strict, --all-errors and --replace",
+            ),
+            (self.format != EncodeFormat::Text, "--format"),
+            (self.transliterate, "--transliterate"),
+            (self.spell_numbers, "--spell-numbers"),
+            (self.notation.is_given(), NOTATION_FLAGS),
+            (self.svg.is_some(), "--svg"),
+            (self.waveform, "--waveform"),
+            (self.animation.is_some(), "--animation"),
+            (self.files.output.is_some(), "--output"),
+        ]
+        .iter()
+        .find(|&&(given, _)| given)
+        .map(|&(_, flags)| flags)
+    }
+
+    fn timing(&self) -> Timing {
+        Timing::farnsworth(self.wpm, self.farnsworth.unwrap_or(self.wpm)).weighted(self.weight.0)
+    }
+
+    fn write_animation(&self, path: &Path, events: &[KeyEvent]) -> Result<()> {
+        let format = image::Animation::from_path(path).ok_or_else(|| {
+            Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("can't tell an animation format from {}", path.display()),
+            ))
+        })?;
+        // End on a word gap so the loop doesn't run the end of the message into its start.
+        let mut events = events.to_vec();
+        events.push(KeyEvent::Up(self.timing().word_gap()));
+        let frames = image::blink(&events, self.lamp_size);
+
+        let file = File::create(path).map_err(|e| file_error(path, e))?;
+        let writer = BufWriter::new(file);
+        match format {
+            image::Animation::Gif => image::gif::write(writer, &frames),
+            image::Animation::Apng => image::png::write_animated(writer, &frames),
+        }
+        .map_err(|e| file_error(path, e))
+    }
+
+    /// Transliterates `input` if asked to, leaving alone what `options` can already encode, such
+    /// as accented letters with --extended.
+    fn transliterated(&self, input: &str, table: Option<&Table>, options: &Options) -> String {
+        if self.transliterate {
+            transliterate::latin_except(input, |c| is_encodable(c, table, self.code, options))
+        } else {
+            input.into()
+        }
+    }
+
+    /// Encodes `input`, also returning the options it was encoded with and the message as it was
+    /// encoded, after any transliteration and spelling out.
+    fn translate(&self, input: &str, table: Option<&Table>) -> Result<(Options, String, String)> {
+        let options = Options {
+            extended: self.extended,
+            errors: self.error_policy(),
+            alphabet: self.alphabet,
+            ..Options::default()
+        };
+
+        let message = self.transliterated(input, table, &options);
+        let message = match options.errors {
+            ErrorPolicy::Skip => sanitize(&message, table, self.code, &options),
+            _ => message.trim().into(),
+        };
+        let message = if self.spell_numbers {
+            numbers::spell(&message)
+        } else {
+            message
+        };
+        let encoded = match (table, self.code) {
+            (Some(table), _) => table.encode_with(&message, &options)?,
+            (None, Standard::International) => morse::encode_with(&message, &options)?,
+            (None, Standard::American) => american::encode_with(&message, &options)?,
+        };
+        Ok((options, message, encoded))
+    }
+
+    /// Keys an encoded message, drawing it as an SVG and an animation if asked.
+    fn draw(
+        &self,
+        encoded: &str,
+        table: Option<&Table>,
+        options: &Options,
+    ) -> Result<Vec<KeyEvent>> {
+        let events = self.key_events(encoded);
+        if let Some(path) = &self.svg {
+            let characters = waveform::characters(encoded, |code| match (table, self.code) {
+                (Some(table), _) => table.character(code).map(String::from),
+                (None, Standard::International) => morse::decode_with(code, options).ok(),
+                (None, Standard::American) => american::decode_with(code, options).ok(),
+            });
+            let svg = waveform::svg(&events, &characters, self.width, self.height);
+            fs::write(path, svg).map_err(|e| file_error(path, e))?;
+        }
+        if let Some(path) = &self.animation {
+            self.write_animation(path, &events)?;
+        }
+        Ok(events)
+    }
+
+    /// Encodes `input` as a packed binary message.
+    fn pack(&self, input: &str, table: Option<&Table>) -> Result<Vec<u8>> {
+        let (options, _, encoded) = self.translate(input, table)?;
+        self.draw(&encoded, table, &options)?;
+        packed::pack(&encoded).map_err(Error::Io)
+    }
+
+    fn encode(&self, input: &str, table: Option<&Table>) -> Result<String> {
+        let (options, message, encoded) = self.translate(input, table)?;
+        let events = self.draw(&encoded, table, &options)?;
+
+        let output = if self.waveform {
+            waveform::ascii(&events)
+        } else if table.is_none() && self.code == Standard::American {
+            // Notations only know about dots and dashes, not American long dashes and spaces.
+            encoded.clone()
+        } else {
+            let notation = self.notation.resolve(|var| env::var(var).ok());
+            notation.apply(&encoded)
+        };
+
+        match self.format {
+            EncodeFormat::Text => return Ok(output),
+            EncodeFormat::Score(score) => {
+                return Ok(sheet::write(score, &events, &self.timing(), &message))
+            }
+            EncodeFormat::Haptic(pattern) => return Ok(haptic::write(pattern, &events)),
+            EncodeFormat::Timing => return Ok(timing::write_key_events(&events)),
+            EncodeFormat::Bin => {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "--format bin packs the whole message into bytes, not text",
+                )))
+            }
+            EncodeFormat::Json => (),
+        }
+
+        // Warnings come from the input as given, before anything unencodable was dropped.
+        let source = self.transliterated(input, table, &options);
+
+        Ok(Report {
+            input: input.into(),
+            output,
+            characters: map_characters(&encoded, |code| {
+                decode_code(code, table, self.code, &options)
+            }),
+            warnings: encode_warnings(&source, table, self.code, &options),
+        }
+        .to_json())
+    }
+
+    fn key_events(&self, encoded: &str) -> Vec<KeyEvent> {
+        let to_key_events = match self.code {
+            _ if self.table.is_some() => timing::to_key_events,
+            Standard::International => timing::to_key_events,
+            Standard::American => american::to_key_events,
+        };
+
+        if self.jitter > 0.0 {
+            let rng = self.seed.map(Rng::new).unwrap_or_else(Rng::from_time);
+            let mut jitter = Jitter::new(self.jitter, rng);
+            to_key_events(encoded, &self.timing(), Some(&mut jitter))
+        } else {
+            to_key_events(encoded, &self.timing(), None)
+        }
+    }
+}
+
+#[derive(Clap, Clone)]
+struct DecodeOpts {
+    /// Print each decoded word on its own line
+    #[clap(long)]
+    one_word_per_line: bool,
+
+    /// Enable non-standard symbols (* % ^ ~ |) and accented letters (É Ü Ä Ñ CH ...)
+    #[clap(long)]
+    extended: bool,
+
+    /// Code table to use (international, american)
+    #[clap(long, default_value = "international")]
+    code: Standard,
+
+    /// Letters to use with the international table (latin, cyrillic, greek, hebrew, arabic,
+    /// wabun, korean)
+    #[clap(long, default_value = "latin")]
+    alphabet: Alphabet,
+
+    /// Load the code table from a TOML file mapping characters to codes, instead of --code
+    #[clap(long, parse(from_os_str))]
+    table: Option<PathBuf>,
+
+    /// Output format (text, json)
+    #[clap(long, default_value = "text")]
+    format: OutputFormat,
+
+    /// Ignore everything from `#` to the end of each line
+    #[clap(long)]
+    ignore_comments: bool,
+
+    /// Read key down and up times in milliseconds instead of dots and dashes, as from a key
+    /// interface or a logic analyzer: positive for down and negative for up, or all positive and
+    /// alternating, starting down
+    #[clap(long)]
+    timing: bool,
+
+    /// Decode codes shared with punctuation (e.g. `.-.-.`) as prosigns (`<AR>`)
+    #[clap(long)]
+    prosigns: bool,
+
+    /// Convert spelled-out numbers back into digits
+    #[clap(long)]
+    spell_numbers: bool,
+
+    /// Keep going past unknown sequences, writing `#` in their place
+    #[clap(long)]
+    lenient: bool,
+
+    /// Keep going past unknown sequences, leaving them out
+    #[clap(long)]
+    skip: bool,
+
+    /// Keep going past unknown sequences, then report every one of them
+    #[clap(long)]
+    all_errors: bool,
+
+    /// Split runs of elements with missing letter gaps into their likeliest letters
+    #[clap(long)]
+    segment: bool,
+
+    /// With --segment, list this many readings of each word instead of the best message
+    #[clap(long, default_value = "1")]
+    candidates: usize,
+
+    /// With --segment, prefer the words in this file, separated by whitespace, over the
+    /// built-in list
+    #[clap(long, parse(from_os_str))]
+    wordlist: Option<PathBuf>,
+
+    /// Character written in place of unknown sequences; implies --lenient
+    #[clap(long)]
+    replace: Option<char>,
+
+    #[clap(flatten)]
+    notation: NotationOpts,
+
+    #[clap(flatten)]
+    files: FileOpts,
+}
+
+impl DecodeOpts {
+    fn error_policy(&self) -> ErrorPolicy {
+        match self.replace {
+            Some(replacement) => ErrorPolicy::Replace(replacement),
+            None if self.all_errors => ErrorPolicy::Collect,
+            None if self.skip => ErrorPolicy::Skip,
+            None if self.lenient => ErrorPolicy::Replace('#'),
+            None => ErrorPolicy::Strict,
+        }
+    }
+
+    /// The first option given that the interactive prompt would ignore.
+    fn prompt_conflict(&self) -> Option<&'static str> {
+        [
+            (self.table.is_some(), "--table"),
+            (self.code != Standard::International, "--code"),
+            (
+                self.error_policy() != ErrorPolicy::Strict,
+                "--lenient, --skip, --all-errors and --replace",
+            ),
+            (self.format != OutputFormat::Text, "--format"),
+            (self.one_word_per_line, "--one-word-per-line"),
+            (self.segment, "--segment"),
+            (self.timing, "--timing"),
+            (self.ignore_comments, "--ignore-comments"),
+            (self.spell_numbers, "--spell-numbers"),
+            (self.notation.is_given(), NOTATION_FLAGS),
+            (self.files.output.is_some(), "--output"),
+        ]
+        .iter()
+        .find(|&&(given, _)| given)
+        .map(|&(_, flags)| flags)
+    }
+
+    fn decode(&self, message: &str, table: Option<&Table>) -> Result<String> {
+        let message = if self.ignore_comments {
+            morse::strip_comments(message)
+        } else {
+            message.into()
+        };
+        let message = if self.timing {
+            self.classify(&message)?
+        } else {
+            message
+        };
+
+        let notation = self.notation.resolve(|var| env::var(var).ok());
+        let options = Options {
+            extended: self.extended,
+            prosigns: self.prosigns,
+            word_sep: if self.one_word_per_line { '\n' } else { ' ' },
+            errors: self.error_policy(),
+            alphabet: self.alphabet,
+        };
+        let canonical = match (table, self.code) {
+            (None, Standard::American) => message.trim().into(),
+            _ => notation.normalize(message.trim()),
+        };
+        let (decoded, unknown, canonical) = if self.segment {
+            let (decoded, split) = self.segmented(&canonical, table, &options)?;
+            (decoded, 0, split)
+        } else {
+            let (decoded, unknown) = match (table, self.code) {
+                (Some(table), _) => table.decode_counting(&canonical, &options),
+                (None, Standard::International) => morse::decode_counting(&canonical, &options),
+                (None, Standard::American) => american::decode_counting(&canonical, &options),
+            }
+            .map_err(|e| {
+                // A loaded table is written with International elements.
+                let standard = table.map_or(self.code, |_| Standard::International);
+                suggest::annotate(e, standard, &|code| {
+                    decode_code(code, table, self.code, &options)
+                })
+            })?;
+            (decoded, unknown, canonical)
+        };
+
+        let output = if self.spell_numbers {
+            numbers::unspell(&decoded, options.word_sep)
+        } else {
+            decoded
+        };
+
+        if self.format == OutputFormat::Text {
+            if unknown > 0 {
+                eprintln!("skipped {} unknown sequence(s)", unknown);
+            }
+            return Ok(output);
+        }
+
+        // Text input leaves no room for doubt: a code either decodes or it does not.
+        let characters: Vec<_> = map_characters(&canonical, |code| {
+            decode_code(code, table, self.code, &options)
+        })
+        .into_iter()
+        .map(|mapping| Mapping {
+            confidence: Some(if mapping.text.is_some() { 1.0 } else { 0.0 }),
+            ..mapping
+        })
+        .collect();
+        let warnings = characters
+            .iter()
+            .filter(|mapping| mapping.text.is_none())
+            .map(|mapping| format!("unknown sequence {:?}", mapping.code))
+            .collect();
+
+        Ok(Report {
+            input: message,
+            output,
+            characters,
+            warnings,
+        }
+        .to_json())
+    }
+
+    /// Classifies key down and up times into dits, dahs and gaps, whatever their speed.
+    fn classify(&self, message: &str) -> Result<String> {
+        if self.table.is_none() && self.code == Standard::American {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--timing can't tell American spaced letters and long dashes apart",
+            )));
+        }
+        Ok(timing::from_key_events(&timing::read_key_events(message)?))
+    }
+
+    /// Decodes by splitting words with missing letter gaps, along with the message as it was
+    /// split, letter gaps restored. With `--candidates`, lists the readings of each word instead.
+    fn segmented(
+        &self,
+        message: &str,
+        table: Option<&Table>,
+        options: &Options,
+    ) -> Result<(String, String)> {
+        let wordlist = match &self.wordlist {
+            Some(path) => fs::read_to_string(path).map_err(|e| file_error(path, e))?,
+            None => String::new(),
+        };
+        let dictionary: Vec<&str> = match &self.wordlist {
+            Some(_) => wordlist.split_whitespace().collect(),
+            None => data::WORDS.to_vec(),
+        };
+        let decode = |code: &str| decode_code(code, table, self.code, options);
+
+        if self.candidates <= 1 {
+            let words = segment::best(message, decode, Some(&dictionary))
+                .ok_or_else(|| Error::Decode(message.into()))?;
+            let text: Vec<_> = words.iter().map(|word| word.text.as_str()).collect();
+            let split: Vec<_> = words.iter().map(|word| word.codes.join(" ")).collect();
+            return Ok((text.join(&options.word_sep.to_string()), split.join(" / ")));
+        }
+
+        let mut lines = Vec::new();
+        for word in message.split('/').filter(|word| !word.trim().is_empty()) {
+            let candidates = segment::word(word, decode, Some(&dictionary), self.candidates);
+            let readings: Vec<_> = candidates.into_iter().map(|c| c.text).collect();
+            lines.push(format!("{}: {}", word.trim(), readings.join(", ")));
+        }
+        Ok((lines.join("\n"), message.into()))
+    }
+}
+
+#[derive(Clap, Clone)]
+struct AutoOpts {
+    #[clap(flatten)]
+    files: FileOpts,
+}
+
+impl AutoOpts {
+    fn translate(&self, message: &str) -> Result<String> {
+        match pipe::detect(message) {
+            Some(pipe::Format::Morse) => morse::decode(message.trim())
+                .inspect(|_| {
+                    if pipe::is_ambiguous(message) {
+                        eprintln!(
+                            "warning: the input reads as both Morse and text, so it was decoded; \
+                             use `morse encode` to send it as text"
+                        );
+                    }
+                })
+                .inspect_err(|_| {
+                    eprintln!(
+                        "hint: the input looks like Morse but does not decode; \
+                         use `morse encode` to send it as text"
+                    );
+                }),
+            Some(pipe::Format::Text) => {
+                morse::encode(&morse::sanitize(message, &Options::default()))
+            }
+            None => Ok(String::new()),
+        }
+    }
+}
+
+#[derive(Clap, Clone)]
+struct VerifyOpts {
+    /// Enable non-standard symbols (* % ^ ~ |) and accented letters (É Ü Ä Ñ CH ...)
+    #[clap(long)]
+    extended: bool,
+
+    /// Decode codes shared with punctuation (e.g. `.-.-.`) as prosigns (`<AR>`)
+    #[clap(long)]
+    prosigns: bool,
+
+    /// Letters to use (latin, cyrillic, greek, hebrew, arabic, wabun, korean)
+    #[clap(long, default_value = "latin")]
+    alphabet: Alphabet,
+
+    #[clap(flatten)]
+    notation: NotationOpts,
+
+    #[clap(flatten)]
+    files: FileOpts,
+}
+
+impl VerifyOpts {
+    /// Round-trips text through Morse, or Morse through text, depending on what the message is.
+    fn verify(&self, message: &str) -> Vec<verify::Discrepancy> {
+        let options = Options {
+            extended: self.extended,
+            prosigns: self.prosigns,
+            alphabet: self.alphabet,
+            ..Options::default()
+        };
+
+        let notation = self.notation.resolve(|var| env::var(var).ok());
+        let normalized = notation.normalize(message);
+        match pipe::detect(&normalized) {
+            Some(pipe::Format::Morse) => verify::morse(&normalized, &options),
+            _ => verify::text(message, &options),
+        }
+    }
+}
+
+#[derive(Clap, Clone)]
+struct StatsOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    /// Enable non-standard symbols (* % ^ ~ |) and accented letters (É Ü Ä Ñ CH ...)
+    #[clap(long)]
+    extended: bool,
+
+    /// Letters to use (latin, cyrillic, greek, hebrew, arabic, wabun, korean)
+    #[clap(long, default_value = "latin")]
+    alphabet: Alphabet,
+
+    /// How many of the most expensive characters to list
+    #[clap(long, default_value = "5")]
+    top: usize,
+
+    #[clap(flatten)]
+    files: FileOpts,
+}
+
+impl StatsOpts {
+    fn report(&self, message: &str) -> Result<String> {
+        let options = Options {
+            extended: self.extended,
+            alphabet: self.alphabet,
+            ..Options::default()
+        };
+        let timing = self.speed.timing();
+        let stats = Stats::of(message, &options, &timing)?;
+
+        let mut lines = vec![
+            format!(
+                "characters: {} ({} distinct) in {} word(s)",
+                stats.character_count(),
+                stats.characters.len(),
+                stats.words
+            ),
+            format!("elements: {} dits, {} dahs", stats.dits, stats.dahs),
+            format!(
+                "time: {:.1} s at {} wpm",
+                stats.duration.as_secs_f64(),
+                timing.effective_wpm
+            ),
+        ];
+        if let Some((word, units)) = &stats.longest_word {
+            lines.push(format!("longest word: {} ({} units)", word, units));
+        }
+        if stats.skipped > 0 {
+            lines.push(format!(
+                "skipped: {} character(s) that can't be encoded",
+                stats.skipped
+            ));
+        }
+        if self.top > 0 {
+            lines.push("most expensive:".into());
+        }
+        for character in stats.most_expensive().into_iter().take(self.top) {
+            lines.push(format!(
+                "  {:<6} {:<8} {:>3} units x {}",
+                character.text, character.code, character.units, character.count
+            ));
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+#[derive(Clap, Clone)]
+struct ChartOpts {
+    /// Code table to use (international, american)
+    #[clap(long, default_value = "international")]
+    code: Standard,
+
+    /// Letters to use with the international table (latin, cyrillic, greek, hebrew, arabic,
+    /// wabun, korean)
+    #[clap(long, default_value = "latin")]
+    alphabet: Alphabet,
+
+    /// Include non-standard symbols and accented letters
+    #[clap(long)]
+    extended: bool,
+
+    /// Only list one kind of character (letters, digits, punctuation, prosigns)
+    #[clap(long)]
+    only: Option<chart::Category>,
+
+    /// Layout of the chart (text, markdown, csv)
+    #[clap(long, default_value = "text")]
+    format: chart::Format,
+}
+
+impl ChartOpts {
+    fn chart(&self) -> String {
+        let options = Options {
+            extended: self.extended,
+            alphabet: self.alphabet,
+            ..Options::default()
+        };
+        let entries: Vec<_> = chart::entries(self.code, &options)
+            .into_iter()
+            .filter(|entry| self.only.is_none_or(|only| entry.category == only))
+            .collect();
+        chart::render(&entries, self.format)
+    }
+}
+
+#[derive(Clap, Clone)]
+struct TreeOpts {
+    /// Write Graphviz DOT instead of ASCII art
+    #[clap(long)]
+    dot: bool,
+}
+
+#[derive(Clap, Clone)]
+struct SearchOpts {
+    /// Pattern to match, e.g. `.-..*`; with spaces, it must also match the gaps between letters
+    pattern: search::Pattern,
+
+    /// File of words to search, separated by whitespace, instead of the built-in list
+    #[clap(long, parse(from_os_str))]
+    wordlist: Option<PathBuf>,
+
+    /// Enable non-standard symbols (* % ^ ~ |) and accented letters (É Ü Ä Ñ CH ...)
+    #[clap(long)]
+    extended: bool,
+}
+
+impl SearchOpts {
+    fn search(&self) -> Result<String> {
+        let options = Options {
+            extended: self.extended,
+            ..Options::default()
+        };
+        let wordlist = match &self.wordlist {
+            Some(path) => fs::read_to_string(path).map_err(|e| file_error(path, e))?,
+            None => String::new(),
+        };
+        let words: Vec<&str> = match &self.wordlist {
+            Some(_) => wordlist.split_whitespace().collect(),
+            None => data::WORDS.to_vec(),
+        };
+
+        let found = search::search(&self.pattern, words, &options);
+        let width = found.iter().map(|(word, _)| word.chars().count()).max();
+        let lines: Vec<_> = found
+            .iter()
+            .map(|(word, encoded)| {
+                let padding = width.unwrap_or(0) - word.chars().count();
+                format!("{}{}  {}", word, " ".repeat(padding), encoded)
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+}
+
+#[derive(Clap, Clone)]
+struct PipeOpts {
+    /// Format of the input (text, morse)
+    #[clap(long)]
+    from: pipe::Format,
+
+    /// Format of the output (text, morse)
+    #[clap(long)]
+    to: pipe::Format,
+}
+
+#[derive(Clap, Clone)]
+struct WavOpts {
+    /// Output file; standard output if omitted
+    #[clap(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+
+    /// Audio format: wav, flac, ogg, pcm for raw samples to pipe into aplay or sox, or midi for
+    /// notes following the keying; taken from the output file's extension if omitted, or else WAV
+    #[clap(long)]
+    format: Option<Encoding>,
+
+    /// MIDI note number to play with --format midi; the nearest to --tone if omitted
+    #[clap(long)]
+    note: Option<u8>,
+
+    /// MIDI channel, from 1 to 16
+    #[clap(long, default_value = "1")]
+    channel: u8,
+
+    /// Also write subtitles to this .srt file, showing each word as it finishes
+    #[clap(long, parse(from_os_str))]
+    srt: Option<PathBuf>,
+
+    /// Sample format: u8, s16, s24 or f32; FLAC takes the integer ones, and Ogg ignores it
+    #[clap(long, default_value = "s16")]
+    sample_format: SampleFormat,
+
+    /// Code table to use (international, american)
+    #[clap(long, default_value = "international")]
+    code: Standard,
+
+    /// Click like a landline telegraph sounder as the key closes and opens, instead of sounding
+    /// a tone
+    #[clap(long)]
+    sounder: bool,
+
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    #[clap(flatten)]
+    qrss: QrssOpts,
+
+    #[clap(flatten)]
+    voice: VoiceOpts,
+
+    #[clap(flatten)]
+    conditions: ConditionsOpts,
+}
+
+impl WavOpts {
+    /// Renders standard input.
+    fn write(&self) -> Result<()> {
+        let message = read_stdin()?;
+        let events = match self.code {
+            Standard::International => self.qrss.key(&message, &self.speed)?,
+            Standard::American if self.qrss.qrss.is_some() => {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "--qrss needs --code international",
+                )))
+            }
+            Standard::American => self.speed.key_code(&message, self.code)?,
+        };
+        if let Some(path) = &self.srt {
+            write_subtitles(path, &message, &events)?;
+        }
+        let voice = self.qrss.voice(self.voice.voice()?)?;
+        let encoding = self
+            .format
+            .or_else(|| self.output.as_deref().and_then(Encoding::from_path))
+            .unwrap_or(Encoding::Wav);
+        check_encoding(encoding)?;
+        if encoding == Encoding::Midi {
+            let note = self.note(&voice)?;
+            return self.output(|writer| midi::write(writer, &events, &note));
+        }
+
+        // Only tones in WAV and PCM can be written as they are rendered, and only if there are no
+        // band conditions to mix in, which need the whole recording at once.
+        let streamed = matches!(encoding, Encoding::Wav | Encoding::Pcm)
+            && self.conditions.is_clear()
+            && !self.sounder;
+        let samples = if streamed {
+            None
+        } else {
+            let mut samples = if self.sounder {
+                audio::sounder::render(&events, &voice)
+            } else {
+                voice.render(&events)
+            };
+            self.conditions.apply(&mut samples, &voice)?;
+            Some(samples)
+        };
+        let write = |writer: &mut dyn Write| match &samples {
+            Some(samples) => encode(
+                writer,
+                encoding,
+                samples,
+                voice.sample_rate,
+                self.sample_format,
+            ),
+            None if encoding == Encoding::Pcm => {
+                audio::wav::write_raw(writer, voice.samples(&events), self.sample_format)
+            }
+            None => audio::wav::write_stream(
+                writer,
+                voice.samples(&events),
+                voice.sample_rate,
+                self.sample_format,
+            ),
+        };
+
+        self.output(write)
+    }
+
+    /// Writes to the output file, or standard output.
+    fn output(&self, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> Result<()> {
+        match &self.output {
+            Some(path) => {
+                let file = File::create(path).map_err(|e| file_error(path, e))?;
+                write(&mut BufWriter::new(file)).map_err(|e| file_error(path, e))
+            }
+            None => write(&mut BufWriter::new(io::stdout().lock())).map_err(Error::Io),
+        }
+    }
+
+    /// The MIDI note to play `voice` on.
+    fn note(&self, voice: &audio::Voice) -> Result<midi::Note> {
+        let invalid = |message: &str| {
+            Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                message.to_string(),
+            )))
+        };
+        if !(1..=16).contains(&self.channel) {
+            return invalid("--channel must be from 1 to 16");
+        }
+        if self.note.is_some_and(|note| note > 127) {
+            return invalid("--note must be from 0 to 127");
+        }
+        Ok(midi::Note {
+            key: self.note.unwrap_or_else(|| midi::nearest_key(voice.tone)),
+            channel: self.channel - 1,
+            velocity: (voice.volume * 127.0).round().max(1.0) as u8,
+        })
+    }
+}
+
+/// How generated audio sounds.
+#[derive(Clap, Clone)]
+struct VoiceOpts {
+    /// Tone frequency in Hz
+    #[clap(long, default_value = "600")]
+    tone: f64,
+
+    /// Peak volume, from 0 to 1 for full scale
+    #[clap(long, default_value = "0.8")]
+    volume: f64,
+
+    /// Sample rate in Hz: 8000, 22050, 44100 or 48000
+    #[clap(long, default_value = "44100")]
+    rate: u32,
+
+    /// Rise and fall time of each element in milliseconds, shaped to keep key clicks out; 0 keys
+    /// the tone hard
+    #[clap(long, default_value = "5")]
+    ramp: f64,
+}
+
+impl VoiceOpts {
+    fn voice(&self) -> Result<audio::Voice> {
+        let invalid = |message: &str| {
+            Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                message.to_string(),
+            )))
+        };
+        if !(0.0..=1.0).contains(&self.volume) {
+            return invalid("--volume must be from 0 to 1");
+        }
+        if !audio::SAMPLE_RATES.contains(&self.rate) {
+            return invalid("--rate must be 8000, 22050, 44100 or 48000");
+        }
+        if self.tone <= 0.0 || self.tone >= f64::from(self.rate) / 2.0 {
+            return invalid("--tone must be above 0 and below half the sample rate");
+        }
+        if !(self.ramp >= 0.0 && self.ramp.is_finite()) {
+            return invalid("--ramp must be a number of milliseconds from 0 up");
+        }
+        Ok(audio::Voice {
+            tone: self.tone,
+            volume: self.volume,
+            sample_rate: self.rate,
+            ramp: Duration::from_secs_f64(self.ramp / 1000.0),
+            shift: None,
+        })
+    }
+}
+
+#[derive(Clap, Clone)]
+struct ConditionsOpts {
+    /// Mix in white noise at this signal-to-noise ratio, in dB over the whole audio bandwidth
+    #[clap(long)]
+    snr: Option<f64>,
+
+    /// Fade the signal in and out (QSB), from 0 for steady to 1 for fading out entirely
+    #[clap(long, default_value = "0")]
+    qsb: f64,
+
+    /// Mix in this many other stations (QRM) calling CQ near the tone
+    #[clap(long, default_value = "0")]
+    qrm: usize,
+}
+
+impl ConditionsOpts {
+    /// Whether there is nothing to mix in.
+    fn is_clear(&self) -> bool {
+        self.snr.is_none() && self.qsb == 0.0 && self.qrm == 0
+    }
+
+    /// Applies the band conditions to audio rendered in `voice`.
+    fn apply(&self, samples: &mut [f32], voice: &audio::Voice) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.qsb) {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--qsb must be from 0 to 1",
+            )));
+        }
+        let conditions = audio::conditions::Conditions {
+            snr: self.snr,
+            qsb: self.qsb,
+            qrm: self.qrm,
+        };
+        conditions.apply(samples, voice, &mut Rng::from_time());
+        Ok(())
+    }
+}
+
+#[derive(Clap, Clone)]
+struct DecodeAudioOpts {
+    /// WAV file to decode, or with --iq a raw IQ recording ("-" for standard input)
+    #[clap(parse(from_os_str))]
+    input: PathBuf,
+
+    /// Read raw interleaved IQ samples, as recorded or piped by rtl_sdr, instead of WAV
+    #[clap(long)]
+    iq: bool,
+
+    /// How far the signal is from the IQ centre frequency, in Hz (use --freq-offset=-700 below it)
+    #[clap(long, default_value = "0")]
+    freq_offset: f64,
+
+    /// Sample rate of the IQ samples
+    #[clap(long, default_value = "2048000")]
+    sample_rate: u32,
+
+    /// Sample type of the IQ samples: u8 (rtl_sdr), s16 or f32
+    #[clap(long, default_value = "u8")]
+    iq_format: audio::iq::Format,
+
+    /// Output format (text, json); json scores each character by how clearly it was keyed
+    #[clap(long, default_value = "text")]
+    format: OutputFormat,
+
+    /// Print the tone, signal-to-noise ratio and timing consistency to stderr
+    #[clap(long)]
+    quality: bool,
+
+    /// Tone to listen for in Hz; the loudest is found if omitted
+    #[clap(long)]
+    center: Option<f64>,
+
+    /// Width of the tone filter in Hz; narrower rejects more interference but blurs fast keying
+    #[clap(long, default_value = "200")]
+    bandwidth: f64,
+
+    /// How often to measure the tone, in milliseconds
+    #[clap(long, default_value = "5")]
+    block: u64,
+
+    /// Follow changes in the sender's speed instead of assuming one throughout, printing the speed
+    /// to stderr whenever it changes
+    #[clap(long)]
+    track_speed: bool,
+
+    /// Decode every signal in the recording separately, one line each with its tone; with
+    /// --format json, one report per line
+    #[clap(long)]
+    multi: bool,
+
+    /// Also draw the recording as a PNG spectrogram, with the keying detected underneath, to see
+    /// why a stretch failed to decode
+    #[clap(long, parse(from_os_str))]
+    spectrogram: Option<PathBuf>,
+}
+
+#[derive(Clap, Clone)]
+struct TrainOpts {
+    #[clap(subcommand)]
+    method: TrainMethod,
+}
+
+#[derive(Clap, Clone)]
+enum TrainMethod {
+    /// Copy random groups from a growing set of characters, all sent at full speed
+    Koch(KochOpts),
+}
+
+#[derive(Clap, Clone)]
+struct KochOpts {
+    /// Lesson to start at; lesson 1 covers K and M, and each further lesson adds a character
+    #[clap(long, default_value = "1")]
+    lesson: usize,
+
+    /// Five-character groups sent per round
+    #[clap(long, default_value = "5")]
+    groups: usize,
+
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    #[clap(flatten)]
+    voice: VoiceOpts,
+
+    /// Write each round to this WAV, FLAC or Ogg file instead of playing it
+    #[clap(long, parse(from_os_str))]
+    wav: Option<PathBuf>,
+
+    /// Sample format of the file: u8, s16, s24 or f32; FLAC takes the integer ones, and Ogg
+    /// ignores it
+    #[clap(long, default_value = "s16")]
+    sample_format: SampleFormat,
+
+    /// Seed for the practice text, to repeat a session
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+impl KochOpts {
+    /// Sends rounds of practice text and scores the copy typed back, one line per round, until
+    /// input ends. Lessons advance whenever a round is copied with 90% accuracy.
+    fn train(&self) -> Result<()> {
+        check_sound(self.wav.as_deref())?;
+
+        let mut rng = self.seed.map(Rng::new).unwrap_or_else(Rng::from_time);
+        let mut lesson = self.lesson.clamp(1, koch::LESSONS);
+        let mut lines = io::stdin().lock().lines();
+
+        loop {
+            let text = koch::practice(lesson, self.groups, &mut rng);
+            let events = self.speed.key(&text)?;
+            let voice = self.voice.voice()?;
+            eprintln!("lesson {} ({})", lesson, koch::characters(lesson));
+            sound(
+                voice.render(&events),
+                voice.sample_rate,
+                self.wav.as_deref(),
+                self.sample_format,
+            )?;
+
+            eprint!("copy> ");
+            io::stderr().flush().map_err(Error::Io)?;
+            let copied = match lines.next() {
+                Some(line) => line.map_err(Error::Io)?,
+                None => break,
+            };
+
+            let accuracy = koch::accuracy(&text, &copied);
+            println!("sent:   {}", text);
+            println!("copied: {}", copied.trim().to_uppercase());
+            println!("accuracy {:.0}%", accuracy * 100.0);
+
+            let next = koch::next_lesson(lesson, accuracy);
+            if next > lesson {
+                let added = &koch::characters(next)[lesson + 1..];
+                println!("moving on to lesson {}, which adds {}", next, added);
+            }
+            lesson = next;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clap, Clone)]
+struct QuizOpts {
+    /// Number of questions
+    #[clap(short = 'n', long, default_value = "20")]
+    count: usize,
+
+    /// Characters to ask about
+    #[clap(long, default_value = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789")]
+    charset: String,
+
+    /// Ask whole words instead of single characters
+    #[clap(long)]
+    words: bool,
+
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    #[clap(flatten)]
+    voice: VoiceOpts,
+
+    /// Write each question to this WAV, FLAC or Ogg file instead of playing it
+    #[clap(long, parse(from_os_str))]
+    wav: Option<PathBuf>,
+
+    /// Sample format of the file: u8, s16, s24 or f32; FLAC takes the integer ones, and Ogg
+    /// ignores it
+    #[clap(long, default_value = "s16")]
+    sample_format: SampleFormat,
+
+    /// Seed for the questions, to repeat a session
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+impl QuizOpts {
+    /// Asks questions until `count` have been answered or input ends, then prints a summary.
+    fn quiz(&self) -> Result<()> {
+        check_sound(self.wav.as_deref())?;
+        let charset: Vec<char> = practice::charset(&self.charset)?.chars().collect();
+        let mut rng = self.seed.map(Rng::new).unwrap_or_else(Rng::from_time);
+        let mut lines = io::stdin().lock().lines();
+        let mut score = quiz::Score::default();
+
+        let count = if self.words || !charset.is_empty() {
+            self.count
+        } else {
+            0
+        };
+        for _ in 0..count {
+            let question = if self.words {
+                data::WORDS[(rng.next_f64() * data::WORDS.len() as f64) as usize].to_uppercase()
+            } else {
+                let idx = (rng.next_f64() * charset.len() as f64) as usize;
+                charset[idx.min(charset.len() - 1)].to_string()
+            };
+            let events = self.speed.key(&question)?;
+            let voice = self.voice.voice()?;
+            sound(
+                voice.render(&events),
+                voice.sample_rate,
+                self.wav.as_deref(),
+                self.sample_format,
+            )?;
+
+            eprint!("? ");
+            io::stderr().flush().map_err(Error::Io)?;
+            let asked = Instant::now();
+            let answer = match lines.next() {
+                Some(line) => line.map_err(Error::Io)?,
+                None => break,
+            };
+            score.record(&question, &answer, asked.elapsed());
+
+            if answer.trim().eq_ignore_ascii_case(&question) {
+                println!("right");
+            } else {
+                println!("no, {} ({})", question, morse::encode(&question)?);
+            }
+        }
+
+        println!(
+            "{}/{} right ({:.0}%), {:.1} s per answer",
+            score.correct,
+            score.questions,
+            score.accuracy() * 100.0,
+            score.mean_latency().as_secs_f64()
+        );
+        for (c, tally) in score.weakest() {
+            println!(
+                "{}  {}/{}  {:>3.0}%  {:.1} s",
+                c,
+                tally.correct,
+                tally.asked,
+                tally.accuracy() * 100.0,
+                tally.mean_latency().as_secs_f64()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clap, Clone)]
+struct GradeOpts {
+    /// The text that was sent
+    #[clap(long, parse(from_os_str))]
+    source: PathBuf,
+
+    /// The copy to grade
+    #[clap(long, parse(from_os_str))]
+    copy: PathBuf,
+}
+
+impl GradeOpts {
+    fn grade(&self) -> Result<String> {
+        let source = fs::read_to_string(&self.source).map_err(|e| file_error(&self.source, e))?;
+        let copy = fs::read_to_string(&self.copy).map_err(|e| file_error(&self.copy, e))?;
+        let grade = Grade::of(&source, &copy);
+
+        let mut lines = vec![
+            grade.marked(),
+            format!("accuracy: {:.1}%", grade.accuracy * 100.0),
+        ];
+        let describe = |c: Option<char>, absent: &str| match c {
+            Some(' ') => "space".to_string(),
+            Some(c) => c.to_string(),
+            None => absent.to_string(),
+        };
+        let mut confusions: Vec<_> = grade.confusions().into_iter().collect();
+        confusions.sort_by_key(|&(_, count)| Reverse(count));
+        for ((sent, copied), count) in confusions {
+            lines.push(format!(
+                "{} -> {}: {}",
+                describe(sent, "(nothing)"),
+                describe(copied, "(missed)"),
+                count
+            ));
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+#[derive(Clap, Clone)]
+struct KeyOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    /// How long the keyboard waits before repeating a held key, in milliseconds
+    #[clap(long, default_value = "500")]
+    repeat_delay: u64,
+
+    /// Write `#` for sequences that do not decode, instead of stopping
+    #[clap(long)]
+    lenient: bool,
+
+    /// Save the timing of what was keyed to this file, for `morse fist`
+    #[clap(long, parse(from_os_str))]
+    record: Option<PathBuf>,
+}
+
+#[derive(Clap, Clone)]
+struct PaddlesOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    /// Squeeze behavior: a stops with the current element, b adds one more opposite element
+    #[clap(long, default_value = "b")]
+    mode: IambicMode,
+
+    /// Key used as the dit paddle
+    #[clap(long, default_value = "z")]
+    dit_key: char,
+
+    /// Key used as the dah paddle
+    #[clap(long, default_value = "x")]
+    dah_key: char,
+
+    /// How long the keyboard waits before repeating a held key, in milliseconds
+    #[clap(long, default_value = "500")]
+    repeat_delay: u64,
+
+    /// Write `#` for sequences that do not decode, instead of stopping
+    #[clap(long)]
+    lenient: bool,
+
+    /// Play each element through the sound card as it is sent, at this pitch in Hz
+    #[cfg(feature = "soundcard")]
+    #[clap(long)]
+    sidetone: Option<f64>,
+}
+
+impl PaddlesOpts {
+    fn run(&self) -> Result<()> {
+        #[cfg(feature = "soundcard")]
+        let sidetone = |len: Duration| {
+            if let Some(tone) = self.sidetone {
+                let samples = audio::render(&[KeyEvent::Down(len)], tone);
+                std::thread::spawn(move || audio::device::play(samples, audio::SAMPLE_RATE));
+            }
+        };
+        #[cfg(not(feature = "soundcard"))]
+        let sidetone = |_: Duration| ();
+
+        eprintln!(
+            "keying at {} WPM with {} and {}; Esc to stop",
+            self.speed.wpm, self.dit_key, self.dah_key
+        );
+        keyboard::paddles(
+            self.speed.timing(),
+            lenient_options(self.lenient),
+            self.mode,
+            (self.dit_key, self.dah_key),
+            Duration::from_millis(self.repeat_delay),
+            sidetone,
+            io::stdout(),
+        )
+    }
+}
+
+/// Whether `c` can be encoded with `table`, or else with the `code` standard.
+fn is_encodable(c: char, table: Option<&Table>, code: Standard, options: &Options) -> bool {
+    match (table, code) {
+        (Some(table), _) => table.code(c).is_some(),
+        (None, Standard::International) => morse::is_encodable_with(c, options),
+        (None, Standard::American) => american::is_encodable(c),
+    }
+}
+
+/// Drops everything `table`, or else the `code` standard, cannot encode from a message, along
+/// with any whitespace that would leave empty words behind.
+fn sanitize(message: &str, table: Option<&Table>, code: Standard, options: &Options) -> String {
+    let sanitized = match (table, code) {
+        // The built-in table also keeps prosigns, written between angle brackets.
+        (None, Standard::International) => morse::sanitize(message, options),
+        _ => message
+            .chars()
+            .filter(|&c| c.is_whitespace() || is_encodable(c, table, code, options))
+            .collect(),
+    };
+    sanitized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A warning for each character of `source` that could not be encoded, saying what became of it.
+fn encode_warnings(
+    source: &str,
+    table: Option<&Table>,
+    code: Standard,
+    options: &Options,
+) -> Vec<String> {
+    let action = match options.errors {
+        ErrorPolicy::Replace(_) => "replaced",
+        _ => "dropped",
+    };
+    let mut warnings = Vec::new();
+    for c in source.chars().filter(|&c| !c.is_whitespace()) {
+        // Prosigns are written between angle brackets.
+        if !is_encodable(c, table, code, options) && c != '<' && c != '>' {
+            let warning = format!("{} unencodable character {:?}", action, c);
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    }
+    warnings
+}
+
+fn lenient_options(lenient: bool) -> Options {
+    Options {
+        errors: if lenient {
+            ErrorPolicy::Replace('#')
+        } else {
+            ErrorPolicy::Strict
+        },
+        ..Options::default()
+    }
+}
+
+#[derive(Clap, Clone)]
+struct FistOpts {
+    /// A WAV recording, or timings saved by `morse key --record`; timings are read from stdin if
+    /// omitted
+    #[clap(parse(from_os_str))]
+    input: Option<PathBuf>,
+}
+
+impl FistOpts {
+    fn analyze(&self) -> Result<String> {
+        let bytes = match &self.input {
+            Some(path) => fs::read(path).map_err(|e| file_error(path, e))?,
+            None => read_stdin()?.into_bytes(),
+        };
+        let events = if bytes.starts_with(b"RIFF") {
+            let (samples, sample_rate) = audio::wav::read(&bytes[..]).map_err(Error::Io)?;
+            audio::key_events(&samples, sample_rate)?
+        } else {
+            timing::read_key_events(&String::from_utf8_lossy(&bytes))?
+        };
+        let fist = fist::analyze(&events).ok_or_else(|| {
+            Error::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "too few dits and dahs to judge",
+            ))
+        })?;
+
+        let optional = |ratio: Option<f64>| ratio.map_or("-".into(), |r| format!("{:.2}", r));
+        Ok([
+            format!("speed: {:.1} wpm", fist.wpm),
+            format!("dah:dit ratio: {:.2} (ideal 3)", fist.dah_ratio),
+            format!("element gap ratio: {:.2} (ideal 1)", fist.gap_ratio),
+            format!(
+                "character gap ratio: {} (ideal 3)",
+                optional(fist.char_gap_ratio)
+            ),
+            format!(
+                "word gap ratio: {} (ideal 7)",
+                optional(fist.word_gap_ratio)
+            ),
+            format!(
+                "spread: dits {:.0}%, dahs {:.0}%, gaps {:.0}%",
+                fist.dit_spread * 100.0,
+                fist.dah_spread * 100.0,
+                fist.gap_spread * 100.0
+            ),
+            format!("rhythm score: {:.0}/100", fist.score),
+        ]
+        .join("\n"))
+    }
+}
+
+#[derive(Clap, Clone)]
+struct PracticeOpts {
+    #[clap(subcommand)]
+    kind: PracticeKind,
+}
+
+#[derive(Clap, Clone)]
+enum PracticeKind {
+    /// Groups of five random characters
+    Groups(GroupsOpts),
+    /// Made-up amateur-radio callsigns, weighted towards the busiest regions
+    Callsigns(CallsignsOpts),
+    /// A simulated contact between two stations, each with its own speed and pitch
+    Qso(QsoOpts),
+}
+
+#[derive(Clap, Clone)]
+struct GroupsOpts {
+    /// Number of groups
+    #[clap(short = 'n', long, default_value = "10")]
+    count: usize,
+
+    /// Characters to draw from
+    #[clap(long, default_value = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789")]
+    charset: String,
+
+    /// Seed for the random groups, to get the same ones again
+    #[clap(long)]
+    seed: Option<u64>,
+
+    #[clap(flatten)]
+    render: RenderOpts,
+}
+
+impl GroupsOpts {
+    fn generate(&self) -> Result<String> {
+        let charset = practice::charset(&self.charset)?;
+        let mut rng = self.seed.map(Rng::new).unwrap_or_else(Rng::from_time);
+        Ok(practice::groups(&charset, self.count, &mut rng))
+    }
+}
+
+#[derive(Clap, Clone)]
+struct CallsignsOpts {
+    /// Number of callsigns
+    #[clap(short = 'n', long, default_value = "20")]
+    count: usize,
+
+    /// Seed for the random callsigns, to get the same ones again
+    #[clap(long)]
+    seed: Option<u64>,
+
+    #[clap(flatten)]
+    render: RenderOpts,
+}
+
+#[derive(Clap, Clone)]
+struct QsoOpts {
+    /// Seed for the simulated contact, to get the same one again
+    #[clap(long)]
+    seed: Option<u64>,
+
+    #[clap(flatten)]
+    render: RenderOpts,
+}
+
+impl QsoOpts {
+    fn simulate(&self) -> Result<()> {
+        let mut rng = self.seed.map(Rng::new).unwrap_or_else(Rng::from_time);
+        let qso =
+            practice::Qso::generate(self.render.speed.timing(), self.render.voice.tone, &mut rng);
+
+        match &self.render.wav {
+            Some(path) => {
+                let voice = self.render.voice.voice()?;
+                let mut samples = qso.render(&voice)?;
+                self.render.conditions.apply(&mut samples, &voice)?;
+                write_audio(path, &samples, voice.sample_rate, self.render.sample_format)
+            }
+            None => {
+                for over in &qso.overs {
+                    println!("{}: {}", qso.stations[over.station].call, over.text);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Where generated practice material goes: printed as text, or keyed into a WAV file.
+#[derive(Clap, Clone)]
+struct RenderOpts {
+    /// Write the practice as CW audio to this WAV, FLAC or Ogg file instead of printing it
+    #[clap(long, parse(from_os_str))]
+    wav: Option<PathBuf>,
+
+    /// Sample format of the file: u8, s16, s24 or f32; FLAC takes the integer ones, and Ogg
+    /// ignores it
+    #[clap(long, default_value = "s16")]
+    sample_format: SampleFormat,
+
+    /// With --wav, also write subtitles to this .srt file, showing each word as it finishes
+    #[clap(long, parse(from_os_str))]
+    srt: Option<PathBuf>,
+
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    #[clap(flatten)]
+    voice: VoiceOpts,
+
+    #[clap(flatten)]
+    conditions: ConditionsOpts,
+}
+
+impl RenderOpts {
+    fn output(&self, text: &str) -> Result<()> {
+        let path = match &self.wav {
+            Some(path) => path,
+            None if self.srt.is_some() => {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "--srt needs --wav",
+                )))
+            }
+            None => {
+                println!("{}", text);
+                return Ok(());
+            }
+        };
+
+        let events = self.speed.key(text)?;
+        if let Some(srt) = &self.srt {
+            write_subtitles(srt, text, &events)?;
+        }
+        let voice = self.voice.voice()?;
+        let mut samples = voice.render(&events);
+        self.conditions.apply(&mut samples, &voice)?;
+        write_audio(path, &samples, voice.sample_rate, self.sample_format)
+    }
+}
+
+/// Writes subtitles for `message`, sent as `events`, to `path`.
+fn write_subtitles(path: &Path, message: &str, events: &[KeyEvent]) -> Result<()> {
+    let message = morse::sanitize(message, &Options::default());
+    fs::write(path, srt::write(&message, events)).map_err(|e| file_error(path, e))
+}
+
+/// Fails early if there is nowhere to send sound: no `--wav` file and no sound card support.
+fn check_sound(wav: Option<&Path>) -> Result<()> {
+    if wav.is_none() && !cfg!(feature = "soundcard") {
+        return Err(Error::Audio(
+            "built without sound card support; use --wav to write the audio to a file".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Plays samples through the sound card, blocking until they finish, or writes them to `wav`.
+fn sound(
+    samples: Vec<f32>,
+    sample_rate: u32,
+    wav: Option<&Path>,
+    sample_format: SampleFormat,
+) -> Result<()> {
+    match wav {
+        Some(path) => {
+            write_audio(path, &samples, sample_rate, sample_format)?;
+            eprintln!("wrote {}", path.display());
+        }
+        #[cfg(feature = "soundcard")]
+        None => audio::device::play(samples, sample_rate)?,
+        #[cfg(not(feature = "soundcard"))]
+        None => drop(samples),
+    }
+    Ok(())
+}
+
+/// Writes samples to `path` in whatever encoding its extension calls for, or as WAV.
+fn write_audio(
+    path: &Path,
+    samples: &[f32],
+    sample_rate: u32,
+    sample_format: SampleFormat,
+) -> Result<()> {
+    let encoding = Encoding::from_path(path).unwrap_or(Encoding::Wav);
+    check_encoding(encoding)?;
+    if encoding == Encoding::Midi {
+        return Err(Error::Audio(format!(
+            "{}: only the wav command writes MIDI",
+            path.display()
+        )));
+    }
+    let file = File::create(path).map_err(|e| file_error(path, e))?;
+    encode(
+        BufWriter::new(file),
+        encoding,
+        samples,
+        sample_rate,
+        sample_format,
+    )
+    .map_err(|e| file_error(path, e))
+}
+
+/// Fails early for an encoding this build cannot write.
+fn check_encoding(encoding: Encoding) -> Result<()> {
+    if encoding == Encoding::Ogg && !cfg!(feature = "vorbis") {
+        return Err(Error::Audio(
+            "built without Ogg Vorbis support; write FLAC or WAV instead".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn encode(
+    writer: impl Write,
+    encoding: Encoding,
+    samples: &[f32],
+    sample_rate: u32,
+    sample_format: SampleFormat,
+) -> io::Result<()> {
+    match encoding {
+        Encoding::Wav => audio::wav::write_as(writer, samples, sample_rate, sample_format),
+        Encoding::Flac => audio::flac::write(writer, samples, sample_rate, sample_format),
+        #[cfg(feature = "vorbis")]
+        Encoding::Ogg => audio::vorbis::write(writer, samples, sample_rate),
+        #[cfg(not(feature = "vorbis"))]
+        Encoding::Ogg => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "built without Ogg Vorbis support",
+        )),
+        Encoding::Pcm => audio::wav::write_raw(writer, samples.iter().copied(), sample_format),
+        Encoding::Midi => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "MIDI is written from key events, not samples",
+        )),
+    }
+}
+
+#[derive(Clap, Clone)]
+struct BeepOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    /// Sound the PC speaker (Linux) or the Windows Beep() call instead of the terminal bell,
+    /// which cannot be held for the length of a dah
+    #[clap(long)]
+    speaker: bool,
+
+    /// Tone frequency of the PC speaker in Hz
+    #[clap(long, default_value = "600")]
+    tone: u32,
+}
+
+impl BeepOpts {
+    fn beep(&self) -> Result<()> {
+        let events = self.speed.key(&read_stdin()?)?;
+        if !self.speaker {
+            return output::send(&mut Bell::new(io::stdout()), &events).map_err(Error::Io);
+        }
+
+        #[cfg(any(target_os = "linux", windows))]
+        {
+            let mut speaker = Speaker::open(self.tone).map_err(speaker_error)?;
+            output::send(&mut speaker, &events).map_err(Error::Io)
+        }
+        #[cfg(not(any(target_os = "linux", windows)))]
+        Err(Error::Audio(
+            "no PC speaker support on this platform".into(),
+        ))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn speaker_error(e: io::Error) -> Error {
+    file_error(Path::new(Speaker::DEVICE), e)
+}
+
+#[cfg(windows)]
+fn speaker_error(e: io::Error) -> Error {
+    Error::Io(e)
+}
+
+#[derive(Clap, Clone)]
+struct SendOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    /// Serial port to key through, e.g. /dev/ttyUSB0 or \\.\COM3
+    #[clap(long, parse(from_os_str))]
+    serial: Option<PathBuf>,
+
+    /// The serial port line wired to the key jack: dtr or rts
+    #[clap(long, default_value = "rts")]
+    key_line: KeyLine,
+
+    /// BCM number of the GPIO pin to key
+    #[cfg(feature = "gpio")]
+    #[clap(long)]
+    gpio: Option<u8>,
+
+    /// Address of a Hamlib rigctld to have the rig send the message, e.g. localhost:4532
+    #[cfg(feature = "rig")]
+    #[clap(long)]
+    rig: Option<String>,
+
+    /// Release the line, or drive the pin low, while the key is down
+    #[clap(long)]
+    active_low: bool,
+
+    /// Hand the message to a K1EL Winkeyer on the --serial port, which keys it itself
+    #[clap(long)]
+    winkeyer: bool,
+
+    /// Let the Winkeyer's speed pot set the speed, from 10 to 40 WPM, instead of --wpm
+    #[clap(long)]
+    pot: bool,
+
+    /// Hand the message to a K3NG Arduino keyer on the --serial port, which keys it itself
+    #[clap(long)]
+    k3ng: bool,
+}
+
+impl SendOpts {
+    fn send(&self) -> Result<()> {
+        #[cfg(feature = "gpio")]
+        if let Some(pin) = self.gpio {
+            let events = self.speed.key(&read_stdin()?)?;
+            let mut pin = output::Gpio::open(pin, self.active_low).map_err(Error::Io)?;
+            return output::send(&mut pin, &events).map_err(Error::Io);
+        }
+
+        #[cfg(feature = "rig")]
+        if let Some(address) = &self.rig {
+            return self.rig(address);
+        }
+
+        let path = self.serial.as_ref().ok_or_else(|| {
+            let mut expected = vec!["--serial"];
+            if cfg!(feature = "gpio") {
+                expected.push("--gpio");
+            }
+            if cfg!(feature = "rig") {
+                expected.push("--rig");
+            }
+            let expected = format!("expected {}", expected.join(" or "));
+            Error::Io(io::Error::new(io::ErrorKind::InvalidInput, expected))
+        })?;
+
+        if self.winkeyer {
+            return self.winkeyer(path);
+        }
+        if self.k3ng {
+            return self.k3ng(path);
+        }
+
+        let events = self.speed.key(&read_stdin()?)?;
+        let mut serial = open_serial(path, self.key_line, self.active_low)?;
+        output::send(&mut serial, &events).map_err(Error::Io)
+    }
+
+    #[cfg(feature = "rig")]
+    fn rig(&self, address: &str) -> Result<()> {
+        let message = read_stdin()?;
+        let mut rig = morse::rig::Rig::connect(address).map_err(Error::Io)?;
+        if !rig.set_speed(self.speed.wpm).map_err(Error::Io)? {
+            eprintln!(
+                "the rig cannot be set to {} WPM; sending at its own speed",
+                self.speed.wpm
+            );
+        }
+        rig.send(&message)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    fn winkeyer(&self, path: &Path) -> Result<()> {
+        let message = read_stdin()?;
+        let mut winkeyer =
+            morse::winkeyer::Winkeyer::open_serial(path).map_err(|e| file_error(path, e))?;
+        if self.pot {
+            winkeyer
+                .use_pot(POT_RANGE.0, POT_RANGE.1)
+                .map_err(Error::Io)?;
+            eprintln!(
+                "Winkeyer version {} at {} WPM (speed pot)",
+                winkeyer.version(),
+                winkeyer.speed_pot().map_err(Error::Io)?
+            );
+        } else {
+            winkeyer
+                .set_timing(&self.speed.timing())
+                .map_err(Error::Io)?;
+            eprintln!("Winkeyer version {}", winkeyer.version());
+        }
+
+        let words: Vec<&str> = message.split_whitespace().collect();
+        winkeyer.send(&words.join(" "))?;
+        winkeyer.wait().map_err(Error::Io)?;
+        winkeyer.close().map_err(Error::Io)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    fn k3ng(&self, path: &Path) -> Result<()> {
+        let message = read_stdin()?;
+        let mut keyer = morse::k3ng::K3ng::open_serial(path, self.speed.timing())
+            .map_err(|e| file_error(path, e))?;
+        keyer.send(&message)?;
+        keyer.wait();
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn winkeyer(&self, path: &Path) -> Result<()> {
+        Err(keyer_unsupported("Winkeyer", path))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn k3ng(&self, path: &Path) -> Result<()> {
+        Err(keyer_unsupported("K3NG keyer", path))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn keyer_unsupported(keyer: &str, path: &Path) -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("no {} support on this platform ({})", keyer, path.display()),
+    ))
+}
+
+fn open_serial(path: &Path, line: KeyLine, active_low: bool) -> Result<output::Serial> {
+    output::Serial::open(path, line, active_low).map_err(|e| file_error(path, e))
+}
+
+#[derive(Clap, Clone)]
+struct BeaconOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    #[clap(flatten)]
+    qrss: QrssOpts,
+
+    /// Time from the start of one transmission to the start of the next, e.g. 90s or 10m
+    #[clap(long, default_value = "60s")]
+    every: Interval,
+
+    /// Where to send the beacon: gpio, audio or serial
+    #[clap(long)]
+    output: beacon::Output,
+
+    /// Largest percentage of the time to spend transmitting, lengthening the interval if need be
+    #[clap(long)]
+    duty_cycle: Option<u32>,
+
+    /// Serial port to key through, for --output serial
+    #[clap(long, parse(from_os_str))]
+    serial: Option<PathBuf>,
+
+    /// The serial port line wired to the key jack: dtr or rts
+    #[clap(long, default_value = "rts")]
+    key_line: KeyLine,
+
+    /// BCM number of the GPIO pin to key, for --output gpio
+    #[cfg(feature = "gpio")]
+    #[clap(long)]
+    gpio: Option<u8>,
+
+    /// Release the line, or drive the pin low, while the key is down
+    #[clap(long)]
+    active_low: bool,
+
+    #[clap(flatten)]
+    voice: VoiceOpts,
+
+    /// The message to repeat
+    message: String,
+}
+
+impl BeaconOpts {
+    fn run(&self) -> Result<()> {
+        let duty_cycle = match self.duty_cycle {
+            Some(percent) if percent == 0 || percent > 100 => {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "--duty-cycle must be from 1 to 100",
+                )))
+            }
+            percent => percent.map(|percent| f64::from(percent) / 100.0),
+        };
+        let schedule = Schedule {
+            every: self.every.0,
+            duty_cycle,
+        };
+        if self.qrss.fsk.is_some() && self.output != beacon::Output::Audio {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--fsk needs --output audio",
+            )));
+        }
+        let events = self.qrss.key(&self.message, &self.speed)?;
+        let missing = |output: &str, flag: &str| {
+            Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("--output {} needs {}", output, flag),
+            ))
+        };
+
+        match self.output {
+            beacon::Output::Serial => {
+                let path = self
+                    .serial
+                    .as_ref()
+                    .ok_or_else(|| missing("serial", "--serial"))?;
+                let mut serial = open_serial(path, self.key_line, self.active_low)?;
+                repeat(&schedule, &events, || {
+                    output::send_while(&mut serial, &events, || !interrupted()).map_err(Error::Io)
+                })
+            }
+
+            #[cfg(feature = "gpio")]
+            beacon::Output::Gpio => {
+                let pin = self.gpio.ok_or_else(|| missing("gpio", "--gpio"))?;
+                let mut pin = output::Gpio::open(pin, self.active_low).map_err(Error::Io)?;
+                repeat(&schedule, &events, || {
+                    output::send_while(&mut pin, &events, || !interrupted()).map_err(Error::Io)
+                })
+            }
+
+            #[cfg(feature = "soundcard")]
+            beacon::Output::Audio => {
+                let voice = self.qrss.voice(self.voice.voice()?)?;
+                let samples = voice.render(&events);
+                repeat(&schedule, &events, || {
+                    audio::device::play(samples.clone(), voice.sample_rate)
+                })
+            }
+
+            #[allow(unreachable_patterns)]
+            output => {
+                let (output, feature) = match output {
+                    beacon::Output::Gpio => ("gpio", "gpio"),
+                    _ => ("audio", "soundcard"),
+                };
+                Err(Error::Io(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "--output {} needs morse built with the {} feature",
+                        output, feature
+                    ),
+                )))
+            }
+        }
+    }
+}
+
+/// Set by Ctrl-C once `catch_interrupt` has been called.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Has Ctrl-C set `INTERRUPTED` instead of ending the process, so that a transmission can be cut
+/// short with the key left up.
+#[cfg(unix)]
+fn catch_interrupt() {
+    use std::os::raw::c_int;
+
+    extern "C" {
+        fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+    }
+
+    extern "C" fn handler(_: c_int) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+
+    const SIGINT: c_int = 2;
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+        signal(SIGINT, handler);
+    }
+}
+
+#[cfg(windows)]
+fn catch_interrupt() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: extern "system" fn(u32) -> i32, add: i32) -> i32;
+    }
+
+    extern "system" fn handler(_: u32) -> i32 {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        1
+    }
+
+    // SAFETY: the handler only stores to an atomic, and runs on a thread of its own.
+    unsafe {
+        SetConsoleCtrlHandler(handler, 1);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn catch_interrupt() {}
+
+/// Calls `transmit` on `schedule` until Ctrl-C is pressed. `events` are what it sends, and give
+/// the length of a transmission for the duty cycle.
+fn repeat(
+    schedule: &Schedule,
+    events: &[KeyEvent],
+    mut transmit: impl FnMut() -> Result<()>,
+) -> Result<()> {
+    /// How often to check for Ctrl-C between transmissions.
+    const TICK: Duration = Duration::from_millis(100);
+
+    catch_interrupt();
+    let length = events.iter().map(|event| event.duration()).sum();
+    let period = schedule.period(length);
+    if period > schedule.every {
+        eprintln!(
+            "transmitting every {:.0}s to keep within the duty cycle",
+            period.as_secs_f64()
+        );
+    }
+
+    let mut start = Instant::now();
+    while !interrupted() {
+        transmit()?;
+        start += period;
+        while !interrupted() {
+            match start.checked_duration_since(Instant::now()) {
+                Some(wait) if !wait.is_zero() => thread::sleep(wait.min(TICK)),
+                _ => break,
+            }
+        }
+    }
+    eprintln!("beacon stopped");
+    Ok(())
+}
+
+#[derive(Clap, Clone)]
+struct ServeOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    /// Speak the UDP protocol of cwdaemon
+    #[clap(long)]
+    cwdaemon: bool,
+
+    /// Serve the HTTP API (/encode, /decode, /wav and the /stream WebSocket) on this address, e.g. 127.0.0.1:8080
+    #[clap(long)]
+    http: Option<SocketAddr>,
+
+    /// Address to listen on; 0.0.0.0 accepts requests from other machines
+    #[clap(long, default_value = "127.0.0.1")]
+    address: IpAddr,
+
+    /// UDP port to listen on
+    #[clap(long, default_value = "6789")]
+    port: u16,
+
+    /// Serial port to key through instead of flashing the terminal, e.g. /dev/ttyUSB0
+    #[clap(long, parse(from_os_str))]
+    serial: Option<PathBuf>,
+
+    /// The serial port line wired to the key jack: dtr or rts
+    #[clap(long, default_value = "rts")]
+    key_line: KeyLine,
+
+    /// Release the serial port line while the key is down
+    #[clap(long)]
+    active_low: bool,
+
+    /// Sound the PC speaker instead of flashing the terminal
+    #[clap(long)]
+    speaker: bool,
+
+    /// Tone frequency of the PC speaker in Hz, until a client changes it
+    #[clap(long, default_value = "600")]
+    tone: u32,
+}
+
+impl ServeOpts {
+    fn serve(&self) -> Result<()> {
+        if let Some(address) = self.http {
+            return http::serve(address).map_err(Error::Io);
+        }
+        if !self.cwdaemon {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "expected --cwdaemon or --http",
+            )));
+        }
+
+        let socket = UdpSocket::bind((self.address, self.port)).map_err(Error::Io)?;
+        eprintln!(
+            "listening for cwdaemon requests on {}",
+            socket.local_addr().map_err(Error::Io)?
+        );
+
+        let timing = self.speed.timing();
+        let result = if let Some(path) = &self.serial {
+            let mut serial = open_serial(path, self.key_line, self.active_low)?;
+            cwdaemon::serve(socket, &mut serial, timing, self.tone)
+        } else if self.speaker {
+            #[cfg(any(target_os = "linux", windows))]
+            {
+                let mut speaker = Speaker::open(self.tone).map_err(speaker_error)?;
+                cwdaemon::serve(socket, &mut speaker, timing, self.tone)
+            }
+            #[cfg(not(any(target_os = "linux", windows)))]
+            return Err(Error::Audio(
+                "no PC speaker support on this platform".into(),
+            ));
+        } else {
+            let mut flash = Flash::new(io::stdout(), FlashStyle::Invert);
+            cwdaemon::serve(socket, &mut flash, timing, self.tone)
+        };
+        result.map_err(Error::Io)
+    }
+}
+
+#[derive(Clap, Clone)]
+struct FldigiOpts {
+    /// Address of fldigi's XML-RPC server
+    #[clap(long, default_value = "127.0.0.1:7362")]
+    address: String,
+
+    /// Speed for fldigi to send at, instead of its own setting
+    #[clap(long)]
+    wpm: Option<u32>,
+
+    /// Print what fldigi decodes as it arrives, instead of sending standard input
+    #[clap(long)]
+    receive: bool,
+}
+
+impl FldigiOpts {
+    /// How often to ask fldigi for newly decoded text.
+    const POLL: Duration = Duration::from_millis(250);
 
-type Code = &'static str;
-type Result<T, E = Error> = std::result::Result<T, E>;
-
-mod data {
-    pub static ENCODED_SEQUENCES: &[&str] = &[
-        ".-", "-...", "-.-.", "-..", ".", "..-.", "--.", "....", "..", ".---", "-.-", ".-..", "--",
-        "-.", "---", ".--.", "--.-", ".-.", "...", "-", "..-", "...-", ".--", "-..-", "-.--",
-        "--..", "-----", ".----", "..---", "...--", "....-", ".....", "-....", "--...", "---..",
-        "----.",
-    ];
-
-    pub static DECODING_ARRAY: &[Option<u8>] = &[
-        None,
-        Some(b'E'),
-        Some(b'T'),
-        Some(b'I'),
-        Some(b'A'),
-        Some(b'N'),
-        Some(b'M'),
-        Some(b'S'),
-        Some(b'U'),
-        Some(b'R'),
-        Some(b'W'),
-        Some(b'D'),
-        Some(b'K'),
-        Some(b'G'),
-        Some(b'O'),
-        Some(b'H'),
-        Some(b'V'),
-        Some(b'F'),
-        None,
-        Some(b'L'),
-        None,
-        Some(b'P'),
-        Some(b'J'),
-        Some(b'B'),
-        Some(b'X'),
-        Some(b'C'),
-        Some(b'Y'),
-        Some(b'Z'),
-        Some(b'Q'),
-        None,
-        None,
-        Some(b'5'),
-        Some(b'4'),
-        None,
-        Some(b'3'),
-        None,
-        None,
-        None,
-        Some(b'2'),
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        Some(b'1'),
-        Some(b'6'),
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        None,
-        Some(b'7'),
-        None,
-        None,
-        None,
-        Some(b'8'),
-        None,
-        Some(b'9'),
-        Some(b'0'),
-    ];
+    fn run(&self) -> Result<()> {
+        let mut fldigi = Fldigi::connect(&self.address).map_err(Error::Io)?;
+        if let Some(wpm) = self.wpm {
+            fldigi.set_wpm(wpm).map_err(Error::Io)?;
+        }
+
+        if !self.receive {
+            return fldigi.send(&read_stdin()?);
+        }
+
+        let mut stdout = io::stdout();
+        loop {
+            let text = fldigi.receive().map_err(Error::Io)?;
+            if !text.is_empty() {
+                write!(stdout, "{}", text)
+                    .and_then(|_| stdout.flush())
+                    .map_err(Error::Io)?;
+            }
+            std::thread::sleep(Self::POLL);
+        }
+    }
 }
 
 #[derive(Clap, Clone)]
-enum Opts {
-    Encode,
-    Decode,
+struct MqttOpts {
+    /// Address of the broker
+    #[clap(long, default_value = "127.0.0.1:1883")]
+    broker: String,
+
+    /// Client identifier to connect as
+    #[clap(long, default_value = "morse")]
+    client_id: String,
+
+    /// Topic to take messages from; may have wildcards
+    #[clap(long)]
+    topic: String,
+
+    /// Topic to publish results on
+    #[clap(long)]
+    output_topic: String,
+
+    /// Decode messages instead of encoding them
+    #[clap(short, long)]
+    decode: bool,
 }
 
-#[derive(Debug)]
-enum Error {
-    Encode(char),
-    Decode(String),
-    Io(io::Error),
+impl MqttOpts {
+    fn run(&self) -> Result<()> {
+        let mut client = mqtt::Client::connect(&self.broker, &self.client_id)
+            .and_then(|mut client| client.subscribe(&self.topic).map(|_| client))
+            .map_err(Error::Io)?;
+        eprintln!("bridging {} to {}", self.topic, self.output_topic);
+
+        loop {
+            let message = match client.receive() {
+                Ok(message) => message,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    client.ping().map_err(Error::Io)?;
+                    continue;
+                }
+                Err(e) => return Err(Error::Io(e)),
+            };
+
+            let text = String::from_utf8_lossy(&message.payload);
+            let result = if self.decode {
+                morse::decode(text.trim())
+            } else {
+                morse::encode(&morse::sanitize(&text, &Options::default()))
+            };
+            match result {
+                Ok(output) => client
+                    .publish(&self.output_topic, output.as_bytes())
+                    .map_err(Error::Io)?,
+                Err(e) => eprintln!("{}: {}", message.topic, e),
+            }
+        }
+    }
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Error::Encode(u) => write!(f, "unable to encode value: {:?}", u),
-            Error::Decode(code) => write!(f, "unable to decode sequence: {:?}", code),
-            Error::Io(e) => e.fmt(f),
+impl DecodeAudioOpts {
+    fn detector(&self) -> Result<audio::detect::Detector> {
+        if self.bandwidth <= 0.0 || self.block == 0 {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--bandwidth and --block must be greater than 0",
+            )));
+        }
+        Ok(audio::detect::Detector {
+            center: self.center,
+            bandwidth: self.bandwidth,
+            block: Duration::from_millis(self.block),
+        })
+    }
+
+    /// Reads the recording as mono audio, demodulating it first if it is IQ, and draws its
+    /// spectrogram if asked to before anything has a chance to fail to decode.
+    fn read(&self) -> Result<(Vec<f32>, u32)> {
+        let (samples, sample_rate) = self.read_samples()?;
+        if let Some(path) = &self.spectrogram {
+            let image = audio::spectrogram::render(&samples, sample_rate, &self.detector()?);
+            let file = File::create(path).map_err(|e| file_error(path, e))?;
+            image::png::write(BufWriter::new(file), &image).map_err(|e| file_error(path, e))?;
+        }
+        Ok((samples, sample_rate))
+    }
+
+    fn read_samples(&self) -> Result<(Vec<f32>, u32)> {
+        let path = &self.input;
+        if !self.iq {
+            let file = File::open(path).map_err(|e| file_error(path, e))?;
+            return audio::wav::read(BufReader::new(file)).map_err(|e| file_error(path, e));
+        }
+
+        let read =
+            |reader| audio::iq::read(reader, self.iq_format, self.sample_rate, self.freq_offset);
+        if path == Path::new("-") {
+            read(Box::new(io::stdin().lock()) as Box<dyn Read>).map_err(Error::Io)
+        } else {
+            let file = File::open(path).map_err(|e| file_error(path, e))?;
+            read(Box::new(BufReader::new(file))).map_err(|e| file_error(path, e))
         }
     }
 }
 
-impl std::error::Error for Error {}
+#[derive(Clap, Clone)]
+struct CodegenOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
 
-fn main() {
-    let opts = Opts::parse();
-    if let Err(e) = run(&opts) {
-        eprintln!("{}", e);
-        process::exit(1);
+    /// What to generate: arduino (a sketch) or c (an array of times in milliseconds)
+    #[clap(long, default_value = "arduino")]
+    target: codegen::Target,
+
+    /// Pin of the LED the sketch blinks, by number or name
+    #[clap(long, default_value = "LED_BUILTIN")]
+    pin: String,
+}
+
+impl CodegenOpts {
+    fn generate(&self) -> Result<String> {
+        let pin = &self.pin;
+        if pin.is_empty() || !pin.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("--pin {:?} is not a pin number or name", pin),
+            )));
+        }
+
+        let message = read_stdin()?;
+        let events = self.speed.key(&message)?;
+        Ok(match self.target {
+            codegen::Target::Arduino => {
+                codegen::arduino(&events, &message, pin, self.speed.timing().word_gap())
+            }
+            codegen::Target::C => codegen::c(&events, &message),
+        })
     }
 }
 
-fn run(opts: &Opts) -> Result<()> {
-    let mut buf = String::new();
-    let message = io::stdin()
-        .read_to_string(&mut buf)
-        .map(|_| buf)
-        .map_err(Error::Io)?;
+#[derive(Clap, Clone)]
+struct FlashOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
 
-    match opts {
-        Opts::Encode => {
-            let message: String = message
-                .trim()
-                .bytes()
-                .filter(|&u| u == b' ' || u.is_ascii_alphanumeric())
-                .map(|u| u as char)
-                .collect();
-            println!("{}", encode_message(&message)?);
+    /// How to show the key being down: invert (the whole screen) or block (a block character)
+    #[clap(long, default_value = "invert")]
+    style: FlashStyle,
+}
+
+#[cfg(feature = "soundcard")]
+#[derive(Clap, Clone)]
+struct PlayOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    #[clap(flatten)]
+    voice: VoiceOpts,
+}
+
+#[cfg(feature = "soundcard")]
+#[derive(Clap, Clone)]
+struct ListenOpts {
+    /// Tone frequency in Hz; detected from the first couple of seconds if omitted
+    #[clap(long)]
+    tone: Option<f64>,
+}
+
+#[cfg(feature = "soundcard")]
+#[derive(Clap, Clone)]
+struct ChatOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    #[clap(flatten)]
+    voice: VoiceOpts,
+
+    /// Tone frequency of the other side in Hz, at least 50 Hz from --tone as both are heard at
+    /// once; detected from the first couple of seconds of their signal if omitted
+    #[clap(long)]
+    listen: Option<f64>,
+}
+
+#[cfg(feature = "tui")]
+#[derive(Clap, Clone)]
+struct TuiOpts {
+    #[clap(flatten)]
+    speed: SpeedOpts,
+
+    #[clap(flatten)]
+    voice: VoiceOpts,
+
+    /// Koch lesson to start at
+    #[clap(long, default_value = "1")]
+    lesson: usize,
+}
+
+/// Where a text subcommand reads its message and writes its result.
+#[derive(Clap, Clone)]
+struct FileOpts {
+    /// The message itself; the words are joined with spaces. Read from --input or stdin if
+    /// omitted. Morse starting with a dash is taken for an option unless it comes after `--`, as
+    /// in `morse decode -- -.-. --.-`
+    message: Vec<String>,
+
+    /// Read the message from this file instead of stdin
+    #[clap(short, long, parse(from_os_str))]
+    input: Option<PathBuf>,
+
+    /// Write the result to this file instead of stdout
+    #[clap(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+
+    /// Convert each line as soon as it is read, for use as a filter in a live pipeline
+    #[clap(long)]
+    line_buffered: bool,
+}
+
+impl FileOpts {
+    /// Whether there is no message to read other than what a person types at a terminal.
+    fn is_interactive(&self) -> bool {
+        self.message.is_empty() && self.input.is_none() && io::stdin().is_terminal()
+    }
+
+    fn read(&self) -> Result<String> {
+        self.read_bytes().and_then(|bytes| self.text(bytes))
+    }
+
+    /// Reads the input as text, or unpacks it if it is a packed message, told by its header.
+    fn read_packed(&self) -> Result<String> {
+        let bytes = self.read_bytes()?;
+        if packed::is_packed(&bytes) {
+            packed::unpack(&bytes).map_err(|e| self.input_error(e))
+        } else {
+            self.text(bytes)
+        }
+    }
+
+    fn read_bytes(&self) -> Result<Vec<u8>> {
+        if !self.message.is_empty() {
+            return Ok(self.message.join(" ").into_bytes());
         }
 
-        Opts::Decode => {
-            println!("{}", decode_message(message.trim())?);
+        match &self.input {
+            Some(path) => fs::read(path).map_err(|e| file_error(path, e)),
+            None => {
+                let mut bytes = Vec::new();
+                io::stdin()
+                    .read_to_end(&mut bytes)
+                    .map(|_| bytes)
+                    .map_err(Error::Io)
+            }
         }
     }
 
-    Ok(())
-}
+    /// The input as text, which it must be.
+    fn text(&self, bytes: Vec<u8>) -> Result<String> {
+        String::from_utf8(bytes).map_err(|e| {
+            self.input_error(io::Error::new(io::ErrorKind::InvalidData, e.utf8_error()))
+        })
+    }
+
+    fn input_error(&self, e: io::Error) -> Error {
+        match &self.input {
+            Some(path) => file_error(path, e),
+            None => Error::Io(e),
+        }
+    }
+
+    /// Writes `bytes` as they are, as the whole result, unless that would put them on a terminal.
+    fn write_binary(&self, bytes: &[u8]) -> Result<()> {
+        if self.output.is_none() && io::stdout().is_terminal() {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "refusing to write binary to a terminal; use --output or a pipe",
+            )));
+        }
+        let mut writer = self.writer()?;
+        let result = writer.write_all(bytes).and_then(|_| writer.flush());
+        match &self.output {
+            Some(path) => result.map_err(|e| file_error(path, e)),
+            None => result.map_err(Error::Io),
+        }
+    }
+
+    fn reader(&self) -> Result<Box<dyn BufRead>> {
+        if !self.message.is_empty() {
+            return Ok(Box::new(io::Cursor::new(self.message.join(" "))));
+        }
 
-fn encode_message(message: &str) -> Result<String> {
-    let mut buf = String::with_capacity(message.len() * 4);
-    let mut bytes = message.bytes();
+        match &self.input {
+            Some(path) => {
+                let file = File::open(path).map_err(|e| file_error(path, e))?;
+                Ok(Box::new(BufReader::new(file)))
+            }
+            None => Ok(Box::new(BufReader::new(io::stdin()))),
+        }
+    }
+
+    fn writer(&self) -> Result<Box<dyn Write>> {
+        match &self.output {
+            Some(path) => {
+                let file = File::create(path).map_err(|e| file_error(path, e))?;
+                Ok(Box::new(BufWriter::new(file)))
+            }
+            None => Ok(Box::new(BufWriter::new(io::stdout()))),
+        }
+    }
 
-    if let Some(u) = bytes.next() {
-        buf.push_str(encode_byte(u)?);
+    /// Runs the whole input through `convert` and writes the result as a single line, or, with
+    /// --line-buffered, converts and flushes each line as soon as it has been read.
+    fn filter(&self, convert: impl FnMut(&str) -> Result<String>) -> Result<()> {
+        self.filter_with(Self::read, convert)
     }
 
-    for u in bytes {
-        match u {
-            b' ' => buf.push_str(" /"),
-            u => {
-                buf.push(' ');
-                buf.push_str(encode_byte(u)?);
+    /// Like `filter`, but reads the whole input with `read` when it is not line buffered.
+    fn filter_with(
+        &self,
+        read: impl FnOnce(&Self) -> Result<String>,
+        mut convert: impl FnMut(&str) -> Result<String>,
+    ) -> Result<()> {
+        let mut writer = self.writer()?;
+        let mut write_line = |text: &str| {
+            let result = writeln!(writer, "{}", text).and_then(|_| writer.flush());
+            match &self.output {
+                Some(path) => result.map_err(|e| file_error(path, e)),
+                None => result.map_err(Error::Io),
+            }
+        };
+
+        if !self.line_buffered {
+            return write_line(&convert(&read(self)?)?);
+        }
+
+        let mut errors = Vec::new();
+        for (idx, line) in self.reader()?.lines().enumerate() {
+            let line = match &self.input {
+                Some(path) => line.map_err(|e| file_error(path, e))?,
+                None => line.map_err(Error::Io)?,
+            };
+            match convert(&line) {
+                Ok(converted) => write_line(&converted)?,
+                // Collected errors are reported once every line has been read.
+                Err(Error::Many(many)) => {
+                    errors.extend(many.into_iter().map(|e| on_line(e, idx + 1)))
+                }
+                Err(e) => return Err(on_line(e, idx + 1)),
             }
         }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Many(errors))
+        }
     }
+}
+
+/// Keying speed for the audio subcommands.
+#[derive(Clap, Clone)]
+struct SpeedOpts {
+    /// Speed at which individual characters are sent, in words per minute
+    #[clap(long, default_value = "20")]
+    wpm: u32,
 
-    Ok(buf)
+    /// Overall speed, reached by stretching the gaps between characters and words while keeping
+    /// the characters themselves at --wpm
+    #[clap(long)]
+    farnsworth: Option<u32>,
+
+    /// Length of a dah in dits, for a heavier or lighter fist than the standard 3
+    #[clap(long, default_value = "3")]
+    weight: Weight,
+
+    /// Send like a human fist, with a wandering pace and uneven elements and spacing, from 0 for
+    /// machine-perfect to 1 for sloppy
+    #[clap(long, default_value = "0")]
+    humanize: f64,
 }
 
-fn decode_message(message: &str) -> Result<String> {
-    let mut buf = String::new();
-    let mut words = message.split('/');
+impl SpeedOpts {
+    fn timing(&self) -> Timing {
+        Timing::farnsworth(self.wpm, self.farnsworth.unwrap_or(self.wpm)).weighted(self.weight.0)
+    }
+
+    /// Keys `message` at this speed, humanized if asked.
+    fn key(&self, message: &str) -> Result<Vec<KeyEvent>> {
+        self.key_code(message, Standard::International)
+    }
+
+    /// Keys `message` in the `code` table at this speed, humanized if asked.
+    fn key_code(&self, message: &str, code: Standard) -> Result<Vec<KeyEvent>> {
+        if !(0.0..=1.0).contains(&self.humanize) {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--humanize must be from 0 to 1",
+            )));
+        }
+        let options = Options::default();
+        let mut jitter = Jitter::human(self.humanize, Rng::from_time());
+        let jitter = Some(&mut jitter).filter(|_| self.humanize > 0.0);
+        Ok(match code {
+            Standard::International => {
+                let encoded = morse::encode(&morse::sanitize(message, &options))?;
+                timing::to_key_events(&encoded, &self.timing(), jitter)
+            }
+            Standard::American => {
+                let encoded = american::encode_with(message, &options)?;
+                american::to_key_events(&encoded, &self.timing(), jitter)
+            }
+        })
+    }
+}
+
+/// Very slow CW for QRSS, read off a spectrogram rather than copied by ear.
+#[derive(Clap, Clone)]
+struct QrssOpts {
+    /// Send QRSS with dits this many seconds long, instead of at --wpm
+    #[clap(long)]
+    qrss: Option<f64>,
+
+    /// Key audio by shifting the tone up this many Hz instead of turning it off (FSK-CW)
+    #[clap(long)]
+    fsk: Option<f64>,
+}
 
-    if let Some(word) = words.next() {
-        decode_word_into(word, &mut buf)?;
+impl QrssOpts {
+    /// Keys `message` at the QRSS speed if there is one, and at `speed` otherwise.
+    fn key(&self, message: &str, speed: &SpeedOpts) -> Result<Vec<KeyEvent>> {
+        let dit = match self.qrss {
+            Some(dit) if dit > 0.0 && dit.is_finite() => Duration::from_secs_f64(dit),
+            Some(_) => {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "--qrss must be a number of seconds above 0",
+                )))
+            }
+            None => return speed.key(message),
+        };
+        let encoded = morse::encode(&morse::sanitize(message, &Options::default()))?;
+        Ok(qrss::key_events(&encoded, dit, speed.weight.0))
     }
 
-    for word in words {
-        buf.push(' ');
-        decode_word_into(word, &mut buf)?;
+    /// `voice`, keyed by frequency if asked.
+    fn voice(&self, voice: audio::Voice) -> Result<audio::Voice> {
+        if self
+            .fsk
+            .is_some_and(|shift| !(shift > 0.0 && shift.is_finite()))
+        {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--fsk must be a number of Hz above 0",
+            )));
+        }
+        Ok(audio::Voice {
+            shift: self.fsk,
+            ..voice
+        })
     }
+}
+
+/// Notation flags. Each falls back to an environment variable (e.g. `MORSE_WORD_SEP`) and then
+/// to the canonical notation.
+#[derive(Clap, Clone)]
+struct NotationOpts {
+    /// Glyph used for dots, e.g. `·` [env: MORSE_DOT]
+    #[clap(long, visible_alias = "dot-char")]
+    dot: Option<String>,
+
+    /// Glyph used for dashes, e.g. `−` [env: MORSE_DASH]
+    #[clap(long, visible_alias = "dash-char")]
+    dash: Option<String>,
+
+    /// Separator between characters [env: MORSE_CHAR_SEP]
+    #[clap(long)]
+    char_sep: Option<String>,
+
+    /// Separator between words [env: MORSE_WORD_SEP]
+    #[clap(long)]
+    word_sep: Option<String>,
 
-    Ok(buf)
+    /// Notation to start from: canonical (. and -) or unicode (· and – with no-break spaces,
+    /// for publication); the flags above still override it
+    #[clap(long, default_value = "canonical")]
+    notation: Notation,
+
+    /// Shorthand for --notation unicode
+    #[clap(long)]
+    unicode: bool,
 }
 
-#[inline]
-fn encode_byte(u: u8) -> Result<Code> {
-    static NUMERIC_RANGE: RangeInclusive<u8> = b'0'..=b'9';
-    match u {
-        u if u.is_ascii_alphabetic() => {
-            Ok(data::ENCODED_SEQUENCES[(u.to_ascii_uppercase() - b'A') as usize])
+/// The options of [`NotationOpts`], as named in errors.
+const NOTATION_FLAGS: &str = "--dot, --dash, --char-sep, --word-sep, --notation and --unicode";
+
+impl NotationOpts {
+    /// Whether any notation option was given.
+    fn is_given(&self) -> bool {
+        self.dot.is_some()
+            || self.dash.is_some()
+            || self.char_sep.is_some()
+            || self.word_sep.is_some()
+            || self.notation != Notation::default()
+            || self.unicode
+    }
+
+    fn resolve(&self, env: impl Fn(&str) -> Option<String>) -> Notation {
+        let default = if self.unicode {
+            Notation::unicode()
+        } else {
+            self.notation.clone()
+        };
+        let pick = |flag: &Option<String>, var: &str, default: String| {
+            flag.clone()
+                .or_else(|| env(var))
+                .filter(|value| !value.is_empty())
+                .unwrap_or(default)
+        };
+
+        Notation {
+            dot: pick(&self.dot, "MORSE_DOT", default.dot),
+            dash: pick(&self.dash, "MORSE_DASH", default.dash),
+            char_sep: pick(&self.char_sep, "MORSE_CHAR_SEP", default.char_sep),
+            word_sep: pick(&self.word_sep, "MORSE_WORD_SEP", default.word_sep),
         }
-        u if NUMERIC_RANGE.contains(&u) => Ok(data::ENCODED_SEQUENCES[(u - b'0' + 26) as usize]),
-        _ => Err(Error::Encode(u as char)),
     }
 }
 
-fn decode_word_into(word: &str, buf: &mut String) -> Result<()> {
-    let mut characters = word.split_whitespace();
+/// The error for options the interactive prompt can't honor.
+fn prompt_error(flags: &str) -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+            "{} can't be used at the interactive prompt; give a message as arguments, \
+             with --input or through a pipe",
+            flags
+        ),
+    ))
+}
 
-    if let Some(character) = characters.next() {
-        buf.push(decode_character(character)? as char);
+fn main() {
+    let opts = Opts::parse();
+    if let Err(e) = run(&opts) {
+        eprintln!("{}", e);
+        process::exit(1);
     }
+}
+
+fn run(opts: &Opts) -> Result<()> {
+    match opts {
+        Opts::Encode(opts) if opts.files.is_interactive() => {
+            if let Some(flags) = opts.prompt_conflict() {
+                return Err(prompt_error(flags));
+            }
+            let options = Options {
+                extended: opts.extended,
+                alphabet: opts.alphabet,
+                ..Options::default()
+            };
+            repl::Repl::new(repl::Mode::Encode, opts.timing(), options)
+                .run(io::stdin().lock(), io::stdout())
+                .map_err(Error::Io)?;
+        }
+
+        Opts::Decode(opts) if opts.files.is_interactive() => {
+            if let Some(flags) = opts.prompt_conflict() {
+                return Err(prompt_error(flags));
+            }
+            let options = Options {
+                extended: opts.extended,
+                prosigns: opts.prosigns,
+                alphabet: opts.alphabet,
+                ..Options::default()
+            };
+            repl::Repl::new(repl::Mode::Decode, Timing::default(), options)
+                .run(io::stdin().lock(), io::stdout())
+                .map_err(Error::Io)?;
+        }
+
+        Opts::Encode(opts) if opts.format == EncodeFormat::Bin => {
+            let table = load_table(&opts.table)?;
+            let packed = opts.pack(&opts.files.read()?, table.as_ref())?;
+            opts.files.write_binary(&packed)?;
+        }
+
+        Opts::Encode(opts) => {
+            let table = load_table(&opts.table)?;
+            opts.files
+                .filter(|message| opts.encode(message, table.as_ref()))?;
+        }
+
+        Opts::Decode(opts) => {
+            let table = load_table(&opts.table)?;
+            opts.files.filter_with(FileOpts::read_packed, |message| {
+                opts.decode(message, table.as_ref())
+            })?;
+        }
+
+        Opts::Auto(opts) => opts.files.filter(|message| opts.translate(message))?,
+
+        Opts::Stats(opts) => opts.files.filter(|message| opts.report(message))?,
+
+        Opts::Table(opts) => println!("{}", opts.chart()),
+
+        Opts::Tree(opts) if opts.dot => print!("{}", tree::dot()),
+        Opts::Tree(_) => print!("{}", tree::ascii()),
+
+        Opts::Search(opts) => {
+            let found = opts.search()?;
+            if !found.is_empty() {
+                println!("{}", found);
+            }
+        }
+
+        Opts::Verify(opts) => {
+            let discrepancies = opts.verify(&opts.files.read()?);
+            if discrepancies.is_empty() {
+                println!("ok");
+            } else {
+                for discrepancy in &discrepancies {
+                    println!("{}", discrepancy);
+                }
+                process::exit(1);
+            }
+        }
+
+        Opts::Pipe(opts) => {
+            let message = read_stdin()?;
+            println!("{}", pipe::convert(&message, opts.from, opts.to)?);
+        }
+
+        Opts::Wav(opts) => opts.write()?,
+
+        Opts::Train(opts) => match &opts.method {
+            TrainMethod::Koch(opts) => opts.train()?,
+        },
+
+        Opts::Quiz(opts) => opts.quiz()?,
+
+        Opts::Grade(opts) => println!("{}", opts.grade()?),
+
+        Opts::Key(opts) => {
+            eprintln!("keying at {} WPM; Esc to stop", opts.speed.wpm);
+            let events = keyboard::straight(
+                opts.speed.timing(),
+                lenient_options(opts.lenient),
+                Duration::from_millis(opts.repeat_delay),
+                io::stdout(),
+            )?;
+            if let Some(path) = &opts.record {
+                fs::write(path, timing::write_key_events(&events) + "\n")
+                    .map_err(|e| file_error(path, e))?;
+            }
+        }
+
+        Opts::Fist(opts) => println!("{}", opts.analyze()?),
+
+        Opts::Paddles(opts) => opts.run()?,
+
+        Opts::Practice(opts) => match &opts.kind {
+            PracticeKind::Groups(opts) => opts.render.output(&opts.generate()?)?,
+            PracticeKind::Callsigns(opts) => {
+                let mut rng = opts.seed.map(Rng::new).unwrap_or_else(Rng::from_time);
+                opts.render
+                    .output(&practice::callsigns(opts.count, &mut rng))?
+            }
+            PracticeKind::Qso(opts) => opts.simulate()?,
+        },
+
+        Opts::DecodeAudio(opts) if opts.multi => {
+            let (samples, sample_rate) = opts.read()?;
+            let options = Options::default();
+            let signals = audio::decode_signals(&samples, sample_rate, &opts.detector()?, &options);
+            if signals.is_empty() {
+                return Err(Error::Audio("no keyed signals found".into()));
+            }
+            for signal in signals {
+                match opts.format {
+                    OutputFormat::Text => println!(
+                        "{:.0} Hz: {}",
+                        signal.tone,
+                        confidence::text(&signal.words, '?', &options)
+                    ),
+                    OutputFormat::Json => println!(
+                        "{{\"tone\":{},\"report\":{}}}",
+                        signal.tone,
+                        scored_report(&opts.input, &signal.words).to_json()
+                    ),
+                }
+            }
+        }
+
+        Opts::DecodeAudio(opts) if opts.track_speed => {
+            let (samples, sample_rate) = opts.read()?;
+            let words = audio::decode_tracking(&samples, sample_rate, &opts.detector()?)?;
+            let mut shown: Option<f64> = None;
+            for (idx, (word, wpm)) in words.iter().enumerate() {
+                if shown.is_none_or(|shown| (shown - wpm).abs() >= SPEED_CHANGE) {
+                    eprintln!("{:.0} WPM from word {} ({:?})", wpm, idx + 1, word);
+                    shown = Some(*wpm);
+                }
+            }
+            let words: Vec<&str> = words.iter().map(|(word, _)| word.as_str()).collect();
+            println!("{}", words.join(" "));
+        }
+
+        Opts::DecodeAudio(opts) => {
+            let (samples, sample_rate) = opts.read()?;
+            let detector = opts.detector()?;
+            let (tone, events) = audio::listen(&samples, sample_rate, &detector)?;
+            // Measured first, as a poor recording is most worth knowing about when it won't decode.
+            if opts.quality {
+                let quality = audio::quality::Quality::measure(
+                    &samples,
+                    sample_rate,
+                    tone,
+                    &events,
+                    &detector,
+                );
+                eprintln!("{}", quality);
+            }
+            match opts.format {
+                OutputFormat::Text => {
+                    println!("{}", morse::decode(&timing::from_key_events(&events))?)
+                }
+                OutputFormat::Json => {
+                    let words = confidence::decode_key_events(&events, &Options::default());
+                    println!("{}", scored_report(&opts.input, &words).to_json());
+                }
+            }
+        }
+
+        Opts::Beep(opts) => opts.beep()?,
+
+        Opts::Send(opts) => opts.send()?,
+
+        Opts::Beacon(opts) => opts.run()?,
+
+        Opts::Serve(opts) => opts.serve()?,
 
-    for character in characters {
-        buf.push(decode_character(character)? as char);
+        Opts::Fldigi(opts) => opts.run()?,
+
+        Opts::Mqtt(opts) => opts.run()?,
+
+        Opts::Codegen(opts) => print!("{}", opts.generate()?),
+
+        Opts::Flash(opts) => {
+            let events = opts.speed.key(&read_stdin()?)?;
+            output::send(&mut Flash::new(io::stdout(), opts.style), &events).map_err(Error::Io)?;
+        }
+
+        #[cfg(feature = "soundcard")]
+        Opts::Play(opts) => {
+            let events = opts.speed.key(&read_stdin()?)?;
+            let voice = opts.voice.voice()?;
+            audio::device::play(voice.render(&events), voice.sample_rate)?;
+        }
+
+        #[cfg(feature = "soundcard")]
+        Opts::Listen(opts) => {
+            let capture = audio::device::capture()?;
+            let mut decoder = audio::stream::StreamDecoder::new(capture.sample_rate, opts.tone);
+            let mut stdout = io::stdout();
+            let mut shown: Option<f64> = None;
+
+            for samples in capture {
+                print!("{}", decoder.push(&samples));
+                stdout.flush().map_err(Error::Io)?;
+                if let Some(wpm) = decoder.wpm() {
+                    if shown.is_none_or(|shown| (shown - wpm).abs() >= SPEED_CHANGE) {
+                        eprint!(" [{:.0} WPM] ", wpm);
+                        shown = Some(wpm);
+                    }
+                }
+            }
+            println!("{}", decoder.finish());
+        }
+
+        #[cfg(feature = "soundcard")]
+        Opts::Chat(opts) => chat::run(opts.speed.timing(), opts.voice.voice()?, opts.listen)?,
+
+        #[cfg(feature = "tui")]
+        Opts::Tui(opts) => tui::run(opts.speed.timing(), opts.voice.voice()?, opts.lesson)?,
     }
 
     Ok(())
 }
 
-#[inline]
-fn decode_character(character: &str) -> Result<u8> {
-    let idx = character_index(character);
-    data::DECODING_ARRAY
-        .get(idx as usize)
-        .copied()
-        .and_then(|x| x)
-        .ok_or_else(|| Error::Decode(character.into()))
+fn read_stdin() -> Result<String> {
+    let mut buf = String::new();
+    io::stdin()
+        .read_to_string(&mut buf)
+        .map(|_| buf)
+        .map_err(Error::Io)
+}
+
+/// Reports a scored decoding, warning about every character that was more guessed than heard.
+fn scored_report(input: &Path, words: &[Vec<Scored>]) -> Report {
+    let options = Options::default();
+    let characters: Vec<_> = words
+        .iter()
+        .flatten()
+        .map(|scored| Mapping {
+            text: scored.text.clone(),
+            code: scored.code.clone(),
+            confidence: Some(scored.confidence),
+        })
+        .collect();
+    let warnings = characters
+        .iter()
+        .filter_map(|mapping| match (&mapping.text, mapping.confidence) {
+            (None, _) => Some(format!("unknown sequence {:?}", mapping.code)),
+            (Some(text), Some(confidence)) if confidence < UNCERTAIN => Some(format!(
+                "uncertain {:?} ({}), confidence {:.2}",
+                text, mapping.code, confidence
+            )),
+            _ => None,
+        })
+        .collect();
+
+    Report {
+        input: input.display().to_string(),
+        output: confidence::text(words, '#', &options),
+        characters,
+        warnings,
+    }
+}
+
+/// Pairs each code in a canonically encoded message with what it decodes to on its own.
+fn map_characters(encoded: &str, decode: impl Fn(&str) -> Option<String>) -> Vec<Mapping> {
+    encoded
+        .split('/')
+        .flat_map(str::split_whitespace)
+        .map(|code| Mapping {
+            text: decode(code),
+            code: code.into(),
+            confidence: None,
+        })
+        .collect()
+}
+
+fn decode_code(
+    code: &str,
+    table: Option<&Table>,
+    standard: Standard,
+    options: &Options,
+) -> Option<String> {
+    let options = Options {
+        errors: ErrorPolicy::Strict,
+        ..*options
+    };
+
+    match (table, standard) {
+        (Some(table), _) => table
+            .decode_counting(code, &options)
+            .ok()
+            .map(|(text, _)| text),
+        (None, Standard::International) => morse::decode_with(code, &options).ok(),
+        (None, Standard::American) => american::decode_with(code, &options).ok(),
+    }
+}
+
+fn load_table(path: &Option<PathBuf>) -> Result<Option<Table>> {
+    match path {
+        Some(path) => {
+            let source = fs::read_to_string(path).map_err(|e| file_error(path, e))?;
+            match Table::parse(&source) {
+                Ok(table) => Ok(Some(table)),
+                Err(Error::Table(reason)) => {
+                    Err(Error::Table(format!("{}: {}", path.display(), reason)))
+                }
+                Err(e) => Err(e),
+            }
+        }
+        None => Ok(None),
+    }
+}
+
+/// Lines are converted on their own, so errors in them only know their column.
+fn on_line(e: Error, line: usize) -> Error {
+    match e {
+        Error::At(position, e) => Error::At(Position { line, ..position }, e),
+        e => e,
+    }
 }
 
-#[inline]
-fn character_index(character: &str) -> i32 {
-    character.bytes().fold(0, |idx, u| match u {
-        b'.' => idx * 2 + 1,
-        b'-' => idx * 2 + 2,
-        _ => idx,
-    })
+/// Attaches the file name to an I/O error, so that the user can tell which file was at fault.
+fn file_error(path: &Path, e: io::Error) -> Error {
+    Error::Io(io::Error::new(
+        e.kind(),
+        format!("{}: {}", path.display(), e),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{DecodeOpts, FileOpts, NotationOpts, OutputFormat, SpeedOpts};
+    use morse::{
+        notation::Notation,
+        table::Table,
+        timing::{Timing, Weight},
+        Alphabet, ErrorPolicy, Options, Standard,
+    };
+
+    #[test]
+    fn warns_about_characters_before_they_are_dropped() {
+        let options = Options::default();
+        assert_eq!(
+            super::encode_warnings("A # B #", None, Standard::International, &options),
+            ["dropped unencodable character '#'"]
+        );
+        let options = Options {
+            errors: ErrorPolicy::Replace('?'),
+            ..options
+        };
+        assert_eq!(
+            super::encode_warnings("<SK> #", None, Standard::International, &options),
+            ["replaced unencodable character '#'"]
+        );
+    }
+
     #[test]
-    fn char_to_code_works() {
-        let sequence = "abcdefghijklmnopqrstuvwxyz0123456789";
-        let pairs = sequence.bytes().zip(super::data::ENCODED_SEQUENCES);
+    fn sanitizing_keeps_what_the_active_table_encodes() {
+        let table = Table::parse("A = \".-\"\n\"#\" = \"..--\"\n\"Ж\" = \"...-\"").unwrap();
+        let options = Options::default();
+        let sanitize =
+            |message, table| super::sanitize(message, table, Standard::International, &options);
+        assert_eq!(sanitize("A # Ж", Some(&table)), "A # Ж");
+        assert_eq!(sanitize("A # Ж", None), "A");
+        assert_eq!(sanitize("A % B", Some(&table)), "A");
+        assert_eq!(
+            super::sanitize("L # Z", None, Standard::American, &options),
+            "L Z"
+        );
+    }
 
-        for (u, &code) in pairs {
-            assert_eq!(super::encode_byte(u).unwrap(), code);
+    fn decode_opts() -> DecodeOpts {
+        DecodeOpts {
+            one_word_per_line: false,
+            extended: false,
+            code: Standard::International,
+            alphabet: Alphabet::Latin,
+            table: None,
+            format: OutputFormat::Text,
+            ignore_comments: false,
+            timing: false,
+            prosigns: false,
+            spell_numbers: false,
+            lenient: false,
+            skip: false,
+            all_errors: false,
+            segment: false,
+            candidates: 1,
+            wordlist: None,
+            replace: None,
+            notation: NotationOpts {
+                dot: None,
+                dash: None,
+                char_sep: None,
+                word_sep: None,
+                notation: Notation::default(),
+                unicode: false,
+            },
+            files: FileOpts {
+                message: Vec::new(),
+                input: None,
+                output: None,
+                line_buffered: false,
+            },
         }
     }
+
+    #[test]
+    fn segmented_output_goes_through_the_usual_formats() {
+        let mut opts = DecodeOpts {
+            spell_numbers: true,
+            segment: true,
+            ..decode_opts()
+        };
+        assert_eq!(opts.decode("-.----- / ..-.---..-.-.", None).unwrap(), "2 4");
+
+        opts.spell_numbers = false;
+        opts.format = OutputFormat::Json;
+        let json = opts.decode("...---...", None).unwrap();
+        assert!(json.contains("\"output\":\"SOS\""), "{}", json);
+        assert!(json.contains("\"code\":\"---\""), "{}", json);
+    }
+
+    #[test]
+    fn prompt_refuses_options_it_would_ignore() {
+        let mut opts = decode_opts();
+        assert_eq!(opts.prompt_conflict(), None);
+        opts.lenient = true;
+        assert_eq!(
+            opts.prompt_conflict(),
+            Some("--lenient, --skip, --all-errors and --replace")
+        );
+        opts.lenient = false;
+        opts.format = OutputFormat::Json;
+        assert_eq!(opts.prompt_conflict(), Some("--format"));
+        opts.code = Standard::American;
+        assert_eq!(opts.prompt_conflict(), Some("--code"));
+
+        let mut opts = decode_opts();
+        opts.notation.notation = Notation::unicode();
+        assert_eq!(opts.prompt_conflict(), Some(super::NOTATION_FLAGS));
+        opts.notation.notation = Notation::default();
+        opts.timing = true;
+        assert_eq!(opts.prompt_conflict(), Some("--timing"));
+    }
+
+    #[test]
+    fn transliteration_keeps_extended_letters() {
+        let options = Options {
+            extended: true,
+            ..Options::default()
+        };
+        let encodable = |c| super::is_encodable(c, None, Standard::International, &options);
+        assert_eq!(
+            morse::transliterate::latin_except("Éte Ñu Œ", encodable),
+            "Éte Ñu OE"
+        );
+    }
+
+    #[test]
+    fn notation_flags_take_precedence_over_env() {
+        let env = |var: &str| match var {
+            "MORSE_WORD_SEP" => Some(String::from(" | ")),
+            "MORSE_DOT" => Some(String::from("*")),
+            _ => None,
+        };
+
+        let opts = NotationOpts {
+            dot: Some(String::from("o")),
+            dash: None,
+            char_sep: None,
+            word_sep: None,
+            notation: Notation::default(),
+            unicode: false,
+        };
+
+        let notation = opts.resolve(env);
+        assert_eq!(notation.word_sep, " | ");
+        assert_eq!(notation.dot, "o");
+        assert_eq!(notation.dash, "-");
+        assert_eq!(notation.char_sep, " ");
+    }
+
+    #[test]
+    fn unicode_flag_is_shorthand_for_the_notation() {
+        let opts = NotationOpts {
+            dot: None,
+            dash: None,
+            char_sep: None,
+            word_sep: None,
+            notation: Notation::default(),
+            unicode: true,
+        };
+        assert_eq!(opts.resolve(|_| None), Notation::unicode());
+    }
+
+    #[test]
+    fn farnsworth_flag_sets_effective_speed() {
+        let opts = SpeedOpts {
+            wpm: 18,
+            farnsworth: Some(8),
+            weight: Weight(3.0),
+            humanize: 0.0,
+        };
+        assert_eq!(opts.timing(), Timing::farnsworth(18, 8));
+
+        let opts = SpeedOpts {
+            wpm: 18,
+            farnsworth: None,
+            weight: Weight(3.2),
+            humanize: 0.0,
+        };
+        assert_eq!(opts.timing(), Timing::new(18).weighted(3.2));
+    }
+
+    #[test]
+    fn positional_words_form_the_message() {
+        let opts = FileOpts {
+            message: vec![String::from("HELLO"), String::from("WORLD")],
+            input: None,
+            output: None,
+            line_buffered: false,
+        };
+        assert_eq!(opts.read().unwrap(), "HELLO WORLD");
+    }
 }
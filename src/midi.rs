@@ -0,0 +1,122 @@
+//! Standard MIDI files of a keyed message: one note held for each key-down period, so a message
+//! can be dropped into a DAW or played through MIDI hardware.
+//!
+//! Files are format 0, a single track, with ticks of one millisecond.
+
+use std::io::{self, Write};
+
+use crate::timing::KeyEvent;
+
+/// Ticks per quarter note; at the default tempo of 120 BPM, one tick is a millisecond.
+const DIVISION: u16 = 500;
+
+/// Microseconds per quarter note, the default tempo, written out for players that want it said.
+const TEMPO: u32 = 500_000;
+
+/// The note a message is played on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Note {
+    /// MIDI note number, 69 being A above middle C.
+    pub key: u8,
+    /// From 0 to 15, shown as 1 to 16 by most software.
+    pub channel: u8,
+    /// From 1 to 127.
+    pub velocity: u8,
+}
+
+/// The MIDI note nearest to `frequency` Hz, with equal temperament tuned to A = 440 Hz.
+pub fn nearest_key(frequency: f64) -> u8 {
+    let key = 69.0 + 12.0 * (frequency / 440.0).log2();
+    key.round().clamp(0.0, 127.0) as u8
+}
+
+/// Writes key events as a MIDI file, each key-down period as `note` held for its length.
+pub fn write<W: Write>(mut writer: W, events: &[KeyEvent], note: &Note) -> io::Result<()> {
+    let mut track = Vec::new();
+    push_delta(&mut track, 0);
+    track.extend_from_slice(&[0xff, 0x51, 0x03]);
+    track.extend_from_slice(&TEMPO.to_be_bytes()[1..]);
+
+    // Ticks are counted from the start rather than event to event, so that rounding does not
+    // build up over a long message.
+    let (mut elapsed, mut written) = (0.0f64, 0);
+    for event in events {
+        let start = (elapsed * 1000.0).round() as u32;
+        elapsed += event.duration().as_secs_f64();
+        if let KeyEvent::Down(_) = event {
+            let end = (elapsed * 1000.0).round() as u32;
+            push_delta(&mut track, start - written);
+            track.extend_from_slice(&[0x90 | note.channel, note.key, note.velocity]);
+            push_delta(&mut track, end - start);
+            track.extend_from_slice(&[0x80 | note.channel, note.key, 0]);
+            written = end;
+        }
+    }
+    let end = (elapsed * 1000.0).round() as u32;
+    push_delta(&mut track, end - written);
+    track.extend_from_slice(&[0xff, 0x2f, 0x00]);
+
+    writer.write_all(b"MThd")?;
+    writer.write_all(&6u32.to_be_bytes())?;
+    writer.write_all(&0u16.to_be_bytes())?; // format 0
+    writer.write_all(&1u16.to_be_bytes())?; // one track
+    writer.write_all(&DIVISION.to_be_bytes())?;
+    writer.write_all(b"MTrk")?;
+    writer.write_all(&(track.len() as u32).to_be_bytes())?;
+    writer.write_all(&track)?;
+    writer.flush()
+}
+
+/// Pushes a delta time as a variable-length quantity: seven bits a byte, most significant first,
+/// with the top bit set on all but the last.
+fn push_delta(track: &mut Vec<u8>, ticks: u32) {
+    let mut bytes = vec![(ticks & 0x7f) as u8];
+    let mut rest = ticks >> 7;
+    while rest > 0 {
+        bytes.push(0x80 | (rest & 0x7f) as u8);
+        rest >>= 7;
+    }
+    track.extend(bytes.iter().rev());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Note;
+    use crate::timing::KeyEvent::{Down, Up};
+
+    #[test]
+    fn holds_a_note_for_each_element() {
+        let ms = Duration::from_millis;
+        let events = [Down(ms(60)), Up(ms(60)), Down(ms(180)), Up(ms(420))];
+        let note = Note {
+            key: 74,
+            channel: 2,
+            velocity: 100,
+        };
+        let mut midi = Vec::new();
+        super::write(&mut midi, &events, &note).unwrap();
+
+        assert_eq!(&midi[..4], b"MThd");
+        assert_eq!(&midi[12..14], [0x01, 0xf4]);
+        assert_eq!(&midi[14..18], b"MTrk");
+        assert_eq!(
+            &midi[22..],
+            [
+                0, 0xff, 0x51, 0x03, 0x07, 0xa1, 0x20, // tempo
+                0, 0x92, 74, 100, 60, 0x82, 74, 0, // dit
+                60, 0x92, 74, 100, 0x81, 0x34, 0x82, 74, 0, // dah, 180 ticks long
+                0x83, 0x24, 0xff, 0x2f, 0x00, // the end, 420 ticks later
+            ]
+        );
+        assert_eq!(midi[21] as usize, midi.len() - 22);
+    }
+
+    #[test]
+    fn finds_the_nearest_key() {
+        assert_eq!(super::nearest_key(440.0), 69);
+        assert_eq!(super::nearest_key(600.0), 74);
+        assert_eq!(super::nearest_key(261.6), 60);
+    }
+}
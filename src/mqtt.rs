@@ -0,0 +1,279 @@
+//! Just enough of MQTT 3.1.1 to bridge a broker: connecting, subscribing to a topic and
+//! publishing, all at QoS 0.
+
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+/// The port brokers listen on without TLS.
+pub const PORT: u16 = 1883;
+
+/// How long the broker waits to hear from a quiet client before dropping it.
+pub const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+const CONNECT: u8 = 1;
+const CONNACK: u8 = 2;
+const PUBLISH: u8 = 3;
+const SUBSCRIBE: u8 = 8;
+const SUBACK: u8 = 9;
+const PINGREQ: u8 = 12;
+const DISCONNECT: u8 = 14;
+
+/// A message published on a topic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    pub topic: String,
+    pub payload: Vec<u8>,
+}
+
+/// A connection to a broker.
+pub struct Client<S: Read + Write> {
+    stream: S,
+    next_id: u16,
+}
+
+impl Client<TcpStream> {
+    /// Connects to the broker at `address`, on its usual port if none is given. Reads time out
+    /// at half the keep-alive interval, so that [`Client::ping`] can be sent while waiting.
+    pub fn connect(address: &str, client_id: &str) -> io::Result<Self> {
+        let stream = if address.contains(':') {
+            TcpStream::connect(address)?
+        } else {
+            TcpStream::connect((address, PORT))?
+        };
+        stream.set_read_timeout(Some(KEEP_ALIVE / 2))?;
+        Client::new(stream, client_id)
+    }
+}
+
+impl<S: Read + Write> Client<S> {
+    /// Starts a clean session as `client_id`.
+    pub fn new(stream: S, client_id: &str) -> io::Result<Self> {
+        let mut client = Client { stream, next_id: 1 };
+
+        let mut body = Vec::new();
+        push_string(&mut body, "MQTT");
+        // Protocol level 4 is 3.1.1; the only flag is for a clean session.
+        body.extend_from_slice(&[4, 0x02]);
+        body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+        push_string(&mut body, client_id);
+        client.write_packet(CONNECT << 4, &body)?;
+
+        match client.read_packet()? {
+            (CONNACK, body) => match body.get(1) {
+                Some(0) => Ok(client),
+                Some(&code) => Err(io::Error::new(
+                    io::ErrorKind::ConnectionRefused,
+                    format!("mqtt: connection refused ({})", refusal(code)),
+                )),
+                None => Err(invalid("short CONNACK")),
+            },
+            (kind, _) => Err(invalid(&format!(
+                "expected CONNACK, got packet type {}",
+                kind
+            ))),
+        }
+    }
+
+    /// Subscribes to `topic`, which may have wildcards.
+    pub fn subscribe(&mut self, topic: &str) -> io::Result<()> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+
+        let mut body = id.to_be_bytes().to_vec();
+        push_string(&mut body, topic);
+        body.push(0);
+        self.write_packet(SUBSCRIBE << 4 | 0x02, &body)?;
+
+        loop {
+            let (kind, body) = self.read_packet()?;
+            if kind != SUBACK || body.get(..2) != Some(&id.to_be_bytes()[..]) {
+                continue;
+            }
+            return match body.get(2) {
+                Some(0x80) => Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("mqtt: subscription to {:?} refused", topic),
+                )),
+                Some(_) => Ok(()),
+                None => Err(invalid("short SUBACK")),
+            };
+        }
+    }
+
+    pub fn publish(&mut self, topic: &str, payload: &[u8]) -> io::Result<()> {
+        let mut body = Vec::new();
+        push_string(&mut body, topic);
+        body.extend_from_slice(payload);
+        self.write_packet(PUBLISH << 4, &body)
+    }
+
+    /// Lets the broker know the client is still there.
+    pub fn ping(&mut self) -> io::Result<()> {
+        self.write_packet(PINGREQ << 4, &[])
+    }
+
+    /// Waits for the next message on a subscribed topic, passing over anything else the broker
+    /// sends.
+    pub fn receive(&mut self) -> io::Result<Message> {
+        loop {
+            let (kind, flags, body) = self.read_packet_with_flags()?;
+            if kind != PUBLISH {
+                continue;
+            }
+
+            let len = body
+                .get(..2)
+                .map(|len| usize::from(u16::from_be_bytes([len[0], len[1]])))
+                .ok_or_else(|| invalid("short PUBLISH"))?;
+            let topic = body
+                .get(2..2 + len)
+                .ok_or_else(|| invalid("short PUBLISH"))?;
+            let topic = String::from_utf8_lossy(topic).into_owned();
+            // Messages at QoS 1 and 2 carry an id, which QoS 0 subscriptions never see
+            // acknowledged.
+            let start = if flags & 0x06 == 0 { 2 + len } else { 4 + len };
+            let payload = body.get(start..).unwrap_or_default().to_vec();
+            return Ok(Message { topic, payload });
+        }
+    }
+
+    pub fn disconnect(mut self) -> io::Result<()> {
+        self.write_packet(DISCONNECT << 4, &[])
+    }
+
+    fn write_packet(&mut self, header: u8, body: &[u8]) -> io::Result<()> {
+        let mut packet = vec![header];
+        let mut len = body.len();
+        loop {
+            let digit = (len % 128) as u8;
+            len /= 128;
+            packet.push(if len > 0 { digit | 0x80 } else { digit });
+            if len == 0 {
+                break;
+            }
+        }
+        packet.extend_from_slice(body);
+        self.stream.write_all(&packet)?;
+        self.stream.flush()
+    }
+
+    fn read_packet(&mut self) -> io::Result<(u8, Vec<u8>)> {
+        self.read_packet_with_flags()
+            .map(|(kind, _, body)| (kind, body))
+    }
+
+    fn read_packet_with_flags(&mut self) -> io::Result<(u8, u8, Vec<u8>)> {
+        let mut header = [0];
+        self.stream.read_exact(&mut header)?;
+
+        let mut len = 0;
+        for shift in (0..4).map(|n| 7 * n) {
+            let mut digit = [0];
+            self.stream.read_exact(&mut digit)?;
+            len |= usize::from(digit[0] & 0x7f) << shift;
+            if digit[0] & 0x80 == 0 {
+                let mut body = vec![0; len];
+                self.stream.read_exact(&mut body)?;
+                return Ok((header[0] >> 4, header[0] & 0x0f, body));
+            }
+        }
+        Err(invalid("bad remaining length"))
+    }
+}
+
+fn push_string(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    buf.extend_from_slice(s.as_bytes());
+}
+
+fn refusal(code: u8) -> &'static str {
+    match code {
+        1 => "unacceptable protocol version",
+        2 => "client identifier rejected",
+        3 => "server unavailable",
+        4 => "bad user name or password",
+        5 => "not authorized",
+        _ => "unknown reason",
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("mqtt: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Read, Write};
+
+    use super::{Client, Message};
+
+    /// The broker's packets, queued up ahead of time, and everything the client sends.
+    struct Broker {
+        packets: Cursor<Vec<u8>>,
+        received: Vec<u8>,
+    }
+
+    impl Read for Broker {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.packets.read(buf)
+        }
+    }
+
+    impl Write for Broker {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.received.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn bridges_messages() {
+        let mut packets = vec![0x20, 2, 0, 0]; // CONNACK
+        packets.extend_from_slice(&[0x90, 3, 0, 1, 0]); // SUBACK
+        packets.extend_from_slice(&[0x30, 8, 0, 3, b'i', b'/', b'n', b's', b'o', b's']);
+        packets.extend_from_slice(&[0x32, 7, 0, 1, b'x', 0, 9, b'h', b'i']);
+
+        let broker = Broker {
+            packets: Cursor::new(packets),
+            received: Vec::new(),
+        };
+        let mut client = Client::new(broker, "m").unwrap();
+        client.subscribe("i/#").unwrap();
+        assert_eq!(
+            client.receive().unwrap(),
+            Message {
+                topic: "i/n".into(),
+                payload: b"sos".to_vec(),
+            }
+        );
+        assert_eq!(client.receive().unwrap().payload, b"hi");
+        client.publish("o", b"...").unwrap();
+
+        let received = client.stream.received;
+        assert_eq!(
+            received,
+            [
+                &[0x10, 13, 0, 4, b'M', b'Q', b'T', b'T', 4, 2, 0, 60, 0, 1, b'm'][..],
+                &[0x82, 8, 0, 1, 0, 3, b'i', b'/', b'#', 0],
+                &[0x30, 6, 0, 1, b'o', b'.', b'.', b'.'],
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn reports_refusals() {
+        let broker = Broker {
+            packets: Cursor::new(vec![0x20, 2, 0, 5]),
+            received: Vec::new(),
+        };
+        let e = Client::new(broker, "m").err().unwrap();
+        assert_eq!(e.to_string(), "mqtt: connection refused (not authorized)");
+    }
+}
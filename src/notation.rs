@@ -0,0 +1,184 @@
+use std::str::FromStr;
+
+/// Describes how encoded messages are written: which glyphs stand for dots and dashes, and what
+/// separates characters and words.
+///
+/// Encoding always produces the canonical notation (`.`, `-`, a space between characters and
+/// `" / "` between words); a notation is applied to that output afterward and stripped from
+/// input again before decoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Notation {
+    pub dot: String,
+    pub dash: String,
+    pub char_sep: String,
+    pub word_sep: String,
+}
+
+impl Default for Notation {
+    fn default() -> Self {
+        Notation {
+            dot: ".".into(),
+            dash: "-".into(),
+            char_sep: " ".into(),
+            word_sep: " / ".into(),
+        }
+    }
+}
+
+/// Parses the name of a built-in notation: `canonical` or `unicode`.
+impl FromStr for Notation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "canonical" => Ok(Notation::default()),
+            "unicode" => Ok(Notation::unicode()),
+            _ => Err(format!(
+                "unknown notation {:?} (expected canonical or unicode)",
+                s
+            )),
+        }
+    }
+}
+
+impl Notation {
+    /// Typographic notation for publication: middle dots and en dashes, with no-break spaces
+    /// between characters so that a line never breaks inside a word.
+    pub fn unicode() -> Self {
+        Notation {
+            dot: "\u{b7}".into(),
+            dash: "\u{2013}".into(),
+            char_sep: "\u{a0}".into(),
+            word_sep: " / ".into(),
+        }
+    }
+
+    /// Rewrites a canonically encoded message in this notation.
+    pub fn apply(&self, encoded: &str) -> String {
+        let words: Vec<String> = encoded
+            .split('/')
+            .filter(|word| !word.trim().is_empty())
+            .map(|word| {
+                let characters: Vec<String> = word
+                    .split_whitespace()
+                    .map(|character| {
+                        character
+                            .chars()
+                            .map(|c| match c {
+                                '.' => self.dot.as_str(),
+                                '-' => self.dash.as_str(),
+                                _ => "",
+                            })
+                            .collect()
+                    })
+                    .collect();
+                characters.join(&self.char_sep)
+            })
+            .collect();
+        words.join(&self.word_sep)
+    }
+
+    /// Rewrites a message written in this notation canonically, so that it can be decoded.
+    ///
+    /// Whitespace around separators is not significant unless the separator is itself made of
+    /// whitespace. Sequences containing unrecognized glyphs are passed through unchanged so
+    /// that the decoder can report them.
+    pub fn normalize(&self, message: &str) -> String {
+        let words: Vec<String> = split_on(message, &self.word_sep)
+            .into_iter()
+            .map(|word| {
+                let characters: Vec<String> = split_on(word, &self.char_sep)
+                    .into_iter()
+                    .map(|character| self.normalize_character(character))
+                    .collect();
+                characters.join(" ")
+            })
+            .filter(|word| !word.is_empty())
+            .collect();
+        words.join(" / ")
+    }
+
+    fn normalize_character(&self, character: &str) -> String {
+        let mut buf = String::with_capacity(character.len());
+        let mut rest = character;
+
+        while !rest.is_empty() {
+            if let Some(tail) = rest.strip_prefix(self.dot.as_str()) {
+                buf.push('.');
+                rest = tail;
+            } else if let Some(tail) = rest.strip_prefix(self.dash.as_str()) {
+                buf.push('-');
+                rest = tail;
+            } else {
+                return character.into();
+            }
+        }
+
+        buf
+    }
+}
+
+fn split_on<'a>(s: &'a str, separator: &str) -> Vec<&'a str> {
+    let trimmed = separator.trim();
+    if !trimmed.is_empty() {
+        s.split(trimmed).map(str::trim).collect()
+    } else if separator.len() > 1 {
+        s.split(separator).map(str::trim).collect()
+    } else {
+        s.split_whitespace().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Notation;
+
+    #[test]
+    fn default_notation_is_canonical() {
+        let notation = Notation::default();
+        assert_eq!(notation.apply(".... .. / - ...."), ".... .. / - ....");
+        assert_eq!(notation.normalize("..../.."), ".... / ..");
+    }
+
+    #[test]
+    fn custom_notation_round_trips() {
+        let notation = Notation {
+            dot: "*".into(),
+            dash: "_".into(),
+            char_sep: "|".into(),
+            word_sep: "   ".into(),
+        };
+
+        let applied = notation.apply(".... .. / - ....");
+        assert_eq!(applied, "****|**   _|****");
+        assert_eq!(notation.normalize(&applied), ".... .. / - ....");
+    }
+
+    #[test]
+    fn multibyte_glyphs_round_trip() {
+        let notation = Notation {
+            dot: "·".into(),
+            dash: "−".into(),
+            ..Notation::default()
+        };
+
+        let applied = notation.apply("... --- ...");
+        assert_eq!(applied, "··· −−− ···");
+        assert_eq!(notation.normalize(&applied), "... --- ...");
+        assert_eq!(crate::decode(&notation.normalize(&applied)).unwrap(), "SOS");
+    }
+
+    #[test]
+    fn unicode_notation_round_trips() {
+        let notation = Notation::unicode();
+        let applied = notation.apply(".... .. / - ....");
+        assert_eq!(applied, "····\u{a0}·· / –\u{a0}····");
+        assert_eq!(notation.normalize(&applied), ".... .. / - ....");
+    }
+
+    #[test]
+    fn notations_parse_by_name() {
+        assert_eq!("Unicode".parse(), Ok(Notation::unicode()));
+        assert_eq!("canonical".parse(), Ok(Notation::default()));
+    }
+}
@@ -0,0 +1,203 @@
+use std::convert::TryFrom;
+
+static ONES: &[&str] = &[
+    "ZERO",
+    "ONE",
+    "TWO",
+    "THREE",
+    "FOUR",
+    "FIVE",
+    "SIX",
+    "SEVEN",
+    "EIGHT",
+    "NINE",
+    "TEN",
+    "ELEVEN",
+    "TWELVE",
+    "THIRTEEN",
+    "FOURTEEN",
+    "FIFTEEN",
+    "SIXTEEN",
+    "SEVENTEEN",
+    "EIGHTEEN",
+    "NINETEEN",
+];
+
+static TENS: &[&str] = &[
+    "", "", "TWENTY", "THIRTY", "FORTY", "FIFTY", "SIXTY", "SEVENTY", "EIGHTY", "NINETY",
+];
+
+/// The largest number that can be spelled out.
+pub const MAX: u32 = 9999;
+
+/// The longest spelling of any number up to `MAX`, in words.
+const MAX_WORDS: usize = 6;
+
+/// Spells out a number from 0 to 9999 in words, e.g. `"TWO THOUSAND TWENTY ONE"`.
+pub fn to_words(n: u32) -> Option<String> {
+    if n > MAX {
+        return None;
+    }
+
+    if n == 0 {
+        return Some(ONES[0].into());
+    }
+
+    let mut words = Vec::new();
+    let thousands = n / 1000;
+    let hundreds = n / 100 % 10;
+    let rest = (n % 100) as usize;
+
+    if thousands > 0 {
+        words.push(ONES[thousands as usize]);
+        words.push("THOUSAND");
+    }
+
+    if hundreds > 0 {
+        words.push(ONES[hundreds as usize]);
+        words.push("HUNDRED");
+    }
+
+    if rest >= 20 {
+        words.push(TENS[rest / 10]);
+        match rest % 10 {
+            0 => (),
+            ones => words.push(ONES[ones]),
+        }
+    } else if rest > 0 {
+        words.push(ONES[rest]);
+    }
+
+    Some(words.join(" "))
+}
+
+/// Reads a number spelled out in words. Only the exact spelling produced by `to_words` is
+/// accepted, so that ordinary text is never mistaken for a number.
+pub fn from_words(words: &[&str]) -> Option<u32> {
+    let mut total: u64 = 0;
+    let mut current: u64 = 0;
+
+    for &word in words {
+        if let Some(n) = ONES.iter().position(|&ones| ones == word) {
+            current += n as u64;
+        } else if let Some(n) = TENS
+            .iter()
+            .position(|&tens| !tens.is_empty() && tens == word)
+        {
+            current += n as u64 * 10;
+        } else if word == "HUNDRED" {
+            current *= 100;
+        } else if word == "THOUSAND" {
+            total += current * 1000;
+            current = 0;
+        } else {
+            return None;
+        }
+    }
+
+    let n = u32::try_from(total + current).ok()?;
+    to_words(n)
+        .filter(|spelled| spelled.split(' ').eq(words.iter().copied()))
+        .map(|_| n)
+}
+
+/// Replaces each run of digits with its spelling, as separate words. Numbers too large to spell
+/// out, or written with leading zeros, are spelled digit by digit.
+pub fn spell(message: &str) -> String {
+    let mut buf = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        let (before, tail) = rest.split_at(start);
+        let end = tail
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (digits, tail) = tail.split_at(end);
+
+        buf.push_str(before);
+        if !buf.is_empty() && !buf.ends_with(' ') {
+            buf.push(' ');
+        }
+
+        let spelled = match digits.parse() {
+            Ok(n) if !(digits.starts_with('0') && digits.len() > 1) => to_words(n),
+            _ => None,
+        };
+
+        match spelled {
+            Some(spelled) => buf.push_str(&spelled),
+            None => {
+                let spelled: Vec<_> = digits.bytes().map(|u| ONES[(u - b'0') as usize]).collect();
+                buf.push_str(&spelled.join(" "));
+            }
+        }
+
+        if !tail.is_empty() && !tail.starts_with(' ') {
+            buf.push(' ');
+        }
+        rest = tail;
+    }
+
+    buf.push_str(rest);
+    buf
+}
+
+/// Replaces spelled-out numbers in `text`, whose words are separated by `word_sep`, with digits.
+pub fn unspell(text: &str, word_sep: char) -> String {
+    let words: Vec<&str> = text.split(word_sep).collect();
+    let mut result: Vec<String> = Vec::with_capacity(words.len());
+    let mut idx = 0;
+
+    while idx < words.len() {
+        let longest = MAX_WORDS.min(words.len() - idx);
+        let number = (1..=longest)
+            .rev()
+            .find_map(|len| from_words(&words[idx..idx + len]).map(|n| (n, len)));
+
+        match number {
+            Some((n, len)) => {
+                result.push(n.to_string());
+                idx += len;
+            }
+            None => {
+                result.push(words[idx].into());
+                idx += 1;
+            }
+        }
+    }
+
+    result.join(&word_sep.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn spells_numbers() {
+        assert_eq!(super::to_words(2).unwrap(), "TWO");
+        assert_eq!(super::to_words(20).unwrap(), "TWENTY");
+        assert_eq!(super::to_words(115).unwrap(), "ONE HUNDRED FIFTEEN");
+        assert_eq!(
+            super::to_words(9999).unwrap(),
+            "NINE THOUSAND NINE HUNDRED NINETY NINE"
+        );
+        assert_eq!(super::to_words(10_000), None);
+    }
+
+    #[test]
+    fn every_spelling_reads_back() {
+        for n in 0..=super::MAX {
+            let spelled = super::to_words(n).unwrap();
+            let words: Vec<_> = spelled.split(' ').collect();
+            assert_eq!(super::from_words(&words), Some(n));
+        }
+    }
+
+    #[test]
+    fn spell_and_unspell_messages() {
+        assert_eq!(super::spell("QTH 20M"), "QTH TWENTY M");
+        assert_eq!(super::spell("007"), "ZERO ZERO SEVEN");
+        assert_eq!(super::unspell("QTH TWENTY M", ' '), "QTH 20 M");
+        assert_eq!(super::unspell("ONE TWO", ' '), "1 2");
+        assert_eq!(super::unspell("HUNDRED", ' '), "HUNDRED");
+    }
+}
@@ -0,0 +1,490 @@
+//! Real-time output backends: anything that can be switched on and off can be keyed with a
+//! message's key events at the right moments.
+
+use std::{
+    io::{self, Write},
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::timing::KeyEvent;
+
+/// Something that can be keyed on and off.
+pub trait Key {
+    /// Keys down for `duration`. Most keys return at once and stay down until `up`; those that
+    /// can only sound for a set time block until it has passed.
+    fn down(&mut self, duration: Duration) -> io::Result<()>;
+    fn up(&mut self) -> io::Result<()>;
+
+    /// Changes the pitch of keys that sound a tone; the rest ignore it.
+    fn set_tone(&mut self, _tone: u32) {}
+}
+
+/// Keys `events` on `key` in real time, leaving it up at the end. Each event is timed from the
+/// start of the message rather than from the end of the one before, so delays do not add up.
+pub fn send(key: &mut impl Key, events: &[KeyEvent]) -> io::Result<()> {
+    send_while(key, events, || true)
+}
+
+/// Like `send`, but stops early, with the key up, once `keep_going` returns false. It is asked
+/// before each event.
+pub fn send_while(
+    key: &mut impl Key,
+    events: &[KeyEvent],
+    keep_going: impl Fn() -> bool,
+) -> io::Result<()> {
+    let mut deadline = Instant::now();
+    for &event in events {
+        if !keep_going() {
+            break;
+        }
+        match event {
+            KeyEvent::Down(duration) => key.down(duration)?,
+            KeyEvent::Up(_) => key.up()?,
+        }
+        deadline += event.duration();
+        thread::sleep(deadline.saturating_duration_since(Instant::now()));
+    }
+    key.up()
+}
+
+/// How the terminal shows the key being down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlashStyle {
+    /// Switch the whole screen to reverse video.
+    Invert,
+    /// Show a block at the start of the current line.
+    Block,
+}
+
+impl FromStr for FlashStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "invert" => Ok(FlashStyle::Invert),
+            "block" => Ok(FlashStyle::Block),
+            _ => Err(format!(
+                "unknown flash style {:?} (expected invert or block)",
+                s
+            )),
+        }
+    }
+}
+
+/// Flashes a terminal, through `out`.
+pub struct Flash<W> {
+    out: W,
+    style: FlashStyle,
+}
+
+impl<W: Write> Flash<W> {
+    pub fn new(out: W, style: FlashStyle) -> Self {
+        Flash { out, style }
+    }
+
+    fn write(&mut self, s: &str) -> io::Result<()> {
+        self.out.write_all(s.as_bytes())?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Key for Flash<W> {
+    fn down(&mut self, _: Duration) -> io::Result<()> {
+        match self.style {
+            FlashStyle::Invert => self.write("\x1b[?5h"),
+            FlashStyle::Block => self.write("\r\u{2588}\u{2588}\u{2588}\u{2588}"),
+        }
+    }
+
+    fn up(&mut self) -> io::Result<()> {
+        match self.style {
+            FlashStyle::Invert => self.write("\x1b[?5l"),
+            FlashStyle::Block => self.write("\r    \r"),
+        }
+    }
+}
+
+/// Rings the terminal bell at the start of each element. The bell sounds for as long as the
+/// terminal decides, so dits and dahs only differ in the gaps after them.
+pub struct Bell<W> {
+    out: W,
+}
+
+impl<W: Write> Bell<W> {
+    pub fn new(out: W) -> Self {
+        Bell { out }
+    }
+}
+
+impl<W: Write> Key for Bell<W> {
+    fn down(&mut self, _: Duration) -> io::Result<()> {
+        self.out.write_all(b"\x07")?;
+        self.out.flush()
+    }
+
+    fn up(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The PC speaker, driven through its Linux input device.
+#[cfg(target_os = "linux")]
+pub struct Speaker {
+    device: std::fs::File,
+    tone: u32,
+}
+
+#[cfg(target_os = "linux")]
+impl Speaker {
+    /// The input device of the `pcspkr` driver.
+    pub const DEVICE: &'static str = "/dev/input/by-path/platform-pcspkr-event-spkr";
+
+    pub fn open(tone: u32) -> io::Result<Self> {
+        let device = std::fs::OpenOptions::new().write(true).open(Self::DEVICE)?;
+        Ok(Speaker { device, tone })
+    }
+
+    /// Writes an `input_event` asking for a tone of `frequency` Hz, or silence for 0.
+    fn sound(&mut self, frequency: u32) -> io::Result<()> {
+        const EV_SND: u16 = 0x12;
+        const SND_TONE: u16 = 0x02;
+
+        // struct input_event { struct timeval time; __u16 type; __u16 code; __s32 value; }, with
+        // the time left zero for the kernel to fill in.
+        let mut event = vec![0u8; 2 * std::mem::size_of::<usize>()];
+        event.extend_from_slice(&EV_SND.to_ne_bytes());
+        event.extend_from_slice(&SND_TONE.to_ne_bytes());
+        event.extend_from_slice(&(frequency as i32).to_ne_bytes());
+        self.device.write_all(&event)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Key for Speaker {
+    fn down(&mut self, _: Duration) -> io::Result<()> {
+        self.sound(self.tone)
+    }
+
+    fn up(&mut self) -> io::Result<()> {
+        self.sound(0)
+    }
+
+    fn set_tone(&mut self, tone: u32) {
+        self.tone = tone;
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Speaker {
+    fn drop(&mut self) {
+        let _ = self.sound(0);
+    }
+}
+
+/// The speaker as driven by the Windows `Beep` call, which sounds for a set time.
+#[cfg(windows)]
+pub struct Speaker {
+    tone: u32,
+}
+
+#[cfg(windows)]
+impl Speaker {
+    pub fn open(tone: u32) -> io::Result<Self> {
+        Ok(Speaker { tone })
+    }
+}
+
+#[cfg(windows)]
+impl Key for Speaker {
+    fn down(&mut self, duration: Duration) -> io::Result<()> {
+        #[link(name = "kernel32")]
+        extern "system" {
+            fn Beep(frequency: u32, duration: u32) -> i32;
+        }
+
+        let ms = duration.as_millis().min(u128::from(u32::MAX)) as u32;
+        // SAFETY: `Beep` takes two integers and has no other preconditions.
+        if unsafe { Beep(self.tone, ms) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn up(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_tone(&mut self, tone: u32) {
+        self.tone = tone;
+    }
+}
+
+/// A Raspberry Pi GPIO pin, driven high while the key is down (or low, if `inverted`).
+#[cfg(feature = "gpio")]
+pub struct Gpio {
+    pin: rppal::gpio::OutputPin,
+    inverted: bool,
+}
+
+#[cfg(feature = "gpio")]
+impl Gpio {
+    /// Claims BCM pin `pin` as an output, starting with the key up.
+    pub fn open(pin: u8, inverted: bool) -> io::Result<Self> {
+        let pin = rppal::gpio::Gpio::new()
+            .and_then(|gpio| gpio.get(pin))
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        let mut gpio = Gpio {
+            pin: pin.into_output(),
+            inverted,
+        };
+        gpio.up()?;
+        Ok(gpio)
+    }
+
+    fn set(&mut self, down: bool) {
+        if down != self.inverted {
+            self.pin.set_high();
+        } else {
+            self.pin.set_low();
+        }
+    }
+}
+
+#[cfg(feature = "gpio")]
+impl Key for Gpio {
+    fn down(&mut self, _: Duration) -> io::Result<()> {
+        self.set(true);
+        Ok(())
+    }
+
+    fn up(&mut self) -> io::Result<()> {
+        self.set(false);
+        Ok(())
+    }
+}
+
+/// The serial port control line used to key a transmitter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyLine {
+    Dtr,
+    Rts,
+}
+
+impl FromStr for KeyLine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dtr" => Ok(KeyLine::Dtr),
+            "rts" => Ok(KeyLine::Rts),
+            _ => Err(format!("unknown key line {:?} (expected dtr or rts)", s)),
+        }
+    }
+}
+
+/// A serial port keying a transmitter through its DTR or RTS line, asserted while the key is
+/// down (or released, if `inverted`). This is the usual transistor or optocoupler interface
+/// between a PC and a rig's key jack.
+pub struct Serial {
+    port: std::fs::File,
+    line: KeyLine,
+    inverted: bool,
+}
+
+impl Serial {
+    /// Opens the serial port at `path`, starting with the key up.
+    pub fn open(path: &std::path::Path, line: KeyLine, inverted: bool) -> io::Result<Self> {
+        let mut serial = Serial {
+            port: Self::open_port(path)?,
+            line,
+            inverted,
+        };
+        serial.up()?;
+        Ok(serial)
+    }
+
+    /// Opens the port without waiting for carrier and without making it the controlling
+    /// terminal, neither of which a keying interface has any use for.
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    pub(crate) fn open_port(path: &std::path::Path) -> io::Result<std::fs::File> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        #[cfg(target_os = "linux")]
+        const FLAGS: i32 = 0o400 | 0o4000;
+        #[cfg(target_os = "macos")]
+        const FLAGS: i32 = 0x20000 | 0x0004;
+
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(FLAGS)
+            .open(path)
+    }
+
+    #[cfg(windows)]
+    pub(crate) fn open_port(path: &std::path::Path) -> io::Result<std::fs::File> {
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+    }
+
+    /// Asserts or releases the key line with the `TIOCMBIS` and `TIOCMBIC` ioctls.
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    fn set(&mut self, down: bool) -> io::Result<()> {
+        use std::os::{
+            raw::{c_int, c_ulong},
+            unix::io::AsRawFd,
+        };
+
+        extern "C" {
+            fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+        }
+
+        #[cfg(target_os = "linux")]
+        const TIOCMBIS: c_ulong = 0x5416;
+        #[cfg(target_os = "linux")]
+        const TIOCMBIC: c_ulong = 0x5417;
+        #[cfg(target_os = "macos")]
+        const TIOCMBIS: c_ulong = 0x8004_746c;
+        #[cfg(target_os = "macos")]
+        const TIOCMBIC: c_ulong = 0x8004_746b;
+        const TIOCM_DTR: c_int = 0x002;
+        const TIOCM_RTS: c_int = 0x004;
+
+        let bits = match self.line {
+            KeyLine::Dtr => TIOCM_DTR,
+            KeyLine::Rts => TIOCM_RTS,
+        };
+        let request = if down != self.inverted {
+            TIOCMBIS
+        } else {
+            TIOCMBIC
+        };
+
+        // SAFETY: the descriptor is open for as long as `self.port` is, and both requests read a
+        // single `int` through the pointer.
+        if unsafe { ioctl(self.port.as_raw_fd(), request, &bits as *const c_int) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    pub(crate) fn open_port(path: &std::path::Path) -> io::Result<std::fs::File> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "no serial port support on this platform ({})",
+                path.display()
+            ),
+        ))
+    }
+
+    /// Asserts or releases the key line with `EscapeCommFunction`.
+    #[cfg(windows)]
+    fn set(&mut self, down: bool) -> io::Result<()> {
+        use std::os::windows::io::AsRawHandle;
+
+        #[link(name = "kernel32")]
+        extern "system" {
+            fn EscapeCommFunction(file: *mut std::ffi::c_void, function: u32) -> i32;
+        }
+
+        const SETRTS: u32 = 3;
+        const CLRRTS: u32 = 4;
+        const SETDTR: u32 = 5;
+        const CLRDTR: u32 = 6;
+
+        let function = match (self.line, down != self.inverted) {
+            (KeyLine::Dtr, true) => SETDTR,
+            (KeyLine::Dtr, false) => CLRDTR,
+            (KeyLine::Rts, true) => SETRTS,
+            (KeyLine::Rts, false) => CLRRTS,
+        };
+
+        // SAFETY: the handle is open for as long as `self.port` is.
+        if unsafe { EscapeCommFunction(self.port.as_raw_handle().cast(), function) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    fn set(&mut self, _: bool) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// Opens the serial port at `path` for a device that speaks a command protocol, setting it up
+/// with `stty` for raw eight-bit data at `baud`, plus any `extra` settings such as `cstopb`.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn open_raw(
+    path: &std::path::Path,
+    baud: u32,
+    extra: &[&str],
+) -> io::Result<std::fs::File> {
+    let port = Serial::open_port(path)?;
+    let output = std::process::Command::new("stty")
+        .arg(baud.to_string())
+        .args(["cs8", "-parenb", "clocal", "raw", "-echo"])
+        .args(extra)
+        .stdin(port.try_clone()?)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(port)
+}
+
+impl Key for Serial {
+    fn down(&mut self, _: Duration) -> io::Result<()> {
+        self.set(true)
+    }
+
+    fn up(&mut self) -> io::Result<()> {
+        self.set(false)
+    }
+}
+
+impl Drop for Serial {
+    fn drop(&mut self) {
+        let _ = self.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bell, Flash, FlashStyle, KeyLine};
+    use crate::timing::{self, Timing};
+
+    #[test]
+    fn flashes_once_per_element() {
+        let events = timing::to_key_events("-- --", &Timing::new(200), None);
+        let mut flash = Flash::new(Vec::new(), FlashStyle::Invert);
+        super::send(&mut flash, &events).unwrap();
+
+        let out = String::from_utf8(flash.out).unwrap();
+        assert_eq!(out.matches("\x1b[?5h").count(), 4);
+        assert!(out.ends_with("\x1b[?5l"));
+    }
+
+    #[test]
+    fn bell_rings_once_per_element() {
+        let events = timing::to_key_events(". -", &Timing::new(200), None);
+        let mut bell = Bell::new(Vec::new());
+        super::send(&mut bell, &events).unwrap();
+        assert_eq!(bell.out, b"\x07\x07");
+    }
+
+    #[test]
+    fn parses_key_lines() {
+        assert_eq!("RTS".parse::<KeyLine>(), Ok(KeyLine::Rts));
+        assert_eq!("dtr".parse::<KeyLine>(), Ok(KeyLine::Dtr));
+        assert!("cts".parse::<KeyLine>().is_err());
+    }
+}
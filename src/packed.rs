@@ -0,0 +1,142 @@
+//! A compact binary container for encoded messages, for storing Morse where every byte counts.
+//!
+//! A container is an eight-byte header followed by the message, two bits a symbol:
+//!
+//! | bytes | contents                                        |
+//! |-------|-------------------------------------------------|
+//! | 0..3  | `MRS`                                           |
+//! | 3     | the version, 1                                  |
+//! | 4..8  | the number of symbols, big-endian               |
+//! | 8..   | the symbols, four to a byte, most significant first, the last byte padded with zeros |
+//!
+//! The symbols are `00` for a dit, `01` for a dah, `10` for the gap between two characters and
+//! `11` for the gap between two words. Gaps between the elements of a character are left
+//! implicit, as they are in the text form.
+
+use std::io;
+
+const MAGIC: &[u8; 3] = b"MRS";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 8;
+
+const DIT: u8 = 0b00;
+const DAH: u8 = 0b01;
+const CHAR_GAP: u8 = 0b10;
+const WORD_GAP: u8 = 0b11;
+
+/// Whether `bytes` start like a container, as opposed to text.
+pub fn is_packed(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Packs a canonically encoded message, such as `encode` returns.
+pub fn pack(encoded: &str) -> io::Result<Vec<u8>> {
+    let mut symbols = Vec::new();
+    for (word_idx, word) in encoded
+        .split('/')
+        .filter(|word| !word.trim().is_empty())
+        .enumerate()
+    {
+        if word_idx > 0 {
+            symbols.push(WORD_GAP);
+        }
+        for (character_idx, character) in word.split_whitespace().enumerate() {
+            if character_idx > 0 {
+                symbols.push(CHAR_GAP);
+            }
+            for element in character.chars() {
+                symbols.push(match element {
+                    '.' => DIT,
+                    '-' => DAH,
+                    other => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("{:?} is not a dot or a dash and can't be packed", other),
+                        ))
+                    }
+                });
+            }
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN + symbols.len().div_ceil(4));
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&(symbols.len() as u32).to_be_bytes());
+    for four in symbols.chunks(4) {
+        let byte = four
+            .iter()
+            .enumerate()
+            .fold(0, |byte, (idx, symbol)| byte | symbol << (6 - 2 * idx));
+        bytes.push(byte);
+    }
+    Ok(bytes)
+}
+
+/// Unpacks a container into a canonically encoded message, such as `decode` takes.
+pub fn unpack(bytes: &[u8]) -> io::Result<String> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    if !is_packed(bytes) || bytes.len() < HEADER_LEN {
+        return Err(invalid("not a packed Morse message".into()));
+    }
+    if bytes[3] != VERSION {
+        return Err(invalid(format!(
+            "packed Morse version {} is not supported",
+            bytes[3]
+        )));
+    }
+    let count = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+    let data = &bytes[HEADER_LEN..];
+    if data.len() != count.div_ceil(4) {
+        return Err(invalid(format!(
+            "packed Morse message should have {} symbols in {} bytes, but has {} bytes",
+            count,
+            count.div_ceil(4),
+            data.len()
+        )));
+    }
+
+    let mut buf = String::with_capacity(count * 2);
+    let symbols = data
+        .iter()
+        .flat_map(|byte| (0..4).map(move |idx| byte >> (6 - 2 * idx) & 0b11))
+        .take(count);
+    for symbol in symbols {
+        buf.push_str(match symbol {
+            DIT => ".",
+            DAH => "-",
+            CHAR_GAP => " ",
+            _ => " / ",
+        });
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn round_trips_messages() {
+        let encoded = crate::encode("CQ DE K1ABC").unwrap();
+        let packed = super::pack(&encoded).unwrap();
+        assert!(super::is_packed(&packed));
+        // 30 elements and 8 gaps.
+        assert_eq!(&packed[..8], b"MRS\x01\x00\x00\x00\x26");
+        assert_eq!(packed.len(), 8 + 10);
+        assert_eq!(super::unpack(&packed).unwrap(), encoded);
+    }
+
+    #[test]
+    fn packs_two_bits_a_symbol() {
+        // Dah, dit, character gap, dit; word gap, dah.
+        let packed = super::pack("-. . / -").unwrap();
+        assert_eq!(&packed[8..], [0b0100_1000, 0b1101_0000]);
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_hold() {
+        assert!(super::pack(". _").is_err());
+        assert!(super::unpack(b"MRS\x02\x00\x00\x00\x00").is_err());
+        assert!(super::unpack(b"MRS\x01\x00\x00\x00\x05\x00").is_err());
+        assert!(super::unpack(b"... ---").is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{Options, Result};
+
+/// A representation a message can be converted from or to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Morse,
+}
+
+impl FromStr for Format {
+    type Err = UnknownFormat;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        CONVERTERS
+            .iter()
+            .find(|converter| converter.name.eq_ignore_ascii_case(s))
+            .map(|converter| converter.format)
+            .ok_or_else(|| UnknownFormat(s.into()))
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownFormat(String);
+
+impl Display for UnknownFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<_> = CONVERTERS.iter().map(|converter| converter.name).collect();
+        write!(
+            f,
+            "unknown format {:?} (expected one of: {})",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+/// Every format is converted through canonical Morse: `read` produces it and `write` consumes
+/// it. Encoding is `text -> morse` and decoding is `morse -> text`.
+struct Converter {
+    format: Format,
+    name: &'static str,
+    read: fn(&str) -> Result<String>,
+    write: fn(&str) -> Result<String>,
+}
+
+static CONVERTERS: &[Converter] = &[
+    Converter {
+        format: Format::Text,
+        name: "text",
+        read: |text| crate::encode(&crate::sanitize(text, &Options::default())),
+        write: crate::decode,
+    },
+    Converter {
+        format: Format::Morse,
+        name: "morse",
+        read: |morse| Ok(morse.trim().into()),
+        write: |morse| Ok(morse.into()),
+    },
+];
+
+fn converter(format: Format) -> &'static Converter {
+    CONVERTERS
+        .iter()
+        .find(|converter| converter.format == format)
+        .expect("every format has a converter")
+}
+
+/// Guesses the format of some input: anything made only of dots, dashes, slashes and whitespace
+/// is taken to be Morse, and anything else text. Blank input has no format.
+pub fn detect(input: &str) -> Option<Format> {
+    if input.trim().is_empty() {
+        None
+    } else if input
+        .chars()
+        .all(|c| c == '.' || c == '-' || c == '/' || c.is_whitespace())
+        && input.contains(&['.', '-'][..])
+    {
+        Some(Format::Morse)
+    } else {
+        Some(Format::Text)
+    }
+}
+
+/// Whether input taken for Morse reads just as well as text: a single run of dots and dashes,
+/// such as `...` or `-`, is as likely to be punctuation as a character.
+pub fn is_ambiguous(input: &str) -> bool {
+    let input = input.trim();
+    detect(input) == Some(Format::Morse) && !input.contains(|c: char| c == '/' || c.is_whitespace())
+}
+
+pub fn convert(input: &str, from: Format, to: Format) -> Result<String> {
+    let morse = (converter(from).read)(input)?;
+    (converter(to).write)(&morse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Format;
+
+    #[test]
+    fn text_to_morse_matches_encode() {
+        let converted = super::convert("Hello world", Format::Text, Format::Morse).unwrap();
+        let encoded = crate::encode("Hello world").unwrap();
+        assert_eq!(converted, encoded);
+    }
+
+    #[test]
+    fn morse_to_text_matches_decode() {
+        let message = ".... . .-.. .-.. --- / .-- --- .-. .-.. -..";
+        let converted = super::convert(message, Format::Morse, Format::Text).unwrap();
+        let decoded = crate::decode(message).unwrap();
+        assert_eq!(converted, decoded);
+    }
+
+    #[test]
+    fn formats_are_detected() {
+        assert_eq!(super::detect("... --- ... / -.-"), Some(Format::Morse));
+        assert_eq!(super::detect("SOS"), Some(Format::Text));
+        assert_eq!(super::detect("- ... 3"), Some(Format::Text));
+        assert_eq!(super::detect("//"), Some(Format::Text));
+        assert_eq!(super::detect("  \n"), None);
+
+        assert!(super::is_ambiguous("..."));
+        assert!(super::is_ambiguous(" - "));
+        assert!(!super::is_ambiguous("... --- ..."));
+        assert!(!super::is_ambiguous("-.-. / --.-"));
+        assert!(!super::is_ambiguous("SOS"));
+    }
+
+    #[test]
+    fn formats_parse_by_name() {
+        assert_eq!("morse".parse::<Format>().unwrap(), Format::Morse);
+        assert_eq!("TEXT".parse::<Format>().unwrap(), Format::Text);
+        assert!("bits".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,268 @@
+//! Generated practice material.
+
+use std::time::Duration;
+
+use crate::{
+    audio, data,
+    rng::Rng,
+    timing::{self, Timing},
+    Result,
+};
+
+/// Characters per group, as in the classic code-group exercise.
+pub const GROUP_LEN: usize = 5;
+
+/// How far each simulated station's speed strays from the one asked for, in WPM either way.
+const SPEED_SPREAD: u32 = 3;
+
+/// How far each simulated station's pitch strays from the one asked for, in Hz either way.
+const TONE_SPREAD: f64 = 100.0;
+
+/// Silence between overs of a simulated contact.
+const TURNAROUND: Duration = Duration::from_millis(1500);
+
+const RST: &[&str] = &["599", "599", "589", "579", "559", "449", "339"];
+const GREETINGS: &[&str] = &["GM", "GA", "GE"];
+
+/// One side of a simulated contact.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Station {
+    pub call: String,
+    pub name: &'static str,
+    pub qth: &'static str,
+    pub timing: Timing,
+    pub tone: f64,
+}
+
+/// One transmission in a contact: which station sent it, and what it sent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Over {
+    pub station: usize,
+    pub text: String,
+}
+
+/// A simulated contact between two stations, from the first station's CQ to both signing off.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Qso {
+    pub stations: [Station; 2],
+    pub overs: Vec<Over>,
+}
+
+impl Qso {
+    /// Makes up a contact, with each station sending a little faster or slower than `timing`,
+    /// Farnsworth spacing and weight kept, and a little higher or lower than `tone`.
+    pub fn generate(timing: Timing, tone: f64, rng: &mut Rng) -> Self {
+        let station = |rng: &mut Rng| {
+            let shift = (rng.next_f64() * f64::from(2 * SPEED_SPREAD + 1)) as u32;
+            let speed = |wpm: u32| (wpm + shift).saturating_sub(SPEED_SPREAD).max(5);
+            Station {
+                call: callsign(rng),
+                name: pick::<&str>(data::OPERATOR_NAMES, rng),
+                qth: pick::<&str>(data::QTHS, rng),
+                timing: Timing {
+                    char_wpm: speed(timing.char_wpm),
+                    effective_wpm: speed(timing.effective_wpm),
+                    ..timing
+                },
+                tone: tone + (rng.next_f64() * 2.0 - 1.0) * TONE_SPREAD,
+            }
+        };
+        let stations = [station(rng), station(rng)];
+
+        let (a, b) = (&stations[0].call, &stations[1].call);
+        let greeting = *pick(GREETINGS, rng);
+        let (rst_a, rst_b) = (*pick(RST, rng), *pick(RST, rng));
+        let texts = [
+            format!("CQ CQ CQ DE {a} {a} {a} K", a = a),
+            format!("{a} DE {b} {b} K", a = a, b = b),
+            format!(
+                "{b} DE {a} {g} TNX FER CALL UR RST {rst} {rst} NAME {name} {name} QTH {qth} {qth} HW? {b} DE {a} K",
+                a = a,
+                b = b,
+                g = greeting,
+                rst = rst_b,
+                name = stations[0].name,
+                qth = stations[0].qth,
+            ),
+            format!(
+                "R {a} DE {b} {g} TNX UR RST {rst} {rst} NAME {name} {name} QTH {qth} {qth} {a} DE {b} K",
+                a = a,
+                b = b,
+                g = greeting,
+                rst = rst_a,
+                name = stations[1].name,
+                qth = stations[1].qth,
+            ),
+            format!("{b} DE {a} TNX FER QSO {name} 73 <SK>", a = a, b = b, name = stations[1].name),
+            format!("73 {name} TU <SK> EE", name = stations[0].name),
+        ];
+
+        let overs = texts
+            .iter()
+            .enumerate()
+            .map(|(idx, text)| Over {
+                station: idx % 2,
+                text: text.clone(),
+            })
+            .collect();
+        Qso { stations, overs }
+    }
+
+    /// Renders the contact as audio, each over at its own station's speed and pitch and otherwise
+    /// sounding like `voice`.
+    pub fn render(&self, voice: &audio::Voice) -> Result<Vec<f32>> {
+        let pause = (TURNAROUND.as_secs_f64() * f64::from(voice.sample_rate)) as usize;
+        let mut samples = Vec::new();
+
+        for (idx, over) in self.overs.iter().enumerate() {
+            if idx > 0 {
+                samples.resize(samples.len() + pause, 0.0);
+            }
+            let station = &self.stations[over.station];
+            let encoded = crate::encode(&over.text)?;
+            let events = timing::to_key_events(&encoded, &station.timing, None);
+            let voice = audio::Voice {
+                tone: station.tone,
+                ..*voice
+            };
+            samples.extend(voice.render(&events));
+        }
+        Ok(samples)
+    }
+}
+
+/// `count` groups of `GROUP_LEN` characters drawn at random from `characters`, or nothing if
+/// there are no characters to draw from.
+pub fn groups(characters: &str, count: usize, rng: &mut Rng) -> String {
+    let characters: Vec<char> = characters.chars().collect();
+    if characters.is_empty() {
+        return String::new();
+    }
+    let groups: Vec<String> = (0..count)
+        .map(|_| (0..GROUP_LEN).map(|_| *pick(&characters, rng)).collect())
+        .collect();
+    groups.join(" ")
+}
+
+/// `count` made-up callsigns, space separated.
+pub fn callsigns(count: usize, rng: &mut Rng) -> String {
+    let calls: Vec<String> = (0..count).map(|_| callsign(rng)).collect();
+    calls.join(" ")
+}
+
+/// A made-up callsign: a prefix, weighted by how busy its region is, then a call-area digit and a
+/// suffix of one to three letters. Three-letter suffixes are the most common, as on the air.
+pub fn callsign(rng: &mut Rng) -> String {
+    let total: u32 = data::CALLSIGN_PREFIXES
+        .iter()
+        .map(|&(_, weight)| weight)
+        .sum();
+    let mut roll = (rng.next_f64() * f64::from(total)) as u32;
+    let prefix = data::CALLSIGN_PREFIXES
+        .iter()
+        .find(|&&(_, weight)| {
+            let found = roll < weight;
+            roll = roll.saturating_sub(weight);
+            found
+        })
+        .map_or("K", |&(prefix, _)| prefix);
+
+    let suffix_len = *pick(&[1, 2, 2, 3, 3, 3], rng);
+    let letters: Vec<char> = ('A'..='Z').collect();
+    let digits: Vec<char> = ('0'..='9').collect();
+
+    let mut call = String::from(prefix);
+    call.push(*pick(&digits, rng));
+    call.extend((0..suffix_len).map(|_| *pick(&letters, rng)));
+    call
+}
+
+/// Checks a character set for use in groups, returning it upper-cased with repeats and whitespace
+/// removed.
+pub fn charset(characters: &str) -> Result<String> {
+    let mut set = String::new();
+    for c in characters.chars().filter(|c| !c.is_whitespace()) {
+        let c = c.to_ascii_uppercase();
+        crate::encode(c.encode_utf8(&mut [0; 4]))?;
+        if !set.contains(c) {
+            set.push(c);
+        }
+    }
+    Ok(set)
+}
+
+/// A uniformly chosen element of a non-empty slice.
+pub(crate) fn pick<'a, T>(items: &'a [T], rng: &mut Rng) -> &'a T {
+    let idx = (rng.next_f64() * items.len() as f64) as usize;
+    &items[idx.min(items.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{rng::Rng, Timing};
+
+    #[test]
+    fn groups_are_reproducible_and_drawn_from_the_set() {
+        let text = super::groups("ABC", 4, &mut Rng::new(9));
+        assert_eq!(text, super::groups("ABC", 4, &mut Rng::new(9)));
+        assert_eq!(text.len(), 4 * 5 + 3);
+        assert!(text.chars().all(|c| " ABC".contains(c)), "{}", text);
+    }
+
+    #[test]
+    fn callsigns_have_prefix_digit_and_suffix() {
+        let mut rng = Rng::new(4);
+        for call in super::callsigns(200, &mut rng).split(' ') {
+            let prefix = crate::data::CALLSIGN_PREFIXES
+                .iter()
+                .map(|&(prefix, _)| prefix)
+                .filter(|prefix| call.starts_with(prefix))
+                .max_by_key(|prefix| prefix.len())
+                .unwrap_or_else(|| panic!("{}", call));
+            let rest = &call[prefix.len()..];
+            assert!(rest.as_bytes()[0].is_ascii_digit(), "{}", call);
+            assert!((2..=4).contains(&rest.len()), "{}", call);
+            assert!(
+                rest[1..].bytes().all(|b| b.is_ascii_uppercase()),
+                "{}",
+                call
+            );
+        }
+    }
+
+    #[test]
+    fn qsos_exchange_calls_and_sign_off() {
+        let qso = super::Qso::generate(Timing::farnsworth(20, 10), 600.0, &mut Rng::new(11));
+        let [a, b] = &qso.stations;
+        assert_ne!(a.call, b.call);
+        assert!((17..=23).contains(&a.timing.char_wpm), "{:?}", a.timing);
+        assert_eq!(
+            a.timing.char_wpm - a.timing.effective_wpm,
+            10,
+            "{:?}",
+            a.timing
+        );
+        assert!((a.tone - 600.0).abs() <= 100.0, "{}", a.tone);
+
+        assert!(qso.overs[0].text.starts_with("CQ CQ CQ DE "));
+        assert!(qso.overs[2]
+            .text
+            .contains(&format!("NAME {} {}", a.name, a.name)));
+        assert!(qso.overs.last().unwrap().text.contains("<SK>"));
+        assert!(qso
+            .overs
+            .iter()
+            .all(|over| crate::encode(&over.text).is_ok()));
+        assert!(!qso.render(&Default::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn charsets_are_cleaned_and_checked() {
+        assert_eq!(super::charset("abc a?").unwrap(), "ABC?");
+        assert!(super::charset("AB~").is_err());
+        assert_eq!(
+            super::groups(&super::charset("  ").unwrap(), 3, &mut Rng::new(1)),
+            ""
+        );
+    }
+}
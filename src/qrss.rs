@@ -0,0 +1,44 @@
+//! QRSS: CW sent so slowly that it is read off a spectrogram rather than copied by ear, which
+//! lets a few milliwatts be seen far below the noise. Speeds are given as the length of a dit,
+//! from a few seconds up to a minute or more, rather than in words per minute.
+
+use std::time::Duration;
+
+use crate::timing::{self, KeyEvent, Timing};
+
+/// Key events for `encoded` with dits `dit` long, dahs `weight` dits long, and gaps in the usual
+/// proportion to them.
+pub fn key_events(encoded: &str, dit: Duration, weight: f64) -> Vec<KeyEvent> {
+    // One WPM is the slowest speed Timing knows; its events are stretched from there.
+    let slowest = Timing::new(1).weighted(weight);
+    let scale = dit.as_secs_f64() / slowest.dit().as_secs_f64();
+    timing::to_key_events(encoded, &slowest, None)
+        .into_iter()
+        .map(|event| match event {
+            KeyEvent::Down(duration) => KeyEvent::Down(duration.mul_f64(scale)),
+            KeyEvent::Up(duration) => KeyEvent::Up(duration.mul_f64(scale)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::timing::KeyEvent::{Down, Up};
+
+    #[test]
+    fn stretches_to_seconds_per_dit() {
+        let secs = Duration::from_secs;
+        assert_eq!(
+            super::key_events(".- / -", secs(6), 3.0),
+            [
+                Down(secs(6)),
+                Up(secs(6)),
+                Down(secs(18)),
+                Up(secs(42)),
+                Down(secs(18))
+            ]
+        );
+    }
+}
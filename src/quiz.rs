@@ -0,0 +1,100 @@
+//! Scoring for ear-training quizzes: how often each character was copied right, and how long the
+//! answers took.
+
+use std::{collections::BTreeMap, time::Duration};
+
+/// How one character has fared so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub asked: u32,
+    pub correct: u32,
+    /// Summed over every question the character was part of.
+    pub latency: Duration,
+}
+
+impl Tally {
+    pub fn accuracy(&self) -> f64 {
+        f64::from(self.correct) / f64::from(self.asked.max(1))
+    }
+
+    pub fn mean_latency(&self) -> Duration {
+        self.latency / self.asked.max(1)
+    }
+}
+
+/// The running score of a quiz session.
+#[derive(Clone, Debug, Default)]
+pub struct Score {
+    pub questions: u32,
+    /// Questions answered exactly right.
+    pub correct: u32,
+    pub latency: Duration,
+    pub characters: BTreeMap<char, Tally>,
+}
+
+impl Score {
+    /// Scores an answer. Each character of `asked` counts as copied if `answer` has it in the same
+    /// place; case and surrounding whitespace are ignored.
+    pub fn record(&mut self, asked: &str, answer: &str, latency: Duration) {
+        let asked = asked.trim().to_uppercase();
+        let answer = answer.trim().to_uppercase();
+
+        self.questions += 1;
+        self.latency += latency;
+        if asked == answer {
+            self.correct += 1;
+        }
+
+        let mut answered = answer.chars();
+        for c in asked.chars() {
+            let tally = self.characters.entry(c).or_default();
+            tally.asked += 1;
+            tally.latency += latency;
+            if answered.next() == Some(c) {
+                tally.correct += 1;
+            }
+        }
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        f64::from(self.correct) / f64::from(self.questions.max(1))
+    }
+
+    pub fn mean_latency(&self) -> Duration {
+        self.latency / self.questions.max(1)
+    }
+
+    /// Characters from least to most accurate, slower answers first among equals.
+    pub fn weakest(&self) -> Vec<(char, Tally)> {
+        let mut characters: Vec<_> = self.characters.iter().map(|(&c, &t)| (c, t)).collect();
+        characters.sort_by(|a, b| {
+            a.1.accuracy()
+                .total_cmp(&b.1.accuracy())
+                .then(b.1.mean_latency().cmp(&a.1.mean_latency()))
+        });
+        characters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn tracks_accuracy_and_latency_per_character() {
+        let mut score = super::Score::default();
+        score.record("K", "k", Duration::from_millis(800));
+        score.record("M", "n", Duration::from_millis(1500));
+        score.record("KM", "KM ", Duration::from_millis(1000));
+
+        assert_eq!(score.questions, 3);
+        assert_eq!(score.correct, 2);
+        assert_eq!(score.mean_latency(), Duration::from_millis(1100));
+
+        let k = score.characters[&'K'];
+        assert_eq!((k.asked, k.correct), (2, 2));
+        assert_eq!(k.mean_latency(), Duration::from_millis(900));
+        assert_eq!(score.weakest()[0].0, 'M');
+        assert_eq!(score.weakest()[0].1.accuracy(), 0.5);
+    }
+}
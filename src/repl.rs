@@ -0,0 +1,196 @@
+//! The interactive prompt, used when a text subcommand is run from a terminal with no message.
+//!
+//! Lines are read as the terminal hands them over, so there is no line editing or arrow-key
+//! recall as with readline; `:history` and `!N` stand in for the latter.
+
+use std::io::{self, BufRead, Write};
+
+use morse::{timing, Options, Timing};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Encode,
+    Decode,
+}
+
+/// What to do with a line typed at the prompt.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Print(String),
+    Quit,
+}
+
+const HELP: &str = "\
+Type a message to translate it, or one of:
+  :mode encode|decode   switch direction
+  :wpm N                set the speed used for timing estimates
+  :history              list previous messages
+  !N                    repeat message N from the history
+  :quit                 leave";
+
+pub struct Repl {
+    mode: Mode,
+    timing: Timing,
+    options: Options,
+    history: Vec<String>,
+}
+
+impl Repl {
+    /// A prompt starting in `mode`, estimating sending times at `timing`.
+    pub fn new(mode: Mode, timing: Timing, options: Options) -> Self {
+        Repl {
+            mode,
+            timing,
+            options,
+            history: Vec::new(),
+        }
+    }
+
+    /// Reads lines from `input` until it ends or the user quits, writing a prompt before each.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        writeln!(output, "morse: {:?} mode, :help for commands", self.mode)?;
+        write!(output, "{}", self.prompt())?;
+        output.flush()?;
+
+        for line in input.lines() {
+            match self.handle(&line?) {
+                Outcome::Print(text) if text.is_empty() => (),
+                Outcome::Print(text) => writeln!(output, "{}", text)?,
+                Outcome::Quit => return Ok(()),
+            }
+            write!(output, "{}", self.prompt())?;
+            output.flush()?;
+        }
+
+        writeln!(output)
+    }
+
+    fn prompt(&self) -> &'static str {
+        match self.mode {
+            Mode::Encode => "encode> ",
+            Mode::Decode => "decode> ",
+        }
+    }
+
+    pub fn handle(&mut self, line: &str) -> Outcome {
+        let line = line.trim();
+
+        if let Some(command) = line.strip_prefix(':') {
+            return self.command(command);
+        }
+
+        let message = match line.strip_prefix('!') {
+            Some(n) => match n.parse::<usize>().ok().and_then(|n| self.history.get(n)) {
+                Some(message) => message.clone(),
+                None => return Outcome::Print(format!("no history entry {:?}", n)),
+            },
+            None => line.into(),
+        };
+
+        if message.is_empty() {
+            return Outcome::Print(String::new());
+        }
+        self.history.push(message.clone());
+        Outcome::Print(self.translate(&message))
+    }
+
+    fn command(&mut self, command: &str) -> Outcome {
+        let mut words = command.split_whitespace();
+        let text = match (words.next(), words.next()) {
+            (Some("quit"), _) | (Some("q"), _) => return Outcome::Quit,
+            (Some("help"), _) => HELP.into(),
+            (Some("mode"), Some("encode")) => {
+                self.mode = Mode::Encode;
+                String::new()
+            }
+            (Some("mode"), Some("decode")) => {
+                self.mode = Mode::Decode;
+                String::new()
+            }
+            (Some("mode"), _) => "usage: :mode encode|decode".into(),
+            (Some("wpm"), Some(wpm)) => match wpm.parse() {
+                Ok(wpm) if wpm > 0 => {
+                    self.timing = Timing::new(wpm);
+                    String::new()
+                }
+                _ => format!("not a speed: {:?}", wpm),
+            },
+            (Some("wpm"), None) => format!("{} wpm", self.timing.char_wpm),
+            (Some("history"), _) => {
+                let lines: Vec<_> = self
+                    .history
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, message)| format!("{:>4}  {}", idx, message))
+                    .collect();
+                lines.join("\n")
+            }
+            _ => format!("unknown command :{}; try :help", command),
+        };
+        Outcome::Print(text)
+    }
+
+    fn translate(&self, message: &str) -> String {
+        let result = match self.mode {
+            Mode::Encode => {
+                morse::encode_with(&morse::sanitize(message, &self.options), &self.options).map(
+                    |encoded| {
+                        let events = timing::to_key_events(&encoded, &self.timing, None);
+                        let seconds: f64 = events
+                            .iter()
+                            .map(|event| event.duration().as_secs_f64())
+                            .sum();
+                        format!(
+                            "{}\n({:.1} s at {} wpm)",
+                            encoded, seconds, self.timing.char_wpm
+                        )
+                    },
+                )
+            }
+            Mode::Decode => morse::decode_with(message, &self.options),
+        };
+
+        result.unwrap_or_else(|e| format!("error: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mode, Outcome, Repl};
+    use morse::{Options, Timing};
+
+    #[test]
+    fn commands_change_mode_and_speed() {
+        let mut repl = Repl::new(Mode::Encode, Timing::default(), Options::default());
+
+        assert_eq!(
+            repl.handle("sos"),
+            Outcome::Print("... --- ...\n(1.6 s at 20 wpm)".into())
+        );
+        assert_eq!(repl.handle(":wpm 10"), Outcome::Print(String::new()));
+        assert_eq!(
+            repl.handle("!0"),
+            Outcome::Print("... --- ...\n(3.2 s at 10 wpm)".into())
+        );
+
+        repl.handle(":mode decode");
+        assert_eq!(repl.handle("... --- ..."), Outcome::Print("SOS".into()));
+        assert_eq!(
+            repl.handle(":history"),
+            Outcome::Print("   0  sos\n   1  sos\n   2  ... --- ...".into())
+        );
+        assert_eq!(repl.handle(":quit"), Outcome::Quit);
+    }
+
+    #[test]
+    fn session_stops_at_quit() {
+        let mut repl = Repl::new(Mode::Decode, Timing::default(), Options::default());
+        let mut output = Vec::new();
+        repl.run(&b".... ..\n:quit\n- ....\n"[..], &mut output)
+            .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("HI"));
+        assert!(!output.contains("TH"));
+    }
+}
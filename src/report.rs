@@ -0,0 +1,176 @@
+//! Structured output for scripts, selected with `--format json`.
+
+use std::{fmt::Write, str::FromStr};
+
+use morse::{haptic::Pattern, sheet::Score};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("unknown format {:?} (expected text or json)", s)),
+        }
+    }
+}
+
+/// What `encode` prints: the encoded message, a report of it, its rhythm as sheet music, a
+/// vibration pattern, its key events as signed milliseconds, or a packed binary message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncodeFormat {
+    Text,
+    Json,
+    Score(Score),
+    Haptic(Pattern),
+    Timing,
+    Bin,
+}
+
+impl FromStr for EncodeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(EncodeFormat::Text),
+            "json" => Ok(EncodeFormat::Json),
+            "timing" => Ok(EncodeFormat::Timing),
+            "bin" => Ok(EncodeFormat::Bin),
+            other => other
+                .parse()
+                .map(EncodeFormat::Score)
+                .or_else(|_| other.parse().map(EncodeFormat::Haptic))
+                .map_err(|_| {
+                    format!(
+                        "unknown format {:?} (expected text, json, abc, lilypond, haptic, \
+                         haptic-csv, timing or bin)",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
+/// One character of the message and its code. `text` is `None` for a code that does not decode.
+/// `confidence` is only given when decoding, and left out of the JSON otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mapping {
+    pub text: Option<String>,
+    pub code: String,
+    pub confidence: Option<f64>,
+}
+
+/// Everything known about one translation.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub input: String,
+    pub output: String,
+    pub characters: Vec<Mapping>,
+    pub warnings: Vec<String>,
+}
+
+impl Report {
+    /// Renders the report as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        let mut buf = String::new();
+        buf.push_str("{\"input\":");
+        push_string(&mut buf, &self.input);
+        buf.push_str(",\"output\":");
+        push_string(&mut buf, &self.output);
+
+        buf.push_str(",\"characters\":[");
+        for (idx, mapping) in self.characters.iter().enumerate() {
+            if idx > 0 {
+                buf.push(',');
+            }
+            buf.push_str("{\"text\":");
+            match &mapping.text {
+                Some(text) => push_string(&mut buf, text),
+                None => buf.push_str("null"),
+            }
+            buf.push_str(",\"code\":");
+            push_string(&mut buf, &mapping.code);
+            if let Some(confidence) = mapping.confidence {
+                let _ = write!(buf, ",\"confidence\":{}", confidence);
+            }
+            buf.push('}');
+        }
+
+        buf.push_str("],\"warnings\":[");
+        for (idx, warning) in self.warnings.iter().enumerate() {
+            if idx > 0 {
+                buf.push(',');
+            }
+            push_string(&mut buf, warning);
+        }
+        buf.push_str("]}");
+        buf
+    }
+}
+
+/// Appends `s` as a JSON string.
+pub fn push_string(buf: &mut String, s: &str) {
+    buf.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(buf, "\\u{:04x}", c as u32);
+            }
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use morse::{haptic::Pattern, sheet::Score};
+
+    use super::{EncodeFormat, Mapping, Report};
+
+    #[test]
+    fn encode_formats_parse() {
+        assert_eq!("Timing".parse(), Ok(EncodeFormat::Timing));
+        assert_eq!("abc".parse(), Ok(EncodeFormat::Score(Score::Abc)));
+        assert_eq!("haptic-csv".parse(), Ok(EncodeFormat::Haptic(Pattern::Csv)));
+        assert_eq!("bin".parse(), Ok(EncodeFormat::Bin));
+    }
+
+    #[test]
+    fn report_renders_as_json() {
+        let report = Report {
+            input: "é\"SO".into(),
+            output: "... ---".into(),
+            characters: vec![
+                Mapping {
+                    text: Some("S".into()),
+                    code: "...".into(),
+                    confidence: None,
+                },
+                Mapping {
+                    text: None,
+                    code: "........-".into(),
+                    confidence: Some(0.0),
+                },
+            ],
+            warnings: vec!["dropped 'é'\n".into()],
+        };
+
+        assert_eq!(
+            report.to_json(),
+            r#"{"input":"é\"SO","output":"... ---","characters":[{"text":"S","code":"..."},{"text":null,"code":"........-","confidence":0}],"warnings":["dropped 'é'\n"]}"#
+        );
+    }
+}
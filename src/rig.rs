@@ -0,0 +1,172 @@
+//! Keying CW through a transceiver under Hamlib's control, by talking to `rigctld` over its
+//! network protocol. The rig does the keying, so messages are handed over as text.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpStream,
+};
+
+use crate::{Options, Result};
+
+/// The port `rigctld` listens on.
+pub const PORT: u16 = 4532;
+
+/// Hamlib's codes for a function the rig or its backend does not have.
+const NOT_IMPLEMENTED: i32 = -4;
+const NOT_AVAILABLE: i32 = -11;
+
+/// A connection to `rigctld`.
+pub struct Rig<S: Read + Write> {
+    stream: BufReader<S>,
+}
+
+impl Rig<TcpStream> {
+    /// Connects to `rigctld` at `address`, on its usual port if none is given.
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let stream = if address.contains(':') {
+            TcpStream::connect(address)?
+        } else {
+            TcpStream::connect((address, PORT))?
+        };
+        Ok(Rig::new(stream))
+    }
+}
+
+impl<S: Read + Write> Rig<S> {
+    pub fn new(stream: S) -> Self {
+        Rig {
+            stream: BufReader::new(stream),
+        }
+    }
+
+    /// Sets the rig's keyer speed, returning false if the rig has no way to do that.
+    pub fn set_speed(&mut self, wpm: u32) -> io::Result<bool> {
+        match self.command(&format!("L KEYSPD {}", wpm)) {
+            Ok(()) => Ok(true),
+            Err(e) if is_unsupported(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Has the rig send `message`, leaving out anything it could not encode and running its
+    /// words together with single spaces.
+    pub fn send(&mut self, message: &str) -> Result<()> {
+        let words: Vec<&str> = message.split_whitespace().collect();
+        let message = crate::sanitize(&words.join(" "), &Options::default());
+        if message.trim().is_empty() {
+            return Ok(());
+        }
+        self.command(&format!("b {}", message.trim().to_uppercase()))
+            .map_err(crate::Error::Io)
+    }
+
+    /// Stops sending, abandoning the rest of the message.
+    pub fn stop(&mut self) -> io::Result<()> {
+        self.command("\\stop_morse")
+    }
+
+    /// Sends a command and reads its `RPRT` reply, turning Hamlib errors into I/O errors.
+    fn command(&mut self, command: &str) -> io::Result<()> {
+        let stream = self.stream.get_mut();
+        stream.write_all(command.as_bytes())?;
+        stream.write_all(b"\n")?;
+        stream.flush()?;
+
+        let mut reply = String::new();
+        if self.stream.read_line(&mut reply)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let code = reply
+            .trim()
+            .strip_prefix("RPRT ")
+            .and_then(|code| code.parse::<i32>().ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unexpected reply from rigctld: {:?}", reply.trim()),
+                )
+            })?;
+        match code {
+            0 => Ok(()),
+            code => Err(io::Error::other(HamlibError(code))),
+        }
+    }
+}
+
+/// An error code reported by Hamlib.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HamlibError(pub i32);
+
+impl std::fmt::Display for HamlibError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.0 {
+            NOT_IMPLEMENTED => f.write_str("rig: function not implemented"),
+            NOT_AVAILABLE => f.write_str("rig: function not available"),
+            code => write!(f, "rig: Hamlib error {}", code),
+        }
+    }
+}
+
+impl std::error::Error for HamlibError {}
+
+fn is_unsupported(e: &io::Error) -> bool {
+    e.get_ref()
+        .and_then(|e| e.downcast_ref::<HamlibError>())
+        .is_some_and(|e| e.0 == NOT_IMPLEMENTED || e.0 == NOT_AVAILABLE)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Read, Write};
+
+    use super::Rig;
+
+    /// rigctld's replies, queued up ahead of time, and every command sent to it.
+    struct Rigctld {
+        replies: Cursor<Vec<u8>>,
+        commands: Vec<u8>,
+    }
+
+    impl Rigctld {
+        fn new(replies: &str) -> Self {
+            Rigctld {
+                replies: Cursor::new(replies.as_bytes().to_vec()),
+                commands: Vec::new(),
+            }
+        }
+    }
+
+    impl Read for Rigctld {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.replies.read(buf)
+        }
+    }
+
+    impl Write for Rigctld {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.commands.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sends_commands() {
+        let mut rig = Rig::new(Rigctld::new("RPRT 0\nRPRT -11\nRPRT 0\nRPRT -9\n"));
+        assert!(rig.set_speed(25).unwrap());
+        assert!(!rig.set_speed(25).unwrap());
+        rig.send("cq  de\nw1aw").unwrap();
+
+        let e = rig.stop().unwrap_err();
+        assert_eq!(e.to_string(), "rig: Hamlib error -9");
+
+        let commands = String::from_utf8(rig.stream.into_inner().commands).unwrap();
+        assert_eq!(
+            commands,
+            "L KEYSPD 25\nL KEYSPD 25\nb CQ DE W1AW\n\\stop_morse\n"
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Searching word lists by the shape of their code, e.g. every word whose code starts `.-..`.
+//!
+//! Patterns are made of `.` and `-`, plus `?` for any one element and `*` for any run of
+//! elements, including none. A pattern without spaces is matched against a word's elements run
+//! together; one with spaces is matched against the code as written, so that it can pin down
+//! where characters start and end.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::Options;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    glob: Vec<char>,
+    spaced: bool,
+}
+
+impl FromStr for Pattern {
+    type Err = InvalidPattern;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(InvalidPattern(s.into()));
+        }
+        if let Some(c) = s
+            .chars()
+            .find(|c| !matches!(c, '.' | '-' | '?' | '*' | ' '))
+        {
+            return Err(InvalidPattern(c.to_string()));
+        }
+
+        Ok(Pattern {
+            glob: s.chars().collect(),
+            spaced: s.contains(' '),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidPattern(String);
+
+impl Display for InvalidPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid pattern {:?} (use . and - for elements, ? for any element and * for any run)",
+            self.0
+        )
+    }
+}
+
+impl Pattern {
+    /// Whether an encoded word, in canonical notation, matches the whole pattern.
+    pub fn matches(&self, encoded: &str) -> bool {
+        let code: Vec<char> = if self.spaced {
+            encoded.chars().collect()
+        } else {
+            encoded.chars().filter(|&c| c != ' ').collect()
+        };
+        glob(&self.glob, &code)
+    }
+}
+
+/// Lists the words, with their codes, whose code matches `pattern`. Words that cannot be encoded
+/// are passed over.
+pub fn search<'a>(
+    pattern: &Pattern,
+    words: impl IntoIterator<Item = &'a str>,
+    options: &Options,
+) -> Vec<(&'a str, String)> {
+    words
+        .into_iter()
+        .filter_map(|word| Some((word, crate::encode_with(word, options).ok()?)))
+        .filter(|(_, encoded)| pattern.matches(encoded))
+        .collect()
+}
+
+/// Matches with backtracking to the most recent `*`, which keeps this linear for patterns with a
+/// single star.
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some('?') if text[t] != ' ' => {
+                p += 1;
+                t += 1;
+            }
+            Some(&c) if c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pattern;
+    use crate::{data, Options};
+
+    #[test]
+    fn patterns_match_with_wildcards() {
+        let pattern: Pattern = ".-..*".parse().unwrap();
+        assert!(pattern.matches(".-.. .. -.-"));
+        assert!(pattern.matches(".- .."));
+        assert!(!pattern.matches("-.. .-"));
+
+        let pattern: Pattern = "?? *".parse().unwrap();
+        assert!(pattern.matches(".. -"));
+        assert!(!pattern.matches(". -"));
+
+        assert!("".parse::<Pattern>().is_err());
+        assert!(".x".parse::<Pattern>().is_err());
+    }
+
+    #[test]
+    fn search_finds_matching_words() {
+        let pattern: Pattern = "...---...".parse().unwrap();
+        let found = super::search(&pattern, data::WORDS.iter().copied(), &Options::default());
+        assert_eq!(found, [("SOS", "... --- ...".to_string())]);
+    }
+}
@@ -0,0 +1,200 @@
+//! Decoding Morse whose letter gaps were lost, such as `...---...`, by searching the ways it can
+//! be split into codes.
+//!
+//! Each split is scored as English text: common letters are cheap, rare letters dearer, and
+//! digits, punctuation and prosigns dearer still, so that fewer, likelier characters win. Words
+//! found in an optional dictionary are preferred over everything else. Gaps that are present are
+//! always kept; only the elements between them are split.
+
+use crate::data;
+
+/// How many partial splits are kept at each point of the search.
+const BEAM_WIDTH: usize = 64;
+
+/// The longest code tried as a single character; prosigns such as `<SOS>` run to nine elements.
+const MAX_CODE_LEN: usize = 9;
+
+/// The cost of a character with no letter frequency, as if it appeared once in 5000.
+const RARE_COST: f64 = 8.5;
+
+/// Taken off the cost of a split that spells a dictionary word, and of a partial split that
+/// could still spell one when the search prunes.
+const DICTIONARY_BONUS: f64 = 100.0;
+
+/// One way of splitting a word.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub text: String,
+    pub codes: Vec<String>,
+    /// Lower is likelier.
+    pub cost: f64,
+}
+
+/// Lists up to `limit` ways of reading one word, in canonical notation with some or all of its
+/// letter gaps missing, best first. `decode` decodes a single code.
+pub fn word(
+    word: &str,
+    decode: impl Fn(&str) -> Option<String>,
+    dictionary: Option<&[&str]>,
+    limit: usize,
+) -> Vec<Candidate> {
+    let word = word.trim();
+    let bytes = word.as_bytes();
+    let mut beams: Vec<Vec<Candidate>> = vec![Vec::new(); bytes.len() + 1];
+    beams[0].push(Candidate {
+        text: String::new(),
+        codes: Vec::new(),
+        cost: 0.0,
+    });
+
+    for start in 0..bytes.len() {
+        let mut beam = std::mem::take(&mut beams[start]);
+        // Ranked with the bonus of the words they could become, so pruning keeps them.
+        let rank = |candidate: &Candidate| {
+            let starts_word = |word: &&str| {
+                word.len() >= candidate.text.len()
+                    && word.is_char_boundary(candidate.text.len())
+                    && word[..candidate.text.len()].eq_ignore_ascii_case(&candidate.text)
+            };
+            match dictionary {
+                Some(dictionary) if dictionary.iter().any(starts_word) => {
+                    candidate.cost - DICTIONARY_BONUS
+                }
+                _ => candidate.cost,
+            }
+        };
+        beam.sort_by(|a, b| rank(a).total_cmp(&rank(b)));
+        beam.truncate(BEAM_WIDTH);
+
+        if bytes[start] == b' ' {
+            beams[start + 1].extend(beam);
+            continue;
+        }
+
+        let end_limit = (start + MAX_CODE_LEN).min(bytes.len());
+        for end in start + 1..=end_limit {
+            if bytes[end - 1] == b' ' {
+                break;
+            }
+            let code = &word[start..end];
+            let text = match decode(code) {
+                Some(text) => text,
+                None => continue,
+            };
+            let cost = cost(&text);
+
+            for partial in &beam {
+                let mut candidate = partial.clone();
+                candidate.text.push_str(&text);
+                candidate.codes.push(code.into());
+                candidate.cost += cost;
+                beams[end].push(candidate);
+            }
+        }
+    }
+
+    let mut candidates = beams.pop().unwrap_or_default();
+    if let Some(dictionary) = dictionary {
+        for candidate in &mut candidates {
+            if dictionary
+                .iter()
+                .any(|word| word.eq_ignore_ascii_case(&candidate.text))
+            {
+                candidate.cost -= DICTIONARY_BONUS;
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+    candidates.dedup_by(|a, b| a.text == b.text);
+    candidates.truncate(limit);
+    candidates
+}
+
+/// Reads a whole message, taking the best reading of each word; words stay separated by `/`.
+pub fn message(
+    message: &str,
+    decode: impl Fn(&str) -> Option<String>,
+    dictionary: Option<&[&str]>,
+    word_sep: char,
+) -> Option<String> {
+    let words: Vec<_> = best(message, decode, dictionary)?
+        .into_iter()
+        .map(|candidate| candidate.text)
+        .collect();
+    Some(words.join(&word_sep.to_string()))
+}
+
+/// The best reading of each word of a message, with the codes it was split into.
+pub fn best(
+    message: &str,
+    decode: impl Fn(&str) -> Option<String>,
+    dictionary: Option<&[&str]>,
+) -> Option<Vec<Candidate>> {
+    message
+        .split('/')
+        .filter(|part| !part.trim().is_empty())
+        .map(|part| word(part, &decode, dictionary, 1).into_iter().next())
+        .collect()
+}
+
+/// The negative log likelihood of a character in English text.
+fn cost(text: &str) -> f64 {
+    let mut chars = text.chars();
+    let frequency = match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii() => data::LETTER_FREQUENCIES
+            .iter()
+            .find(|&&(u, _)| u == c as u8)
+            .map(|&(_, frequency)| frequency),
+        _ => None,
+    };
+
+    match frequency {
+        Some(frequency) => -(frequency / 100.0).ln(),
+        None => RARE_COST,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data;
+
+    fn decode(code: &str) -> Option<String> {
+        crate::decode(code).ok()
+    }
+
+    #[test]
+    fn splits_run_together_letters() {
+        let candidates = super::word("...---...", decode, Some(data::WORDS), 5);
+        assert_eq!(candidates[0].text, "SOS");
+        assert_eq!(candidates[0].codes, ["...", "---", "..."]);
+        assert!(candidates.len() > 1);
+    }
+
+    #[test]
+    fn keeps_existing_gaps() {
+        let candidates = super::word(".... . .-...-..---", decode, Some(data::WORDS), 3);
+        assert_eq!(candidates[0].text, "HELLO");
+
+        let candidates = super::word("-- -", decode, None, 10);
+        assert!(candidates
+            .iter()
+            .all(|c| c.codes.last().map(String::as_str) == Some("-")));
+        assert!(candidates.iter().all(|c| !c.text.contains('O')));
+    }
+
+    #[test]
+    fn segments_whole_messages() {
+        let decoded = super::message("-.-.--.- / -..-..-", decode, Some(data::WORDS), ' ');
+        assert_eq!(decoded.as_deref(), Some("CQ DX"));
+    }
+
+    #[test]
+    fn keeps_dictionary_words_through_pruning() {
+        // Rare letters early on would be pruned on cost alone before the word is complete.
+        let dictionary = ["QZXJ"];
+        let code: String = ["--.-", "--..", "-..-", ".---"].concat();
+        let candidates = super::word(&code, decode, Some(&dictionary), 1);
+        assert_eq!(candidates[0].text, "QZXJ");
+    }
+}
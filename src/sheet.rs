@@ -0,0 +1,193 @@
+//! The rhythm of a message as sheet music, in ABC notation or as LilyPond source: each dit a
+//! sixteenth note, and every other element and gap as many sixteenths as it lasts dits, all on one
+//! pitch. Each word gets a bar of its own.
+
+use std::{fmt::Write, str::FromStr};
+
+use crate::timing::{KeyEvent, Timing};
+
+/// Note lengths in sixteenths that can be written as a single note: plain, dotted and double
+/// dotted, longest first.
+const LENGTHS: [u32; 10] = [16, 14, 12, 8, 7, 6, 4, 3, 2, 1];
+
+/// Words to a line of ABC, to keep lines readable.
+const WORDS_PER_LINE: usize = 4;
+
+/// A sheet music language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Score {
+    Abc,
+    LilyPond,
+}
+
+impl FromStr for Score {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "abc" => Ok(Score::Abc),
+            "lilypond" | "ly" => Ok(Score::LilyPond),
+            _ => Err(format!(
+                "unknown score format {:?} (expected abc or lilypond)",
+                s
+            )),
+        }
+    }
+}
+
+/// Writes key events sent at `timing` as a score titled `title`.
+pub fn write(score: Score, events: &[KeyEvent], timing: &Timing, title: &str) -> String {
+    let words = words(events, timing);
+    match score {
+        Score::Abc => abc(&words, title),
+        Score::LilyPond => lilypond(&words, title),
+    }
+}
+
+/// One element or gap: whether it is a note, and its length in sixteenths.
+type Beat = (bool, u32);
+
+/// Splits key events into words of beats, leaving the gaps between words out.
+fn words(events: &[KeyEvent], timing: &Timing) -> Vec<Vec<Beat>> {
+    let dit = timing.dit().as_secs_f64();
+    let word_gap = (timing.char_gap() + timing.word_gap()).as_secs_f64() / 2.0;
+    let mut words = vec![Vec::new()];
+    for event in events {
+        let duration = event.duration().as_secs_f64();
+        let units = ((duration / dit).round() as u32).max(1);
+        match event {
+            KeyEvent::Up(_) if duration >= word_gap => words.push(Vec::new()),
+            KeyEvent::Up(_) => words.last_mut().expect("never empty").push((false, units)),
+            KeyEvent::Down(_) => words.last_mut().expect("never empty").push((true, units)),
+        }
+    }
+    words.retain(|word| word.iter().any(|&(note, _)| note));
+    words
+}
+
+/// Splits a length in sixteenths into lengths that can each be written as one note.
+fn split(mut units: u32) -> Vec<u32> {
+    let mut parts = Vec::new();
+    while units > 0 {
+        let part = *LENGTHS
+            .iter()
+            .find(|&&length| length <= units)
+            .expect("1 fits anything");
+        parts.push(part);
+        units -= part;
+    }
+    parts
+}
+
+fn abc(words: &[Vec<Beat>], title: &str) -> String {
+    let mut buf = String::new();
+    let _ = writeln!(buf, "X:1");
+    let _ = writeln!(buf, "T:{}", title.trim());
+    let _ = writeln!(buf, "M:none");
+    let _ = writeln!(buf, "L:1/16");
+    let _ = writeln!(buf, "K:C");
+
+    for (idx, word) in words.iter().enumerate() {
+        let beats: Vec<String> = word
+            .iter()
+            .map(|&(note, units)| {
+                let parts: Vec<String> = split(units)
+                    .into_iter()
+                    .map(|part| {
+                        let symbol = if note { "B" } else { "z" };
+                        match part {
+                            1 => symbol.to_string(),
+                            part => format!("{}{}", symbol, part),
+                        }
+                    })
+                    .collect();
+                // Parts of a note are tied; parts of a rest simply follow one another.
+                parts.join(if note { "-" } else { "" })
+            })
+            .collect();
+        let _ = write!(buf, "{} |", beats.join(" "));
+        let end_of_line = (idx + 1) % WORDS_PER_LINE == 0 || idx + 1 == words.len();
+        buf.push(if end_of_line { '\n' } else { ' ' });
+    }
+    buf
+}
+
+fn lilypond(words: &[Vec<Beat>], title: &str) -> String {
+    let mut buf = String::new();
+    let _ = writeln!(buf, "\\version \"2.24.0\"");
+    let _ = writeln!(
+        buf,
+        "\\header {{ title = \"{}\" }}",
+        title.trim().replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    let _ = writeln!(buf, "{{");
+    let _ = writeln!(buf, "  \\cadenzaOn");
+
+    for word in words {
+        let beats: Vec<String> = word
+            .iter()
+            .map(|&(note, units)| {
+                let parts: Vec<String> = split(units)
+                    .into_iter()
+                    .map(|part| {
+                        let symbol = if note { "b'" } else { "r" };
+                        format!("{}{}", symbol, duration(part))
+                    })
+                    .collect();
+                parts.join(if note { "~ " } else { " " })
+            })
+            .collect();
+        let _ = writeln!(buf, "  {} \\bar \"|\"", beats.join(" "));
+    }
+    let _ = writeln!(buf, "}}");
+    buf
+}
+
+/// The LilyPond duration of a length in sixteenths from `LENGTHS`.
+fn duration(units: u32) -> &'static str {
+    match units {
+        16 => "1",
+        14 => "2..",
+        12 => "2.",
+        8 => "2",
+        7 => "4..",
+        6 => "4.",
+        4 => "4",
+        3 => "8.",
+        2 => "8",
+        _ => "16",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Score;
+    use crate::timing::{self, Timing};
+
+    #[test]
+    fn writes_abc() {
+        let timing = Timing::new(20);
+        let events = timing::to_key_events(".- / -", &timing, None);
+        assert_eq!(
+            super::write(Score::Abc, &events, &timing, "A T"),
+            "X:1\nT:A T\nM:none\nL:1/16\nK:C\nB z B3 | B3 |\n"
+        );
+    }
+
+    #[test]
+    fn writes_lilypond_with_ties_for_odd_lengths() {
+        // Dahs five dits long have no single note value.
+        let timing = Timing::new(20).weighted(5.0);
+        let events = timing::to_key_events("-", &timing, None);
+        let score = super::write(Score::LilyPond, &events, &timing, "\"T\"");
+        assert!(score.contains("title = \"\\\"T\\\"\""), "{}", score);
+        assert!(score.contains("  b'4~ b'16 \\bar \"|\"\n"), "{}", score);
+    }
+
+    #[test]
+    fn parses_scores() {
+        assert_eq!("ABC".parse(), Ok(Score::Abc));
+        assert_eq!("ly".parse(), Ok(Score::LilyPond));
+        assert!("midi".parse::<Score>().is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! SKATS (Standard Korean Alphabet Transliteration System).
+//!
+//! Hangul syllables are split into jamo, each jamo is written as one or more Latin letters, and
+//! those letters are sent in International Morse. Decoding reverses this, recomposing runs of
+//! letters into syllables. A doubled consonant between two vowels (e.g. `KK`) is read as a final
+//! followed by an initial rather than as a double initial, since the letters alone cannot tell
+//! the two apart.
+
+use crate::{data, Alphabet, Error, Options, Result};
+
+const SYLLABLES: u32 = 0xac00;
+const LAST_SYLLABLE: u32 = 0xd7a3;
+const VOWEL_COUNT: u32 = 21;
+const FINAL_COUNT: u32 = 28;
+
+/// Encodes a message, transliterating Hangul into SKATS letters first.
+pub fn encode_with(message: &str, options: &Options) -> Result<String> {
+    crate::encode_with(&transliterate(message), &latin(options)).map_err(|e| {
+        let mut seen = Vec::new();
+        match e {
+            Error::Many(errors) => Error::Many(
+                errors
+                    .into_iter()
+                    .map(|e| relocate(e, message, &mut seen))
+                    .collect(),
+            ),
+            e => relocate(e, message, &mut seen),
+        }
+    })
+}
+
+/// Moves the position of an error from the transliterated message back to the original. Hangul
+/// always transliterates to encodable letters and everything else is left alone, so the nth
+/// error for a character is at the nth place it appears in the original message.
+fn relocate(e: Error, message: &str, seen: &mut Vec<char>) -> Error {
+    let c = match &e {
+        Error::At(_, inner) => match **inner {
+            Error::Encode(c) => c,
+            _ => return e,
+        },
+        _ => return e,
+    };
+
+    let n = seen.iter().filter(|&&other| other == c).count();
+    seen.push(c);
+    match message.match_indices(c).nth(n) {
+        Some((_, part)) => e.at(message, part),
+        None => e.without_position(),
+    }
+}
+
+/// Decodes a message, recomposing SKATS letters into Hangul. Also returns the number of
+/// sequences that could not be decoded.
+pub fn decode_counting(message: &str, options: &Options) -> Result<(String, usize)> {
+    let (decoded, unknown) = crate::decode_counting(message, &latin(options))?;
+    Ok((recompose(&decoded), unknown))
+}
+
+/// Returns whether `c` is Hangul that can be written in SKATS.
+pub fn is_encodable(c: char) -> bool {
+    is_syllable(c) || jamo_letters(c).is_some()
+}
+
+/// Writes Hangul syllables and jamo as SKATS letters, leaving everything else alone.
+pub fn transliterate(text: &str) -> String {
+    let mut buf = String::with_capacity(text.len() * 2);
+
+    for c in text.chars() {
+        if is_syllable(c) {
+            let idx = c as u32 - SYLLABLES;
+            let initial = idx / (VOWEL_COUNT * FINAL_COUNT);
+            let vowel = idx % (VOWEL_COUNT * FINAL_COUNT) / FINAL_COUNT;
+            let last = idx % FINAL_COUNT;
+
+            buf.push_str(data::SKATS_INITIALS[initial as usize].1);
+            buf.push_str(data::SKATS_VOWELS[vowel as usize].1);
+            if last > 0 {
+                buf.push_str(data::SKATS_FINALS[last as usize - 1].1);
+            }
+        } else if let Some(letters) = jamo_letters(c) {
+            buf.push_str(letters);
+        } else {
+            buf.push(c);
+        }
+    }
+
+    buf
+}
+
+/// Recomposes each run of SKATS letters into Hangul.
+pub fn recompose(text: &str) -> String {
+    let mut buf = String::with_capacity(text.len());
+    let mut run = Vec::new();
+
+    for c in text.chars() {
+        if c.is_ascii_alphabetic() {
+            run.push(c.to_ascii_uppercase() as u8);
+        } else {
+            compose_into(&run, &mut buf);
+            run.clear();
+            buf.push(c);
+        }
+    }
+
+    compose_into(&run, &mut buf);
+    buf
+}
+
+fn compose_into(letters: &[u8], buf: &mut String) {
+    let mut i = 0;
+
+    while i < letters.len() {
+        let rest = &letters[i..];
+        if !is_consonant(rest[0]) {
+            match longest_match(data::SKATS_VOWELS, rest) {
+                Some((idx, len)) => {
+                    buf.push(data::SKATS_VOWELS[idx].0);
+                    i += len;
+                }
+                None => {
+                    buf.push(rest[0] as char);
+                    i += 1;
+                }
+            }
+            continue;
+        }
+
+        // A double initial is only taken when a vowel follows it directly.
+        let doubled = rest.len() > 2 && rest[1] == rest[0] && is_vowel(rest[2]);
+        let initial_len = if doubled { 2 } else { 1 };
+        let initial = find(data::SKATS_INITIALS, &rest[..initial_len]);
+        let vowel = longest_match(data::SKATS_VOWELS, &rest[initial_len..]);
+
+        let (initial, (vowel, vowel_len)) = match (initial, vowel) {
+            (Some(initial), Some(vowel)) => (initial, vowel),
+            _ => {
+                buf.push(consonant_jamo(rest[0]));
+                i += 1;
+                continue;
+            }
+        };
+
+        // Of the consonants that follow, the last belongs to the next syllable if a vowel comes
+        // after it; the rest form the final, if they make a valid one.
+        let after = &rest[initial_len + vowel_len..];
+        let run = after.iter().take_while(|&&u| is_consonant(u)).count();
+        let available = if run < after.len() {
+            run.saturating_sub(1)
+        } else {
+            run
+        };
+        let (last, last_len) = (1..=available.min(2))
+            .rev()
+            .find_map(|len| find(data::SKATS_FINALS, &after[..len]).map(|idx| (idx + 1, len)))
+            .unwrap_or((0, 0));
+
+        let code =
+            SYLLABLES + (initial as u32 * VOWEL_COUNT + vowel as u32) * FINAL_COUNT + last as u32;
+        buf.extend(std::char::from_u32(code));
+        i += initial_len + vowel_len + last_len;
+    }
+}
+
+fn latin(options: &Options) -> Options {
+    Options {
+        alphabet: Alphabet::Latin,
+        ..*options
+    }
+}
+
+fn is_syllable(c: char) -> bool {
+    (SYLLABLES..=LAST_SYLLABLE).contains(&(c as u32))
+}
+
+fn is_vowel(u: u8) -> bool {
+    b"FEVRWQUYOI".contains(&u)
+}
+
+fn is_consonant(u: u8) -> bool {
+    b"KNTLMPSGJCZDBH".contains(&u)
+}
+
+/// The letters for a standalone (compatibility) jamo.
+fn jamo_letters(c: char) -> Option<&'static str> {
+    data::SKATS_INITIALS
+        .iter()
+        .chain(data::SKATS_VOWELS)
+        .chain(data::SKATS_FINALS)
+        .find(|&&(jamo, _)| jamo == c)
+        .map(|&(_, letters)| letters)
+}
+
+fn consonant_jamo(u: u8) -> char {
+    find(data::SKATS_INITIALS, &[u])
+        .map(|idx| data::SKATS_INITIALS[idx].0)
+        .unwrap_or(u as char)
+}
+
+fn find(table: &[(char, &str)], letters: &[u8]) -> Option<usize> {
+    table
+        .iter()
+        .position(|&(_, candidate)| candidate.as_bytes() == letters)
+}
+
+/// The longest entry of `table` at the start of `letters`, with its length.
+fn longest_match(table: &[(char, &str)], letters: &[u8]) -> Option<(usize, usize)> {
+    (1..=letters.len().min(3))
+        .rev()
+        .find_map(|len| find(table, &letters[..len]).map(|idx| (idx, len)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Alphabet, Options};
+
+    #[test]
+    fn syllables_transliterate() {
+        assert_eq!(super::transliterate("한국어"), "HFNKUKGV");
+        assert_eq!(super::transliterate("서울 2024"), "SVGUL 2024");
+        assert_eq!(super::transliterate("ㅋㅋ"), "ZZ");
+    }
+
+    #[test]
+    fn letters_recompose() {
+        for &text in &["한국어", "서울", "값", "괜찮아", "뛰어", "안녕하세요"] {
+            assert_eq!(super::recompose(&super::transliterate(text)), text);
+        }
+    }
+
+    #[test]
+    fn korean_round_trip() {
+        let options = Options {
+            alphabet: Alphabet::Korean,
+            ..Options::default()
+        };
+
+        let encoded = crate::encode_with("안녕 73", &options).unwrap();
+        assert_eq!(
+            encoded,
+            crate::encode(&super::transliterate("안녕 73")).unwrap()
+        );
+        assert_eq!(crate::decode_with(&encoded, &options).unwrap(), "안녕 73");
+        assert_eq!(crate::sanitize("안녕!", &options), "안녕!");
+    }
+}
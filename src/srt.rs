@@ -0,0 +1,94 @@
+//! SubRip (`.srt`) subtitles for rendered audio, revealing each word just as its last element
+//! ends, so that a practice video shows the answer only once it has been heard.
+
+use std::{fmt::Write, time::Duration};
+
+use crate::timing::{self, KeyEvent, Mark};
+
+/// How long the last word stays up, there being no next word to replace it.
+const LAST_HOLD: Duration = Duration::from_secs(3);
+
+/// When each word sent in `events` ends, found by classifying them as a listener would, so that
+/// it works for any speed and any fist.
+pub fn word_ends(events: &[KeyEvent]) -> Vec<Duration> {
+    // Classifying skips leading gaps, so skip them here too to keep the two in step.
+    let start = events
+        .iter()
+        .position(|event| matches!(event, KeyEvent::Down(_)))
+        .unwrap_or(events.len());
+    let mut elapsed: Duration = events[..start].iter().map(|event| event.duration()).sum();
+
+    let mut ends = Vec::new();
+    let mut last_mark = None;
+    for (event, classified) in events[start..]
+        .iter()
+        .zip(timing::classify(&events[start..]))
+    {
+        if classified.mark == Mark::WordGap {
+            ends.extend(last_mark.take());
+        }
+        elapsed += event.duration();
+        if let KeyEvent::Down(_) = event {
+            last_mark = Some(elapsed);
+        }
+    }
+    ends.extend(last_mark);
+    ends
+}
+
+/// Subtitles showing each word of `message`, sent as `events`, from the moment it ends until the
+/// next one does.
+pub fn write(message: &str, events: &[KeyEvent]) -> String {
+    let ends = word_ends(events);
+    let mut buf = String::new();
+    for (idx, (word, &start)) in message.split_whitespace().zip(&ends).enumerate() {
+        let end = ends.get(idx + 1).copied().unwrap_or(start + LAST_HOLD);
+        let _ = writeln!(buf, "{}", idx + 1);
+        let _ = writeln!(buf, "{} --> {}", timestamp(start), timestamp(end));
+        let _ = writeln!(buf, "{}", word);
+        let _ = writeln!(buf);
+    }
+    buf
+}
+
+/// A time as SubRip writes it: `hh:mm:ss,mmm`.
+fn timestamp(time: Duration) -> String {
+    let ms = time.as_millis();
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::timing::{self, Timing};
+
+    #[test]
+    fn reveals_words_as_they_end() {
+        let encoded = crate::encode("CQ DE K1ABC").unwrap();
+        let events = timing::to_key_events(&encoded, &Timing::new(20), None);
+        let ends = super::word_ends(&events);
+        assert_eq!(ends.len(), 3);
+        let total: Duration = events.iter().map(|event| event.duration()).sum();
+        assert_eq!(ends[2], total);
+
+        let srt = super::write("CQ DE K1ABC", &events);
+        let cues: Vec<&str> = srt.split("\n\n").collect();
+        assert_eq!(cues.len(), 4, "{}", srt);
+        // CQ is 27 units long at 60ms each, and DE ends 18 units later.
+        assert_eq!(cues[0], "1\n00:00:01,620 --> 00:00:02,700\nCQ");
+        assert!(cues[2].ends_with("\nK1ABC"));
+    }
+
+    #[test]
+    fn writes_long_timestamps() {
+        let time = Duration::from_millis(3_723_004);
+        assert_eq!(super::timestamp(time), "01:02:03,004");
+    }
+}
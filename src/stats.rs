@@ -0,0 +1,178 @@
+//! Transmission analysis: how much a message costs to send, and where that cost comes from.
+
+use std::{cmp::Reverse, time::Duration};
+
+use crate::{timing, ErrorPolicy, Options, Result, Timing};
+
+/// The length of a code in dits, counting one dit for each gap between its elements.
+pub fn units(code: &str) -> u32 {
+    let elements = code
+        .bytes()
+        .map(|u| match u {
+            b'.' => 1,
+            b'-' => 3,
+            _ => 0,
+        })
+        .collect::<Vec<u32>>();
+
+    elements.iter().sum::<u32>() + elements.len().saturating_sub(1) as u32
+}
+
+/// One distinct character of a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CharacterStats {
+    /// The character as written, or a whole prosign such as `<SK>`.
+    pub text: String,
+    pub code: String,
+    pub count: usize,
+    pub units: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stats {
+    /// Each distinct character, in order of first appearance.
+    pub characters: Vec<CharacterStats>,
+    pub words: usize,
+    pub dits: usize,
+    pub dahs: usize,
+    /// How long the whole message takes to send, gaps included.
+    pub duration: Duration,
+    /// The word that takes longest to send, and its length in dits.
+    pub longest_word: Option<(String, u32)>,
+    /// Characters left out because they can't be encoded.
+    pub skipped: usize,
+}
+
+impl Stats {
+    /// Analyzes a message as it would be sent with `options` at the given speed. Characters that
+    /// can't be encoded are left out and counted, whatever `options.errors` says.
+    pub fn of(message: &str, options: &Options, timing: &Timing) -> Result<Self> {
+        let strict = Options {
+            errors: ErrorPolicy::Strict,
+            ..*options
+        };
+        let mut characters: Vec<CharacterStats> = Vec::new();
+        let mut longest_word: Option<(String, u32)> = None;
+        let mut skipped = 0;
+
+        for word in message.split_whitespace() {
+            let mut word_units = 0;
+            let mut rest = word;
+            while let Some((text, tail)) = crate::split_character(rest, options) {
+                rest = tail;
+                let text = text.to_uppercase();
+
+                let idx = match characters.iter().position(|c| c.text == text) {
+                    Some(idx) => idx,
+                    None => {
+                        let code = match crate::encode_with(&text, &strict) {
+                            Ok(code) => code,
+                            Err(_) => {
+                                skipped += 1;
+                                continue;
+                            }
+                        };
+                        characters.push(CharacterStats {
+                            units: units(&code),
+                            text,
+                            code,
+                            count: 0,
+                        });
+                        characters.len() - 1
+                    }
+                };
+
+                let character = &mut characters[idx];
+                character.count += 1;
+                if word_units > 0 {
+                    word_units += 3;
+                }
+                word_units += character.units;
+            }
+
+            if longest_word
+                .as_ref()
+                .is_none_or(|&(_, longest)| word_units > longest)
+            {
+                longest_word = Some((word.to_uppercase(), word_units));
+            }
+        }
+
+        let skip = Options {
+            errors: ErrorPolicy::Skip,
+            ..*options
+        };
+        let encoded = crate::encode_with(message.trim(), &skip)?;
+        let duration = timing::to_key_events(&encoded, timing, None)
+            .iter()
+            .map(|event| event.duration())
+            .sum();
+
+        Ok(Stats {
+            words: message.split_whitespace().count(),
+            dits: encoded.matches('.').count(),
+            dahs: encoded.matches('-').count(),
+            duration,
+            longest_word,
+            characters,
+            skipped,
+        })
+    }
+
+    /// The number of characters sent, counting each prosign once.
+    pub fn character_count(&self) -> usize {
+        self.characters.iter().map(|c| c.count).sum()
+    }
+
+    /// The distinct characters, longest to send first.
+    pub fn most_expensive(&self) -> Vec<&CharacterStats> {
+        let mut characters: Vec<_> = self.characters.iter().collect();
+        characters.sort_by_key(|c| Reverse(c.units));
+        characters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stats;
+    use crate::{Options, Timing};
+    use std::time::Duration;
+
+    #[test]
+    fn units_count_elements_and_gaps() {
+        assert_eq!(super::units("."), 1);
+        assert_eq!(super::units(".-"), 5);
+        assert_eq!(super::units("-----"), 19);
+    }
+
+    #[test]
+    fn stats_describe_a_message() {
+        let stats = Stats::of("Paris paris <SK>", &Options::default(), &Timing::new(20)).unwrap();
+        assert_eq!(stats.words, 3);
+        assert_eq!(stats.character_count(), 11);
+        assert_eq!(stats.characters.len(), 6);
+        assert_eq!((stats.dits, stats.dahs), (24, 10));
+        assert_eq!(stats.longest_word, Some(("PARIS".into(), 43)));
+        assert_eq!(stats.most_expensive()[0].text, "<SK>");
+
+        // PARIS is 50 dits including the word gap that follows it.
+        let paris = Stats::of("PARIS", &Options::default(), &Timing::new(20)).unwrap();
+        assert_eq!(paris.duration, Duration::from_millis(60 * 43));
+    }
+
+    #[test]
+    fn stats_skip_what_cannot_be_encoded() {
+        let stats = Stats::of("A # B ~", &Options::default(), &Timing::new(20)).unwrap();
+        assert_eq!(stats.skipped, 2);
+        assert_eq!(stats.character_count(), 2);
+
+        let options = Options {
+            extended: true,
+            ..Options::default()
+        };
+        let stats = Stats::of("CHE", &options, &Timing::new(20)).unwrap();
+        assert_eq!(stats.character_count(), 2);
+        assert_eq!(stats.characters[0].text, "CH");
+        assert_eq!(stats.characters[0].code, "----");
+    }
+}
@@ -0,0 +1,240 @@
+//! "Did you mean" hints for sequences that could not be decoded.
+//!
+//! A bad sequence is most often a good one with a single element added, dropped or misheard, or
+//! two good ones run together, so those are the only edits considered.
+
+use crate::{Error, Standard};
+
+/// The most alternatives offered for a single sequence.
+const MAX_SUGGESTIONS: usize = 4;
+
+/// How many of those are kept for run-together sequences when there are also edits to offer.
+const SPLIT_SLOTS: usize = 2;
+
+/// A decoded sequence that might have been meant instead of a bad one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Suggestion {
+    /// One element added, removed or flipped.
+    Edit { code: String, text: String },
+    /// Two sequences sent without a gap between them.
+    Split {
+        first: (String, String),
+        second: (String, String),
+    },
+}
+
+/// The elements of a standard's codes, each of which an edit may add, drop or swap for another.
+/// American codes have long dashes and the space inside spaced letters besides dots and dashes.
+pub fn elements(standard: Standard) -> &'static [char] {
+    match standard {
+        Standard::International => &['.', '-'],
+        Standard::American => &['.', '-', '_', '=', '\''],
+    }
+}
+
+/// Lists valid sequences near `sequence`, nearest edits first and then run-together sequences,
+/// using `decode` to tell whether a sequence is valid and what it stands for. Edits use the
+/// elements of `standard`.
+pub fn nearby(
+    sequence: &str,
+    standard: Standard,
+    decode: impl Fn(&str) -> Option<String>,
+) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+    let mut tried = vec![sequence.to_string()];
+
+    for code in edits(sequence, elements(standard)) {
+        if tried.contains(&code) {
+            continue;
+        }
+        if let Some(text) = decode(&code) {
+            suggestions.push(Suggestion::Edit {
+                code: code.clone(),
+                text,
+            });
+        }
+        tried.push(code);
+    }
+
+    let mut splits = Vec::new();
+    for (mid, _) in sequence.char_indices().skip(1) {
+        let (first, second) = sequence.split_at(mid);
+        if let (Some(a), Some(b)) = (decode(first), decode(second)) {
+            splits.push(Suggestion::Split {
+                first: (first.into(), a),
+                second: (second.into(), b),
+            });
+        }
+    }
+
+    // Edits are likelier, but not so much that they crowd splits out altogether.
+    splits.truncate(SPLIT_SLOTS);
+    suggestions.truncate(MAX_SUGGESTIONS - splits.len());
+    suggestions.extend(splits);
+    suggestions
+}
+
+/// Words the suggestions as a question, e.g. `did you mean '.-.' (R) or '.-..' (L)?`.
+pub fn did_you_mean(
+    sequence: &str,
+    standard: Standard,
+    decode: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    let suggestions = nearby(sequence, standard, decode);
+    let (last, rest) = suggestions.split_last()?;
+
+    let mut buf = String::from("did you mean ");
+    for (idx, suggestion) in rest.iter().enumerate() {
+        if idx > 0 {
+            buf.push_str(", ");
+        }
+        buf.push_str(&describe(suggestion));
+    }
+    if !rest.is_empty() {
+        buf.push_str(" or ");
+    }
+    buf.push_str(&describe(last));
+    buf.push('?');
+    Some(buf)
+}
+
+/// Attaches a hint to every decoding error in `e` for which there is one.
+pub fn annotate(e: Error, standard: Standard, decode: &impl Fn(&str) -> Option<String>) -> Error {
+    match e {
+        Error::Many(errors) => Error::Many(
+            errors
+                .into_iter()
+                .map(|e| annotate(e, standard, decode))
+                .collect(),
+        ),
+        Error::At(position, e) => Error::At(position, Box::new(annotate(*e, standard, decode))),
+        Error::Decode(sequence) => match did_you_mean(&sequence, standard, decode) {
+            Some(hint) => Error::Hint(Box::new(Error::Decode(sequence)), hint),
+            None => Error::Decode(sequence),
+        },
+        e => e,
+    }
+}
+
+fn describe(suggestion: &Suggestion) -> String {
+    match suggestion {
+        Suggestion::Edit { code, text } => format!("'{}' ({})", code, text),
+        Suggestion::Split { first, second } => format!(
+            "'{}' ({}) followed by '{}' ({})",
+            first.0, first.1, second.0, second.1
+        ),
+    }
+}
+
+/// Every sequence one of `alphabet` away from `sequence`: swaps, then removals, then insertions.
+fn edits(sequence: &str, alphabet: &[char]) -> Vec<String> {
+    let elements: Vec<char> = sequence.chars().collect();
+    let mut edits = Vec::new();
+
+    for idx in 0..elements.len() {
+        if !alphabet.contains(&elements[idx]) {
+            continue;
+        }
+        for &element in alphabet.iter().filter(|&&element| element != elements[idx]) {
+            let mut swapped = elements.clone();
+            swapped[idx] = element;
+            edits.push(swapped.iter().collect());
+        }
+    }
+
+    for idx in 0..elements.len() {
+        let mut removed = elements.clone();
+        removed.remove(idx);
+        if !removed.is_empty() {
+            edits.push(removed.iter().collect());
+        }
+    }
+
+    for idx in 0..=elements.len() {
+        for &element in alphabet {
+            let mut inserted = elements.clone();
+            inserted.insert(idx, element);
+            edits.push(inserted.iter().collect());
+        }
+    }
+
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Suggestion;
+    use crate::{american, Options, Standard};
+
+    fn decode(code: &str) -> Option<String> {
+        crate::decode(code).ok()
+    }
+
+    fn nearby(sequence: &str) -> Vec<Suggestion> {
+        super::nearby(sequence, Standard::International, decode)
+    }
+
+    #[test]
+    fn suggests_single_edits_first() {
+        let suggestions = nearby(".-.-");
+        assert_eq!(
+            suggestions[0],
+            Suggestion::Edit {
+                code: "--.-".into(),
+                text: "Q".into()
+            }
+        );
+
+        let suggestions = nearby("......");
+        assert_eq!(
+            suggestions[0],
+            Suggestion::Edit {
+                code: ".....".into(),
+                text: "5".into()
+            }
+        );
+    }
+
+    #[test]
+    fn suggests_run_together_sequences() {
+        let suggestions = nearby("-.-.--.-");
+        assert!(suggestions.contains(&Suggestion::Split {
+            first: ("-.-.".into(), "C".into()),
+            second: ("--.-".into(), "Q".into()),
+        }));
+
+        let hint = super::did_you_mean("-.-.--.-", Standard::International, decode).unwrap();
+        assert!(hint.starts_with("did you mean "), "{}", hint);
+        assert!(
+            hint.contains("'-.-.' (C) followed by '--.-' (Q)"),
+            "{}",
+            hint
+        );
+
+        // Plenty of single edits, but room is kept for splits.
+        let suggestions = nearby(".-.-");
+        assert_eq!(suggestions.len(), super::MAX_SUGGESTIONS);
+        assert!(suggestions
+            .iter()
+            .any(|suggestion| matches!(suggestion, Suggestion::Split { .. })));
+    }
+
+    #[test]
+    fn edits_american_elements() {
+        let decode = |code: &str| american::decode_with(code, &Options::default()).ok();
+        // L is a long dash, which a misheard dash is one edit from.
+        let suggestions = super::nearby("-", Standard::American, decode);
+        assert!(suggestions.contains(&Suggestion::Edit {
+            code: "_".into(),
+            text: "L".into()
+        }));
+    }
+
+    #[test]
+    fn annotates_nested_errors() {
+        let e = crate::decode("... ........-").unwrap_err();
+        let e = super::annotate(e, Standard::International, &decode);
+        assert!(e.to_string().ends_with('?'), "{}", e);
+        assert!(e.to_string().starts_with("line 1, column 5: "), "{}", e);
+    }
+}
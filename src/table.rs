@@ -0,0 +1,272 @@
+//! Code tables supplied at runtime.
+//!
+//! A table is a flat TOML document mapping each character to its code:
+//!
+//! ```toml
+//! # Letters may be bare keys; anything else must be quoted.
+//! A = ".-"
+//! "?" = "..--.."
+//! ```
+//!
+//! Only this subset of TOML (bare or quoted keys, basic string values, comments) is understood.
+
+use std::collections::HashMap;
+
+use crate::{data, Error, ErrorPolicy, Options, Result};
+
+/// A code table with lookups in both directions.
+#[derive(Clone, Debug, Default)]
+pub struct Table {
+    codes: HashMap<char, String>,
+    characters: HashMap<String, char>,
+}
+
+impl Table {
+    /// The built-in ITU table: letters, digits and punctuation.
+    pub fn international() -> Self {
+        let letters = (b'A'..=b'Z')
+            .chain(b'0'..=b'9')
+            .zip(data::ENCODED_SEQUENCES);
+        let mut table = Table::default();
+        for (u, &code) in letters.chain(data::PUNCTUATION.iter().map(|(u, code)| (*u, code))) {
+            table
+                .insert(u as char, code.into())
+                .expect("built-in table is consistent");
+        }
+        table
+    }
+
+    /// Reads a table, checking that every code is made of dots and dashes and that no character
+    /// or code appears twice.
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut table = Table::default();
+
+        for (idx, line) in source.lines().enumerate() {
+            let invalid = |reason: &str| Error::Table(format!("line {}: {}", idx + 1, reason));
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, rest) = parse_key(line).ok_or_else(|| invalid("expected a key"))?;
+            let rest = rest
+                .trim_start()
+                .strip_prefix('=')
+                .ok_or_else(|| invalid("expected `=` after the key"))?;
+            let (code, rest) =
+                parse_string(rest.trim_start()).ok_or_else(|| invalid("expected a quoted code"))?;
+
+            let rest = rest.trim_start();
+            if !rest.is_empty() && !rest.starts_with('#') {
+                return Err(invalid("unexpected text after the code"));
+            }
+
+            let mut chars = key.chars();
+            let c = match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(invalid("keys must be a single character")),
+            };
+
+            table.insert(c, code).map_err(|reason| invalid(&reason))?;
+        }
+
+        if table.codes.is_empty() {
+            return Err(Error::Table("the table is empty".into()));
+        }
+
+        Ok(table)
+    }
+
+    fn insert(&mut self, c: char, code: String) -> std::result::Result<(), String> {
+        if code.is_empty() || !code.bytes().all(|u| u == b'.' || u == b'-') {
+            return Err(format!("{:?} is not made of dots and dashes", code));
+        }
+        if self.codes.contains_key(&c) {
+            return Err(format!("{:?} is defined twice", c));
+        }
+        if let Some(other) = self.characters.get(&code) {
+            return Err(format!("{:?} is already used for {:?}", code, other));
+        }
+
+        self.characters.insert(code.clone(), c);
+        self.codes.insert(c, code);
+        Ok(())
+    }
+
+    /// The code for `c`, trying its uppercase form if it has no code of its own.
+    pub fn code(&self, c: char) -> Option<&str> {
+        self.codes
+            .get(&c)
+            .or_else(|| {
+                let mut upper = c.to_uppercase();
+                match (upper.next(), upper.next()) {
+                    (Some(upper), None) => self.codes.get(&upper),
+                    _ => None,
+                }
+            })
+            .map(String::as_str)
+    }
+
+    pub fn character(&self, code: &str) -> Option<char> {
+        self.characters.get(code).copied()
+    }
+
+    /// Encodes a message with this table, in canonical notation.
+    pub fn encode_with(&self, message: &str, options: &Options) -> Result<String> {
+        let mut buf = String::with_capacity(message.len() * 4);
+        let mut errors = Vec::new();
+
+        for (idx, c) in message.char_indices() {
+            if c == ' ' {
+                buf.push_str(" /");
+                continue;
+            }
+
+            let code = match self.code(c) {
+                Some(code) => Some(code),
+                None => {
+                    let e = Error::Encode(c).at(message, &message[idx..idx + c.len_utf8()]);
+                    match options.errors {
+                        ErrorPolicy::Strict => return Err(e),
+                        ErrorPolicy::Skip => None,
+                        ErrorPolicy::Collect => {
+                            errors.push(e);
+                            None
+                        }
+                        ErrorPolicy::Replace(replacement) => {
+                            Some(self.code(replacement).ok_or(Error::Encode(replacement))?)
+                        }
+                    }
+                }
+            };
+
+            if let Some(code) = code {
+                if !buf.is_empty() {
+                    buf.push(' ');
+                }
+                buf.push_str(code);
+            }
+        }
+
+        crate::collected(buf, errors)
+    }
+
+    /// Decodes a canonical message with this table, also returning the number of sequences that
+    /// could not be decoded and were instead handled according to `options.errors`.
+    pub fn decode_counting(&self, message: &str, options: &Options) -> Result<(String, usize)> {
+        let mut words = Vec::new();
+        let mut unknown = 0;
+        let mut errors = Vec::new();
+
+        for word in message.split('/').filter(|word| !word.trim().is_empty()) {
+            let mut buf = String::new();
+            for character in word.split_whitespace() {
+                match self.character(character) {
+                    Some(c) => buf.push(c),
+                    None => {
+                        unknown += 1;
+                        let e = Error::Decode(character.into()).at(message, character);
+                        match options.errors {
+                            ErrorPolicy::Strict => return Err(e),
+                            ErrorPolicy::Skip => (),
+                            ErrorPolicy::Collect => errors.push(e),
+                            ErrorPolicy::Replace(replacement) => buf.push(replacement),
+                        }
+                    }
+                }
+            }
+            words.push(buf);
+        }
+
+        let decoded = words.join(&options.word_sep.to_string());
+        crate::collected((decoded, unknown), errors)
+    }
+}
+
+fn parse_key(line: &str) -> Option<(String, &str)> {
+    if line.starts_with('"') {
+        return parse_string(line);
+    }
+
+    let end = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(line.len());
+    if end == 0 {
+        return None;
+    }
+    Some((line[..end].into(), &line[end..]))
+}
+
+/// Reads a basic string, returning its contents and whatever follows the closing quote.
+fn parse_string(s: &str) -> Option<(String, &str)> {
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    let mut buf = String::new();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Some((buf, &s[idx + 2..])),
+            '\\' => match chars.next()?.1 {
+                '"' => buf.push('"'),
+                '\\' => buf.push('\\'),
+                't' => buf.push('\t'),
+                'n' => buf.push('\n'),
+                _ => return None,
+            },
+            c => buf.push(c),
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Table;
+    use crate::Options;
+
+    #[test]
+    fn custom_table_round_trips() {
+        let table = Table::parse(
+            r#"
+            # A deliberately scrambled table.
+            A = "-"
+            B = "."   # trailing comments are fine
+            "?" = "--.."
+            "\"" = ".-.-"
+            "#,
+        )
+        .unwrap();
+
+        let options = Options::default();
+        let encoded = table.encode_with("ab ?\"", &options).unwrap();
+        assert_eq!(encoded, "- . / --.. .-.-");
+
+        let (decoded, unknown) = table.decode_counting(&encoded, &options).unwrap();
+        assert_eq!(decoded, "AB ?\"");
+        assert_eq!(unknown, 0);
+    }
+
+    #[test]
+    fn invalid_tables_are_rejected() {
+        assert!(Table::parse("").is_err());
+        assert!(Table::parse("A = \".-\"\nB = \".-\"").is_err());
+        assert!(Table::parse("A = \".-\"\nA = \"-\"").is_err());
+        assert!(Table::parse("A = \".x\"").is_err());
+        assert!(Table::parse("AB = \".-\"").is_err());
+        assert!(Table::parse("A = .-").is_err());
+
+        let e = Table::parse("A = \".-\"\n\nB = \"-\" x").unwrap_err();
+        assert!(e.to_string().contains("line 3"), "{}", e);
+    }
+
+    #[test]
+    fn international_table_matches_encode() {
+        let table = Table::international();
+        let message = "HELLO, WORLD 73?";
+        assert_eq!(
+            table.encode_with(message, &Options::default()).unwrap(),
+            crate::encode(message).unwrap()
+        );
+    }
+}
@@ -0,0 +1,741 @@
+//! The timing model shared by every output backend: speeds in words per minute are turned into
+//! element and gap durations here, and consumers only ever see the resulting key events.
+
+use std::{str::FromStr, time::Duration};
+
+pub use crate::elements::{KeyEvent, Timing, WEIGHT};
+
+use crate::{rng::Rng, Error, Result};
+
+/// A dah length in dits, as given on the command line: from 2 to 4, around the standard
+/// [`WEIGHT`] of 3.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Weight(pub f64);
+
+impl FromStr for Weight {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().parse::<f64>() {
+            Ok(weight) if (2.0..=4.0).contains(&weight) => Ok(Weight(weight)),
+            _ => Err(format!(
+                "bad weight {:?} (expected a dah length from 2 to 4 dits, e.g. 3.2)",
+                s
+            )),
+        }
+    }
+}
+
+/// Random variation applied to every element and gap, to imitate a hand-keyed signal.
+#[derive(Clone, Debug)]
+pub struct Jitter {
+    kind: JitterKind,
+    rng: Rng,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum JitterKind {
+    /// Each duration is independently within `percent` percent of nominal.
+    Uniform { percent: f64 },
+    /// A pace that wanders slowly, plus a little error on each element and more on each gap.
+    Human { amount: f64, pace: f64 },
+}
+
+/// At full humanization: how far the pace wanders, how far elements and gaps stray from it, as
+/// standard deviations relative to nominal, and how much of the pace carries over from one
+/// element to the next.
+const HUMAN_PACE: f64 = 0.08;
+const HUMAN_MARK: f64 = 0.07;
+const HUMAN_GAP: f64 = 0.15;
+const HUMAN_CARRY: f64 = 0.95;
+
+impl Jitter {
+    /// Each duration will be scaled by a random factor within `percent` percent of nominal.
+    pub fn new(percent: f64, rng: Rng) -> Self {
+        Jitter {
+            kind: JitterKind::Uniform {
+                percent: percent.clamp(0.0, 100.0),
+            },
+            rng,
+        }
+    }
+
+    /// Jitter like a human fist's, from 0 for none to 1 for a sloppy one. Errors are correlated,
+    /// as an operator speeds up and slows down over a few characters rather than from one
+    /// element to the next, and spacing is less even than the elements themselves.
+    pub fn human(amount: f64, rng: Rng) -> Self {
+        Jitter {
+            kind: JitterKind::Human {
+                amount: amount.clamp(0.0, 1.0),
+                pace: 0.0,
+            },
+            rng,
+        }
+    }
+
+    pub(crate) fn apply(&mut self, event: KeyEvent) -> KeyEvent {
+        let factor = match &mut self.kind {
+            JitterKind::Uniform { percent } => {
+                1.0 + (self.rng.next_f64() * 2.0 - 1.0) * *percent / 100.0
+            }
+            JitterKind::Human { amount, pace } => {
+                let step = (1.0 - HUMAN_CARRY * HUMAN_CARRY).sqrt();
+                *pace =
+                    HUMAN_CARRY * *pace + step * HUMAN_PACE * *amount * self.rng.next_gaussian();
+                let stray = match event {
+                    KeyEvent::Down(_) => HUMAN_MARK,
+                    KeyEvent::Up(_) => HUMAN_GAP,
+                };
+                // Kept well clear of zero, which no fist gets near.
+                (1.0 + *pace + stray * *amount * self.rng.next_gaussian()).max(0.3)
+            }
+        };
+        match event {
+            KeyEvent::Down(duration) => KeyEvent::Down(duration.mul_f64(factor)),
+            KeyEvent::Up(duration) => KeyEvent::Up(duration.mul_f64(factor)),
+        }
+    }
+}
+
+/// Key events as they are produced, with jitter applied to each if there is any. Shared by the
+/// expansions of every code table.
+pub(crate) struct Keying<'a> {
+    events: Vec<KeyEvent>,
+    jitter: Option<&'a mut Jitter>,
+}
+
+impl<'a> Keying<'a> {
+    pub(crate) fn new(jitter: Option<&'a mut Jitter>) -> Self {
+        Keying {
+            events: Vec::new(),
+            jitter,
+        }
+    }
+
+    pub(crate) fn push(&mut self, event: KeyEvent) {
+        self.events.push(match self.jitter.as_deref_mut() {
+            Some(jitter) => jitter.apply(event),
+            None => event,
+        });
+    }
+
+    pub(crate) fn finish(self) -> Vec<KeyEvent> {
+        self.events
+    }
+}
+
+/// Expands an encoded message into key events. Dits are one unit long and dahs as many as the
+/// weight of `timing`, elements are separated by one unit, and characters and words by the gaps
+/// given by `timing`.
+pub fn to_key_events(encoded: &str, timing: &Timing, jitter: Option<&mut Jitter>) -> Vec<KeyEvent> {
+    let mut events = Keying::new(jitter);
+
+    for (word_idx, word) in encoded
+        .split('/')
+        .filter(|word| !word.trim().is_empty())
+        .enumerate()
+    {
+        if word_idx > 0 {
+            events.push(KeyEvent::Up(timing.word_gap()));
+        }
+
+        for (character_idx, character) in word.split_whitespace().enumerate() {
+            if character_idx > 0 {
+                events.push(KeyEvent::Up(timing.char_gap()));
+            }
+
+            for (element_idx, element) in character.bytes().enumerate() {
+                if element_idx > 0 {
+                    events.push(KeyEvent::Up(timing.dit()));
+                }
+
+                match element {
+                    b'.' => events.push(KeyEvent::Down(timing.dit())),
+                    b'-' => events.push(KeyEvent::Down(timing.dah())),
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    events.finish()
+}
+
+/// What a key event was taken to be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mark {
+    Dit,
+    Dah,
+    ElementGap,
+    CharGap,
+    WordGap,
+}
+
+/// A classified key event. `confidence` runs from 0, for a duration right on the boundary between
+/// two kinds of mark, to 1, for one at least a third again as far from it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Classified {
+    pub mark: Mark,
+    pub confidence: f64,
+}
+
+/// Recovers a canonically encoded message from key events of unknown speed, by classifying each
+/// key-down period as a dit or a dah and each key-up period as an element, character or word gap.
+pub fn from_key_events(events: &[KeyEvent]) -> String {
+    let mut buf = String::new();
+    for classified in classify(events) {
+        match classified.mark {
+            Mark::Dit => buf.push('.'),
+            Mark::Dah => buf.push('-'),
+            Mark::ElementGap => (),
+            Mark::CharGap => buf.push(' '),
+            Mark::WordGap => buf.push_str(" / "),
+        }
+    }
+    buf
+}
+
+/// Writes key events as whitespace-separated milliseconds, positive for key-down periods and
+/// negative for key-up ones, e.g. `60 -60 180`.
+pub fn write_key_events(events: &[KeyEvent]) -> String {
+    let values: Vec<String> = events
+        .iter()
+        .map(|event| {
+            let ms = event.duration().as_secs_f64() * 1000.0;
+            match event {
+                KeyEvent::Down(_) => format!("{}", ms),
+                KeyEvent::Up(_) => format!("-{}", ms),
+            }
+        })
+        .collect();
+    values.join(" ")
+}
+
+/// Reads key events written by `write_key_events`, or as alternating key-down and key-up times
+/// that are all positive, starting with a key-down, as `haptic` writes them. Values may be
+/// separated by commas as well as whitespace, and brackets around them are ignored.
+pub fn read_key_events(text: &str) -> Result<Vec<KeyEvent>> {
+    let values = text
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | ']'))
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse()
+                .ok()
+                .filter(|ms: &f64| *ms != 0.0)
+                .and_then(|ms| Some((ms, Duration::try_from_secs_f64(ms.abs() / 1000.0).ok()?)))
+                .ok_or_else(|| Error::Decode(value.into()).at(text, value))
+        })
+        .collect::<Result<Vec<(f64, Duration)>>>()?;
+
+    let alternating = values.iter().all(|&(ms, _)| ms > 0.0);
+    Ok(values
+        .iter()
+        .enumerate()
+        .map(|(idx, &(ms, duration))| {
+            if ms > 0.0 && !(alternating && idx % 2 == 1) {
+                KeyEvent::Down(duration)
+            } else {
+                KeyEvent::Up(duration)
+            }
+        })
+        .collect())
+}
+
+/// Classifies key events of unknown speed. Gaps before the first key-down and after the last are
+/// left out.
+pub fn classify(events: &[KeyEvent]) -> Vec<Classified> {
+    let unit = match estimate_unit(events) {
+        Some(unit) => unit,
+        None => return Vec::new(),
+    };
+    let units = vec![unit; events.len()];
+    mark(events, &units)
+        .into_iter()
+        .map(|(_, classified)| classified)
+        .collect()
+}
+
+/// How quickly `classify_tracking` follows a change of speed: the weight each new dit or dah gets
+/// in the running estimate of the dit length.
+const TRACKING: f64 = 0.3;
+
+/// How many key-down periods the starting speed of `classify_tracking` is estimated from.
+const TRACKING_SEED: usize = 12;
+
+/// Classifies key events like `classify`, but follows the sender's speed as it changes instead of
+/// assuming one speed throughout: every dit or dah nudges the estimated dit length toward its own.
+/// Each mark comes with the speed in force when it was read, in WPM.
+pub fn classify_tracking(events: &[KeyEvent]) -> Vec<(Classified, f64)> {
+    let seed = events
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| matches!(event, KeyEvent::Down(_)))
+        .nth(TRACKING_SEED)
+        .map_or(events.len(), |(idx, _)| idx);
+    let mut unit = match estimate_unit(&events[..seed]) {
+        Some(unit) => unit,
+        None => return Vec::new(),
+    };
+
+    let units: Vec<f64> = events
+        .iter()
+        .map(|&event| {
+            let current = unit;
+            if let KeyEvent::Down(duration) = event {
+                let len = duration.as_secs_f64();
+                let elements = if len < unit * 2.0 { 1.0 } else { 3.0 };
+                unit += (len / elements - unit) * TRACKING;
+            }
+            current
+        })
+        .collect();
+    mark(events, &units)
+        .into_iter()
+        .map(|(idx, classified)| (classified, 1.2 / units[idx]))
+        .collect()
+}
+
+/// Recovers a message like `from_key_events` while following changes of speed, as canonically
+/// encoded words, each with the average speed it was sent at in WPM.
+pub fn from_key_events_tracking(events: &[KeyEvent]) -> Vec<(String, f64)> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut speeds = Vec::new();
+
+    let mut finish = |word: &mut String, speeds: &mut Vec<f64>| {
+        if !word.is_empty() {
+            let wpm = speeds.iter().sum::<f64>() / speeds.len().max(1) as f64;
+            words.push((std::mem::take(word), wpm));
+        }
+        speeds.clear();
+    };
+    for (classified, wpm) in classify_tracking(events) {
+        match classified.mark {
+            Mark::Dit => word.push('.'),
+            Mark::Dah => word.push('-'),
+            Mark::ElementGap => (),
+            Mark::CharGap => word.push(' '),
+            Mark::WordGap => finish(&mut word, &mut speeds),
+        }
+        if matches!(classified.mark, Mark::Dit | Mark::Dah) {
+            speeds.push(wpm);
+        }
+    }
+    finish(&mut word, &mut speeds);
+    words
+}
+
+/// Classifies each event given the dit length in force at it, in seconds, returning the marks
+/// with the index of the event each came from.
+fn mark(events: &[KeyEvent], units: &[f64]) -> Vec<(usize, Classified)> {
+    let word_gap = word_gap_threshold(
+        events
+            .iter()
+            .zip(units)
+            .filter(|(event, _)| matches!(event, KeyEvent::Up(_)))
+            .map(|(event, unit)| event.duration().as_secs_f64() / unit),
+    );
+
+    let mut marks = Vec::new();
+    for (idx, (&event, unit)) in events.iter().zip(units).enumerate() {
+        let units = event.duration().as_secs_f64() / unit;
+        let (mark, threshold) = match event {
+            KeyEvent::Down(_) if units < 2.0 => (Mark::Dit, 2.0),
+            KeyEvent::Down(_) => (Mark::Dah, 2.0),
+            KeyEvent::Up(_) if marks.is_empty() => continue,
+            KeyEvent::Up(_) if units < 2.0 => (Mark::ElementGap, 2.0),
+            // Character gaps can be mistaken for element gaps or for word gaps, whichever is nearer.
+            KeyEvent::Up(_) if units < word_gap => {
+                let nearer = if units * units < 2.0 * word_gap {
+                    2.0
+                } else {
+                    word_gap
+                };
+                (Mark::CharGap, nearer)
+            }
+            KeyEvent::Up(_) => (Mark::WordGap, word_gap),
+        };
+        marks.push((
+            idx,
+            Classified {
+                mark,
+                confidence: margin(units, threshold),
+            },
+        ));
+    }
+
+    while marks
+        .last()
+        .is_some_and(|(_, classified)| !matches!(classified.mark, Mark::Dit | Mark::Dah))
+    {
+        marks.pop();
+    }
+    marks
+}
+
+/// The speed and steadiness of a fist, as recovered from its key events.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rhythm {
+    pub wpm: f64,
+    /// From 0 to 1: one minus the average amount by which dits, dahs and the gaps between them
+    /// stray from their ideal lengths. Character and word gaps are left out, since Farnsworth
+    /// spacing stretches them on purpose.
+    pub consistency: f64,
+}
+
+/// Measures the rhythm of key events of unknown speed.
+pub fn rhythm(events: &[KeyEvent]) -> Option<Rhythm> {
+    let unit = estimate_unit(events)?;
+    let deviations: Vec<f64> = events
+        .iter()
+        .filter_map(|&event| {
+            let units = event.duration().as_secs_f64() / unit;
+            let ideal = match event {
+                KeyEvent::Down(_) if units < 2.0 => 1.0,
+                KeyEvent::Down(_) => 3.0,
+                KeyEvent::Up(_) if units < 2.0 => 1.0,
+                KeyEvent::Up(_) => return None,
+            };
+            Some((units / ideal - 1.0).abs())
+        })
+        .collect();
+
+    let spread = deviations.iter().sum::<f64>() / deviations.len().max(1) as f64;
+    Some(Rhythm {
+        wpm: 1.2 / unit,
+        consistency: (1.0 - spread).max(0.0),
+    })
+}
+
+/// How far `units` lies from `threshold`, on a log scale, as a confidence from 0 to 1.
+fn margin(units: f64, threshold: f64) -> f64 {
+    ((units / threshold).ln().abs() / (4.0f64 / 3.0).ln()).min(1.0)
+}
+
+/// The length, in dits, above which a gap is taken to be a word gap. Character and word gaps are
+/// told apart by clustering when both are present, which copes with Farnsworth spacing;
+/// otherwise anything over five units is taken to be a word gap.
+fn word_gap_threshold(gaps: impl Iterator<Item = f64>) -> f64 {
+    let gaps: Vec<f64> = gaps.filter(|&units| units >= 2.0).collect();
+
+    let shortest = gaps.iter().copied().fold(f64::INFINITY, f64::min);
+    let longest = gaps.iter().copied().fold(0.0, f64::max);
+    if !gaps.is_empty() && longest >= shortest * 2.0 {
+        two_means_threshold(&gaps, shortest, longest)
+    } else {
+        5.0
+    }
+}
+
+/// Estimates the length of a dit, in seconds. Key-down periods are split into a short (dit) and
+/// long (dah) cluster; when they do not separate, the shortest gap decides whether they are all
+/// dits or all dahs.
+fn estimate_unit(events: &[KeyEvent]) -> Option<f64> {
+    let downs: Vec<f64> = events
+        .iter()
+        .filter(|event| matches!(event, KeyEvent::Down(_)))
+        .map(|event| event.duration().as_secs_f64())
+        .collect();
+
+    let shortest = downs.iter().copied().fold(f64::INFINITY, f64::min);
+    let longest = downs.iter().copied().fold(0.0, f64::max);
+    if downs.is_empty() || shortest <= 0.0 {
+        return None;
+    }
+
+    if longest >= shortest * 2.0 {
+        let threshold = two_means_threshold(&downs, shortest, longest);
+        let dits: Vec<f64> = downs.into_iter().filter(|&d| d < threshold).collect();
+        return Some(dits.iter().sum::<f64>() / dits.len() as f64);
+    }
+
+    let shortest_gap = events
+        .iter()
+        .filter(|event| matches!(event, KeyEvent::Up(_)))
+        .map(|event| event.duration().as_secs_f64())
+        .fold(f64::INFINITY, f64::min);
+
+    if shortest_gap * 2.0 <= shortest {
+        Some(shortest / 3.0)
+    } else {
+        Some(shortest)
+    }
+}
+
+fn two_means_threshold(values: &[f64], mut low: f64, mut high: f64) -> f64 {
+    for _ in 0..16 {
+        let threshold = (low + high) / 2.0;
+        let (short, long): (Vec<f64>, Vec<f64>) = values.iter().partition(|&&v| v < threshold);
+        if short.is_empty() || long.is_empty() {
+            break;
+        }
+        low = short.iter().sum::<f64>() / short.len() as f64;
+        high = long.iter().sum::<f64>() / long.len() as f64;
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Jitter,
+        KeyEvent::{Down, Up},
+        Mark, Timing, Weight,
+    };
+    use crate::rng::Rng;
+    use std::{str::FromStr, time::Duration};
+
+    #[test]
+    fn key_events_follow_standard_spacing() {
+        let timing = Timing::new(20);
+        let unit = Duration::from_millis(60);
+        let events = super::to_key_events(".- / -", &timing, None);
+        assert_eq!(
+            events,
+            [
+                Down(unit),
+                Up(unit),
+                Down(unit * 3),
+                Up(unit * 7),
+                Down(unit * 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn paris_takes_one_minute_per_word() {
+        let encoded = crate::encode("PARIS PARIS").unwrap();
+        for &timing in &[Timing::new(5), Timing::new(20), Timing::farnsworth(18, 8)] {
+            let events = super::to_key_events(&encoded, &timing, None);
+            let total: Duration = events.iter().map(|event| event.duration()).sum();
+
+            // The second word's trailing word gap is never sent.
+            let expected =
+                120.0 / f64::from(timing.effective_wpm) - timing.word_gap().as_secs_f64();
+            assert!(
+                (total.as_secs_f64() - expected).abs() < 0.001,
+                "{:?}: {:?}",
+                timing,
+                total
+            );
+        }
+    }
+
+    #[test]
+    fn farnsworth_stretches_only_gaps() {
+        let standard = Timing::new(18);
+        let farnsworth = Timing::farnsworth(18, 8);
+
+        assert_eq!(farnsworth.dit(), standard.dit());
+        assert_eq!(farnsworth.dah(), standard.dah());
+        assert!(farnsworth.char_gap() > standard.char_gap());
+        assert!(farnsworth.word_gap() > standard.word_gap());
+
+        let events = super::to_key_events(". . / .", &farnsworth, None);
+        assert_eq!(
+            events,
+            [
+                Down(farnsworth.dit()),
+                Up(farnsworth.char_gap()),
+                Down(farnsworth.dit()),
+                Up(farnsworth.word_gap()),
+                Down(farnsworth.dit()),
+            ]
+        );
+        assert_ne!(farnsworth.char_gap(), farnsworth.dit() * 3);
+    }
+
+    #[test]
+    fn weight_lengthens_only_dahs() {
+        let unit = Duration::from_millis(60);
+        let timing = Timing::new(20).weighted(3.5);
+        assert_eq!(timing.dah(), Duration::from_millis(210));
+        assert_eq!(timing.char_gap(), unit * 3);
+
+        let events = super::to_key_events("-.", &timing, None);
+        assert_eq!(events, [Down(timing.dah()), Up(unit), Down(unit)]);
+        assert_eq!(super::from_key_events(&events), "-.");
+
+        assert_eq!("2.8".parse(), Ok(Weight(2.8)));
+        assert!(Weight::from_str("5").is_err());
+        assert!(Weight::from_str("heavy").is_err());
+    }
+
+    #[test]
+    fn seeded_jitter_is_bounded_and_reproducible() {
+        let timing = Timing::new(20);
+        let nominal = super::to_key_events("... --- ... / -.-", &timing, None);
+
+        let mut jitter = Jitter::new(10.0, Rng::new(42));
+        let jittered = super::to_key_events("... --- ... / -.-", &timing, Some(&mut jitter));
+
+        let mut jitter = Jitter::new(10.0, Rng::new(42));
+        let repeated = super::to_key_events("... --- ... / -.-", &timing, Some(&mut jitter));
+
+        assert_eq!(jittered, repeated);
+        assert_ne!(jittered, nominal);
+        assert_eq!(jittered.len(), nominal.len());
+
+        for (actual, expected) in jittered.iter().zip(&nominal) {
+            let ratio = actual.duration().as_secs_f64() / expected.duration().as_secs_f64();
+            assert!(
+                (0.9..=1.1).contains(&ratio),
+                "ratio out of bounds: {}",
+                ratio
+            );
+            assert_eq!(
+                matches!(actual, Down(_)),
+                matches!(expected, Down(_)),
+                "jitter must not change the kind of event"
+            );
+        }
+    }
+
+    #[test]
+    fn key_events_classify_back_to_morse() {
+        let encoded = "-- --- .-. ... . / -.-. --- -.. .";
+        for &timing in &[Timing::new(13), Timing::new(35), Timing::farnsworth(18, 5)] {
+            let events = super::to_key_events(encoded, &timing, None);
+            assert_eq!(super::from_key_events(&events), encoded);
+        }
+    }
+
+    #[test]
+    fn jittered_key_events_classify_back_to_morse() {
+        let encoded = "... --- ... / - . ... -";
+        let mut jitter = Jitter::new(20.0, Rng::new(7));
+        let events = super::to_key_events(encoded, &Timing::new(20), Some(&mut jitter));
+        assert_eq!(super::from_key_events(&events), encoded);
+    }
+
+    #[test]
+    fn human_jitter_drifts_but_still_decodes() {
+        let encoded = crate::encode("PARIS ".repeat(10).trim()).unwrap();
+        let timing = Timing::new(20);
+        let nominal = super::to_key_events(&encoded, &timing, None);
+
+        let mut jitter = Jitter::human(0.0, Rng::new(3));
+        assert_eq!(
+            super::to_key_events(&encoded, &timing, Some(&mut jitter)),
+            nominal
+        );
+
+        let mut jitter = Jitter::human(0.6, Rng::new(3));
+        let events = super::to_key_events(&encoded, &timing, Some(&mut jitter));
+        assert_eq!(super::from_key_events(&events), encoded);
+
+        // Each element's error is much like the one before it.
+        let errors: Vec<f64> = events
+            .iter()
+            .zip(&nominal)
+            .filter(|(event, _)| matches!(event, Down(_)))
+            .map(|(actual, expected)| {
+                actual.duration().as_secs_f64() / expected.duration().as_secs_f64() - 1.0
+            })
+            .collect();
+        let mean = errors.iter().sum::<f64>() / errors.len() as f64;
+        let covariance = |lag: usize| {
+            errors
+                .iter()
+                .zip(&errors[lag..])
+                .map(|(a, b)| (a - mean) * (b - mean))
+                .sum::<f64>()
+                / (errors.len() - lag) as f64
+        };
+        let correlation = covariance(1) / covariance(0);
+        assert!(correlation > 0.3, "{}", correlation);
+    }
+
+    #[test]
+    fn tracking_follows_change_of_speed() {
+        let (slow, fast) = (
+            "-.-. --.- / -.-. --.- / -.-. --.-",
+            "- . ... - / - . ... - / - . ... -",
+        );
+        let mut events = super::to_key_events(slow, &Timing::new(15), None);
+        events.push(Up(Timing::new(15).word_gap()));
+        events.extend(super::to_key_events(fast, &Timing::new(30), None));
+
+        // The first fast word is read before the tracker has caught up; the rest are not.
+        let words = super::from_key_events_tracking(&events);
+        let codes: Vec<&str> = words.iter().map(|(code, _)| code.as_str()).collect();
+        assert_eq!(codes.len(), 6, "{:?}", codes);
+        assert_eq!(codes[..3], ["-.-. --.-"; 3]);
+        assert_eq!(codes[4..], ["- . ... -"; 2]);
+        assert!((words[0].1 - 15.0).abs() < 0.5, "{:?}", words);
+        assert!((words[5].1 - 30.0).abs() < 1.5, "{:?}", words);
+
+        // Assuming one speed throughout reads the fast dahs as dits.
+        assert_ne!(
+            super::from_key_events(&events),
+            format!("{} / {}", slow, fast)
+        );
+    }
+
+    #[test]
+    fn key_events_round_trip_through_text() {
+        let events = super::to_key_events("-. / .", &Timing::new(20), None);
+        let text = super::write_key_events(&events);
+        assert_eq!(text, "180 -60 60 -420 60");
+        assert_eq!(super::read_key_events(&text).unwrap(), events);
+
+        let e = super::read_key_events("60 -60\n180 x").unwrap_err();
+        assert!(e.to_string().starts_with("line 2, column 5: "), "{}", e);
+
+        let e = super::read_key_events("60 -1e30").unwrap_err();
+        assert!(e.to_string().starts_with("line 1, column 4: "), "{}", e);
+    }
+
+    #[test]
+    fn key_events_read_from_alternating_times() {
+        let events = super::to_key_events("-. / .", &Timing::new(20), None);
+        assert_eq!(
+            super::read_key_events("[180,60,60,420,60]").unwrap(),
+            events
+        );
+        assert_eq!(super::read_key_events("180, -60, 60").unwrap(), events[..3]);
+    }
+
+    #[test]
+    fn rhythm_measures_speed_and_steadiness() {
+        let events = super::to_key_events("-.-. --.-", &Timing::new(20), None);
+        let rhythm = super::rhythm(&events).unwrap();
+        assert!((rhythm.wpm - 20.0).abs() < 1e-9, "{}", rhythm.wpm);
+        assert_eq!(rhythm.consistency, 1.0);
+
+        let mut jitter = Jitter::new(20.0, Rng::new(7));
+        let events = super::to_key_events("-.-. --.-", &Timing::new(20), Some(&mut jitter));
+        let sloppy = super::rhythm(&events).unwrap();
+        assert!(
+            sloppy.consistency < 1.0 && sloppy.consistency > 0.5,
+            "{:?}",
+            sloppy
+        );
+    }
+
+    #[test]
+    fn clean_key_events_are_classified_confidently() {
+        let events = super::to_key_events("-.-. / --.-", &Timing::new(20), None);
+        let classified = super::classify(&events);
+        assert_eq!(classified.len(), events.len());
+        assert_eq!(classified[0].mark, Mark::Dah);
+        assert_eq!(classified[7].mark, Mark::WordGap);
+        assert!(classified.iter().all(|c| c.confidence == 1.0));
+
+        let unit = Duration::from_millis(60);
+        let events = [
+            Down(unit),
+            Up(unit),
+            Down(unit * 3),
+            Up(unit * 3),
+            Down(unit),
+            Up(unit),
+            Down(unit * 21 / 10),
+        ];
+        let classified = super::classify(&events);
+        assert_eq!(classified[6].mark, Mark::Dah);
+        assert!(classified[6].confidence < 0.5);
+        assert_eq!(classified[2].confidence, 1.0);
+    }
+}
@@ -0,0 +1,100 @@
+//! Transliteration of accented Latin text and typographic punctuation into characters the
+//! standard table covers, so that such text encodes sensibly instead of losing letters.
+
+/// Accented letters, each sent as the letter at the same position in `PLAIN`. Generated from
+/// the NFKD decompositions of Latin-1 Supplement and Latin Extended-A, less combining marks.
+const ACCENTED: &str = "ÀÁÂÃÄÅÇÈÉÊËÌÍÎÏÑÒÓÔÕÖÙÚÛÜÝàáâãäåçèéêëìíîïñòóôõöùúûüýÿĀāĂăĄąĆćĈĉĊċČčĎďĒēĔĕĖėĘęĚěĜĝĞğĠġĢģĤĥĨĩĪīĬĭĮįİĴĵĶķĹĺĻļĽľŃńŅņŇňŌōŎŏŐőŔŕŖŗŘřŚśŜŝŞşŠšŢţŤťŨũŪūŬŭŮůŰűŲųŴŵŶŷŸŹźŻżŽžſ";
+const PLAIN: &str = "AAAAAACEEEEIIIINOOOOOUUUUYaaaaaaceeeeiiiinooooouuuuyyAaAaAaCcCcCcCcDdEeEeEeEeEeGgGgGgGgHhIiIiIiIiIJjKkLlLlLlNnNnNnOoOoOoRrRrRrSsSsSsSsTtTtUuUuUuUuUuUuWwYyYZzZzZzs";
+
+/// Letters without a decomposition, ligatures, and punctuation with a plain equivalent.
+static SPELLED: &[(char, &str)] = &[
+    ('Æ', "AE"),
+    ('æ', "ae"),
+    ('Ð', "D"),
+    ('ð', "d"),
+    ('Ø', "O"),
+    ('ø', "o"),
+    ('Þ', "TH"),
+    ('þ', "th"),
+    ('ß', "ss"),
+    ('Đ', "D"),
+    ('đ', "d"),
+    ('Ħ', "H"),
+    ('ħ', "h"),
+    ('ı', "i"),
+    ('Ĳ', "IJ"),
+    ('ĳ', "ij"),
+    ('Ŀ', "L"),
+    ('ŀ', "l"),
+    ('Ł', "L"),
+    ('ł', "l"),
+    ('ŉ', "'n"),
+    ('Ŋ', "NG"),
+    ('ŋ', "ng"),
+    ('Œ', "OE"),
+    ('œ', "oe"),
+    ('Ŧ', "T"),
+    ('ŧ', "t"),
+    ('×', "x"),
+    ('‘', "'"),
+    ('’', "'"),
+    ('“', "\""),
+    ('”', "\""),
+    ('«', "\""),
+    ('»', "\""),
+    ('–', "-"),
+    ('—', "-"),
+    ('…', "..."),
+];
+
+/// Rewrites accented letters as plain ones (`é` as `e`, `ß` as `ss`) and typographic
+/// punctuation as its ASCII equivalent. Anything else is left alone.
+pub fn latin(text: &str) -> String {
+    latin_except(text, |_| false)
+}
+
+/// Rewrites text like `latin`, but leaves alone the characters `keep` is true of, such as those
+/// that can be encoded as they are.
+pub fn latin_except(text: &str, keep: impl Fn(char) -> bool) -> String {
+    let mut buf = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if keep(c) {
+            buf.push(c);
+        } else if let Some(plain) = ACCENTED
+            .chars()
+            .zip(PLAIN.chars())
+            .find(|&(accented, _)| accented == c)
+            .map(|(_, plain)| plain)
+        {
+            buf.push(plain);
+        } else if let Some(&(_, spelled)) = SPELLED.iter().find(|&&(symbol, _)| symbol == c) {
+            buf.push_str(spelled);
+        } else {
+            buf.push(c);
+        }
+    }
+
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn tables_line_up() {
+        assert_eq!(super::ACCENTED.chars().count(), super::PLAIN.len());
+    }
+
+    #[test]
+    fn accented_text_encodes() {
+        let text = super::latin("Café Müller, naïve façade – Straße “Łódź”");
+        assert_eq!(text, "Cafe Muller, naive facade - Strasse \"Lodz\"");
+        assert!(crate::encode(&text).is_ok());
+    }
+
+    #[test]
+    fn kept_characters_are_left_alone() {
+        let text = super::latin_except("Ñandú “ß”", |c| c == 'Ñ' || c == 'ú');
+        assert_eq!(text, "Ñandú \"ss\"");
+    }
+}
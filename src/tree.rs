@@ -0,0 +1,126 @@
+//! The dichotomic search tree behind `data::DECODING_ARRAY`, drawn for people to read.
+//!
+//! Decoding walks the tree from the root, going left on a dot and right on a dash, so a
+//! character's code is simply the path to it.
+
+use std::fmt::Write;
+
+use crate::data;
+
+/// The code leading to the node at `idx`.
+pub fn code_at(mut idx: usize) -> String {
+    let mut code = Vec::new();
+    while idx > 0 {
+        code.push(if idx % 2 == 1 { '.' } else { '-' });
+        idx = (idx - 1) / 2;
+    }
+    code.iter().rev().collect()
+}
+
+/// Draws the tree with box-drawing characters, dots above dashes. Empty nodes are drawn as `*`
+/// when something lies below them.
+pub fn ascii() -> String {
+    let mut buf = String::from("START\n");
+    draw_children(0, "", &mut buf);
+    buf
+}
+
+/// Writes the tree as a Graphviz `digraph`, for rendering with `dot -Tsvg`.
+pub fn dot() -> String {
+    let mut buf = String::from(
+        "digraph morse {\n    node [shape=circle];\n    n0 [label=\"START\", shape=box];\n",
+    );
+
+    for idx in 1..data::DECODING_ARRAY.len() {
+        if !occupied(idx) {
+            continue;
+        }
+
+        let label = match data::DECODING_ARRAY[idx] {
+            Some(b'"') => "\\\"".into(),
+            Some(b'\\') => "\\\\".into(),
+            Some(u) => (u as char).to_string(),
+            None => String::new(),
+        };
+        let element = if idx % 2 == 1 { "." } else { "-" };
+        let _ = writeln!(buf, "    n{} [label=\"{}\"];", idx, label);
+        let _ = writeln!(
+            buf,
+            "    n{} -> n{} [label=\"{}\"];",
+            (idx - 1) / 2,
+            idx,
+            element
+        );
+    }
+
+    buf.push_str("}\n");
+    buf
+}
+
+fn draw_children(idx: usize, prefix: &str, buf: &mut String) {
+    let children: Vec<usize> = [idx * 2 + 1, idx * 2 + 2]
+        .iter()
+        .copied()
+        .filter(|&child| occupied(child))
+        .collect();
+
+    for (n, &child) in children.iter().enumerate() {
+        let last = n + 1 == children.len();
+        let element = if child % 2 == 1 { '.' } else { '-' };
+        let label = data::DECODING_ARRAY[child].map_or('*', |u| u as char);
+
+        let _ = writeln!(
+            buf,
+            "{}{} {} {}",
+            prefix,
+            if last { "└──" } else { "├──" },
+            element,
+            label
+        );
+        let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        draw_children(child, &prefix, buf);
+    }
+}
+
+/// Whether the node at `idx`, or any node below it, holds a character.
+fn occupied(idx: usize) -> bool {
+    match data::DECODING_ARRAY.get(idx) {
+        Some(Some(_)) => true,
+        Some(None) => occupied(idx * 2 + 1) || occupied(idx * 2 + 2),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data;
+
+    #[test]
+    fn tree_paths_match_the_code_table() {
+        for (idx, &character) in data::DECODING_ARRAY.iter().enumerate() {
+            if let Some(u) = character {
+                let encoded = crate::encode(&(u as char).to_string()).unwrap();
+                assert_eq!(super::code_at(idx), encoded, "{}", u as char);
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_tree_starts_with_e_and_t() {
+        let tree = super::ascii();
+        let lines: Vec<_> = tree.lines().collect();
+        assert_eq!(lines[0], "START");
+        assert_eq!(lines[1], "├── . E");
+        assert_eq!(lines[2], "│   ├── . I");
+        assert!(lines.contains(&"└── - T"));
+    }
+
+    #[test]
+    fn dot_tree_links_every_character() {
+        let dot = super::dot();
+        assert!(dot.starts_with("digraph morse {"));
+        assert!(dot.contains("n0 -> n1 [label=\".\"];"));
+        assert!(dot.contains("n1 [label=\"E\"];"));
+        assert!(dot.contains("[label=\"\\\"\"];"));
+    }
+}
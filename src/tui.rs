@@ -0,0 +1,333 @@
+//! A full-screen practice application: receiving practice, sending practice, speed controls and
+//! running statistics in one terminal, built on the same library calls as the subcommands.
+//!
+//! Sending uses the space bar as a straight key, which needs a terminal that reports key
+//! releases (the kitty keyboard protocol); elsewhere only receiving practice is available.
+
+use std::{
+    io, thread,
+    time::{Duration, Instant},
+};
+
+use morse::{
+    audio,
+    fist::{self, Fist},
+    grade::Grade,
+    keyer::StraightKey,
+    koch,
+    rng::Rng,
+    timing::{self, KeyEvent},
+    Error, Options, Result, Timing,
+};
+use ratatui::{
+    crossterm::{
+        event::{
+            self, Event, KeyCode, KeyEventKind, KeyboardEnhancementFlags,
+            PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+        },
+        execute, terminal,
+    },
+    layout::{Constraint, Layout},
+    text::Line,
+    widgets::{Block, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+
+/// How often the screen is redrawn and silences checked for the end of a sent character.
+const TICK: Duration = Duration::from_millis(10);
+
+/// Five-character groups sent per receiving round.
+const GROUPS: usize = 3;
+
+const HELP: &str = "Tab: receive/send  Up/Down: speed  Esc: quit";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Receive,
+    Send,
+}
+
+struct App {
+    mode: Mode,
+    timing: Timing,
+    voice: audio::Voice,
+    lesson: usize,
+    rng: Rng,
+    /// Whether the terminal reports key releases, without which the space bar cannot be timed.
+    releases: bool,
+
+    // Receiving: the text played, what has been typed so far, and the last round's result.
+    sent: String,
+    copy: String,
+    checked: Option<(String, Grade)>,
+
+    // Sending: the text to send, the key decoding it, and the timing kept for analysis.
+    target: String,
+    key: StraightKey,
+    keyed: String,
+    events: Vec<KeyEvent>,
+    start: Instant,
+    down: Option<Instant>,
+    up: Option<Instant>,
+    fist: Option<Fist>,
+
+    rounds: u32,
+    total_accuracy: f64,
+    last_accuracy: Option<f64>,
+}
+
+/// Runs the application until Esc is pressed.
+pub fn run(timing: Timing, voice: audio::Voice, lesson: usize) -> Result<()> {
+    let releases = terminal::supports_keyboard_enhancement().unwrap_or(false);
+    let mut terminal = ratatui::try_init().map_err(Error::Io)?;
+    if releases {
+        execute!(
+            io::stdout(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )
+        .map_err(Error::Io)?;
+    }
+
+    let mut app = App::new(timing, voice, lesson, releases);
+    let result = app.run(&mut terminal);
+
+    if releases {
+        let _ = execute!(io::stdout(), PopKeyboardEnhancementFlags);
+    }
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn new(timing: Timing, voice: audio::Voice, lesson: usize, releases: bool) -> Self {
+        let mut rng = Rng::from_time();
+        let lesson = lesson.clamp(1, koch::LESSONS);
+        App {
+            mode: Mode::Receive,
+            timing,
+            voice,
+            lesson,
+            releases,
+            sent: koch::practice(lesson, GROUPS, &mut rng),
+            copy: String::new(),
+            checked: None,
+            target: koch::practice(lesson, 1, &mut rng),
+            key: StraightKey::new(timing, Options::default()),
+            keyed: String::new(),
+            events: Vec::new(),
+            start: Instant::now(),
+            down: None,
+            up: None,
+            fist: None,
+            rng,
+            rounds: 0,
+            total_accuracy: 0.0,
+            last_accuracy: None,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.play()?;
+        loop {
+            terminal.draw(|frame| self.draw(frame)).map_err(Error::Io)?;
+
+            if event::poll(TICK).map_err(Error::Io)? {
+                if let Event::Key(key) = event::read().map_err(Error::Io)? {
+                    let now = Instant::now();
+                    match (key.code, key.kind) {
+                        (KeyCode::Esc, _) => return Ok(()),
+                        (_, KeyEventKind::Repeat) => (),
+                        (KeyCode::Char(' '), kind) if self.mode == Mode::Send => {
+                            self.space(kind == KeyEventKind::Press, now)?
+                        }
+                        (_, KeyEventKind::Release) => (),
+                        (KeyCode::Tab, _) => self.switch(),
+                        (KeyCode::Up, _) => self.set_speed(self.timing.char_wpm + 1),
+                        (KeyCode::Down, _) => {
+                            self.set_speed(self.timing.char_wpm.saturating_sub(1).max(5))
+                        }
+                        (code, _) => self.typed(code)?,
+                    }
+                }
+            }
+
+            if self.mode == Mode::Send {
+                let text = self.key.idle(self.start.elapsed())?;
+                self.keyed.push_str(&text);
+            }
+        }
+    }
+
+    /// Plays the receiving text in the background.
+    fn play(&self) -> Result<()> {
+        let encoded = morse::encode(&self.sent)?;
+        let events = timing::to_key_events(&encoded, &self.timing, None);
+        let samples = self.voice.render(&events);
+        let sample_rate = self.voice.sample_rate;
+        thread::spawn(move || audio::device::play(samples, sample_rate));
+        Ok(())
+    }
+
+    fn switch(&mut self) {
+        self.mode = match self.mode {
+            Mode::Receive => Mode::Send,
+            Mode::Send => Mode::Receive,
+        };
+        self.reset_key();
+    }
+
+    fn set_speed(&mut self, wpm: u32) {
+        self.timing = Timing::farnsworth(
+            wpm,
+            self.timing.effective_wpm.min(self.timing.char_wpm).min(wpm),
+        );
+        self.reset_key();
+    }
+
+    fn reset_key(&mut self) {
+        self.key = StraightKey::new(self.timing, Options::default());
+        self.keyed.clear();
+        self.events.clear();
+        self.start = Instant::now();
+        self.down = None;
+        self.up = None;
+    }
+
+    /// The space bar went down or came up while sending.
+    fn space(&mut self, pressed: bool, now: Instant) -> Result<()> {
+        let at = now - self.start;
+        match (pressed, self.down) {
+            (true, None) => {
+                if let Some(up) = self.up {
+                    self.events.push(KeyEvent::Up(now - up));
+                }
+                self.down = Some(now);
+                let text = self.key.press(at)?;
+                self.keyed.push_str(&text);
+            }
+            (false, Some(down)) => {
+                self.events.push(KeyEvent::Down(now - down));
+                self.down = None;
+                self.up = Some(now);
+                self.key.release(at);
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn typed(&mut self, code: KeyCode) -> Result<()> {
+        match (self.mode, code) {
+            (Mode::Receive, KeyCode::Char(c)) => self.copy.push(c.to_ascii_uppercase()),
+            (Mode::Receive, KeyCode::Backspace) => {
+                self.copy.pop();
+            }
+            (Mode::Receive, KeyCode::F(5)) => self.play()?,
+            (Mode::Receive, KeyCode::Enter) => {
+                let grade = Grade::of(&self.sent, &self.copy);
+                self.score(grade.accuracy);
+                let sent = koch::practice(self.lesson, GROUPS, &mut self.rng);
+                self.checked = Some((std::mem::replace(&mut self.sent, sent), grade));
+                self.copy.clear();
+                self.play()?;
+            }
+            (Mode::Send, KeyCode::Backspace) => self.reset_key(),
+            (Mode::Send, KeyCode::Enter) => {
+                self.keyed.push_str(&self.key.finish()?);
+                let accuracy = Grade::of(&self.target, &self.keyed).accuracy;
+                self.score(accuracy);
+                self.fist = fist::analyze(&self.events);
+                self.target = koch::practice(self.lesson, 1, &mut self.rng);
+                self.reset_key();
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn score(&mut self, accuracy: f64) {
+        self.rounds += 1;
+        self.total_accuracy += accuracy;
+        self.last_accuracy = Some(accuracy);
+        self.lesson = koch::next_lesson(self.lesson, accuracy);
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let areas = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(6),
+        ])
+        .split(frame.area());
+
+        let title = format!(
+            "{:?} practice, {} WPM, lesson {} ({})",
+            self.mode,
+            self.timing.char_wpm,
+            self.lesson,
+            koch::characters(self.lesson)
+        );
+        frame.render_widget(
+            Paragraph::new(HELP).block(Block::bordered().title(title)),
+            areas[0],
+        );
+
+        let practice = match self.mode {
+            Mode::Receive => {
+                let mut lines = vec![
+                    "Type what you hear, Enter to check it, F5 to hear it again".to_string(),
+                    String::new(),
+                    format!("copy: {}", self.copy),
+                ];
+                if let Some((sent, grade)) = &self.checked {
+                    lines.push(String::new());
+                    lines.push(format!("last sent: {}", sent));
+                    lines.push(format!("your copy: {}", grade.marked()));
+                }
+                lines
+            }
+            Mode::Send if !self.releases => vec![
+                "This terminal does not report key releases, so the space bar cannot be used as a key."
+                    .to_string(),
+                "Try a terminal with the kitty keyboard protocol, or `morse key`.".to_string(),
+            ],
+            Mode::Send => vec![
+                "Key with the space bar, Enter when done, Backspace to start over".to_string(),
+                String::new(),
+                format!("send:    {}", self.target),
+                format!("decoded: {}", self.keyed),
+            ],
+        };
+        let lines: Vec<Line> = practice.into_iter().map(Line::from).collect();
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::bordered().title("practice"))
+                .wrap(Wrap { trim: false }),
+            areas[1],
+        );
+
+        let mut stats = vec![
+            format!("rounds: {}", self.rounds),
+            format!(
+                "accuracy: {} last, {} overall",
+                percent(self.last_accuracy),
+                percent((self.rounds > 0).then(|| self.total_accuracy / f64::from(self.rounds)))
+            ),
+        ];
+        if let Some(fist) = &self.fist {
+            stats.push(format!(
+                "fist: {:.1} WPM, dah:dit {:.2}, rhythm {:.0}/100",
+                fist.wpm, fist.dah_ratio, fist.score
+            ));
+        }
+        let lines: Vec<Line> = stats.into_iter().map(Line::from).collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("statistics")),
+            areas[2],
+        );
+    }
+}
+
+fn percent(value: Option<f64>) -> String {
+    value.map_or("-".into(), |value| format!("{:.0}%", value * 100.0))
+}
@@ -0,0 +1,173 @@
+//! Round-trip checks, for finding what a message loses on its way through Morse.
+//!
+//! Every character or prosign of a text message is encoded and decoded again on its own, and
+//! every sequence of an encoded message is decoded and encoded again, so that each problem can be
+//! pinned to the place it came from.
+
+use std::fmt::Display;
+
+use crate::{wabun, Alphabet, ErrorPolicy, Options, Position};
+
+/// Something that does not survive the round trip.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Discrepancy {
+    pub position: Position,
+    /// The character, prosign or sequence as it appears in the message.
+    pub found: String,
+    pub problem: Problem,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Problem {
+    Unencodable,
+    Undecodable,
+    /// It comes back as something else; an empty string means it is silently left out.
+    Changed(String),
+}
+
+impl Display for Discrepancy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: '{}' ", self.position, self.found)?;
+        match &self.problem {
+            Problem::Unencodable => write!(f, "cannot be encoded"),
+            Problem::Undecodable => write!(f, "cannot be decoded"),
+            Problem::Changed(result) if result.is_empty() => write!(f, "is left out"),
+            Problem::Changed(result) => write!(f, "comes back as '{}'", result),
+        }
+    }
+}
+
+/// Checks that a text message decodes to what was encoded, ignoring case and spacing.
+pub fn text(message: &str, options: &Options) -> Vec<Discrepancy> {
+    let options = strict(options);
+    let mut discrepancies = Vec::new();
+    let mut rest = message;
+
+    while let Some((token, tail)) = crate::split_character(rest, &options) {
+        rest = tail;
+        if token.trim().is_empty() {
+            continue;
+        }
+
+        let problem = match crate::encode_with(token, &options) {
+            Err(_) => Problem::Unencodable,
+            Ok(encoded) => match crate::decode_with(&encoded, &options) {
+                Ok(decoded) if decoded == token.to_uppercase() => continue,
+                Ok(decoded) => Problem::Changed(decoded),
+                Err(_) => Problem::Undecodable,
+            },
+        };
+
+        discrepancies.push(Discrepancy {
+            position: Position::of(message, token),
+            found: token.into(),
+            problem,
+        });
+    }
+
+    discrepancies
+}
+
+/// Checks that an encoded message, in canonical notation, encodes back to the same sequences.
+pub fn morse(message: &str, options: &Options) -> Vec<Discrepancy> {
+    let options = strict(options);
+    let mut discrepancies = Vec::new();
+    let mut shifted = false;
+
+    for sequence in message.split('/').flat_map(str::split_whitespace) {
+        // Wabun sequences only mean anything once shifted into, so they are checked with the
+        // shift around them.
+        let (decoded, expected) = match options.alphabet {
+            Alphabet::Wabun if sequence == wabun::DO || (shifted && sequence == wabun::SN) => {
+                shifted = sequence == wabun::DO;
+                continue;
+            }
+            Alphabet::Wabun if shifted => (
+                crate::decode_with(&format!("{} {}", wabun::DO, sequence), &options),
+                format!("{} {} {}", wabun::DO, sequence, wabun::SN),
+            ),
+            _ => (crate::decode_with(sequence, &options), sequence.to_string()),
+        };
+
+        let problem = match decoded {
+            Err(_) => Problem::Undecodable,
+            Ok(decoded) => match crate::encode_with(&decoded, &options) {
+                Ok(encoded) if encoded == expected => continue,
+                Ok(_) if decoded.is_empty() => Problem::Changed(String::new()),
+                Ok(_) => Problem::Changed(decoded),
+                Err(_) => Problem::Unencodable,
+            },
+        };
+
+        discrepancies.push(Discrepancy {
+            position: Position::of(message, sequence),
+            found: sequence.into(),
+            problem,
+        });
+    }
+
+    discrepancies
+}
+
+fn strict(options: &Options) -> Options {
+    Options {
+        errors: ErrorPolicy::Strict,
+        ..*options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Problem;
+    use crate::{Alphabet, Options};
+
+    #[test]
+    fn text_reports_lossy_characters() {
+        let options = Options {
+            alphabet: Alphabet::Arabic,
+            ..Options::default()
+        };
+        assert!(super::text("sos <SK>\n73", &Options::default()).is_empty());
+
+        let discrepancies = super::text("a~\nbé", &Options::default());
+        let found: Vec<_> = discrepancies
+            .iter()
+            .map(|d| (d.position.line, d.position.column, d.found.as_str()))
+            .collect();
+        assert_eq!(found, [(1, 2, "~"), (2, 2, "é")]);
+        assert_eq!(discrepancies[0].problem, Problem::Unencodable);
+
+        let discrepancies = super::text("بَ", &options);
+        assert_eq!(discrepancies[0].problem, Problem::Changed(String::new()));
+        assert_eq!(
+            discrepancies[0].to_string(),
+            "line 1, column 2: '\u{64e}' is left out"
+        );
+    }
+
+    #[test]
+    fn text_reports_ambiguous_characters() {
+        let options = Options {
+            prosigns: true,
+            ..Options::default()
+        };
+        let discrepancies = super::text("2+2", &options);
+        assert_eq!(discrepancies.len(), 1);
+        assert_eq!(
+            discrepancies[0].to_string(),
+            "line 1, column 2: '+' comes back as '<AR>'"
+        );
+    }
+
+    #[test]
+    fn morse_reports_undecodable_sequences() {
+        assert!(super::morse("... --- ... / .-.-.", &Options::default()).is_empty());
+
+        let discrepancies = super::morse("... --- ... / .-.-. ..--..-", &Options::default());
+        assert_eq!(discrepancies.len(), 1);
+        assert_eq!(
+            discrepancies[0].to_string(),
+            "line 1, column 21: '..--..-' cannot be decoded"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Wabun code for Japanese kana.
+//!
+//! Kana are sent in their own code, which reuses International sequences, so a transmission
+//! switches to Wabun with the `<DO>` prosign and back with `<SN>`. Text outside kana is sent in
+//! International Morse between the two.
+
+use crate::{data, Alphabet, Code, Error, ErrorPolicy, Options, Result};
+
+/// Switches from International Morse to Wabun.
+pub const DO: Code = "-..---";
+
+/// Switches from Wabun back to International Morse.
+pub const SN: Code = "...-.";
+
+const DAKUTEN: char = '゛';
+const HANDAKUTEN: char = '゜';
+
+/// Encodes a message, shifting into Wabun for each run of kana and back out again afterward.
+/// Hiragana are sent as the corresponding katakana.
+pub fn encode_with(message: &str, options: &Options) -> Result<String> {
+    let international = international(options);
+    let mut buf = String::with_capacity(message.len() * 4);
+    let mut errors = Vec::new();
+    let mut wabun = false;
+
+    for (idx, c) in message.char_indices() {
+        if c == ' ' {
+            buf.push_str(" /");
+            continue;
+        }
+
+        match kana_codes(c) {
+            Some((code, mark)) => {
+                if !wabun {
+                    push_code(&mut buf, DO);
+                    wabun = true;
+                }
+                push_code(&mut buf, code);
+                if let Some(mark) = mark {
+                    push_code(&mut buf, mark);
+                }
+            }
+            None => {
+                let current = &message[idx..idx + c.len_utf8()];
+                let encoded = match crate::encode_with(current, &international) {
+                    Ok(encoded) => encoded,
+                    Err(e) if options.errors == ErrorPolicy::Collect => {
+                        errors.extend(e.at(message, current).into_errors());
+                        continue;
+                    }
+                    Err(e) => return Err(e.at(message, current)),
+                };
+                if encoded.is_empty() {
+                    continue;
+                }
+                if wabun {
+                    push_code(&mut buf, SN);
+                    wabun = false;
+                }
+                push_code(&mut buf, &encoded);
+            }
+        }
+    }
+
+    if wabun {
+        push_code(&mut buf, SN);
+    }
+
+    crate::collected(buf, errors)
+}
+
+/// Decodes a message, also returning the number of sequences that could not be decoded. Decoding
+/// starts in International Morse; the shift prosigns themselves are not written out.
+pub fn decode_counting(message: &str, options: &Options) -> Result<(String, usize)> {
+    let international = international(options);
+    let mut words = Vec::new();
+    let mut unknown = 0;
+    let mut errors = Vec::new();
+    let mut wabun = false;
+
+    for word in message.split('/').filter(|word| !word.trim().is_empty()) {
+        let mut buf = String::new();
+        let mut only_shifts = true;
+
+        for character in word.split_whitespace() {
+            if character == DO {
+                wabun = true;
+                continue;
+            }
+            if wabun && character == SN {
+                wabun = false;
+                continue;
+            }
+            only_shifts = false;
+
+            if !wabun {
+                match crate::decode_counting(character, &international) {
+                    Ok((decoded, n)) => {
+                        buf.push_str(&decoded);
+                        unknown += n;
+                    }
+                    Err(e) if options.errors == ErrorPolicy::Collect => {
+                        errors.extend(e.at(message, character).into_errors());
+                        unknown += 1;
+                    }
+                    Err(e) => return Err(e.at(message, character)),
+                }
+                continue;
+            }
+
+            match decode_kana(character) {
+                Some(c) => push_kana(&mut buf, c),
+                None => {
+                    unknown += 1;
+                    let e = Error::Decode(character.into()).at(message, character);
+                    match options.errors {
+                        ErrorPolicy::Strict => return Err(e),
+                        ErrorPolicy::Skip => (),
+                        ErrorPolicy::Collect => errors.push(e),
+                        ErrorPolicy::Replace(replacement) => buf.push(replacement),
+                    }
+                }
+            }
+        }
+
+        if !only_shifts {
+            words.push(buf);
+        }
+    }
+
+    let decoded = words.join(&options.word_sep.to_string());
+    crate::collected((decoded, unknown), errors)
+}
+
+/// Returns whether `c` is a kana that can be sent in Wabun.
+pub fn is_encodable(c: char) -> bool {
+    kana_codes(c).is_some()
+}
+
+fn international(options: &Options) -> Options {
+    Options {
+        alphabet: Alphabet::Latin,
+        ..*options
+    }
+}
+
+fn push_code(buf: &mut String, code: &str) {
+    if !buf.is_empty() {
+        buf.push(' ');
+    }
+    buf.push_str(code);
+}
+
+/// The code for a kana, and for the voicing mark that follows it, if any.
+fn kana_codes(c: char) -> Option<(Code, Option<Code>)> {
+    let (base, mark) = decompose(c);
+    let code = lookup(base)?;
+    match mark {
+        Some(mark) => Some((code, Some(lookup(mark)?))),
+        None => Some((code, None)),
+    }
+}
+
+fn lookup(c: char) -> Option<Code> {
+    data::WABUN
+        .iter()
+        .find(|&&(kana, _)| kana == c)
+        .map(|&(_, code)| code)
+}
+
+fn decode_kana(character: &str) -> Option<char> {
+    data::WABUN
+        .iter()
+        .find(|&&(_, code)| code == character)
+        .map(|&(kana, _)| kana)
+}
+
+/// Splits a kana into the full-size katakana that is sent for it and any voicing mark.
+fn decompose(c: char) -> (char, Option<char>) {
+    let offset = |c: char, by: u32| std::char::from_u32(c as u32 + by).unwrap_or(c);
+    let back = |c: char, by: u32| std::char::from_u32(c as u32 - by).unwrap_or(c);
+
+    // Hiragana sit exactly 0x60 below their katakana.
+    let c = match c {
+        '\u{3041}'..='\u{3096}' => offset(c, 0x60),
+        c => c,
+    };
+
+    match c {
+        'ァ' | 'ィ' | 'ゥ' | 'ェ' | 'ォ' | 'ッ' | 'ャ' | 'ュ' | 'ョ' | 'ヮ' => {
+            (offset(c, 1), None)
+        }
+        'ヴ' => ('ウ', Some(DAKUTEN)),
+        c if is_voiceable(back(c, 1)) => (back(c, 1), Some(DAKUTEN)),
+        c if is_semi_voiceable(back(c, 2)) => (back(c, 2), Some(HANDAKUTEN)),
+        c => (c, None),
+    }
+}
+
+/// Pushes a decoded kana, combining a voicing mark with the kana before it where possible.
+fn push_kana(buf: &mut String, c: char) {
+    let combined = match (buf.chars().last(), c) {
+        (Some('ウ'), DAKUTEN) => Some('ヴ'),
+        (Some(base), DAKUTEN) if is_voiceable(base) => std::char::from_u32(base as u32 + 1),
+        (Some(base), HANDAKUTEN) if is_semi_voiceable(base) => std::char::from_u32(base as u32 + 2),
+        _ => None,
+    };
+
+    match combined {
+        Some(combined) => {
+            buf.pop();
+            buf.push(combined);
+        }
+        None => buf.push(c),
+    }
+}
+
+/// Kana of the K, S, T and H rows, whose voiced forms follow them directly.
+fn is_voiceable(c: char) -> bool {
+    matches!(
+        c,
+        'カ' | 'キ'
+            | 'ク'
+            | 'ケ'
+            | 'コ'
+            | 'サ'
+            | 'シ'
+            | 'ス'
+            | 'セ'
+            | 'ソ'
+            | 'タ'
+            | 'チ'
+            | 'ツ'
+            | 'テ'
+            | 'ト'
+    ) || is_semi_voiceable(c)
+}
+
+fn is_semi_voiceable(c: char) -> bool {
+    matches!(c, 'ハ' | 'ヒ' | 'フ' | 'ヘ' | 'ホ')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Alphabet, Options};
+
+    const WABUN: Options = Options {
+        extended: false,
+        prosigns: false,
+        word_sep: ' ',
+        errors: crate::ErrorPolicy::Strict,
+        alphabet: Alphabet::Wabun,
+    };
+
+    #[test]
+    fn kana_are_shifted_in_and_out() {
+        let encoded = crate::encode_with("QTH とうきょう", &WABUN).unwrap();
+        assert_eq!(encoded, "--.- - .... / -..--- ..-.. ..- -.-.. -- ..- ...-.");
+        assert_eq!(
+            crate::decode_with(&encoded, &WABUN).unwrap(),
+            "QTH トウキヨウ"
+        );
+    }
+
+    #[test]
+    fn voiced_kana_round_trip() {
+        let encoded = crate::encode_with("ガンバ パン ヴ", &WABUN).unwrap();
+        assert_eq!(
+            crate::decode_with(&encoded, &WABUN).unwrap(),
+            "ガンバ パン ヴ"
+        );
+    }
+
+    #[test]
+    fn codes_are_unique() {
+        let table = crate::data::WABUN;
+        for (idx, &(_, code)) in table.iter().enumerate() {
+            assert!(table[idx + 1..].iter().all(|&(_, other)| other != code));
+            assert_ne!(code, super::DO);
+        }
+    }
+
+    #[test]
+    fn sanitize_keeps_kana() {
+        assert_eq!(crate::sanitize("ひらがな?", &WABUN), "ひらがな?");
+    }
+}
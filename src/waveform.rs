@@ -0,0 +1,202 @@
+use std::fmt::Write;
+
+use crate::timing::KeyEvent;
+
+/// A character to label on a timing diagram: its text, and how many elements it keys.
+pub type Character = (String, usize);
+
+/// The characters of an encoded message in order, labelled by `decode`, or with their code where
+/// it gives nothing. Elements are counted as both International and American keying send them.
+pub fn characters(encoded: &str, decode: impl Fn(&str) -> Option<String>) -> Vec<Character> {
+    encoded
+        .split('/')
+        .flat_map(str::split_whitespace)
+        .map(|code| {
+            let elements = code.chars().filter(|c| ".-_=".contains(*c)).count();
+            (decode(code).unwrap_or_else(|| code.into()), elements)
+        })
+        .collect()
+}
+
+/// Renders key events as a standalone SVG timing diagram, with one rectangle per key-down
+/// period sitting on a baseline. Each period, on or off, is marked with its length in
+/// milliseconds where there is room, and each of `characters` is named above its elements;
+/// characters are left unnamed unless they account for every key-down period.
+pub fn svg(events: &[KeyEvent], characters: &[Character], width: u32, height: u32) -> String {
+    let total: f64 = events
+        .iter()
+        .map(|event| event.duration().as_secs_f64())
+        .sum();
+    let scale = if total > 0.0 {
+        f64::from(width) / total
+    } else {
+        0.0
+    };
+    let height = f64::from(height);
+    let (top, baseline) = (height * 0.3, height * 0.7);
+    let (label_size, duration_size) = (height * 0.15, height * 0.1);
+
+    let mut buf = String::new();
+    let _ = writeln!(
+        buf,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height,
+    );
+    let _ = writeln!(
+        buf,
+        r#"  <g font-family="sans-serif" text-anchor="middle" font-size="{:.2}">"#,
+        duration_size,
+    );
+    let _ = writeln!(
+        buf,
+        r#"  <line x1="0" y1="{y:.2}" x2="{w}" y2="{y:.2}" stroke="black" />"#,
+        y = baseline,
+        w = width,
+    );
+
+    let mut offset = 0.0;
+    let mut spans = Vec::new();
+    for &event in events {
+        let duration = event.duration().as_secs_f64();
+        let (x, span) = (offset * scale, duration * scale);
+        let ms = format!("{:.0}", duration * 1000.0);
+        if let KeyEvent::Down(_) = event {
+            let _ = writeln!(
+                buf,
+                r#"  <rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="black"><title>{} ms</title></rect>"#,
+                x,
+                top,
+                span,
+                baseline - top,
+                ms,
+            );
+            spans.push((x, x + span));
+        }
+        // Digits are about half as wide as they are tall.
+        if span >= duration_size * 0.6 * ms.len() as f64 {
+            let _ = writeln!(
+                buf,
+                r#"  <text x="{:.2}" y="{:.2}" fill="gray">{}</text>"#,
+                x + span / 2.0,
+                baseline + duration_size * 1.5,
+                ms,
+            );
+        }
+        offset += duration;
+    }
+
+    if characters
+        .iter()
+        .map(|&(_, elements)| elements)
+        .sum::<usize>()
+        == spans.len()
+    {
+        let mut spans = spans.iter();
+        for (text, elements) in characters {
+            let mut character = spans.by_ref().take(*elements);
+            let start = character.next().map_or(0.0, |&(start, _)| start);
+            let end = character.last().map_or(start, |&(_, end)| end);
+            let _ = writeln!(
+                buf,
+                r#"  <text x="{:.2}" y="{:.2}" font-size="{:.2}">{}</text>"#,
+                (start + end) / 2.0,
+                top - label_size * 0.5,
+                label_size,
+                escape(text),
+            );
+        }
+    }
+
+    buf.push_str("  </g>\n</svg>\n");
+    buf
+}
+
+/// Escapes text for use in XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Renders key events as a two-line ASCII waveform, one column per dit. Each edge is marked in
+/// the first column of the event it starts, so that every event starts at the column its time
+/// calls for.
+pub fn ascii(events: &[KeyEvent]) -> String {
+    let column = events
+        .iter()
+        .map(|event| event.duration())
+        .min()
+        .unwrap_or_default()
+        .as_secs_f64();
+
+    let mut high = String::new();
+    let mut low = String::new();
+
+    for (idx, &event) in events.iter().enumerate() {
+        let units = (event.duration().as_secs_f64() / column).round() as usize;
+        let (bar, under) = match event {
+            KeyEvent::Down(_) => ('_', ' '),
+            KeyEvent::Up(_) => (' ', '_'),
+        };
+        for unit in 0..units {
+            high.push(bar);
+            low.push(if idx > 0 && unit == 0 { '|' } else { under });
+        }
+    }
+
+    format!("{}\n{}", high.trim_end(), low.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timing::{self, Timing};
+
+    #[test]
+    fn svg_has_one_rect_per_element() {
+        // "SOS" has nine elements.
+        let events = timing::to_key_events("... --- ...", &Timing::default(), None);
+        let svg = super::svg(&events, &[], 800, 60);
+        assert_eq!(svg.matches("<rect").count(), 9);
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn svg_labels_characters_and_durations() {
+        let encoded = ".- / ...-.-";
+        let events = timing::to_key_events(encoded, &Timing::new(20), None);
+        let characters = super::characters(encoded, |code| match code {
+            ".-" => Some("A".into()),
+            _ => None,
+        });
+        assert_eq!(characters, [("A".into(), 2), ("...-.-".into(), 6)]);
+
+        let svg = super::svg(&events, &characters, 800, 120);
+        assert!(svg.contains(">A</text>"), "{}", svg);
+        assert!(svg.contains(">...-.-</text>"), "{}", svg);
+        // The dah of A, and the word gap after it.
+        assert!(svg.contains("<title>180 ms</title>"), "{}", svg);
+        assert!(svg.contains(">420</text>"), "{}", svg);
+
+        // Characters that don't match the elements keyed are left out.
+        let svg = super::svg(&events, &characters[..1], 800, 120);
+        assert!(!svg.contains(">A</text>"), "{}", svg);
+    }
+
+    #[test]
+    fn escapes_prosigns() {
+        assert_eq!(super::escape("<SK> & co"), "&lt;SK&gt; &amp; co");
+    }
+
+    #[test]
+    fn ascii_waveform_tracks_element_lengths() {
+        let events = timing::to_key_events(".-", &Timing::default(), None);
+        assert_eq!(super::ascii(&events), "_ ___\n ||");
+
+        // The dah of T starts four columns in, after the dit of E and the character gap.
+        let events = timing::to_key_events(". -", &Timing::default(), None);
+        assert_eq!(super::ascii(&events), "_   ___\n |__|");
+    }
+}
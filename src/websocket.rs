@@ -0,0 +1,391 @@
+//! The WebSocket endpoint of `morse serve --http`, for practice clients in the browser.
+//!
+//! A client connects to `/stream`, optionally with `mode`, `wpm`, `farnsworth` and `tone` in the
+//! query string, and sends text frames. Each word is answered as soon as it is ready: with a
+//! text frame holding its code in `morse` mode (the default), or in `audio` mode with a binary
+//! frame of 16-bit little-endian mono PCM at 44.1 kHz, the word followed by a word gap. A
+//! message that would take longer than [`http::MAX_AUDIO`] to play closes the connection.
+
+use std::{
+    io::{self, Read, Write},
+    time::Duration,
+};
+
+use morse::{audio, base64, timing, KeyEvent, Options, Timing};
+
+use crate::http::{self, Params, Request};
+
+/// The largest message accepted from a client.
+const MAX_MESSAGE: usize = 1 << 16;
+
+const CONTINUATION: u8 = 0x0;
+const TEXT: u8 = 0x1;
+const BINARY: u8 = 0x2;
+const CLOSE: u8 = 0x8;
+const PING: u8 = 0x9;
+const PONG: u8 = 0xa;
+
+/// The close status for a message too big to process.
+const TOO_BIG: u16 = 1009;
+
+/// What to send back for each word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Morse,
+    Audio,
+}
+
+/// Settings for one connection, from the query string.
+struct Stream {
+    mode: Mode,
+    timing: Timing,
+    tone: u32,
+}
+
+impl Stream {
+    fn from_params(params: &Params) -> Result<Self, String> {
+        let mode = match params.get("mode").map(String::as_str) {
+            None | Some("morse") => Mode::Morse,
+            Some("audio") => Mode::Audio,
+            Some(mode) => return Err(format!("unknown mode {:?} (expected morse or audio)", mode)),
+        };
+        let wpm = http::number(params, "wpm", 20)?;
+        let farnsworth = http::number(params, "farnsworth", wpm)?;
+        Ok(Stream {
+            mode,
+            timing: Timing::farnsworth(wpm, farnsworth),
+            tone: http::number(params, "tone", 600)?,
+        })
+    }
+
+    /// Answers one message from the client, sending each word's frame as soon as it is ready.
+    /// Returns false once the connection has been closed because the message would play for too
+    /// long.
+    fn answer(&self, message: &str, stream: &mut impl Write) -> io::Result<bool> {
+        let options = Options::default();
+        let mut length = Duration::default();
+        let codes = message
+            .split_whitespace()
+            .filter_map(|word| morse::encode(&morse::sanitize(word, &options)).ok())
+            .filter(|code| !code.is_empty());
+        for code in codes {
+            if self.mode == Mode::Morse {
+                write_frame(stream, TEXT, code.as_bytes())?;
+                continue;
+            }
+
+            let mut events = timing::to_key_events(&code, &self.timing, None);
+            events.push(KeyEvent::Up(self.timing.word_gap()));
+            length += events
+                .iter()
+                .map(|event| event.duration())
+                .sum::<Duration>();
+            if length > http::MAX_AUDIO {
+                write_frame(stream, CLOSE, &TOO_BIG.to_be_bytes())?;
+                return Ok(false);
+            }
+            let pcm: Vec<u8> = audio::render(&events, f64::from(self.tone))
+                .into_iter()
+                .flat_map(|sample| {
+                    ((sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16).to_le_bytes()
+                })
+                .collect();
+            write_frame(stream, BINARY, &pcm)?;
+        }
+        Ok(true)
+    }
+}
+
+/// Whether `request` asks to be upgraded to a WebSocket.
+pub fn is_upgrade(request: &Request) -> bool {
+    request
+        .headers
+        .get("upgrade")
+        .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+}
+
+/// Completes the handshake for `request` and serves the connection until the client closes it.
+pub fn run(mut stream: impl Read + Write, request: &Request) -> io::Result<()> {
+    let settings = match (
+        request.path.as_str(),
+        request.headers.get("sec-websocket-key"),
+    ) {
+        ("/stream", Some(key)) => Stream::from_params(&request.params)
+            .map(|stream| (stream, key))
+            .map_err(|message| (400, "Bad Request", message)),
+        ("/stream", None) => Err((400, "Bad Request", "missing Sec-WebSocket-Key".into())),
+        _ => Err((404, "Not Found", "not found".into())),
+    };
+    let (settings, key) = match settings {
+        Ok(settings) => settings,
+        Err((status, reason, message)) => {
+            return write!(
+                stream,
+                "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                reason,
+                message.len(),
+                message
+            )
+        }
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept(key)
+    )?;
+
+    let mut message = Vec::new();
+    loop {
+        let (fin, opcode, payload) = read_frame(&mut stream)?;
+        match opcode {
+            TEXT | CONTINUATION => {
+                message.extend_from_slice(&payload);
+                if message.len() > MAX_MESSAGE {
+                    return write_frame(&mut stream, CLOSE, &TOO_BIG.to_be_bytes());
+                }
+                if !fin {
+                    continue;
+                }
+                let text = String::from_utf8_lossy(&message).into_owned();
+                if !settings.answer(&text, &mut stream)? {
+                    return Ok(());
+                }
+                message.clear();
+            }
+            PING => write_frame(&mut stream, PONG, &payload)?,
+            CLOSE => return write_frame(&mut stream, CLOSE, &payload),
+            _ => (),
+        }
+    }
+}
+
+/// The `Sec-WebSocket-Accept` value for a client's key.
+fn accept(key: &str) -> String {
+    const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+    base64::encode(&sha1(format!("{}{}", key.trim(), GUID).as_bytes()))
+}
+
+/// Reads a frame, unmasking its payload. Returns the FIN bit, the opcode and the payload.
+fn read_frame(stream: &mut impl Read) -> io::Result<(bool, u8, Vec<u8>)> {
+    let mut head = [0; 2];
+    stream.read_exact(&mut head)?;
+    let fin = head[0] & 0x80 != 0;
+    let opcode = head[0] & 0x0f;
+    let masked = head[1] & 0x80 != 0;
+
+    let len = match head[1] & 0x7f {
+        126 => {
+            let mut len = [0; 2];
+            stream.read_exact(&mut len)?;
+            u64::from(u16::from_be_bytes(len))
+        }
+        127 => {
+            let mut len = [0; 8];
+            stream.read_exact(&mut len)?;
+            u64::from_be_bytes(len)
+        }
+        len => u64::from(len),
+    };
+    if len > MAX_MESSAGE as u64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "websocket frame too large",
+        ));
+    }
+
+    let mut mask = [0; 4];
+    if masked {
+        stream.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0; len as usize];
+    stream.read_exact(&mut payload)?;
+    for (idx, u) in payload.iter_mut().enumerate() {
+        *u ^= mask[idx % 4];
+    }
+    Ok((fin, opcode, payload))
+}
+
+/// Writes an unfragmented, unmasked frame.
+fn write_frame(stream: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= usize::from(u16::MAX) => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)?;
+    stream.flush()
+}
+
+/// SHA-1, which the handshake needs and nothing else does.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xefcd_ab89,
+        0x98ba_dcfe,
+        0x1032_5476,
+        0xc3d2_e1f0,
+    ];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (idx, word) in block.chunks(4).enumerate() {
+            w[idx] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for idx in 16..80 {
+            w[idx] = (w[idx - 3] ^ w[idx - 8] ^ w[idx - 14] ^ w[idx - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (idx, &word) in w.iter().enumerate() {
+            let (f, k) = match idx {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (h, x) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(x);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (idx, word) in h.iter().enumerate() {
+        digest[idx * 4..idx * 4 + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Read, Write};
+
+    use crate::http::Request;
+
+    /// A client's frames, queued up ahead of time, and everything the server writes back.
+    struct Client {
+        frames: Cursor<Vec<u8>>,
+        written: Vec<u8>,
+    }
+
+    impl Read for Client {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.frames.read(buf)
+        }
+    }
+
+    impl Write for Client {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A masked client frame.
+    fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [1, 2, 3, 4];
+        let mut frame = vec![0x80 | opcode, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(idx, u)| u ^ mask[idx % 4]));
+        frame
+    }
+
+    #[test]
+    fn hashes_handshake_key() {
+        // The example from RFC 6455.
+        assert_eq!(
+            super::accept("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    /// Runs a connection to `/stream` with `params` on which the client sends `frames`,
+    /// returning what the server sent after the handshake.
+    fn serve(params: &[(&str, &str)], frames: Vec<u8>) -> Vec<u8> {
+        let mut client = Client {
+            frames: Cursor::new(frames),
+            written: Vec::new(),
+        };
+
+        let mut request = Request {
+            path: "/stream".into(),
+            params: params
+                .iter()
+                .map(|&(name, value)| (name.into(), value.into()))
+                .collect(),
+            ..Request::default()
+        };
+        request.headers.insert(
+            "sec-websocket-key".into(),
+            "dGhlIHNhbXBsZSBub25jZQ==".into(),
+        );
+        super::run(&mut client, &request).unwrap();
+
+        let written = client.written;
+        let header_end = written.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        assert!(written.starts_with(b"HTTP/1.1 101 "));
+        written[header_end..].to_vec()
+    }
+
+    #[test]
+    fn streams_words() {
+        let mut frames = frame(super::TEXT, b"sos  cq");
+        frames.extend(frame(super::CLOSE, &[]));
+        let written = serve(&[], frames);
+
+        let mut server = Cursor::new(&written[..]);
+        let words: Vec<_> = (0..2)
+            .map(|_| super::read_frame(&mut server).unwrap())
+            .collect();
+        assert_eq!(words[0], (true, super::TEXT, b"... --- ...".to_vec()));
+        assert_eq!(words[1], (true, super::TEXT, b"-.-. --.-".to_vec()));
+        assert_eq!(super::read_frame(&mut server).unwrap().1, super::CLOSE);
+    }
+
+    #[test]
+    fn closes_on_audio_too_long_to_play() {
+        // Thirty zeros at 1 wpm take over thirteen minutes.
+        let zeros = "0".repeat(30);
+        let written = serve(
+            &[("mode", "audio"), ("wpm", "1")],
+            frame(super::TEXT, zeros.as_bytes()),
+        );
+
+        let mut server = Cursor::new(&written[..]);
+        let (_, opcode, payload) = super::read_frame(&mut server).unwrap();
+        assert_eq!(opcode, super::CLOSE);
+        assert_eq!(payload, super::TOO_BIG.to_be_bytes());
+    }
+}
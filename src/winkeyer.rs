@@ -0,0 +1,350 @@
+//! The host protocol of K1EL's Winkeyer, a keyer chip that does its own timing. Messages are
+//! handed to it as text, and it reports back its buffer status, its speed pot position and each
+//! character as it starts sending it.
+
+use std::{
+    io::{self, Read, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{elements, timing::WEIGHT, Error, Result, Timing};
+
+/// The Winkeyer's fixed serial speed; it also wants eight data bits, no parity and two stop bits.
+pub const BAUD: u32 = 1200;
+
+const ADMIN: u8 = 0x00;
+const HOST_OPEN: u8 = 0x02;
+const HOST_CLOSE: u8 = 0x03;
+const SET_SPEED: u8 = 0x02;
+const SET_POT_RANGE: u8 = 0x05;
+const GET_POT: u8 = 0x07;
+const CLEAR_BUFFER: u8 = 0x0a;
+const SET_FARNSWORTH: u8 = 0x0d;
+const GET_STATUS: u8 = 0x15;
+const SET_RATIO: u8 = 0x17;
+const MERGE: u8 = 0x1b;
+
+/// How long to wait for a reply before giving up on the Winkeyer.
+const TIMEOUT: Duration = Duration::from_secs(2);
+const POLL: Duration = Duration::from_millis(10);
+
+/// The Winkeyer's status byte.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Status {
+    /// The buffer is more than two thirds full; no more text should be sent for now.
+    pub xoff: bool,
+    /// A paddle was touched, interrupting the message.
+    pub break_in: bool,
+    /// A message is being sent.
+    pub busy: bool,
+    pub key_down: bool,
+    /// A wait command is running.
+    pub waiting: bool,
+}
+
+/// A byte sent by the Winkeyer, unasked or in reply to a command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Response {
+    Status(Status),
+    /// The speed pot's position, in words per minute above the bottom of its range.
+    Pot(u8),
+    /// A character the Winkeyer has started sending.
+    Echo(char),
+}
+
+impl Response {
+    /// Status bytes start with the bits `11`, speed pot bytes with `10` and echoes with `0`.
+    pub fn parse(byte: u8) -> Response {
+        match byte & 0xc0 {
+            0xc0 => Response::Status(Status {
+                xoff: byte & 0x01 != 0,
+                break_in: byte & 0x02 != 0,
+                busy: byte & 0x04 != 0,
+                key_down: byte & 0x08 != 0,
+                waiting: byte & 0x10 != 0,
+            }),
+            0x80 => Response::Pot(byte & 0x3f),
+            _ => Response::Echo(char::from(byte)),
+        }
+    }
+}
+
+/// A Winkeyer on the other end of `port`, which should not block when there is nothing to read.
+pub struct Winkeyer<P: Read + Write> {
+    port: P,
+    version: u8,
+    status: Status,
+    pot_min: u32,
+}
+
+impl<P: Read + Write> Winkeyer<P> {
+    /// Opens a host session, which the Winkeyer acknowledges with its firmware version.
+    pub fn open(port: P) -> io::Result<Self> {
+        let mut winkeyer = Winkeyer {
+            port,
+            version: 0,
+            status: Status::default(),
+            pot_min: 0,
+        };
+        winkeyer.port.write_all(&[ADMIN, HOST_OPEN])?;
+        winkeyer.version = winkeyer.reply(|_| true)?;
+        Ok(winkeyer)
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Sets the speed, in the Winkeyer's own words per minute. Farnsworth timing is kept, though
+    /// the Winkeyer does not stretch gaps below 10 WPM, and so is the weight, within the
+    /// Winkeyer's range of 2 to 4 dits a dah.
+    pub fn set_timing(&mut self, timing: &Timing) -> io::Result<()> {
+        self.port
+            .write_all(&[SET_SPEED, wpm_byte(timing.effective_wpm)])?;
+        if timing.effective_wpm < timing.char_wpm {
+            self.port
+                .write_all(&[SET_FARNSWORTH, wpm_byte(timing.char_wpm)])?;
+        }
+        if timing.weight != WEIGHT {
+            // 50 is the standard ratio of 3, and each step either way is 1/50 of it.
+            let ratio = (timing.weight * 50.0 / WEIGHT).round().clamp(33.0, 66.0);
+            self.port.write_all(&[SET_RATIO, ratio as u8])?;
+        }
+        Ok(())
+    }
+
+    /// Hands the speed over to the speed pot, which then spans `min` to `max` words per minute.
+    pub fn use_pot(&mut self, min: u32, max: u32) -> io::Result<()> {
+        let min = wpm_byte(min);
+        let range = wpm_byte(max).saturating_sub(min).min(31);
+        self.pot_min = u32::from(min);
+        self.port.write_all(&[SET_POT_RANGE, min, range, 0])?;
+        self.port.write_all(&[SET_SPEED, 0])
+    }
+
+    /// Reads the speed pot, in words per minute.
+    pub fn speed_pot(&mut self) -> io::Result<u32> {
+        self.port.write_all(&[GET_POT])?;
+        let pot = self.reply(|byte| matches!(Response::parse(byte), Response::Pot(_)))?;
+        Ok(self.pot_min + u32::from(pot & 0x3f))
+    }
+
+    pub fn status(&mut self) -> io::Result<Status> {
+        self.port.write_all(&[GET_STATUS])?;
+        self.reply(|byte| matches!(Response::parse(byte), Response::Status(_)))?;
+        Ok(self.status)
+    }
+
+    /// Queues `message` for sending. The Winkeyer's buffer only holds 128 bytes, so this holds back
+    /// whenever the Winkeyer reports it two thirds full.
+    pub fn send(&mut self, message: &str) -> Result<()> {
+        for command in text(message)? {
+            while self.status.xoff {
+                if self.poll().map_err(Error::Io)?.is_none() {
+                    thread::sleep(POLL);
+                }
+            }
+            self.port.write_all(&command).map_err(Error::Io)?;
+            while self.poll().map_err(Error::Io)?.is_some() {}
+        }
+        Ok(())
+    }
+
+    /// Waits until the Winkeyer has finished sending.
+    pub fn wait(&mut self) -> io::Result<()> {
+        // Give the Winkeyer a moment to pick up the end of the message before asking after it.
+        thread::sleep(POLL * 10);
+        while self.status()?.busy {
+            thread::sleep(POLL * 10);
+        }
+        Ok(())
+    }
+
+    /// Abandons whatever has not been sent yet.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.port.write_all(&[CLEAR_BUFFER])
+    }
+
+    /// Ends the host session, returning the Winkeyer to standalone operation.
+    pub fn close(mut self) -> io::Result<()> {
+        self.port.write_all(&[ADMIN, HOST_CLOSE])?;
+        self.port.flush()
+    }
+
+    /// Reads one byte, if there is one, keeping track of the status.
+    fn poll(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        match self.port.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => (),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        if let Response::Status(status) = Response::parse(byte[0]) {
+            self.status = status;
+        }
+        Ok(Some(byte[0]))
+    }
+
+    /// Reads until `done` accepts a byte, returning that byte.
+    fn reply(&mut self, done: impl Fn(u8) -> bool) -> io::Result<u8> {
+        let deadline = Instant::now() + TIMEOUT;
+        while Instant::now() < deadline {
+            match self.poll()? {
+                Some(byte) if done(byte) => return Ok(byte),
+                Some(_) => (),
+                None => thread::sleep(POLL),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "no reply from the Winkeyer",
+        ))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+impl Winkeyer<std::fs::File> {
+    /// Opens a Winkeyer on the serial port at `path`, which is set up for it first.
+    pub fn open_serial(path: &std::path::Path) -> io::Result<Self> {
+        let port = crate::output::open_raw(path, BAUD, &["cstopb"])?;
+        Winkeyer::open(port)
+    }
+}
+
+/// The commands that send `message`: each character as itself, upper case, and two-letter
+/// prosigns such as `<SK>` merged into one character.
+pub fn text(message: &str) -> Result<Vec<Vec<u8>>> {
+    let mut commands = Vec::new();
+    let mut rest = message;
+
+    while let Some(c) = rest.chars().next() {
+        let (command, tail) = match c {
+            '<' => {
+                let end = rest
+                    .find('>')
+                    .ok_or_else(|| Error::Encode(c).at(message, rest))?;
+                let letters: Vec<char> = rest[1..end].chars().collect();
+                match letters[..] {
+                    [a, b] if is_sendable(a) && is_sendable(b) => (
+                        vec![
+                            MERGE,
+                            a.to_ascii_uppercase() as u8,
+                            b.to_ascii_uppercase() as u8,
+                        ],
+                        &rest[end + 1..],
+                    ),
+                    _ => return Err(Error::Encode(c).at(message, rest)),
+                }
+            }
+            ' ' => (vec![b' '], &rest[1..]),
+            c if is_sendable(c) => (vec![c.to_ascii_uppercase() as u8], &rest[1..]),
+            c => return Err(Error::Encode(c).at(message, rest)),
+        };
+        commands.push(command);
+        rest = tail;
+    }
+
+    Ok(commands)
+}
+
+fn is_sendable(c: char) -> bool {
+    c.is_ascii() && elements::code(c).is_some()
+}
+
+/// The Winkeyer takes speeds from 5 to 99 WPM.
+fn wpm_byte(wpm: u32) -> u8 {
+    wpm.clamp(5, 99) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        io::{self, Read, Write},
+    };
+
+    use super::{Response, Status, Winkeyer};
+    use crate::Timing;
+
+    /// A Winkeyer's replies, queued up ahead of time, and everything written to it.
+    #[derive(Default)]
+    struct Port {
+        replies: VecDeque<u8>,
+        written: Vec<u8>,
+    }
+
+    impl Read for Port {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.replies.pop_front() {
+                Some(byte) => {
+                    buf[0] = byte;
+                    Ok(1)
+                }
+                None => Err(io::ErrorKind::WouldBlock.into()),
+            }
+        }
+    }
+
+    impl Write for Port {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn parses_responses() {
+        assert_eq!(
+            Response::parse(0xc5),
+            Response::Status(Status {
+                xoff: true,
+                busy: true,
+                ..Status::default()
+            })
+        );
+        assert_eq!(Response::parse(0x8a), Response::Pot(10));
+        assert_eq!(Response::parse(b'K'), Response::Echo('K'));
+    }
+
+    #[test]
+    fn talks_to_winkeyer() {
+        let port = Port {
+            replies: vec![23, 0x8c].into(),
+            ..Port::default()
+        };
+        let mut winkeyer = Winkeyer::open(port).unwrap();
+        assert_eq!(winkeyer.version(), 23);
+
+        winkeyer
+            .set_timing(&Timing::farnsworth(18, 12).weighted(3.3))
+            .unwrap();
+        winkeyer.use_pot(10, 40).unwrap();
+        assert_eq!(winkeyer.speed_pot().unwrap(), 22);
+        winkeyer.send("cq <sk>").unwrap();
+
+        assert_eq!(
+            winkeyer.port.written,
+            [
+                0x00, 0x02, 0x02, 12, 0x0d, 18, 0x17, 55, 0x05, 10, 30, 0, 0x02, 0, 0x07, b'C',
+                b'Q', b' ', 0x1b, b'S', b'K'
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unsendable_text() {
+        let e = super::text("ok <SOS>").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 4: unable to encode value: '<'"
+        );
+        assert!(super::text("é").is_err());
+    }
+}